@@ -0,0 +1,60 @@
+use bplustree::BPlusTreeMap;
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use rand::prelude::*;
+
+/// Compares the branchless comparator-based child search with the previous
+/// `binary_search`-based routing on the fullest branch of a large-capacity tree
+fn benchmark_branch_search(c: &mut Criterion) {
+    let mut group = c.benchmark_group("BranchChildSearch");
+
+    for capacity in [16, 64, 256] {
+        let mut rng = StdRng::seed_from_u64(42);
+        let mut tree = BPlusTreeMap::new(capacity).unwrap();
+        for _ in 0..(capacity * capacity * 4) {
+            let key: i64 = rng.gen_range(0..i64::MAX);
+            tree.insert(key, ());
+        }
+
+        // Pick the branch with the most separators
+        let branch = tree
+            .level_order()
+            .filter(|(_, _, is_leaf)| !is_leaf)
+            .filter_map(|(_, id, _)| tree.get_branch(id))
+            .max_by_key(|branch| branch.keys().len())
+            .unwrap();
+        let probes: Vec<i64> = (0..1000).map(|_| rng.gen_range(0..i64::MAX)).collect();
+
+        group.bench_with_input(
+            BenchmarkId::new("Branchless", capacity),
+            &capacity,
+            |b, _| {
+                b.iter(|| {
+                    for key in &probes {
+                        black_box(branch.find_child_index_by(black_box(key), i64::cmp));
+                    }
+                });
+            },
+        );
+
+        group.bench_with_input(
+            BenchmarkId::new("BinarySearch", capacity),
+            &capacity,
+            |b, _| {
+                b.iter(|| {
+                    for key in &probes {
+                        let index = match branch.keys().binary_search(black_box(key)) {
+                            Ok(index) => index + 1,
+                            Err(index) => index,
+                        };
+                        black_box(index);
+                    }
+                });
+            },
+        );
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, benchmark_branch_search);
+criterion_main!(benches);
@@ -200,7 +200,7 @@ fn bench_mixed_operations(c: &mut Criterion) {
 
         group.bench_with_input(BenchmarkId::new("BTreeMap", size), &operations, |b, ops| {
             b.iter_batched(
-                || BTreeMap::new(),
+                BTreeMap::new,
                 |mut map| {
                     for &(op, key, value) in ops {
                         match op {
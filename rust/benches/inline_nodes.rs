@@ -0,0 +1,95 @@
+use bplustree::{BPlusTreeMap, BPlusTreeMapN};
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use rand::prelude::*;
+
+const CAPACITY: usize = 16;
+
+fn shuffled_keys(size: u64) -> Vec<u64> {
+    let mut keys: Vec<u64> = (0..size).collect();
+    keys.shuffle(&mut StdRng::seed_from_u64(42));
+    keys
+}
+
+fn vec_tree(keys: &[u64]) -> BPlusTreeMap<u64, u64> {
+    let mut tree = BPlusTreeMap::new(CAPACITY).unwrap();
+    for &key in keys {
+        tree.insert(key, key);
+    }
+    tree
+}
+
+fn inline_tree(keys: &[u64]) -> BPlusTreeMapN<u64, u64, CAPACITY> {
+    let mut tree = BPlusTreeMapN::new();
+    for &key in keys {
+        tree.insert(key, key);
+    }
+    tree
+}
+
+/// Inline nodes allocate once per node instead of once per buffer, so
+/// building the tree should be cheaper
+fn benchmark_insert(c: &mut Criterion) {
+    let mut group = c.benchmark_group("InlineNodes/Insert");
+
+    for size in [1_000, 100_000] {
+        let keys = shuffled_keys(size);
+        group.bench_with_input(BenchmarkId::new("Vec", size), &keys, |b, keys| {
+            b.iter(|| black_box(vec_tree(keys)));
+        });
+        group.bench_with_input(BenchmarkId::new("Inline", size), &keys, |b, keys| {
+            b.iter(|| black_box(inline_tree(keys)));
+        });
+    }
+
+    group.finish();
+}
+
+/// Lookups skip one pointer chase per level when keys live inside the node
+fn benchmark_get(c: &mut Criterion) {
+    let mut group = c.benchmark_group("InlineNodes/Get");
+
+    for size in [1_000, 100_000] {
+        let keys = shuffled_keys(size);
+        let vec = vec_tree(&keys);
+        let inline = inline_tree(&keys);
+
+        group.bench_with_input(BenchmarkId::new("Vec", size), &keys, |b, keys| {
+            b.iter(|| {
+                for key in keys {
+                    black_box(vec.get(key));
+                }
+            });
+        });
+        group.bench_with_input(BenchmarkId::new("Inline", size), &keys, |b, keys| {
+            b.iter(|| {
+                for key in keys {
+                    black_box(inline.get(key));
+                }
+            });
+        });
+    }
+
+    group.finish();
+}
+
+fn benchmark_iterate(c: &mut Criterion) {
+    let mut group = c.benchmark_group("InlineNodes/Iterate");
+
+    for size in [1_000, 100_000] {
+        let keys = shuffled_keys(size);
+        let vec = vec_tree(&keys);
+        let inline = inline_tree(&keys);
+
+        group.bench_with_input(BenchmarkId::new("Vec", size), &size, |b, _| {
+            b.iter(|| black_box(vec.items().map(|(_, v)| *v).sum::<u64>()));
+        });
+        group.bench_with_input(BenchmarkId::new("Inline", size), &size, |b, _| {
+            b.iter(|| black_box(inline.iter().map(|(_, v)| *v).sum::<u64>()));
+        });
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, benchmark_insert, benchmark_get, benchmark_iterate);
+criterion_main!(benches);
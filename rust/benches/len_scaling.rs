@@ -0,0 +1,24 @@
+use bplustree::BPlusTreeMap;
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+
+/// `len` reads the root's entry count, so its cost should stay flat as the
+/// tree grows by orders of magnitude
+fn benchmark_len(c: &mut Criterion) {
+    let mut group = c.benchmark_group("Len");
+
+    for size in [1_000, 100_000, 1_000_000] {
+        let mut tree = BPlusTreeMap::new(16).unwrap();
+        for i in 0..size {
+            tree.insert(i, i);
+        }
+
+        group.bench_with_input(BenchmarkId::from_parameter(size), &size, |b, _| {
+            b.iter(|| black_box(black_box(&tree).len()));
+        });
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, benchmark_len);
+criterion_main!(benches);
@@ -5,7 +5,6 @@ use rand::prelude::*;
 /// Profiling benchmark for balanced workload analysis
 /// This benchmark creates a realistic workload with mixed operations
 /// to identify performance bottlenecks by function and operation type.
-
 fn profile_balanced_workload(c: &mut Criterion) {
     let mut group = c.benchmark_group("balanced_workload_profiling");
     
@@ -5,7 +5,6 @@ use rand::prelude::*;
 
 /// Simple comparison benchmark aligned with Go and Zig implementations
 /// Compares B+ Tree vs BTreeMap for basic operations
-
 fn benchmark_sequential_insert(c: &mut Criterion) {
     let mut group = c.benchmark_group("SequentialInsert");
     let sizes = [100, 1000, 10000, 100000];
@@ -47,7 +46,7 @@ fn benchmark_random_insert(c: &mut Criterion) {
         let keys: Vec<i32> = (0..size).map(|_| rng.gen_range(0..size*10)).collect();
         
         // B+ Tree
-        group.bench_with_input(BenchmarkId::new("BPlusTree", size), &size, |b, &size| {
+        group.bench_with_input(BenchmarkId::new("BPlusTree", size), &size, |b, &_size| {
             b.iter(|| {
                 let mut tree = BPlusTreeMap::new(128).unwrap();
                 for &key in &keys {
@@ -58,7 +57,7 @@ fn benchmark_random_insert(c: &mut Criterion) {
         });
         
         // BTreeMap
-        group.bench_with_input(BenchmarkId::new("BTreeMap", size), &size, |b, &size| {
+        group.bench_with_input(BenchmarkId::new("BTreeMap", size), &size, |b, &_size| {
             b.iter(|| {
                 let mut btree = BTreeMap::new();
                 for &key in &keys {
@@ -100,7 +100,7 @@ fn main() {
     println!("Cap | Leaf Size | Total Leaves | Memory Used | Overhead");
     println!("----|-----------|--------------|-------------|----------");
 
-    for capacity in vec![4, 8, 16, 32, 64, 128, 256] {
+    for capacity in [4, 8, 16, 32, 64, 128, 256] {
         let (_, _, _, leaves, free) = benchmark_capacity(capacity);
 
         // Approximate memory per leaf (keys + values + overhead)
@@ -1,7 +1,9 @@
-use std::convert::TryFrom;
+#[cfg(not(feature = "std"))]
+use alloc::{format, string::String, vec::Vec};
+use core::convert::TryFrom;
 /// Enhanced generic arena allocator that eliminates all arena duplication
 /// This replaces ~160 lines of duplicated arena code with a single generic implementation
-use std::fmt::Debug;
+use core::fmt::Debug;
 
 pub type NodeId = u32;
 pub const NULL_NODE: NodeId = u32::MAX;
@@ -12,6 +14,8 @@ pub struct ArenaStats {
     pub total_capacity: usize,
     pub allocated_count: usize,
     pub free_count: usize,
+    /// Slots the backing storage can hold before it has to reallocate
+    pub reserved_capacity: usize,
     pub utilization: f64,
     pub fragmentation: f64,
 }
@@ -22,6 +26,8 @@ pub struct ArenaStats {
 pub struct Arena<T> {
     storage: Vec<Option<T>>,
     free_ids: Vec<NodeId>,
+    /// Exclusive upper bound on the IDs handed out, so `NULL_NODE` never is
+    id_limit: NodeId,
 }
 
 impl<T> Arena<T> {
@@ -30,6 +36,7 @@ impl<T> Arena<T> {
         Self {
             storage: Vec::new(),
             free_ids: Vec::new(),
+            id_limit: NULL_NODE,
         }
     }
 
@@ -38,21 +45,46 @@ impl<T> Arena<T> {
         Self {
             storage: Vec::with_capacity(capacity),
             free_ids: Vec::new(),
+            id_limit: NULL_NODE,
         }
     }
 
     /// Allocate a new item in the arena and return its ID
+    ///
+    /// Panics if every ID below the limit is in use; see `try_allocate`.
     pub fn allocate(&mut self, item: T) -> NodeId {
-        let id = self.next_id();
+        self.try_allocate(item)
+            .expect("arena has no NodeId left below its limit")
+    }
+
+    /// Allocate a new item, or return `None` if every ID below the limit is
+    /// in use, so that an ID can never alias `NULL_NODE`
+    pub fn try_allocate(&mut self, item: T) -> Option<NodeId> {
+        let id = self.next_id()?;
 
         // Extend storage if needed
-        let id_usize = usize::try_from(id).expect("NodeId should fit in usize");
+        let id_usize = usize::try_from(id).ok()?;
         if id_usize >= self.storage.len() {
             self.storage.resize_with(id_usize + 1, || None);
         }
 
         self.storage[id_usize] = Some(item);
-        id
+        Some(id)
+    }
+
+    /// Restrict new IDs to those below `limit`
+    ///
+    /// IDs already handed out stay valid. Mainly useful for exercising the
+    /// exhaustion path without allocating billions of slots.
+    pub fn set_id_limit(&mut self, limit: NodeId) {
+        self.id_limit = limit;
+    }
+
+    /// Get the number of items that can still be allocated before the ID
+    /// space runs out
+    pub fn remaining_ids(&self) -> usize {
+        let limit = usize::try_from(self.id_limit).unwrap_or(usize::MAX);
+        self.free_ids.len() + limit.saturating_sub(self.storage.len())
     }
 
     /// Deallocate an item from the arena and return it
@@ -85,6 +117,32 @@ impl<T> Arena<T> {
         self.storage.get_mut(id_usize)?.as_mut()
     }
 
+    /// Get mutable references to several distinct items at once
+    ///
+    /// References are returned in the order of `ids`. Returns `None` if any ID
+    /// is missing or appears more than once, since the references would alias.
+    pub fn get_disjoint_mut(&mut self, ids: &[NodeId]) -> Option<Vec<&mut T>> {
+        let mut order: Vec<usize> = (0..ids.len()).collect();
+        order.sort_unstable_by_key(|&position| ids[position]);
+
+        // Walk the storage once in ID order, splitting off one slot per ID
+        let mut slots: Vec<Option<&mut T>> = ids.iter().map(|_| None).collect();
+        let mut rest: &mut [Option<T>] = &mut self.storage;
+        let mut offset = 0;
+        for position in order {
+            let id_usize = usize::try_from(ids[position]).ok()?;
+            let skip = id_usize.checked_sub(offset)?;
+            let (slot, tail) = core::mem::take(&mut rest)
+                .get_mut(skip..)?
+                .split_first_mut()?;
+            slots[position] = Some(slot.as_mut()?);
+            rest = tail;
+            offset = id_usize + 1;
+        }
+
+        slots.into_iter().collect()
+    }
+
     /// Check if an ID is valid and allocated
     pub fn contains(&self, id: NodeId) -> bool {
         if id == NULL_NODE {
@@ -97,10 +155,13 @@ impl<T> Arena<T> {
     }
 
     /// Get the next available ID (from free list or storage length)
-    fn next_id(&mut self) -> NodeId {
-        self.free_ids.pop().unwrap_or_else(|| {
-            u32::try_from(self.storage.len()).expect("Arena size exceeds maximum NodeId capacity")
-        })
+    fn next_id(&mut self) -> Option<NodeId> {
+        if let Some(id) = self.free_ids.pop() {
+            return Some(id);
+        }
+        u32::try_from(self.storage.len())
+            .ok()
+            .filter(|&id| id < self.id_limit)
     }
 
     // ============================================================================
@@ -122,6 +183,16 @@ impl<T> Arena<T> {
         self.storage.len()
     }
 
+    /// Get the number of slots storage can hold without reallocating
+    pub fn reserved_capacity(&self) -> usize {
+        self.storage.capacity()
+    }
+
+    /// Reserve storage for at least `additional` more slots
+    pub fn reserve(&mut self, additional: usize) {
+        self.storage.reserve(additional);
+    }
+
     /// Get the utilization ratio (allocated / total_capacity)
     pub fn utilization(&self) -> f64 {
         if self.storage.is_empty() {
@@ -156,6 +227,7 @@ impl<T> Arena<T> {
             total_capacity: self.total_capacity(),
             allocated_count: self.allocated_count(),
             free_count: self.free_count(),
+            reserved_capacity: self.reserved_capacity(),
             utilization: self.utilization(),
             fragmentation: self.fragmentation(),
         }
@@ -202,6 +274,21 @@ impl<T> Arena<T> {
     // ITERATION SUPPORT
     // ============================================================================
 
+    /// Convert every item, keeping IDs and free slots unchanged
+    pub fn map<U>(self, mut f: impl FnMut(NodeId, T) -> U) -> Arena<U> {
+        let storage = self
+            .storage
+            .into_iter()
+            .enumerate()
+            .map(|(id, item)| item.map(|item| f(id as NodeId, item)))
+            .collect();
+        Arena {
+            storage,
+            free_ids: self.free_ids,
+            id_limit: self.id_limit,
+        }
+    }
+
     /// Iterate over all allocated items with their IDs
     pub fn iter(&self) -> impl Iterator<Item = (NodeId, &T)> {
         self.storage.iter().enumerate().filter_map(|(id, item)| {
@@ -414,4 +501,46 @@ mod tests {
         assert_eq!(arena.total_capacity(), 3);
         assert_eq!(arena.allocated_count(), 3);
     }
+
+    #[test]
+    fn test_arena_get_disjoint_mut() {
+        let mut arena: Arena<i32> = Arena::new();
+        let ids: Vec<NodeId> = (0..5).map(|i| arena.allocate(i * 10)).collect();
+        arena.deallocate(ids[2]);
+
+        // References come back in request order, not ID order
+        let refs = arena.get_disjoint_mut(&[ids[4], ids[0], ids[3]]).unwrap();
+        for value in refs {
+            *value += 1;
+        }
+        assert_eq!(arena.get(ids[0]), Some(&1));
+        assert_eq!(arena.get(ids[3]), Some(&31));
+        assert_eq!(arena.get(ids[4]), Some(&41));
+        assert_eq!(arena.get(ids[1]), Some(&10));
+
+        // Duplicates, freed slots and out-of-range IDs are rejected
+        assert!(arena.get_disjoint_mut(&[ids[1], ids[1]]).is_none());
+        assert!(arena.get_disjoint_mut(&[ids[2]]).is_none());
+        assert!(arena.get_disjoint_mut(&[99]).is_none());
+        assert_eq!(arena.get_disjoint_mut(&[]).map(|refs| refs.len()), Some(0));
+    }
+
+    #[test]
+    fn test_arena_refuses_ids_past_limit() {
+        let mut arena: Arena<i32> = Arena::new();
+        arena.set_id_limit(3);
+        let ids: Vec<NodeId> = (0..3).map(|i| arena.try_allocate(i).unwrap()).collect();
+        assert_eq!(arena.remaining_ids(), 0);
+        assert_eq!(arena.try_allocate(3), None);
+        assert_eq!(arena.total_capacity(), 3);
+
+        // Freed IDs are still handed out again
+        arena.deallocate(ids[1]);
+        assert_eq!(arena.remaining_ids(), 1);
+        assert_eq!(arena.try_allocate(4), Some(ids[1]));
+
+        // The sentinel itself is never handed out
+        arena.set_id_limit(NULL_NODE);
+        assert_eq!(arena.remaining_ids(), NULL_NODE as usize - 3);
+    }
 }
@@ -0,0 +1,198 @@
+//! Compact binary dump and load for [`BPlusTreeMap`].
+//!
+//! The format is a 4-byte magic, a little-endian `u64` entry count, then each
+//! entry in key order as a length-prefixed key followed by a length-prefixed
+//! value. Lengths are little-endian `u32`s and the bytes in between come from
+//! [`BinaryCodec`].
+
+use std::io::{self, Read, Write};
+
+use crate::{BPlusTreeError, BPlusTreeMap};
+
+/// Identifies a tree dump and its format version.
+const MAGIC: &[u8; 4] = b"BPT1";
+
+/// Conversion between a value and the bytes stored for it by
+/// [`BPlusTreeMap::write_to`].
+///
+/// Framing is handled by the tree, so an implementation only has to round
+/// trip its own bytes: `decode` receives exactly what `encode` appended.
+pub trait BinaryCodec: Sized {
+    /// Append the encoded form of `self` to `out`.
+    fn encode(&self, out: &mut Vec<u8>);
+
+    /// Rebuild a value from the bytes produced by `encode`.
+    fn decode(bytes: &[u8]) -> io::Result<Self>;
+}
+
+macro_rules! impl_codec_for_numbers {
+    ($($ty:ty),*) => {
+        $(
+            impl BinaryCodec for $ty {
+                fn encode(&self, out: &mut Vec<u8>) {
+                    out.extend_from_slice(&self.to_le_bytes());
+                }
+
+                fn decode(bytes: &[u8]) -> io::Result<Self> {
+                    bytes
+                        .try_into()
+                        .map(<$ty>::from_le_bytes)
+                        .map_err(|_| invalid_data(concat!("wrong byte length for ", stringify!($ty))))
+                }
+            }
+        )*
+    };
+}
+
+impl_codec_for_numbers!(u8, u16, u32, u64, u128, i8, i16, i32, i64, i128, f32, f64);
+
+impl BinaryCodec for bool {
+    fn encode(&self, out: &mut Vec<u8>) {
+        out.push(u8::from(*self));
+    }
+
+    fn decode(bytes: &[u8]) -> io::Result<Self> {
+        match bytes {
+            [0] => Ok(false),
+            [1] => Ok(true),
+            _ => Err(invalid_data("invalid bool encoding")),
+        }
+    }
+}
+
+impl BinaryCodec for () {
+    fn encode(&self, _out: &mut Vec<u8>) {}
+
+    fn decode(bytes: &[u8]) -> io::Result<Self> {
+        if bytes.is_empty() {
+            Ok(())
+        } else {
+            Err(invalid_data("unit value must be empty"))
+        }
+    }
+}
+
+impl BinaryCodec for String {
+    fn encode(&self, out: &mut Vec<u8>) {
+        out.extend_from_slice(self.as_bytes());
+    }
+
+    fn decode(bytes: &[u8]) -> io::Result<Self> {
+        String::from_utf8(bytes.to_vec()).map_err(|_| invalid_data("string is not valid UTF-8"))
+    }
+}
+
+impl BinaryCodec for Vec<u8> {
+    fn encode(&self, out: &mut Vec<u8>) {
+        out.extend_from_slice(self);
+    }
+
+    fn decode(bytes: &[u8]) -> io::Result<Self> {
+        Ok(bytes.to_vec())
+    }
+}
+
+impl<K, V> BPlusTreeMap<K, V>
+where
+    K: Ord + Clone + BinaryCodec,
+    V: Clone + BinaryCodec,
+{
+    /// Write every entry to `w` in key order using the compact binary format.
+    ///
+    /// The output can be loaded back with [`read_from`](Self::read_from).
+    /// Capacity and comparator are not recorded.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bplustree::BPlusTreeMap;
+    /// use std::io::Cursor;
+    ///
+    /// let tree: BPlusTreeMap<u32, String> = (0..100).map(|i| (i, i.to_string())).collect();
+    /// let mut bytes = Vec::new();
+    /// tree.write_to(&mut bytes).unwrap();
+    ///
+    /// let loaded = BPlusTreeMap::<u32, String>::read_from(16, &mut Cursor::new(bytes)).unwrap();
+    /// assert_eq!(loaded, tree);
+    /// ```
+    pub fn write_to<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        w.write_all(MAGIC)?;
+        w.write_all(&(self.len() as u64).to_le_bytes())?;
+
+        let mut buffer = Vec::new();
+        for (key, value) in self.items() {
+            write_framed(w, &mut buffer, key)?;
+            write_framed(w, &mut buffer, value)?;
+        }
+        Ok(())
+    }
+
+    /// Load a tree written by [`write_to`](Self::write_to), bulk-building it
+    /// with nodes that hold up to `capacity` keys.
+    ///
+    /// # Errors
+    ///
+    /// Returns `InvalidInput` for a bad capacity, `InvalidData` if the stream
+    /// is not a tree dump, a key or value fails to decode, or the keys are
+    /// not strictly increasing, and `UnexpectedEof` if it is truncated.
+    pub fn read_from<R: Read>(capacity: usize, r: &mut R) -> io::Result<Self> {
+        // Fail fast on a bad capacity before reading anything
+        Self::new(capacity).map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+
+        let mut magic = [0; 4];
+        r.read_exact(&mut magic)?;
+        if &magic != MAGIC {
+            return Err(invalid_data("not a B+ tree dump"));
+        }
+
+        let mut count = [0; 8];
+        r.read_exact(&mut count)?;
+        let count = u64::from_le_bytes(count);
+
+        let mut entries = Vec::new();
+        let mut buffer = Vec::new();
+        for _ in 0..count {
+            let key = read_framed(r, &mut buffer)?;
+            let value = read_framed(r, &mut buffer)?;
+            entries.push((key, value));
+        }
+
+        Self::from_sorted_vec(capacity, entries).map_err(|e| match e {
+            BPlusTreeError::DataIntegrityError(_) => invalid_data(&e.to_string()),
+            other => io::Error::other(other),
+        })
+    }
+}
+
+/// Write `item` as a `u32` length followed by its encoded bytes.
+fn write_framed<W: Write, T: BinaryCodec>(
+    w: &mut W,
+    buffer: &mut Vec<u8>,
+    item: &T,
+) -> io::Result<()> {
+    buffer.clear();
+    item.encode(buffer);
+    let len = u32::try_from(buffer.len())
+        .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "encoded item exceeds 4 GiB"))?;
+    w.write_all(&len.to_le_bytes())?;
+    w.write_all(buffer)
+}
+
+/// Read one length-prefixed item written by `write_framed`.
+fn read_framed<R: Read, T: BinaryCodec>(r: &mut R, buffer: &mut Vec<u8>) -> io::Result<T> {
+    let mut len = [0; 4];
+    r.read_exact(&mut len)?;
+    let len = u32::from_le_bytes(len);
+
+    // Read through `take` so a corrupt length cannot force a huge allocation
+    buffer.clear();
+    r.take(u64::from(len)).read_to_end(buffer)?;
+    if buffer.len() != len as usize {
+        return Err(io::ErrorKind::UnexpectedEof.into());
+    }
+    T::decode(buffer)
+}
+
+fn invalid_data(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message.to_string())
+}
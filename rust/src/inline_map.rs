@@ -0,0 +1,808 @@
+//! B+ tree whose node capacity is fixed at compile time, with entries stored
+//! inline in each node rather than in separately allocated vectors.
+
+use alloc::vec::Vec;
+use core::mem;
+use core::ops::{Bound, RangeBounds};
+
+use crate::arena::{Arena, NodeId, NULL_NODE};
+use crate::MIN_CAPACITY;
+
+/// Leaf node with its entries stored inline. Slots past `len` hold
+/// `Default` values.
+#[derive(Debug)]
+struct InlineLeaf<K, V, const CAP: usize> {
+    len: usize,
+    keys: [K; CAP],
+    values: [V; CAP],
+    next: NodeId,
+}
+
+impl<K: Default, V: Default, const CAP: usize> InlineLeaf<K, V, CAP> {
+    fn new() -> Self {
+        Self {
+            len: 0,
+            keys: core::array::from_fn(|_| K::default()),
+            values: core::array::from_fn(|_| V::default()),
+            next: NULL_NODE,
+        }
+    }
+
+    fn keys(&self) -> &[K] {
+        &self.keys[..self.len]
+    }
+
+    fn is_full(&self) -> bool {
+        self.len == CAP
+    }
+
+    /// Insert an entry at `index`; the leaf must not be full.
+    fn insert_at(&mut self, index: usize, key: K, value: V) {
+        self.keys[self.len] = key;
+        self.values[self.len] = value;
+        self.keys[index..=self.len].rotate_right(1);
+        self.values[index..=self.len].rotate_right(1);
+        self.len += 1;
+    }
+
+    /// Remove and return the entry at `index`.
+    fn remove_at(&mut self, index: usize) -> (K, V) {
+        self.keys[index..self.len].rotate_left(1);
+        self.values[index..self.len].rotate_left(1);
+        self.len -= 1;
+        (
+            mem::take(&mut self.keys[self.len]),
+            mem::take(&mut self.values[self.len]),
+        )
+    }
+
+    /// Move the entries from `at` onward into a new leaf, which takes over
+    /// this leaf's `next` link.
+    fn split_off(&mut self, at: usize) -> Self {
+        let mut right = Self::new();
+        for i in at..self.len {
+            right.keys[i - at] = mem::take(&mut self.keys[i]);
+            right.values[i - at] = mem::take(&mut self.values[i]);
+        }
+        right.len = self.len - at;
+        right.next = self.next;
+        self.len = at;
+        right
+    }
+
+    /// Move every entry of `other` onto the end of this leaf.
+    fn append(&mut self, other: &mut Self) {
+        for i in 0..other.len {
+            self.keys[self.len + i] = mem::take(&mut other.keys[i]);
+            self.values[self.len + i] = mem::take(&mut other.values[i]);
+        }
+        self.len += other.len;
+        other.len = 0;
+    }
+}
+
+/// Branch node with `len` separator keys and `len + 1` children stored
+/// inline, so it holds at most `CAP - 1` keys and `CAP` children.
+#[derive(Debug)]
+struct InlineBranch<K, const CAP: usize> {
+    len: usize,
+    keys: [K; CAP],
+    children: [NodeId; CAP],
+}
+
+impl<K: Ord + Default, const CAP: usize> InlineBranch<K, CAP> {
+    fn new() -> Self {
+        Self {
+            len: 0,
+            keys: core::array::from_fn(|_| K::default()),
+            children: [NULL_NODE; CAP],
+        }
+    }
+
+    fn keys(&self) -> &[K] {
+        &self.keys[..self.len]
+    }
+
+    fn is_full(&self) -> bool {
+        self.len + 1 == CAP
+    }
+
+    /// Index of the child whose subtree holds `key`.
+    fn child_index(&self, key: &K) -> usize {
+        match self.keys().binary_search(key) {
+            Ok(index) => index + 1,
+            Err(index) => index,
+        }
+    }
+
+    /// Insert `key` at `index` with `child` to its right; the branch must
+    /// not be full.
+    fn insert_at(&mut self, index: usize, key: K, child: NodeId) {
+        self.keys[self.len] = key;
+        self.keys[index..=self.len].rotate_right(1);
+        self.children[self.len + 1] = child;
+        self.children[index + 1..=self.len + 1].rotate_right(1);
+        self.len += 1;
+    }
+
+    /// Remove the key at `index` and the child to its right.
+    fn remove_at(&mut self, index: usize) -> (K, NodeId) {
+        self.keys[index..self.len].rotate_left(1);
+        self.children[index + 1..=self.len].rotate_left(1);
+        self.len -= 1;
+        (
+            mem::take(&mut self.keys[self.len]),
+            self.children[self.len + 1],
+        )
+    }
+
+    fn push_front(&mut self, key: K, child: NodeId) {
+        self.keys[self.len] = key;
+        self.keys[..=self.len].rotate_right(1);
+        self.children[self.len + 1] = child;
+        self.children[..=self.len + 1].rotate_right(1);
+        self.len += 1;
+    }
+
+    fn push_back(&mut self, key: K, child: NodeId) {
+        self.keys[self.len] = key;
+        self.children[self.len + 1] = child;
+        self.len += 1;
+    }
+
+    fn pop_front(&mut self) -> (K, NodeId) {
+        self.keys[..self.len].rotate_left(1);
+        self.children[..=self.len].rotate_left(1);
+        self.len -= 1;
+        (
+            mem::take(&mut self.keys[self.len]),
+            self.children[self.len + 1],
+        )
+    }
+
+    fn pop_back(&mut self) -> (K, NodeId) {
+        self.len -= 1;
+        (
+            mem::take(&mut self.keys[self.len]),
+            self.children[self.len + 1],
+        )
+    }
+
+    /// Append `separator` and then all of `other`'s keys and children.
+    fn append(&mut self, separator: K, other: &mut Self) {
+        self.keys[self.len] = separator;
+        for i in 0..other.len {
+            self.keys[self.len + 1 + i] = mem::take(&mut other.keys[i]);
+        }
+        self.children[self.len + 1..=self.len + 1 + other.len]
+            .copy_from_slice(&other.children[..=other.len]);
+        self.len += other.len + 1;
+        other.len = 0;
+    }
+
+    /// Split a full branch while inserting `key` at `index` with `child` to
+    /// its right, returning the promoted key and the new right branch.
+    ///
+    /// The combined `CAP` keys cannot fit in one node, so each position of
+    /// the combined sequence is read straight from its source: keys and
+    /// children after the promoted key move right, the rest are shifted in
+    /// place.
+    fn split_insert(&mut self, index: usize, key: K, child: NodeId) -> (K, Self) {
+        let mid = CAP / 2;
+        let mut key = Some(key);
+        let mut right = Self::new();
+
+        for i in mid + 1..CAP {
+            right.keys[i - mid - 1] = match i.cmp(&index) {
+                core::cmp::Ordering::Less => mem::take(&mut self.keys[i]),
+                core::cmp::Ordering::Equal => key.take().unwrap_or_default(),
+                core::cmp::Ordering::Greater => mem::take(&mut self.keys[i - 1]),
+            };
+        }
+        for i in mid + 1..=CAP {
+            right.children[i - mid - 1] = match i.cmp(&(index + 1)) {
+                core::cmp::Ordering::Less => self.children[i],
+                core::cmp::Ordering::Equal => child,
+                core::cmp::Ordering::Greater => self.children[i - 1],
+            };
+        }
+        right.len = CAP - 1 - mid;
+
+        let promoted = if index >= mid {
+            if index == mid {
+                key.take().unwrap_or_default()
+            } else {
+                mem::take(&mut self.keys[mid])
+            }
+        } else {
+            let promoted = mem::take(&mut self.keys[mid - 1]);
+            self.keys[index..mid].rotate_right(1);
+            self.keys[index] = key.take().unwrap_or_default();
+            self.children[index + 1..=mid].rotate_right(1);
+            self.children[index + 1] = child;
+            promoted
+        };
+        self.len = mid;
+        (promoted, right)
+    }
+}
+
+/// A B+ tree map whose nodes hold up to `CAP` entries in fixed-size arrays.
+///
+/// [`BPlusTreeMap`](crate::BPlusTreeMap) keeps each node's keys and values in
+/// heap-allocated `Vec`s, so reading a node means following a pointer to the
+/// node and then to its buffers. Here the arrays live inside the node, saving
+/// one pointer chase per node visited, which matters most for small keys and
+/// values. The trade-offs:
+///
+/// - the capacity is a type parameter, checked at compile time to be at
+///   least 4, rather than a runtime argument;
+/// - `K` and `V` must implement `Default`, which fills unused slots, and
+///   every node takes its full size up front;
+/// - the API is a core subset of `BPlusTreeMap`'s: insert, lookup, removal,
+///   ordered and range iteration.
+///
+/// # Examples
+///
+/// ```
+/// use bplustree::BPlusTreeMapN;
+///
+/// let mut tree: BPlusTreeMapN<u64, u64, 16> = BPlusTreeMapN::new();
+/// for i in 0..100 {
+///     tree.insert(i, i * 10);
+/// }
+/// assert_eq!(tree.get(&42), Some(&420));
+/// assert_eq!(tree.remove(&42), Some(420));
+/// assert_eq!(tree.range(10..13).map(|(k, _)| *k).collect::<Vec<_>>(), vec![10, 11, 12]);
+/// ```
+///
+/// A capacity below 4 is rejected when the tree is built:
+///
+/// ```compile_fail
+/// use bplustree::BPlusTreeMapN;
+///
+/// let tree: BPlusTreeMapN<u64, u64, 3> = BPlusTreeMapN::new();
+/// ```
+pub struct BPlusTreeMapN<K, V, const CAP: usize> {
+    leaves: Arena<InlineLeaf<K, V, CAP>>,
+    branches: Arena<InlineBranch<K, CAP>>,
+    root: NodeId,
+    /// Number of branch levels above the leaves; 0 when the root is a leaf.
+    height: usize,
+    len: usize,
+}
+
+impl<K: Ord + Clone + Default, V: Default, const CAP: usize> BPlusTreeMapN<K, V, CAP> {
+    /// Fails to compile for a capacity below `MIN_CAPACITY`.
+    const CAPACITY_CHECK: () = assert!(CAP >= MIN_CAPACITY, "CAP must be at least 4");
+
+    /// Fewest keys a non-root branch may hold.
+    const MIN_BRANCH_KEYS: usize = (CAP - 1) / 2;
+
+    /// Create an empty tree.
+    pub fn new() -> Self {
+        let () = Self::CAPACITY_CHECK;
+        let mut leaves = Arena::new();
+        let root = leaves.allocate(InlineLeaf::new());
+        Self {
+            leaves,
+            branches: Arena::new(),
+            root,
+            height: 0,
+            len: 0,
+        }
+    }
+
+    /// Returns the number of entries.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns true if the tree has no entries.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Remove every entry.
+    pub fn clear(&mut self) {
+        *self = Self::new();
+    }
+
+    /// Get a reference to the value stored under `key`.
+    pub fn get(&self, key: &K) -> Option<&V> {
+        let leaf = self.leaf(self.find_leaf(key));
+        let index = leaf.keys().binary_search(key).ok()?;
+        Some(&leaf.values[index])
+    }
+
+    /// Get a mutable reference to the value stored under `key`.
+    pub fn get_mut(&mut self, key: &K) -> Option<&mut V> {
+        let leaf = self.leaf_mut(self.find_leaf(key));
+        let index = leaf.keys().binary_search(key).ok()?;
+        Some(&mut leaf.values[index])
+    }
+
+    /// Returns true if the tree contains `key`.
+    pub fn contains_key(&self, key: &K) -> bool {
+        self.get(key).is_some()
+    }
+
+    /// Insert a key-value pair, returning the previous value for the key.
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        let (old_value, split) = self.insert_into(self.root, self.height, key, value);
+        if let Some((separator, right)) = split {
+            let mut root = InlineBranch::new();
+            root.children[0] = self.root;
+            root.push_back(separator, right);
+            self.root = self.branches.allocate(root);
+            self.height += 1;
+        }
+        if old_value.is_none() {
+            self.len += 1;
+        }
+        old_value
+    }
+
+    /// Remove `key`, returning its value if it was present.
+    pub fn remove(&mut self, key: &K) -> Option<V> {
+        let removed = self.remove_from(self.root, self.height, key)?;
+        self.len -= 1;
+
+        // A root branch left with a single child hands the root down
+        if self.height > 0 && self.branch(self.root).len == 0 {
+            let old_root = self.root;
+            self.root = self.branch(old_root).children[0];
+            self.branches.deallocate(old_root);
+            self.height -= 1;
+        }
+        Some(removed)
+    }
+
+    /// Returns the entry with the smallest key.
+    pub fn first(&self) -> Option<(&K, &V)> {
+        let leaf = self.leaf(self.edge_leaf(|_| 0));
+        Some((leaf.keys().first()?, &leaf.values[0]))
+    }
+
+    /// Returns the entry with the largest key.
+    pub fn last(&self) -> Option<(&K, &V)> {
+        let leaf = self.leaf(self.edge_leaf(|branch| branch.len));
+        let last = leaf.len.checked_sub(1)?;
+        Some((&leaf.keys[last], &leaf.values[last]))
+    }
+
+    /// Iterate over all entries in key order.
+    pub fn iter(&self) -> IterN<'_, K, V, CAP> {
+        self.range(..)
+    }
+
+    /// Iterate over all keys in order.
+    pub fn keys(&self) -> impl Iterator<Item = &K> + '_ {
+        self.iter().map(|(key, _)| key)
+    }
+
+    /// Iterate over all values in key order.
+    pub fn values(&self) -> impl Iterator<Item = &V> + '_ {
+        self.iter().map(|(_, value)| value)
+    }
+
+    /// Iterate over the entries whose keys fall in `range`, in key order.
+    pub fn range<R: RangeBounds<K>>(&self, range: R) -> IterN<'_, K, V, CAP> {
+        let (leaf, index) = match range.start_bound() {
+            Bound::Unbounded => (self.edge_leaf(|_| 0), 0),
+            Bound::Included(start) => {
+                let leaf = self.find_leaf(start);
+                (leaf, self.leaf(leaf).keys().partition_point(|k| k < start))
+            }
+            Bound::Excluded(start) => {
+                let leaf = self.find_leaf(start);
+                (leaf, self.leaf(leaf).keys().partition_point(|k| k <= start))
+            }
+        };
+        IterN {
+            tree: self,
+            leaf,
+            index,
+            end: range.end_bound().cloned(),
+        }
+    }
+
+    /// Check the structural invariants: keys sorted within and between
+    /// nodes, every non-root node at least half full, the leaf chain in
+    /// key order, and `len` matching the entries stored.
+    pub fn check_invariants(&self) -> bool {
+        let mut leaves = Vec::new();
+        if !self.check_node(self.root, self.height, None, None, true, &mut leaves) {
+            return false;
+        }
+
+        let mut chained = leaves.first().copied().unwrap_or(NULL_NODE);
+        for &leaf in &leaves {
+            if chained != leaf {
+                return false;
+            }
+            chained = self.leaf(leaf).next;
+        }
+        chained == NULL_NODE
+            && leaves.iter().map(|&id| self.leaf(id).len).sum::<usize>() == self.len
+    }
+
+    // ============================================================================
+    // HELPERS
+    // ============================================================================
+
+    fn leaf(&self, id: NodeId) -> &InlineLeaf<K, V, CAP> {
+        self.leaves.get(id).expect("leaf ids in the tree are live")
+    }
+
+    fn leaf_mut(&mut self, id: NodeId) -> &mut InlineLeaf<K, V, CAP> {
+        self.leaves
+            .get_mut(id)
+            .expect("leaf ids in the tree are live")
+    }
+
+    fn branch(&self, id: NodeId) -> &InlineBranch<K, CAP> {
+        self.branches
+            .get(id)
+            .expect("branch ids in the tree are live")
+    }
+
+    fn branch_mut(&mut self, id: NodeId) -> &mut InlineBranch<K, CAP> {
+        self.branches
+            .get_mut(id)
+            .expect("branch ids in the tree are live")
+    }
+
+    /// Descend to the leaf responsible for `key`.
+    fn find_leaf(&self, key: &K) -> NodeId {
+        let mut id = self.root;
+        for _ in 0..self.height {
+            let branch = self.branch(id);
+            id = branch.children[branch.child_index(key)];
+        }
+        id
+    }
+
+    /// Descend to a leaf, taking the child `pick` chooses at each branch.
+    fn edge_leaf(&self, pick: impl Fn(&InlineBranch<K, CAP>) -> usize) -> NodeId {
+        let mut id = self.root;
+        for _ in 0..self.height {
+            let branch = self.branch(id);
+            id = branch.children[pick(branch)];
+        }
+        id
+    }
+
+    /// Insert below node `id` at `level` (0 for leaves), returning the old
+    /// value and, if the node split, the separator and new right node.
+    fn insert_into(
+        &mut self,
+        id: NodeId,
+        level: usize,
+        key: K,
+        value: V,
+    ) -> (Option<V>, Option<(K, NodeId)>) {
+        if level == 0 {
+            let leaf = self.leaf_mut(id);
+            let index = match leaf.keys().binary_search(&key) {
+                Ok(index) => return (Some(mem::replace(&mut leaf.values[index], value)), None),
+                Err(index) => index,
+            };
+            if !leaf.is_full() {
+                leaf.insert_at(index, key, value);
+                return (None, None);
+            }
+
+            // Choose the side by position so no comparison runs mid-split
+            let mid = CAP.div_ceil(2);
+            let mut right = leaf.split_off(mid);
+            if index <= mid {
+                leaf.insert_at(index, key, value);
+            } else {
+                right.insert_at(index - mid, key, value);
+            }
+            let separator = right.keys[0].clone();
+            let right_id = self.leaves.allocate(right);
+            self.leaf_mut(id).next = right_id;
+            return (None, Some((separator, right_id)));
+        }
+
+        let branch = self.branch(id);
+        let child_index = branch.child_index(&key);
+        let child = branch.children[child_index];
+        let (old_value, split) = self.insert_into(child, level - 1, key, value);
+        let Some((separator, new_child)) = split else {
+            return (old_value, None);
+        };
+
+        let branch = self.branch_mut(id);
+        if !branch.is_full() {
+            branch.insert_at(child_index, separator, new_child);
+            return (old_value, None);
+        }
+        let (promoted, right) = branch.split_insert(child_index, separator, new_child);
+        let right_id = self.branches.allocate(right);
+        (old_value, Some((promoted, right_id)))
+    }
+
+    /// Remove `key` below node `id` at `level`, rebalancing any child left
+    /// underfull on the way back up.
+    fn remove_from(&mut self, id: NodeId, level: usize, key: &K) -> Option<V> {
+        if level == 0 {
+            let leaf = self.leaf_mut(id);
+            let index = leaf.keys().binary_search(key).ok()?;
+            return Some(leaf.remove_at(index).1);
+        }
+
+        let branch = self.branch(id);
+        let child_index = branch.child_index(key);
+        let child = branch.children[child_index];
+        let removed = self.remove_from(child, level - 1, key)?;
+        if self.node_len(child, level - 1) < Self::min_len(level - 1) {
+            self.rebalance(id, child_index, level - 1);
+        }
+        Some(removed)
+    }
+
+    /// Entries in a leaf, or keys in a branch.
+    fn node_len(&self, id: NodeId, level: usize) -> usize {
+        if level == 0 {
+            self.leaf(id).len
+        } else {
+            self.branch(id).len
+        }
+    }
+
+    fn min_len(level: usize) -> usize {
+        if level == 0 {
+            CAP / 2
+        } else {
+            Self::MIN_BRANCH_KEYS
+        }
+    }
+
+    /// Refill the underfull child at `index` of `parent` by borrowing from a
+    /// sibling that can spare an entry, or else by merging with one.
+    fn rebalance(&mut self, parent: NodeId, index: usize, level: usize) {
+        let branch = self.branch(parent);
+        let child = branch.children[index];
+        let left = (index > 0).then(|| branch.children[index - 1]);
+        let right = (index < branch.len).then(|| branch.children[index + 1]);
+        let can_lend = |id: NodeId| self.node_len(id, level) > Self::min_len(level);
+
+        if let Some(left) = left.filter(|&left| can_lend(left)) {
+            self.move_right(parent, index - 1, left, child, level);
+        } else if let Some(right) = right.filter(|&right| can_lend(right)) {
+            self.move_left(parent, index, child, right, level);
+        } else if let Some(left) = left {
+            self.merge(parent, index - 1, left, child, level);
+        } else if let Some(right) = right {
+            self.merge(parent, index, child, right, level);
+        }
+    }
+
+    /// Move the last entry of `left` to the front of `right`, where the two
+    /// are separated by key `separator` of `parent`.
+    fn move_right(
+        &mut self,
+        parent: NodeId,
+        separator: usize,
+        left: NodeId,
+        right: NodeId,
+        level: usize,
+    ) {
+        if level == 0 {
+            let left_leaf = self.leaf_mut(left);
+            let (key, value) = left_leaf.remove_at(left_leaf.len - 1);
+            self.branch_mut(parent).keys[separator] = key.clone();
+            self.leaf_mut(right).insert_at(0, key, value);
+        } else {
+            let (key, child) = self.branch_mut(left).pop_back();
+            let old_separator = mem::replace(&mut self.branch_mut(parent).keys[separator], key);
+            self.branch_mut(right).push_front(old_separator, child);
+        }
+    }
+
+    /// Move the first entry of `right` to the end of `left`.
+    fn move_left(
+        &mut self,
+        parent: NodeId,
+        separator: usize,
+        left: NodeId,
+        right: NodeId,
+        level: usize,
+    ) {
+        if level == 0 {
+            let right_leaf = self.leaf_mut(right);
+            let (key, value) = right_leaf.remove_at(0);
+            let new_separator = right_leaf.keys[0].clone();
+            self.branch_mut(parent).keys[separator] = new_separator;
+            let left_leaf = self.leaf_mut(left);
+            left_leaf.insert_at(left_leaf.len, key, value);
+        } else {
+            let (key, child) = self.branch_mut(right).pop_front();
+            let old_separator = mem::replace(&mut self.branch_mut(parent).keys[separator], key);
+            self.branch_mut(left).push_back(old_separator, child);
+        }
+    }
+
+    /// Fold `right` into `left` and drop it, along with its separator, from
+    /// `parent`.
+    fn merge(
+        &mut self,
+        parent: NodeId,
+        separator: usize,
+        left: NodeId,
+        right: NodeId,
+        level: usize,
+    ) {
+        let (separator_key, _) = self.branch_mut(parent).remove_at(separator);
+        if level == 0 {
+            let mut right_leaf = self
+                .leaves
+                .deallocate(right)
+                .expect("leaf ids in the tree are live");
+            let left_leaf = self.leaf_mut(left);
+            left_leaf.append(&mut right_leaf);
+            left_leaf.next = right_leaf.next;
+        } else {
+            let mut right_branch = self
+                .branches
+                .deallocate(right)
+                .expect("branch ids in the tree are live");
+            self.branch_mut(left)
+                .append(separator_key, &mut right_branch);
+        }
+    }
+
+    /// Check the subtree at `id`, with every key in `lower..upper`, and
+    /// collect its leaves in order.
+    fn check_node(
+        &self,
+        id: NodeId,
+        level: usize,
+        lower: Option<&K>,
+        upper: Option<&K>,
+        is_root: bool,
+        leaves: &mut Vec<NodeId>,
+    ) -> bool {
+        let keys = if level == 0 {
+            self.leaves.get(id).map(InlineLeaf::keys)
+        } else {
+            self.branches.get(id).map(InlineBranch::keys)
+        };
+        let Some(keys) = keys else {
+            return false;
+        };
+
+        let sorted = keys.windows(2).all(|pair| pair[0] < pair[1]);
+        let in_bounds = keys
+            .first()
+            .is_none_or(|first| lower.is_none_or(|low| low <= first))
+            && keys
+                .last()
+                .is_none_or(|last| upper.is_none_or(|high| last < high));
+        let filled = is_root || keys.len() >= Self::min_len(level);
+        if !(sorted && in_bounds && filled) {
+            return false;
+        }
+        if level == 0 {
+            leaves.push(id);
+            return true;
+        }
+
+        let branch = self.branch(id);
+        (!is_root || branch.len > 0)
+            && (0..=branch.len).all(|i| {
+                let child_lower = if i == 0 {
+                    lower
+                } else {
+                    Some(&branch.keys[i - 1])
+                };
+                let child_upper = if i == branch.len {
+                    upper
+                } else {
+                    Some(&branch.keys[i])
+                };
+                self.check_node(
+                    branch.children[i],
+                    level - 1,
+                    child_lower,
+                    child_upper,
+                    false,
+                    leaves,
+                )
+            })
+    }
+}
+
+impl<K: Ord + Clone + Default, V: Default, const CAP: usize> Default for BPlusTreeMapN<K, V, CAP> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K: Ord + Clone + Default, V: Clone + Default, const CAP: usize> Clone
+    for BPlusTreeMapN<K, V, CAP>
+{
+    fn clone(&self) -> Self {
+        self.iter()
+            .map(|(key, value)| (key.clone(), value.clone()))
+            .collect()
+    }
+}
+
+impl<K, V, const CAP: usize> core::fmt::Debug for BPlusTreeMapN<K, V, CAP>
+where
+    K: Ord + Clone + Default + core::fmt::Debug,
+    V: Default + core::fmt::Debug,
+{
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+impl<K: Ord + Clone + Default, V: Default + PartialEq, const CAP: usize> PartialEq
+    for BPlusTreeMapN<K, V, CAP>
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.len == other.len && self.iter().eq(other.iter())
+    }
+}
+
+impl<K: Ord + Clone + Default, V: Default, const CAP: usize> FromIterator<(K, V)>
+    for BPlusTreeMapN<K, V, CAP>
+{
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        let mut tree = Self::new();
+        tree.extend(iter);
+        tree
+    }
+}
+
+impl<K: Ord + Clone + Default, V: Default, const CAP: usize> Extend<(K, V)>
+    for BPlusTreeMapN<K, V, CAP>
+{
+    fn extend<I: IntoIterator<Item = (K, V)>>(&mut self, iter: I) {
+        for (key, value) in iter {
+            self.insert(key, value);
+        }
+    }
+}
+
+/// Iterator over the entries of a [`BPlusTreeMapN`] in key order, optionally
+/// limited to a range. Created by [`BPlusTreeMapN::iter`] and
+/// [`BPlusTreeMapN::range`].
+pub struct IterN<'a, K, V, const CAP: usize> {
+    tree: &'a BPlusTreeMapN<K, V, CAP>,
+    leaf: NodeId,
+    index: usize,
+    end: Bound<K>,
+}
+
+impl<'a, K: Ord + Clone + Default, V: Default, const CAP: usize> Iterator for IterN<'a, K, V, CAP> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.leaf != NULL_NODE {
+            let leaf = self.tree.leaf(self.leaf);
+            if self.index < leaf.len {
+                let key = &leaf.keys[self.index];
+                let in_range = match &self.end {
+                    Bound::Included(end) => key <= end,
+                    Bound::Excluded(end) => key < end,
+                    Bound::Unbounded => true,
+                };
+                if !in_range {
+                    self.leaf = NULL_NODE;
+                    return None;
+                }
+                self.index += 1;
+                return Some((key, &leaf.values[self.index - 1]));
+            }
+            self.leaf = leaf.next;
+            self.index = 0;
+        }
+        None
+    }
+}
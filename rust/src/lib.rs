@@ -2,19 +2,72 @@
 //!
 //! This module provides a B+ tree data structure with a dictionary-like interface,
 //! supporting efficient insertion, deletion, lookup, and range queries.
-
-use std::marker::PhantomData;
-use std::ops::{Bound, RangeBounds};
+//!
+//! # `no_std`
+//!
+//! The crate only needs an allocator. Build with `default-features = false,
+//! features = ["alloc"]` to drop the `std` dependency; this removes the
+//! `std::error::Error` impl for [`BPlusTreeError`] and `print_node_chain`.
+//! The target needs pointer-width atomics for operation counters and `Arc`.
+
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
+
+#[cfg(not(feature = "alloc"))]
+compile_error!("bplustree requires the `alloc` feature (enabled by `std`)");
+
+extern crate alloc;
+
+use alloc::collections::{BinaryHeap, VecDeque};
+use alloc::sync::Arc;
+#[cfg(not(feature = "std"))]
+use alloc::{
+    boxed::Box,
+    format,
+    string::{String, ToString},
+    vec,
+    vec::Vec,
+};
+use core::borrow::Borrow;
+use core::cmp::Ordering;
+use core::marker::PhantomData;
+use core::ops::{Add, Bound, RangeBounds, Sub};
+use core::sync::atomic::{AtomicUsize, Ordering as AtomicOrdering};
 
 // Import our new modules
 mod arena;
+#[cfg(feature = "std")]
+mod codec;
+mod inline_map;
 mod macros;
+mod multimap;
+mod set;
+mod snapshot;
 
 pub use arena::{Arena, ArenaStats, NodeId as ArenaNodeId, NULL_NODE as ARENA_NULL_NODE};
+#[cfg(feature = "std")]
+pub use codec::BinaryCodec;
+pub use inline_map::{BPlusTreeMapN, IterN};
+pub use multimap::BPlusTreeMultiMap;
+pub use set::{BPlusTreeSet, Difference, Intersection, Union};
+pub use snapshot::Snapshot;
 
 // Constants
 const MIN_CAPACITY: usize = 4;
 
+/// Largest node capacity accepted by the constructors. Nodes this large
+/// already defeat the point of a B+ tree; anything bigger is almost always
+/// a bug and could otherwise request an absurd allocation.
+pub const MAX_CAPACITY: usize = 65536;
+
+/// Free arena slots required before automatic compaction may fire.
+///
+/// Compacting a handful of nodes costs more than the slack it reclaims, and
+/// in a small tree a single freed node can push the free ratio past any
+/// threshold, so below this many free slots auto compaction waits. Since
+/// compaction leaves no free slots, this also spaces successive
+/// compactions at least this many node frees apart.
+pub const AUTO_COMPACT_MIN_FREE_NODES: usize = 32;
+
 /// Node ID type for arena-based allocation
 pub type NodeId = u32;
 
@@ -52,6 +105,14 @@ impl BPlusTreeError {
         ))
     }
 
+    /// Create an InvalidCapacity error for a capacity above the maximum
+    pub fn capacity_too_large(capacity: usize, max_allowed: usize) -> Self {
+        Self::InvalidCapacity(format!(
+            "Capacity {} is invalid (maximum allowed: {})",
+            capacity, max_allowed
+        ))
+    }
+
     /// Create a DataIntegrityError with context
     pub fn data_integrity(context: &str, details: &str) -> Self {
         Self::DataIntegrityError(format!("{}: {}", context, details))
@@ -83,8 +144,8 @@ impl BPlusTreeError {
     }
 }
 
-impl std::fmt::Display for BPlusTreeError {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+impl core::fmt::Display for BPlusTreeError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         match self {
             BPlusTreeError::KeyNotFound => write!(f, "Key not found in tree"),
             BPlusTreeError::InvalidCapacity(msg) => write!(f, "Invalid capacity: {}", msg),
@@ -98,11 +159,19 @@ impl std::fmt::Display for BPlusTreeError {
     }
 }
 
+#[cfg(feature = "std")]
 impl std::error::Error for BPlusTreeError {}
 
 /// Internal result type for tree operations
 type TreeResult<T> = Result<T, BPlusTreeError>;
 
+/// Position of an entry in the leaf chain, as `(leaf_id, index)`.
+pub type LeafPosition = (NodeId, usize);
+
+/// Resolved range bounds: start position, whether to skip the first key
+/// (excluded start bound), and the end key with its inclusiveness.
+type ResolvedRange<K> = (Option<LeafPosition>, bool, Option<(K, bool)>);
+
 /// Public result type for tree operations that may fail
 pub type BTreeResult<T> = Result<T, BPlusTreeError>;
 
@@ -160,7 +229,10 @@ impl<T> BTreeResultExt<T> for Result<T, BPlusTreeError> {
         match self {
             Ok(value) => value,
             Err(e) => {
+                #[cfg(feature = "std")]
                 eprintln!("Warning: B+ Tree operation failed, using default: {}", e);
+                #[cfg(not(feature = "std"))]
+                let _ = e;
                 T::default()
             }
         }
@@ -176,8 +248,11 @@ impl<T> BTreeResultExt<T> for Result<T, BPlusTreeError> {
 ///
 /// # Type Parameters
 ///
-/// * `K` - Key type that must implement `Ord + Clone + Debug`
-/// * `V` - Value type that must implement `Clone + Debug`
+/// * `K` - Key type that must implement `Ord + Clone`
+/// * `V` - Value type that must implement `Clone`
+///
+/// `Debug` is only needed to format the tree itself or to render it with
+/// [`to_dot`](Self::to_dot), which requires `K: Debug`.
 ///
 /// # Examples
 ///
@@ -208,13 +283,17 @@ impl<T> BTreeResultExt<T> for Result<T, BPlusTreeError> {
 /// # Capacity Guidelines
 ///
 /// - Minimum capacity: 4 (enforced)
+/// - Maximum capacity: [`MAX_CAPACITY`] (enforced)
 /// - Recommended capacity: 16-128 depending on use case
 /// - Higher capacity = fewer tree levels but larger nodes
 /// - Lower capacity = more tree levels but smaller nodes
-#[derive(Debug)]
 pub struct BPlusTreeMap<K, V> {
-    /// Maximum number of keys per node.
-    capacity: usize,
+    /// Maximum number of keys per leaf node.
+    leaf_capacity: usize,
+    /// Maximum number of keys per branch node.
+    branch_capacity: usize,
+    /// Key ordering: `Ord` unless built with `with_comparator`.
+    order: KeyOrder<K>,
     /// The root node of the tree.
     root: NodeRef<K, V>,
 
@@ -223,6 +302,28 @@ pub struct BPlusTreeMap<K, V> {
     leaf_arena: Arena<LeafNode<K, V>>,
     /// Arena storage for branch nodes.
     branch_arena: Arena<BranchNode<K, V>>,
+
+    /// Value arithmetic for the branches' `subtree_sum`, set while range
+    /// sums are enabled.
+    sum_ops: Option<SumOps<V>>,
+
+    /// Operation counters, present only while instrumentation is enabled.
+    counters: Option<Box<OpCounterCells>>,
+
+    /// Free-slot ratio above which removals trigger `compact`, if enabled.
+    auto_compact_threshold: Option<f64>,
+
+    /// Sink that receives each successful insert and remove, if set.
+    op_log: Option<OpLogSink<K, V>>,
+
+    /// Whether `insert` and `remove` check the invariants after each call.
+    validation_mode: bool,
+
+    /// The most recent invariant violation found in validation mode.
+    last_error: Option<BPlusTreeError>,
+
+    /// Version stamp of the most recent write; see `changed_since`.
+    version: u64,
 }
 
 /// Node reference that can be either a leaf or branch node
@@ -245,6 +346,14 @@ impl<K, V> NodeRef<K, V> {
     pub fn is_leaf(&self) -> bool {
         matches!(self, NodeRef::Leaf(_, _))
     }
+
+    /// Reinterpret this reference for a tree with a different value type.
+    fn cast<V2>(self) -> NodeRef<K, V2> {
+        match self {
+            NodeRef::Leaf(id, _) => NodeRef::Leaf(id, PhantomData),
+            NodeRef::Branch(id, _) => NodeRef::Branch(id, PhantomData),
+        }
+    }
 }
 
 /// Node data that can be allocated in the arena after a split.
@@ -274,6 +383,199 @@ pub enum RemoveResult<V> {
     Updated(Option<V>, bool),
 }
 
+/// Snapshot of the work done by tree operations while counters are enabled.
+///
+/// See [`BPlusTreeMap::enable_counters`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct OpCounters {
+    /// Nodes fetched from the arenas.
+    pub node_visits: u64,
+    /// Key comparisons made while searching nodes during lookups,
+    /// insertions and removals.
+    pub comparisons: u64,
+    /// Leaf and branch splits.
+    pub splits: u64,
+    /// Leaf and branch merges.
+    pub merges: u64,
+}
+
+/// Approximate memory footprint of a tree.
+///
+/// See [`BPlusTreeMap::memory_stats`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct MemoryStats {
+    /// Leaf nodes currently allocated in the leaf arena.
+    pub leaf_nodes: usize,
+    /// Branch nodes currently allocated in the branch arena.
+    pub branch_nodes: usize,
+    /// Deallocated leaf arena slots waiting to be reused.
+    pub free_leaf_slots: usize,
+    /// Deallocated branch arena slots waiting to be reused.
+    pub free_branch_slots: usize,
+    /// Bytes used by arena slots plus the heap buffers of every node's
+    /// key, value and child vectors, including their spare capacity.
+    pub estimated_bytes: usize,
+}
+
+/// Smallest, largest and mean key count over a set of nodes.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct FillStats {
+    /// Fewest keys held by any node.
+    pub min: usize,
+    /// Most keys held by any node.
+    pub max: usize,
+    /// Mean keys per node, or 0.0 if there are no nodes.
+    pub avg: f64,
+}
+
+impl FillStats {
+    fn from_sizes(sizes: &[usize]) -> Self {
+        if sizes.is_empty() {
+            return Self::default();
+        }
+        Self {
+            min: sizes.iter().copied().min().unwrap_or(0),
+            max: sizes.iter().copied().max().unwrap_or(0),
+            avg: sizes.iter().sum::<usize>() as f64 / sizes.len() as f64,
+        }
+    }
+}
+
+/// Shape and occupancy of a tree.
+///
+/// See [`BPlusTreeMap::structure_report`].
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct StructureReport {
+    /// Leaf nodes reachable from the root.
+    pub leaf_count: usize,
+    /// Branch nodes reachable from the root.
+    pub branch_count: usize,
+    /// Number of levels, counting the leaf level.
+    pub height: usize,
+    /// Keys per leaf.
+    pub leaf_fill: FillStats,
+    /// Separator keys per branch.
+    pub branch_fill: FillStats,
+    /// Number of nodes on each level, root first.
+    pub nodes_per_level: Vec<usize>,
+}
+
+/// Live counters behind `OpCounters`; atomic so lookups can count through `&self`.
+///
+/// Pointer-width atomics keep the crate building on 32-bit targets that have
+/// no 64-bit atomics; the counts are widened to `u64` when read.
+#[derive(Debug, Default)]
+struct OpCounterCells {
+    node_visits: AtomicUsize,
+    comparisons: AtomicUsize,
+    splits: AtomicUsize,
+    merges: AtomicUsize,
+}
+
+/// A mutation reported to the sink installed with [`BPlusTreeMap::set_op_log`].
+///
+/// Applying the operations in order to an empty tree reproduces the tree
+/// that emitted them.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Operation<K, V> {
+    /// `key` was inserted with `value`, adding or replacing an entry.
+    Insert { key: K, value: V },
+    /// The entry for `key` was removed.
+    Remove { key: K },
+}
+
+/// Sink supplied to [`BPlusTreeMap::set_op_log`].
+type OpLogSink<K, V> = Box<dyn FnMut(&Operation<K, V>) + Send + Sync>;
+
+/// Value arithmetic captured by [`BPlusTreeMap::enable_range_sums`], so the
+/// branch sums can be kept up to date without an `Add` bound on every method.
+struct SumOps<V> {
+    zero: fn() -> V,
+    add: fn(&V, &V) -> V,
+}
+
+impl<V> Clone for SumOps<V> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<V> Copy for SumOps<V> {}
+
+impl<V> SumOps<V> {
+    /// Sum of the values held by one leaf.
+    fn sum_leaf<K>(&self, leaf: &LeafNode<K, V>) -> V {
+        leaf.values
+            .iter()
+            .fold((self.zero)(), |total, value| (self.add)(&total, value))
+    }
+}
+
+/// Comparator supplied to [`BPlusTreeMap::with_comparator`].
+type Comparator<K> = Arc<dyn Fn(&K, &K) -> Ordering + Send + Sync>;
+
+/// How a tree orders its keys: by `Ord`, or by a custom comparator.
+struct KeyOrder<K>(Option<Comparator<K>>);
+
+impl<K: Ord> KeyOrder<K> {
+    fn cmp(&self, a: &K, b: &K) -> Ordering {
+        match &self.0 {
+            Some(comparator) => comparator(a, b),
+            None => a.cmp(b),
+        }
+    }
+
+    /// Whether `key` lies past the end bound `end`.
+    fn beyond_end(&self, key: &K, end: &K, inclusive: bool) -> bool {
+        match self.cmp(key, end) {
+            Ordering::Less => false,
+            Ordering::Equal => !inclusive,
+            Ordering::Greater => true,
+        }
+    }
+}
+
+impl<K> Clone for KeyOrder<K> {
+    fn clone(&self) -> Self {
+        Self(self.0.clone())
+    }
+}
+
+impl<K> Default for KeyOrder<K> {
+    fn default() -> Self {
+        Self(None)
+    }
+}
+
+impl<K> core::fmt::Debug for KeyOrder<K> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str(if self.0.is_some() {
+            "Comparator"
+        } else {
+            "Ord"
+        })
+    }
+}
+
+/// Number of comparisons a binary search for `key` makes over `keys`.
+fn search_comparisons<K, F>(keys: &[K], key: &K, cmp: F) -> usize
+where
+    F: Fn(&K, &K) -> Ordering,
+{
+    let (mut low, mut high) = (0, keys.len());
+    let mut comparisons = 0;
+    while low < high {
+        let mid = low + (high - low) / 2;
+        comparisons += 1;
+        match cmp(&keys[mid], key) {
+            Ordering::Less => low = mid + 1,
+            Ordering::Greater => high = mid,
+            Ordering::Equal => break,
+        }
+    }
+    comparisons
+}
+
 impl<K: Ord + Clone, V: Clone> BPlusTreeMap<K, V> {
     // ============================================================================
     // CONSTRUCTION
@@ -283,7 +585,8 @@ impl<K: Ord + Clone, V: Clone> BPlusTreeMap<K, V> {
     ///
     /// # Arguments
     ///
-    /// * `capacity` - Maximum number of keys per node (minimum 4)
+    /// * `capacity` - Maximum number of keys per node (minimum 4, at most
+    ///   [`MAX_CAPACITY`])
     ///
     /// # Returns
     ///
@@ -298,25 +601,200 @@ impl<K: Ord + Clone, V: Clone> BPlusTreeMap<K, V> {
     /// assert!(tree.is_empty());
     /// ```
     pub fn new(capacity: usize) -> InitResult<Self> {
-        if capacity < MIN_CAPACITY {
-            return Err(BPlusTreeError::invalid_capacity(capacity, MIN_CAPACITY));
-        }
+        Self::new_with_capacities(capacity, capacity)
+    }
+
+    /// Create a B+ tree with separate leaf and branch node capacities.
+    ///
+    /// Leaves hold keys and values while branches hold keys and child IDs,
+    /// so the optimal fanout often differs between the two levels.
+    ///
+    /// # Arguments
+    ///
+    /// * `leaf_capacity` - Maximum number of keys per leaf node (minimum 4)
+    /// * `branch_capacity` - Maximum number of keys per branch node (minimum 4)
+    ///
+    /// Both capacities must also be at most [`MAX_CAPACITY`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bplustree::BPlusTreeMap;
+    ///
+    /// let mut tree = BPlusTreeMap::new_with_capacities(128, 8).unwrap();
+    /// tree.insert(1, "one");
+    /// assert_eq!(tree.get(&1), Some(&"one"));
+    /// ```
+    pub fn new_with_capacities(leaf_capacity: usize, branch_capacity: usize) -> InitResult<Self> {
+        Self::check_capacities(leaf_capacity, branch_capacity)?;
 
         // Initialize arena with the first leaf at id=0
         let mut leaf_arena = Arena::new();
-        let root_id = leaf_arena.allocate(LeafNode::new(capacity));
+        let root_id = leaf_arena.allocate(LeafNode::new(leaf_capacity));
 
         // Initialize branch arena (starts empty)
         let branch_arena = Arena::new();
 
         Ok(Self {
-            capacity,
+            leaf_capacity,
+            branch_capacity,
+            order: KeyOrder::default(),
             root: NodeRef::Leaf(root_id, PhantomData),
             leaf_arena,
             branch_arena,
+            sum_ops: None,
+            counters: None,
+            auto_compact_threshold: None,
+            op_log: None,
+            validation_mode: false,
+            last_error: None,
+            version: 0,
         })
     }
 
+    /// Validate leaf and branch capacities against `MIN_CAPACITY` and
+    /// `MAX_CAPACITY`.
+    fn check_capacities(leaf_capacity: usize, branch_capacity: usize) -> InitResult<()> {
+        if leaf_capacity < MIN_CAPACITY {
+            return Err(BPlusTreeError::invalid_capacity(
+                leaf_capacity,
+                MIN_CAPACITY,
+            ));
+        }
+        if branch_capacity < MIN_CAPACITY {
+            return Err(BPlusTreeError::invalid_capacity(
+                branch_capacity,
+                MIN_CAPACITY,
+            ));
+        }
+        if let Some(capacity) = [leaf_capacity, branch_capacity]
+            .into_iter()
+            .find(|&capacity| capacity > MAX_CAPACITY)
+        {
+            return Err(BPlusTreeError::capacity_too_large(capacity, MAX_CAPACITY));
+        }
+        Ok(())
+    }
+
+    /// Create a B+ tree that orders keys with `cmp` instead of `Ord`.
+    ///
+    /// Every search, insertion, removal, range bound and invariant check
+    /// goes through `cmp`, which must be a consistent total order. `K` still
+    /// has to implement `Ord` to satisfy the map's bounds, but that ordering
+    /// is never used. Trees derived from this one, such as clones and
+    /// `split_off` halves, keep the comparator.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bplustree::BPlusTreeMap;
+    ///
+    /// let mut tree = BPlusTreeMap::with_comparator(4, |a: &i32, b: &i32| b.cmp(a)).unwrap();
+    /// for i in 0..5 {
+    ///     tree.insert(i, i * 10);
+    /// }
+    /// assert_eq!(tree.keys().copied().collect::<Vec<_>>(), vec![4, 3, 2, 1, 0]);
+    /// assert_eq!(tree.range(3..=1).count(), 3);
+    /// ```
+    pub fn with_comparator<C>(capacity: usize, cmp: C) -> InitResult<Self>
+    where
+        C: Fn(&K, &K) -> Ordering + Send + Sync + 'static,
+    {
+        let mut tree = Self::new(capacity)?;
+        tree.order = KeyOrder(Some(Arc::new(cmp)));
+        Ok(tree)
+    }
+
+    /// An empty tree with the same capacities, key ordering and settings as
+    /// `self`: range sums, the auto-compaction threshold and validation mode.
+    fn empty_like(&self) -> Self {
+        let mut tree = Self::new_with_capacities(self.leaf_capacity, self.branch_capacity)
+            .expect("capacities of an existing tree are valid");
+        tree.order = self.order.clone();
+        tree.version = self.version;
+        tree.sum_ops = self.sum_ops;
+        tree.auto_compact_threshold = self.auto_compact_threshold;
+        tree.validation_mode = self.validation_mode;
+        tree
+    }
+
+    /// Compare two keys using the tree's ordering.
+    fn compare(&self, a: &K, b: &K) -> Ordering {
+        self.order.cmp(a, b)
+    }
+
+    /// Create a B+ tree from strictly increasing key-value pairs.
+    ///
+    /// The tree is built bottom-up: leaves are packed to capacity and
+    /// chained, then branch levels are built over them. This is much faster
+    /// than inserting one entry at a time and yields a denser tree.
+    ///
+    /// # Errors
+    ///
+    /// Returns `InvalidCapacity` if the capacity is too small, or
+    /// `DataIntegrityError` if the keys are not strictly increasing.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bplustree::BPlusTreeMap;
+    ///
+    /// let tree = BPlusTreeMap::bulk_load(16, (0..1000).map(|i| (i, i * 2))).unwrap();
+    /// assert_eq!(tree.len(), 1000);
+    /// assert_eq!(tree.get(&500), Some(&1000));
+    ///
+    /// assert!(BPlusTreeMap::bulk_load(16, vec![(2, 'b'), (1, 'a')]).is_err());
+    /// ```
+    pub fn bulk_load<I>(capacity: usize, sorted: I) -> InitResult<Self>
+    where
+        I: IntoIterator<Item = (K, V)>,
+    {
+        let mut tree = Self::new(capacity)?;
+        tree.build_from_sorted(sorted)?;
+        Ok(tree)
+    }
+
+    /// Create a B+ tree by consuming a vector of pairs sorted by key.
+    ///
+    /// The owned counterpart to [`bulk_load`](Self::bulk_load): the whole
+    /// vector is checked before any node is built, then each pair is moved
+    /// straight into a packed leaf without being cloned.
+    ///
+    /// # Errors
+    ///
+    /// Returns `InvalidCapacity` if the capacity is too small, or
+    /// `DataIntegrityError` naming the first offending index if the keys are
+    /// not strictly increasing (which includes duplicates).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bplustree::BPlusTreeMap;
+    ///
+    /// let pairs: Vec<_> = (0..100).map(|i| (i, i.to_string())).collect();
+    /// let tree = BPlusTreeMap::from_sorted_vec(16, pairs).unwrap();
+    /// assert_eq!(tree.get(&42).map(String::as_str), Some("42"));
+    ///
+    /// assert!(BPlusTreeMap::from_sorted_vec(16, vec![(1, 'a'), (1, 'b')]).is_err());
+    /// ```
+    pub fn from_sorted_vec(capacity: usize, sorted: Vec<(K, V)>) -> InitResult<Self> {
+        let mut tree = Self::new(capacity)?;
+        if let Some(index) = sorted
+            .windows(2)
+            .position(|pair| tree.compare(&pair[0].0, &pair[1].0).is_ge())
+        {
+            return Err(BPlusTreeError::data_integrity(
+                "From sorted vec",
+                &format!(
+                    "key at index {} is not greater than the key before it",
+                    index + 1
+                ),
+            ));
+        }
+        tree.build_from_sorted(sorted)?;
+        Ok(tree)
+    }
+
     // ============================================================================
     // GET OPERATIONS
     // ============================================================================
@@ -346,11 +824,113 @@ impl<K: Ord + Clone, V: Clone> BPlusTreeMap<K, V> {
         self.get_recursive(node, key)
     }
 
+    /// Get the stored key and its value for a key.
+    ///
+    /// The returned key is the one held by the tree, which matters when the
+    /// key type carries data that its ordering ignores.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bplustree::BPlusTreeMap;
+    ///
+    /// let mut tree = BPlusTreeMap::new(16).unwrap();
+    /// tree.insert(1, "one");
+    /// assert_eq!(tree.get_key_value(&1), Some((&1, &"one")));
+    /// assert_eq!(tree.get_key_value(&2), None);
+    /// ```
+    pub fn get_key_value(&self, key: &K) -> Option<(&K, &V)> {
+        let leaf = self.get_leaf(self.find_leaf_for_key(key)?)?;
+        self.record_comparisons(&leaf.keys, key);
+        let index = leaf.keys.binary_search_by(|k| self.compare(k, key)).ok()?;
+        Some((&leaf.keys[index], &leaf.values[index]))
+    }
+
     /// Check if key exists in the tree.
     pub fn contains_key(&self, key: &K) -> bool {
         self.get(key).is_some()
     }
 
+    /// Check if any entry holds `value`.
+    ///
+    /// Values are not indexed, so this is a linear O(n) scan of the leaves
+    /// that stops at the first match.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bplustree::BPlusTreeMap;
+    ///
+    /// let mut tree = BPlusTreeMap::new(4).unwrap();
+    /// tree.insert(1, "one");
+    /// assert!(tree.contains_value(&"one"));
+    /// assert!(!tree.contains_value(&"two"));
+    /// ```
+    pub fn contains_value(&self, value: &V) -> bool
+    where
+        V: PartialEq,
+    {
+        self.values().any(|v| v == value)
+    }
+
+    /// Check that every key in `other` is also a key of `self`.
+    ///
+    /// Both leaf chains are walked once in step, so this is O(n + m) rather
+    /// than one O(log n) lookup per key of `other`. Values are ignored, and
+    /// keys are compared with `self`'s ordering; both trees must be sorted
+    /// the same way.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bplustree::BPlusTreeMap;
+    ///
+    /// let all: BPlusTreeMap<i32, ()> = (0..10).map(|i| (i, ())).collect();
+    /// let evens: BPlusTreeMap<i32, ()> = (0..10).step_by(2).map(|i| (i, ())).collect();
+    /// assert!(all.contains_all_keys(&evens));
+    /// assert!(!evens.contains_all_keys(&all));
+    /// ```
+    pub fn contains_all_keys(&self, other: &BPlusTreeMap<K, V>) -> bool {
+        if other.len() > self.len() {
+            return false;
+        }
+
+        let mut ours = self.keys().peekable();
+        other.keys().all(|key| {
+            while ours
+                .next_if(|candidate| self.compare(candidate, key).is_lt())
+                .is_some()
+            {}
+            ours.next_if(|candidate| self.compare(candidate, key).is_eq())
+                .is_some()
+        })
+    }
+
+    /// Returns every key whose value equals `value`, in key order.
+    ///
+    /// Values are not indexed, so this is a linear O(n) scan of every leaf.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bplustree::BPlusTreeMap;
+    ///
+    /// let mut tree = BPlusTreeMap::new(4).unwrap();
+    /// tree.insert(3, "odd");
+    /// tree.insert(2, "even");
+    /// tree.insert(1, "odd");
+    /// assert_eq!(tree.find_keys_by_value(&"odd"), vec![&1, &3]);
+    /// ```
+    pub fn find_keys_by_value(&self, value: &V) -> Vec<&K>
+    where
+        V: PartialEq,
+    {
+        self.items()
+            .filter(|(_, v)| *v == value)
+            .map(|(k, _)| k)
+            .collect()
+    }
+
     /// Get value for a key with default.
     pub fn get_or_default<'a>(&'a self, key: &K, default: &'a V) -> &'a V {
         self.get(key).unwrap_or(default)
@@ -362,46 +942,300 @@ impl<K: Ord + Clone, V: Clone> BPlusTreeMap<K, V> {
         self.get(key).ok_or(BPlusTreeError::KeyNotFound)
     }
 
-    /// Get a mutable reference to the value for a key.
-    pub fn get_mut(&mut self, key: &K) -> Option<&mut V> {
-        let root = self.root.clone();
-        self.get_mut_recursive(&root, key)
-    }
-
-    // ============================================================================
-    // HELPERS FOR GET OPERATIONS
-    // ============================================================================
-
-    fn get_recursive<'a>(&'a self, node: &'a NodeRef<K, V>, key: &K) -> Option<&'a V> {
-        match node {
-            NodeRef::Leaf(id, _) => self.get_leaf(*id).and_then(|leaf| leaf.get(key)),
-            NodeRef::Branch(id, _) => self
-                .get_branch(*id)
-                .and_then(|branch| branch.get_child(key))
-                .and_then(|child| self.get_recursive(child, key)),
-        }
-    }
-
-    /// Get mutable reference recursively
-    fn get_mut_recursive(&mut self, node: &NodeRef<K, V>, key: &K) -> Option<&mut V> {
-        match node {
-            NodeRef::Leaf(id, _) => self.get_leaf_mut(*id).and_then(|leaf| leaf.get_mut(key)),
-            NodeRef::Branch(id, _) => {
-                let (_child_index, child_ref) = self.get_child_for_key(*id, key)?;
-                self.get_mut_recursive(&child_ref, key)
+    /// Look up a key, telling a missing key apart from a broken tree.
+    ///
+    /// Returns `Ok(None)` if the key is absent. Unlike [`get`](Self::get),
+    /// which treats a dangling node reference as a miss, this returns
+    /// `Err(CorruptedTree)` if the descent reaches a node that is missing
+    /// from its arena or a branch without the child the key routes to.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bplustree::BPlusTreeMap;
+    ///
+    /// let mut tree = BPlusTreeMap::new(16).unwrap();
+    /// tree.insert(1, "one");
+    /// assert_eq!(tree.get_checked(&1), Ok(Some(&"one")));
+    /// assert_eq!(tree.get_checked(&2), Ok(None));
+    /// ```
+    pub fn get_checked(&self, key: &K) -> BTreeResult<Option<&V>> {
+        let mut node = &self.root;
+        loop {
+            match node {
+                NodeRef::Leaf(id, _) => {
+                    let leaf = self.get_leaf(*id).ok_or_else(|| {
+                        BPlusTreeError::corrupted_tree(
+                            "Leaf",
+                            &format!("node {} is missing from the arena", id),
+                        )
+                    })?;
+                    self.record_comparisons(&leaf.keys, key);
+                    return Ok(leaf.get_by(key, |a, b| self.compare(a, b)));
+                }
+                NodeRef::Branch(id, _) => {
+                    let branch = self.get_branch(*id).ok_or_else(|| {
+                        BPlusTreeError::corrupted_tree(
+                            "Branch",
+                            &format!("node {} is missing from the arena", id),
+                        )
+                    })?;
+                    self.record_comparisons(&branch.keys, key);
+                    let index = branch.find_child_index_by(key, |a, b| self.compare(a, b));
+                    node = branch.children.get(index).ok_or_else(|| {
+                        BPlusTreeError::corrupted_tree(
+                            "Branch",
+                            &format!("node {} has no child at index {}", id, index),
+                        )
+                    })?;
+                }
             }
         }
     }
 
-    /// Helper to get child info for a key in a branch
-    fn get_child_for_key(&self, branch_id: NodeId, key: &K) -> Option<(usize, NodeRef<K, V>)> {
-        let branch = self.get_branch(branch_id)?;
-        let child_index = branch.find_child_index(key);
-        branch
-            .children
-            .get(child_index)
-            .cloned()
-            .map(|child| (child_index, child))
+    /// Get a mutable reference to the value for a key.
+    ///
+    /// The entry is stamped as written, whether or not the value is then
+    /// changed; see [`changed_since`](Self::changed_since).
+    pub fn get_mut(&mut self, key: &K) -> Option<&mut V> {
+        let (leaf_id, index) = self.find_entry_position(key)?;
+        self.stamped_value_mut(leaf_id, index)
+    }
+
+    /// Get mutable references to the values of several distinct keys at once.
+    ///
+    /// Returns `None` if any key is missing or if two of the keys are equal,
+    /// since that would hand out aliasing references.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bplustree::BPlusTreeMap;
+    ///
+    /// let mut tree = BPlusTreeMap::new(4).unwrap();
+    /// for i in 0..20 {
+    ///     tree.insert(i, i * 10);
+    /// }
+    /// if let Some([a, b]) = tree.get_many_mut([&2, &17]) {
+    ///     std::mem::swap(a, b);
+    /// }
+    /// assert_eq!(tree.get(&2), Some(&170));
+    /// assert_eq!(tree.get(&17), Some(&20));
+    /// assert!(tree.get_many_mut([&1, &1]).is_none());
+    /// assert!(tree.get_many_mut([&1, &99]).is_none());
+    /// ```
+    pub fn get_many_mut<const N: usize>(&mut self, keys: [&K; N]) -> Option<[&mut V; N]> {
+        let mut positions = [(NULL_NODE, 0); N];
+        for (position, key) in positions.iter_mut().zip(keys) {
+            let leaf_id = self.find_leaf_for_key(key)?;
+            let leaf = self.get_leaf(leaf_id)?;
+            let index = leaf.keys.binary_search_by(|k| self.compare(k, key)).ok()?;
+            *position = (leaf_id, index);
+        }
+        if (1..N).any(|i| positions[..i].contains(&positions[i])) {
+            return None;
+        }
+        let version = self.next_version();
+        for &(leaf_id, index) in &positions {
+            if let Some(leaf) = self.leaf_arena.get_mut(leaf_id) {
+                leaf.versions[index] = version;
+            }
+        }
+
+        let mut leaf_ids: Vec<NodeId> = positions.iter().map(|(leaf_id, _)| *leaf_id).collect();
+        leaf_ids.sort_unstable();
+        leaf_ids.dedup();
+
+        // Split each leaf's values into disjoint references, one per position
+        let mut values: [Option<&mut V>; N] = core::array::from_fn(|_| None);
+        for (leaf_id, leaf) in leaf_ids.iter().zip(self.leaves_disjoint_mut(&leaf_ids)) {
+            for (index, value) in leaf.values.iter_mut().enumerate() {
+                if let Some(slot) = positions.iter().position(|p| *p == (*leaf_id, index)) {
+                    values[slot] = Some(value);
+                }
+            }
+        }
+        if values.iter().any(Option::is_none) {
+            return None;
+        }
+        Some(values.map(|value| value.expect("every slot was filled")))
+    }
+
+    /// Gets the entry for `key` for in-place manipulation.
+    ///
+    /// The tree is descended once; an occupied entry remembers the leaf and
+    /// slot holding the key, so reading or updating it needs no second lookup.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bplustree::BPlusTreeMap;
+    ///
+    /// let mut counts = BPlusTreeMap::new(16).unwrap();
+    /// for word in ["a", "b", "a"] {
+    ///     *counts.entry(word).or_insert(0) += 1;
+    /// }
+    /// assert_eq!(counts.get(&"a"), Some(&2));
+    /// assert_eq!(counts.get(&"b"), Some(&1));
+    /// ```
+    pub fn entry(&mut self, key: K) -> Entry<'_, K, V> {
+        match self.find_entry_slot(&key) {
+            Some((leaf_id, Ok(index))) => Entry::Occupied(OccupiedEntry {
+                tree: self,
+                leaf_id,
+                index,
+            }),
+            Some((leaf_id, Err(index))) => Entry::Vacant(VacantEntry {
+                tree: self,
+                key,
+                leaf_id,
+                index,
+            }),
+            None => Entry::Vacant(VacantEntry {
+                tree: self,
+                key,
+                leaf_id: NULL_NODE,
+                index: 0,
+            }),
+        }
+    }
+
+    /// Returns a mutable reference to the value for `key`, first inserting
+    /// the result of `default` if the key is absent.
+    ///
+    /// A shorthand for `entry(key).or_insert_with(default)` that descends
+    /// the tree once whether or not the key is present. `default` is only
+    /// called when it is absent.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bplustree::BPlusTreeMap;
+    ///
+    /// let mut counts = BPlusTreeMap::new(4).unwrap();
+    /// for word in ["a", "b", "a"] {
+    ///     *counts.get_or_insert_with(word, || 0) += 1;
+    /// }
+    /// assert_eq!(counts.get(&"a"), Some(&2));
+    /// assert_eq!(counts.get(&"b"), Some(&1));
+    /// ```
+    pub fn get_or_insert_with<F: FnOnce() -> V>(&mut self, key: K, default: F) -> &mut V {
+        let order = self.order.clone();
+        let version = self.next_version();
+        // Leaf and slot of the key, plus the leaf's length if it was inserted
+        let mut slot = None;
+        self.insert_with(key, |leaf_id, leaf, key| {
+            match leaf.keys.binary_search_by(|k| order.cmp(k, &key)) {
+                Ok(index) => {
+                    leaf.versions[index] = version;
+                    slot = Some((leaf_id, index, None));
+                    InsertResult::Updated(None)
+                }
+                Err(index) => {
+                    let result = leaf.insert_absent(index, key, default(), version);
+                    slot = Some((leaf_id, index, Some(leaf.keys.len())));
+                    result
+                }
+            }
+        });
+        match slot.expect("insert_with reaches a leaf") {
+            (leaf_id, index, None) => self
+                .writable_value(leaf_id, index)
+                .expect("insert_with reports a live leaf"),
+            (leaf_id, index, Some(leaf_len)) => self.inserted_value_mut(leaf_id, index, leaf_len),
+        }
+    }
+
+    /// Locate the leaf and slot holding `key`, if present.
+    fn find_entry_position(&self, key: &K) -> Option<(NodeId, usize)> {
+        let (leaf_id, slot) = self.find_entry_slot(key)?;
+        Some((leaf_id, slot.ok()?))
+    }
+
+    /// Locate the leaf responsible for `key` and the key's slot in it:
+    /// `Ok(index)` if present, or `Err(index)` where it would be inserted.
+    fn find_entry_slot(&self, key: &K) -> Option<(NodeId, Result<usize, usize>)> {
+        let leaf_id = self.find_leaf_for_key(key)?;
+        let leaf = self.get_leaf(leaf_id)?;
+        self.record_comparisons(&leaf.keys, key);
+        Some((
+            leaf_id,
+            leaf.keys.binary_search_by(|k| self.compare(k, key)),
+        ))
+    }
+
+    /// Finish inserting a new entry that `insert_absent` put at `index` of
+    /// `leaf_id`, leaving that leaf `leaf_len` long: log the insert and
+    /// return the value without descending again.
+    ///
+    /// If the leaf split and the entry moved right, it is in the leaf's new
+    /// successor in the chain.
+    fn inserted_value_mut(&mut self, leaf_id: NodeId, index: usize, leaf_len: usize) -> &mut V {
+        let (leaf_id, index) = if index < leaf_len {
+            (leaf_id, index)
+        } else {
+            let next = self
+                .get_leaf(leaf_id)
+                .expect("insert_with reports a live leaf")
+                .next;
+            (next, index - leaf_len)
+        };
+        if self.op_log.is_some() {
+            let leaf = self.get_leaf(leaf_id).expect("the split leaf is linked");
+            let operation = Operation::Insert {
+                key: leaf.keys[index].clone(),
+                value: leaf.values[index].clone(),
+            };
+            self.log_operation(|| operation);
+        }
+        self.validate_if_enabled();
+        self.writable_value(leaf_id, index)
+            .expect("the split leaf is linked")
+    }
+
+    // ============================================================================
+    // HELPERS FOR GET OPERATIONS
+    // ============================================================================
+
+    fn get_recursive<'a>(&'a self, node: &'a NodeRef<K, V>, key: &K) -> Option<&'a V> {
+        match node {
+            NodeRef::Leaf(id, _) => self.get_leaf(*id).and_then(|leaf| {
+                self.record_comparisons(&leaf.keys, key);
+                leaf.get_by(key, |a, b| self.compare(a, b))
+            }),
+            NodeRef::Branch(id, _) => self
+                .get_branch(*id)
+                .and_then(|branch| {
+                    self.record_comparisons(&branch.keys, key);
+                    branch
+                        .children
+                        .get(branch.find_child_index_by(key, |a, b| self.compare(a, b)))
+                })
+                .and_then(|child| self.get_recursive(child, key)),
+        }
+    }
+
+    /// Descend to the leaf responsible for `key`.
+    fn find_leaf_for_key(&self, key: &K) -> Option<NodeId> {
+        let mut node = self.root.clone();
+        loop {
+            match node {
+                NodeRef::Leaf(id, _) => return Some(id),
+                NodeRef::Branch(id, _) => node = self.get_child_for_key(id, key)?.1,
+            }
+        }
+    }
+
+    /// Helper to get child info for a key in a branch
+    fn get_child_for_key(&self, branch_id: NodeId, key: &K) -> Option<(usize, NodeRef<K, V>)> {
+        let branch = self.get_branch(branch_id)?;
+        self.record_comparisons(&branch.keys, key);
+        let child_index = branch.find_child_index_by(key, |a, b| self.compare(a, b));
+        branch
+            .children
+            .get(child_index)
+            .cloned()
+            .map(|child| (child_index, child))
     }
 
     /// Helper to check if a node is underfull
@@ -485,26 +1319,173 @@ impl<K: Ord + Clone, V: Clone> BPlusTreeMap<K, V> {
     /// assert_eq!(tree.insert(1, "second"), Some("first"));
     /// ```
     pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        let logged = self.op_log.is_some().then(|| Operation::Insert {
+            key: key.clone(),
+            value: value.clone(),
+        });
+        let order = self.order.clone();
+        let version = self.next_version();
+        let old_value = self.insert_with(key, move |_, leaf, key| {
+            leaf.insert_stamped_by(key, value, version, |a, b| order.cmp(a, b))
+        });
+        if let Some(operation) = logged {
+            self.log_operation(|| operation);
+        }
+        self.validate_if_enabled();
+        old_value
+    }
+
+    /// Insert a key-value pair only if the key is not already present.
+    ///
+    /// On a collision the stored value is left untouched and the rejected
+    /// pair is handed back, so nothing is lost. The tree is descended once.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bplustree::BPlusTreeMap;
+    ///
+    /// let mut tree = BPlusTreeMap::new(16).unwrap();
+    /// assert_eq!(tree.insert_if_absent(1, "first"), Ok(()));
+    /// assert_eq!(tree.insert_if_absent(1, "second"), Err((1, "second")));
+    /// assert_eq!(tree.get(&1), Some(&"first"));
+    /// ```
+    pub fn insert_if_absent(&mut self, key: K, value: V) -> Result<(), (K, V)> {
+        let order = self.order.clone();
+        let version = self.next_version();
+        let logging = self.op_log.is_some();
+        let mut logged = None;
+        let mut rejected = None;
+        self.insert_with(key, |_, leaf, key| {
+            match leaf.keys.binary_search_by(|k| order.cmp(k, &key)) {
+                Ok(_) => {
+                    rejected = Some((key, value));
+                    InsertResult::Updated(None)
+                }
+                Err(_) => {
+                    if logging {
+                        logged = Some(Operation::Insert {
+                            key: key.clone(),
+                            value: value.clone(),
+                        });
+                    }
+                    leaf.insert_stamped_by(key, value, version, |a, b| order.cmp(a, b))
+                }
+            }
+        });
+        if let Some(operation) = logged {
+            self.log_operation(|| operation);
+        }
+        self.validate_if_enabled();
+        rejected.map_or(Ok(()), Err)
+    }
+
+    /// Insert `value` if `key` is absent, or fold it into the stored value
+    /// with `combine(existing, value)` if present.
+    ///
+    /// Unlike going through the entry API, no default value is constructed
+    /// and the tree is descended once either way.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bplustree::BPlusTreeMap;
+    ///
+    /// let mut totals = BPlusTreeMap::new(16).unwrap();
+    /// for (account, amount) in [("a", 5), ("b", 1), ("a", 7)] {
+    ///     totals.merge_with(account, amount, |total, amount| *total += amount);
+    /// }
+    /// assert_eq!(totals.get(&"a"), Some(&12));
+    /// assert_eq!(totals.get(&"b"), Some(&1));
+    /// ```
+    pub fn merge_with<F>(&mut self, key: K, value: V, combine: F)
+    where
+        F: FnOnce(&mut V, V),
+    {
+        let order = self.order.clone();
+        let version = self.next_version();
+        let logging = self.op_log.is_some();
+        let mut logged = None;
+        self.insert_with(key, |_, leaf, key| {
+            match leaf.keys.binary_search_by(|k| order.cmp(k, &key)) {
+                Ok(index) => {
+                    combine(&mut leaf.values[index], value);
+                    leaf.versions[index] = version;
+                    if logging {
+                        logged = Some(Operation::Insert {
+                            key,
+                            value: leaf.values[index].clone(),
+                        });
+                    }
+                    InsertResult::Updated(None)
+                }
+                Err(_) => {
+                    if logging {
+                        logged = Some(Operation::Insert {
+                            key: key.clone(),
+                            value: value.clone(),
+                        });
+                    }
+                    leaf.insert_stamped_by(key, value, version, |a, b| order.cmp(a, b))
+                }
+            }
+        });
+        if let Some(operation) = logged {
+            self.log_operation(|| operation);
+        }
+        self.validate_if_enabled();
+    }
+
+    // ============================================================================
+    // HELPERS FOR INSERT OPERATIONS
+    // ============================================================================
+
+    /// Descend once to the leaf responsible for `key`, apply `leaf_op` to it
+    /// and its ID, and propagate any resulting split up to the root.
+    ///
+    /// Running out of node IDs panics, like a `Vec` exceeding its maximum
+    /// capacity; use `try_insert` to get an `AllocationError` instead. Any
+    /// other internal error fails a debug assertion and is otherwise ignored.
+    fn insert_with<F>(&mut self, key: K, leaf_op: F) -> Option<V>
+    where
+        F: FnOnce(NodeId, &mut LeafNode<K, V>, K) -> InsertResult<K, V>,
+    {
+        let result = self.try_insert_with(key, leaf_op);
+        if let Err(error @ BPlusTreeError::AllocationError(_)) = &result {
+            panic!("{}", error);
+        }
+        debug_assert!(
+            result.is_ok(),
+            "BPlusTree internal error during insert: {:?}",
+            result.as_ref().err()
+        );
+        result.unwrap_or(None)
+    }
+
+    /// Like [`insert_with`](Self::insert_with), but surfaces internal errors.
+    fn try_insert_with<F>(&mut self, key: K, leaf_op: F) -> BTreeResult<Option<V>>
+    where
+        F: FnOnce(NodeId, &mut LeafNode<K, V>, K) -> InsertResult<K, V>,
+    {
+        self.ensure_room_for_insert()?;
+
         // Use insert_recursive to handle the insertion
-        let result = self.insert_recursive(&self.root.clone(), key, value);
+        let result = self.insert_recursive(&self.root.clone(), key, leaf_op);
 
-        match result {
+        let old_value = match result {
             InsertResult::Updated(old_value) => old_value,
-            InsertResult::Error(_error) => {
-                // Log the error but maintain API compatibility
-                // This should never happen with correct split logic
-                eprintln!("BPlusTree internal error during insert - data integrity violation");
-                None
-            }
+            InsertResult::Error(error) => return Err(error),
             InsertResult::Split {
                 old_value,
                 new_node_data,
                 separator_key,
             } => {
+                self.record_split();
+
                 // Root split - need to create a new root
                 let new_node_ref = match new_node_data {
                     SplitNodeData::Leaf(new_leaf_data) => {
-                        let new_id = self.allocate_leaf(new_leaf_data);
+                        let new_id = self.allocate_leaf(new_leaf_data)?;
 
                         // Update linked list pointers for root leaf split
                         if let Some(leaf) = matches!(&self.root, NodeRef::Leaf(_, _))
@@ -517,34 +1498,31 @@ impl<K: Ord + Clone, V: Clone> BPlusTreeMap<K, V> {
                         NodeRef::Leaf(new_id, PhantomData)
                     }
                     SplitNodeData::Branch(new_branch_data) => {
-                        let new_id = self.allocate_branch(new_branch_data);
+                        let new_id = self.allocate_branch(new_branch_data)?;
                         NodeRef::Branch(new_id, PhantomData)
                     }
                 };
 
                 // Create new root with the split nodes
                 let new_root = self.new_root(new_node_ref, separator_key);
-                let root_id = self.allocate_branch(new_root);
+                let root_id = self.allocate_branch(new_root)?;
                 self.root = NodeRef::Branch(root_id, PhantomData);
 
                 old_value
             }
-        }
+        };
+        Ok(old_value)
     }
 
-    // ============================================================================
-    // HELPERS FOR INSERT OPERATIONS
-    // ============================================================================
-
     /// New roots are the only BranchNodes allowed to remain underfull
     fn new_root(&mut self, new_node: NodeRef<K, V>, separator_key: K) -> BranchNode<K, V> {
-        let mut new_root = BranchNode::new(self.capacity);
+        let mut new_root = BranchNode::new(self.branch_capacity);
         new_root.keys.push(separator_key);
 
         // Move the current root to be the left child
         // Use a dummy NodeRef with NULL_NODE to avoid arena allocation
         let dummy = NodeRef::Leaf(NULL_NODE, PhantomData);
-        let old_root = std::mem::replace(&mut self.root, dummy);
+        let old_root = core::mem::replace(&mut self.root, dummy);
 
         new_root.children.push(old_root);
         new_root.children.push(new_node);
@@ -553,11 +1531,21 @@ impl<K: Ord + Clone, V: Clone> BPlusTreeMap<K, V> {
     }
 
     /// Recursively insert a key with proper arena access.
-    fn insert_recursive(&mut self, node: &NodeRef<K, V>, key: K, value: V) -> InsertResult<K, V> {
+    fn insert_recursive<F>(
+        &mut self,
+        node: &NodeRef<K, V>,
+        key: K,
+        leaf_op: F,
+    ) -> InsertResult<K, V>
+    where
+        F: FnOnce(NodeId, &mut LeafNode<K, V>, K) -> InsertResult<K, V>,
+    {
         match node {
-            NodeRef::Leaf(id, _) => self
-                .get_leaf_mut(*id)
-                .map_or(InsertResult::Updated(None), |leaf| leaf.insert(key, value)),
+            NodeRef::Leaf(id, _) => {
+                self.record_leaf_comparisons(*id, &key);
+                self.get_leaf_mut(*id)
+                    .map_or(InsertResult::Updated(None), |leaf| leaf_op(*id, leaf, key))
+            }
             NodeRef::Branch(id, _) => {
                 let id = *id;
 
@@ -568,10 +1556,10 @@ impl<K: Ord + Clone, V: Clone> BPlusTreeMap<K, V> {
                 };
 
                 // Recursively insert
-                let child_result = self.insert_recursive(&child_ref, key, value);
+                let child_result = self.insert_recursive(&child_ref, key, leaf_op);
 
                 // Handle the result
-                match child_result {
+                let result = match child_result {
                     InsertResult::Updated(old_value) => InsertResult::Updated(old_value),
                     InsertResult::Error(error) => InsertResult::Error(error),
                     InsertResult::Split {
@@ -579,22 +1567,30 @@ impl<K: Ord + Clone, V: Clone> BPlusTreeMap<K, V> {
                         new_node_data,
                         separator_key,
                     } => {
+                        self.record_split();
+
                         // Allocate the new node based on its type
                         let new_node = match new_node_data {
                             SplitNodeData::Leaf(new_leaf_data) => {
-                                let new_id = self.allocate_leaf(new_leaf_data);
+                                let new_id = match self.allocate_leaf(new_leaf_data) {
+                                    Ok(id) => id,
+                                    Err(error) => return InsertResult::Error(error),
+                                };
 
                                 // Update linked list pointers for leaf splits
-                                // Update linked list pointers for leaf splits using Option combinators
                                 if let NodeRef::Leaf(original_id, _) = child_ref {
-                                    self.get_leaf_mut(original_id)
-                                        .map(|original_leaf| original_leaf.next = new_id);
+                                    if let Some(original_leaf) = self.get_leaf_mut(original_id) {
+                                        original_leaf.next = new_id;
+                                    }
                                 }
 
                                 NodeRef::Leaf(new_id, PhantomData)
                             }
                             SplitNodeData::Branch(new_branch_data) => {
-                                let new_id = self.allocate_branch(new_branch_data);
+                                let new_id = match self.allocate_branch(new_branch_data) {
+                                    Ok(id) => id,
+                                    Err(error) => return InsertResult::Error(error),
+                                };
                                 NodeRef::Branch(new_id, PhantomData)
                             }
                         };
@@ -621,7 +1617,11 @@ impl<K: Ord + Clone, V: Clone> BPlusTreeMap<K, V> {
                             }
                         }
                     }
-                }
+                };
+
+                // The child gained an entry or this branch kept only its left half
+                self.refresh_subtree_len(id);
+                result
             }
         }
     }
@@ -660,6 +1660,16 @@ impl<K: Ord + Clone, V: Clone> BPlusTreeMap<K, V> {
     /// # Panics
     /// Never panics - all operations are memory safe
     pub fn remove(&mut self, key: &K) -> Option<V> {
+        let removed_value = self.remove_unlogged(key);
+        if removed_value.is_some() {
+            self.log_operation(|| Operation::Remove { key: key.clone() });
+        }
+        self.validate_if_enabled();
+        removed_value
+    }
+
+    /// Remove a key and rebalance, without logging or validating.
+    fn remove_unlogged(&mut self, key: &K) -> Option<V> {
         // Use remove_recursive to handle the removal
         let result = self.remove_recursive(&self.root.clone(), key);
 
@@ -668,6 +1678,7 @@ impl<K: Ord + Clone, V: Clone> BPlusTreeMap<K, V> {
                 // Check if root needs collapsing after removal
                 if removed_value.is_some() {
                     self.collapse_root_if_needed();
+                    self.auto_compact_if_needed();
                 }
                 removed_value
             }
@@ -688,6 +1699,39 @@ impl<K: Ord + Clone, V: Clone> BPlusTreeMap<K, V> {
             .with_context("Key lookup operation")
     }
 
+    /// Insert a key-value pair, returning internal errors instead of
+    /// ignoring them.
+    ///
+    /// Unlike [`try_insert`](Self::try_insert) this does not validate the
+    /// whole tree, so it costs the same as [`insert`](Self::insert). An error
+    /// means a node could not be allocated because the `NodeId` space is
+    /// exhausted; the entry is not inserted and the existing entries are kept.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bplustree::BPlusTreeMap;
+    ///
+    /// let mut tree = BPlusTreeMap::new(4).unwrap();
+    /// assert_eq!(tree.try_insert_checked(1, "one").unwrap(), None);
+    /// assert_eq!(tree.try_insert_checked(1, "uno").unwrap(), Some("one"));
+    /// ```
+    pub fn try_insert_checked(&mut self, key: K, value: V) -> BTreeResult<Option<V>> {
+        let logged = self.op_log.is_some().then(|| Operation::Insert {
+            key: key.clone(),
+            value: value.clone(),
+        });
+        let order = self.order.clone();
+        let version = self.next_version();
+        let old_value = self.try_insert_with(key, move |_, leaf, key| {
+            leaf.insert_stamped_by(key, value, version, |a, b| order.cmp(a, b))
+        })?;
+        if let Some(operation) = logged {
+            self.log_operation(|| operation);
+        }
+        Ok(old_value)
+    }
+
     /// Insert with comprehensive error handling and rollback on failure
     pub fn try_insert(&mut self, key: K, value: V) -> ModifyResult<Option<V>>
     where
@@ -699,7 +1743,18 @@ impl<K: Ord + Clone, V: Clone> BPlusTreeMap<K, V> {
             return Err(BPlusTreeError::DataIntegrityError(e));
         }
 
-        let old_value = self.insert(key, value);
+        let logged = self.op_log.is_some().then(|| Operation::Insert {
+            key: key.clone(),
+            value: value.clone(),
+        });
+        let order = self.order.clone();
+        let version = self.next_version();
+        let old_value = self.try_insert_with(key, move |_, leaf, key| {
+            leaf.insert_stamped_by(key, value, version, |a, b| order.cmp(a, b))
+        })?;
+        if let Some(operation) = logged {
+            self.log_operation(|| operation);
+        }
 
         // Validate tree state after insertion
         if let Err(e) = self.check_invariants_detailed() {
@@ -751,66 +1806,495 @@ impl<K: Ord + Clone, V: Clone> BPlusTreeMap<K, V> {
             }
         }
 
+        self.validate_if_enabled();
         Ok(results)
     }
 
-    /// Get multiple keys with detailed error reporting
-    pub fn get_many(&self, keys: &[K]) -> BTreeResult<Vec<&V>> {
-        let mut values = Vec::new();
-
-        for (_index, key) in keys.iter().enumerate() {
-            match self.get(key) {
-                Some(value) => values.push(value),
-                None => {
-                    return Err(BPlusTreeError::KeyNotFound);
-                }
+    /// Insert a batch of pairs, appending keys past the current maximum
+    /// directly onto the right edge of the tree.
+    ///
+    /// When the batch is strictly increasing, every entry whose key is
+    /// greater than all keys already present is pushed onto the rightmost
+    /// leaf. Leaves are filled to capacity and new leaves and branches are
+    /// added along the right edge only as needed, so a large append runs at
+    /// close to bulk-load speed and leaves the tree densely packed. Entries
+    /// that fall inside the existing key range, and every entry of a batch
+    /// that is not strictly increasing, go through [`insert`](Self::insert).
+    ///
+    /// # Errors
+    ///
+    /// Returns `AllocationError` if the arenas run out of node IDs while
+    /// appending. Entries appended before that point are kept and the tree
+    /// remains valid.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bplustree::BPlusTreeMap;
+    ///
+    /// let mut tree = BPlusTreeMap::new(16).unwrap();
+    /// tree.insert(5, 5);
+    /// tree.insert_sorted_batch((0..1000).map(|i| (i, i)).collect()).unwrap();
+    /// assert_eq!(tree.len(), 1000);
+    /// assert!(tree.check_invariants());
+    /// ```
+    pub fn insert_sorted_batch(&mut self, sorted_pairs: Vec<(K, V)>) -> BTreeResult<()> {
+        let strictly_increasing = sorted_pairs
+            .windows(2)
+            .all(|pair| self.compare(&pair[0].0, &pair[1].0).is_lt());
+        if !strictly_increasing {
+            for (key, value) in sorted_pairs {
+                self.insert(key, value);
             }
+            return Ok(());
         }
 
-        Ok(values)
-    }
+        let mut inside = sorted_pairs;
+        let split = match self.last() {
+            Some((max, _)) => inside.partition_point(|(key, _)| self.compare(key, max).is_le()),
+            None => 0,
+        };
+        let appended = inside.split_off(split);
+        for (key, value) in inside {
+            self.insert(key, value);
+        }
+        if appended.is_empty() {
+            return Ok(());
+        }
 
-    /// Check if tree is in a valid state for operations
-    pub fn validate_for_operation(&self, operation: &str) -> BTreeResult<()> {
-        self.check_invariants_detailed().map_err(|e| {
-            BPlusTreeError::DataIntegrityError(format!("Validation for {}: {}", operation, e))
-        })
+        let mut spine = Vec::new();
+        let mut node = self.root.clone();
+        while let NodeRef::Branch(id, _) = node {
+            spine.push(id);
+            node = self
+                .get_branch(id)
+                .and_then(|branch| branch.children.last().cloned())
+                .ok_or_else(|| BPlusTreeError::corrupted_tree("branch", "has no children"))?;
+        }
+        // Branches whose entry counts go stale, with their height above the leaves
+        let mut touched: Vec<(usize, NodeId)> = spine
+            .iter()
+            .enumerate()
+            .map(|(depth, &id)| (spine.len() - depth, id))
+            .collect();
+
+        let result = self.append_to_right_edge(appended, node.id(), &mut spine, &mut touched);
+
+        touched.sort_by_key(|&(height, _)| height);
+        for (_, id) in touched {
+            self.refresh_subtree_len(id);
+        }
+        self.rebalance_edge(true);
+        self.validate_if_enabled();
+        result
     }
 
-    // ============================================================================
-    // HELPERS FOR DELETE OPERATIONS
-    // ============================================================================
+    /// Push entries, all greater than every key in the tree, onto the
+    /// rightmost leaf `leaf_id`, starting a new leaf whenever it is full.
+    ///
+    /// `spine` holds the branches on the path to the rightmost leaf, root
+    /// first, and is kept up to date; every branch that gains entries is
+    /// recorded in `touched`.
+    fn append_to_right_edge(
+        &mut self,
+        entries: Vec<(K, V)>,
+        mut leaf_id: NodeId,
+        spine: &mut Vec<NodeId>,
+        touched: &mut Vec<(usize, NodeId)>,
+    ) -> BTreeResult<()> {
+        for (key, value) in entries {
+            let logged = self.op_log.is_some().then(|| Operation::Insert {
+                key: key.clone(),
+                value: value.clone(),
+            });
+            let version = self.next_version();
+
+            let leaf = self.leaf_arena.get_mut(leaf_id).ok_or_else(|| {
+                BPlusTreeError::corrupted_tree("leaf", "rightmost leaf is missing")
+            })?;
+            if leaf.is_full() {
+                self.ensure_room_for_insert()?;
+                let separator = key.clone();
+                let mut new_leaf = LeafNode::new(self.leaf_capacity);
+                new_leaf.keys.push(key);
+                new_leaf.values.push(value);
+                new_leaf.versions.push(version);
+                let new_id = self.allocate_leaf(new_leaf)?;
+                self.set_leaf_next(leaf_id, new_id);
+                leaf_id = new_id;
+                self.attach_to_right_edge(
+                    separator,
+                    NodeRef::Leaf(new_id, PhantomData),
+                    spine,
+                    touched,
+                )?;
+            } else {
+                leaf.keys.push(key);
+                leaf.values.push(value);
+                leaf.versions.push(version);
+            }
 
-    /// Recursively remove a key with proper arena access.
-    fn remove_recursive(&mut self, node: &NodeRef<K, V>, key: &K) -> RemoveResult<V> {
-        match node {
-            NodeRef::Leaf(id, _) => {
-                self.get_leaf_mut(*id)
-                    .map_or(RemoveResult::Updated(None, false), |leaf| {
-                        let removed_value = leaf.remove(key);
-                        let is_underfull = leaf.is_underfull();
-                        RemoveResult::Updated(removed_value, is_underfull)
-                    })
+            if let Some(operation) = logged {
+                self.log_operation(|| operation);
             }
-            NodeRef::Branch(id, _) => {
-                let id = *id;
+        }
+        Ok(())
+    }
 
-                // First get child info without mutable borrow
-                let (child_index, child_ref) = match self.get_child_for_key(id, key) {
-                    Some(info) => info,
-                    None => return RemoveResult::Updated(None, false),
-                };
+    /// Add `child`, whose smallest key is `separator`, as the new rightmost
+    /// node on its level, creating branches up the right edge as needed.
+    fn attach_to_right_edge(
+        &mut self,
+        separator: K,
+        mut child: NodeRef<K, V>,
+        spine: &mut Vec<NodeId>,
+        touched: &mut Vec<(usize, NodeId)>,
+    ) -> BTreeResult<()> {
+        let mut level = spine.len();
+        while level > 0 {
+            let parent = self.branch_arena.get_mut(spine[level - 1]).ok_or_else(|| {
+                BPlusTreeError::corrupted_tree("branch", "spine branch is missing")
+            })?;
+            if !parent.is_full() {
+                parent.keys.push(separator);
+                parent.children.push(child);
+                return Ok(());
+            }
 
-                // Recursively remove
-                let child_result = self.remove_recursive(&child_ref, key);
+            // The parent is full: start a new rightmost branch on its level
+            let mut branch = BranchNode::new(self.branch_capacity);
+            branch.children.push(child);
+            let branch_id = self.allocate_branch(branch)?;
+            spine[level - 1] = branch_id;
+            touched.push((spine.len() - (level - 1), branch_id));
+            child = NodeRef::Branch(branch_id, PhantomData);
+            level -= 1;
+        }
 
-                // Handle the result
-                match child_result {
-                    RemoveResult::Updated(removed_value, child_became_underfull) => {
+        // Every level was full: grow a new root
+        let mut root = BranchNode::new(self.branch_capacity);
+        root.keys.push(separator);
+        root.children.push(self.root.clone());
+        root.children.push(child);
+        let root_id = self.allocate_branch(root)?;
+        self.root = NodeRef::Branch(root_id, PhantomData);
+        spine.insert(0, root_id);
+        touched.push((spine.len(), root_id));
+        Ok(())
+    }
+
+    /// Restore minimum occupancy along the left (`right == false`) or right
+    /// edge, where appending or popping can leave the outermost node on each
+    /// level underfull.
+    ///
+    /// Levels are fixed top-down so that each node has a sibling under the
+    /// same parent by the time it is reached. A merge can leave a parent
+    /// underfull again, so passes repeat until nothing changes.
+    fn rebalance_edge(&mut self, right: bool) {
+        loop {
+            self.collapse_root_if_needed();
+            let mut changed = false;
+            let mut parent = self.root.clone();
+            while let NodeRef::Branch(parent_id, _) = parent {
+                loop {
+                    let Some(state) = self.edge_child_state(parent_id, right) else {
+                        return;
+                    };
+                    let (index, child, underfull, count) = state;
+                    if count < 2 || !underfull {
+                        break;
+                    }
+                    let before = (count, child.id(), self.node_key_count(&child));
+                    self.rebalance_child(parent_id, index);
+                    let Some((_, child, _, count)) = self.edge_child_state(parent_id, right) else {
+                        return;
+                    };
+                    if (count, child.id(), self.node_key_count(&child)) == before {
+                        break;
+                    }
+                    changed = true;
+                }
+                parent = match self.edge_child_state(parent_id, right) {
+                    Some((_, child, _, _)) => child,
+                    None => return,
+                };
+            }
+            if !changed {
+                return;
+            }
+        }
+    }
+
+    /// The index of a branch's first or last child, the child, whether it is
+    /// underfull, and the branch's child count.
+    fn edge_child_state(
+        &self,
+        branch_id: NodeId,
+        right: bool,
+    ) -> Option<(usize, NodeRef<K, V>, bool, usize)> {
+        let branch = self.get_branch(branch_id)?;
+        let count = branch.children.len();
+        let index = if right { count.checked_sub(1)? } else { 0 };
+        let child = branch.children.get(index)?.clone();
+        let underfull = self.is_node_underfull(&child);
+        Some((index, child, underfull, count))
+    }
+
+    /// Move up to `n - out.len()` entries off the left (`from_back == false`)
+    /// or right edge of the subtree at `node` into `out`, nearest the edge
+    /// first. Nodes emptied along the way are freed and unlinked from their
+    /// parents; the caller repairs occupancy afterwards.
+    ///
+    /// Returns true if the subtree was emptied.
+    fn take_from_edge(
+        &mut self,
+        node: &NodeRef<K, V>,
+        n: usize,
+        from_back: bool,
+        out: &mut Vec<(K, V)>,
+    ) -> bool {
+        match node {
+            NodeRef::Leaf(id, _) => {
+                let Some(leaf) = self.get_leaf_mut(*id) else {
+                    return false;
+                };
+                let take = (n - out.len()).min(leaf.keys.len());
+                let range = if from_back {
+                    leaf.keys.len() - take..leaf.keys.len()
+                } else {
+                    0..take
+                };
+                leaf.versions.drain(range.clone());
+                let entries = leaf.keys.drain(range.clone()).zip(leaf.values.drain(range));
+                if from_back {
+                    out.extend(entries.rev());
+                } else {
+                    out.extend(entries);
+                }
+                leaf.keys.is_empty()
+            }
+            NodeRef::Branch(id, _) => {
+                let id = *id;
+                while out.len() < n {
+                    let Some(branch) = self.get_branch(id) else {
+                        break;
+                    };
+                    let child = if from_back {
+                        branch.children.last()
+                    } else {
+                        branch.children.first()
+                    };
+                    let Some(child) = child.cloned() else {
+                        break;
+                    };
+                    if !self.take_from_edge(&child, n, from_back, out) {
+                        break;
+                    }
+
+                    match child {
+                        NodeRef::Leaf(child_id, _) => drop(self.deallocate_leaf(child_id)),
+                        NodeRef::Branch(child_id, _) => drop(self.deallocate_branch(child_id)),
+                    }
+                    let Some(branch) = self.get_branch_mut(id) else {
+                        break;
+                    };
+                    if from_back {
+                        branch.children.pop();
+                        branch.keys.pop();
+                    } else {
+                        branch.children.remove(0);
+                        if !branch.keys.is_empty() {
+                            branch.keys.remove(0);
+                        }
+                    }
+                }
+                self.refresh_subtree_len(id);
+                self.get_branch(id)
+                    .is_none_or(|branch| branch.children.is_empty())
+            }
+        }
+    }
+
+    /// Pop up to `n` entries off one edge of the tree without disturbing the
+    /// rest: drain the edge leaves, free the emptied nodes, then rebalance
+    /// along that edge once.
+    fn pop_edge(&mut self, n: usize, from_back: bool) -> Vec<(K, V)> {
+        let mut popped = Vec::with_capacity(n);
+        self.take_from_edge(&self.root.clone(), n, from_back, &mut popped);
+
+        if from_back {
+            // The new last leaf may still link to a freed one
+            if let Some(last) = self.get_last_leaf_id() {
+                self.set_leaf_next(last, NULL_NODE);
+            }
+        }
+        self.rebalance_edge(from_back);
+
+        self.log_removals(popped.iter().map(|(key, _)| key));
+        self.auto_compact_if_needed();
+        self.validate_if_enabled();
+        popped
+    }
+
+    /// Number of keys held directly by a node.
+    fn node_key_count(&self, node: &NodeRef<K, V>) -> usize {
+        match node {
+            NodeRef::Leaf(id, _) => self.get_leaf(*id).map_or(0, |leaf| leaf.keys.len()),
+            NodeRef::Branch(id, _) => self.get_branch(*id).map_or(0, |branch| branch.keys.len()),
+        }
+    }
+
+    /// Run `f` against the tree as a single all-or-nothing unit.
+    ///
+    /// Every insert and remove made through the [`TxnView`] records the
+    /// previous state of the affected key. If `f` returns `Err` or panics,
+    /// those entries are restored in reverse order, leaving the tree with
+    /// exactly the contents it had before the call. Restoring entries rather
+    /// than node state keeps rollback correct across any splits or merges;
+    /// the node layout itself may differ afterwards.
+    ///
+    /// A rollback also puts back each entry's version stamp and the tree's
+    /// [`version`](Self::version). Changes are passed to the operation log
+    /// only once `f` returns `Ok`, so a rolled-back transaction logs nothing.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bplustree::BPlusTreeMap;
+    ///
+    /// let mut accounts = BPlusTreeMap::new(4).unwrap();
+    /// accounts.insert("alice", 100);
+    /// accounts.insert("bob", 20);
+    ///
+    /// let transfer = accounts.transaction(|txn| {
+    ///     let amount = 150;
+    ///     let bob = *txn.get(&"bob").unwrap();
+    ///     txn.insert("bob", bob + amount);
+    ///     let alice = *txn.get(&"alice").unwrap();
+    ///     if alice < amount {
+    ///         return Err("insufficient funds");
+    ///     }
+    ///     txn.insert("alice", alice - amount);
+    ///     Ok(())
+    /// });
+    /// assert_eq!(transfer, Err("insufficient funds"));
+    /// assert_eq!(accounts.get(&"bob"), Some(&20));
+    /// ```
+    pub fn transaction<F, E>(&mut self, f: F) -> Result<(), E>
+    where
+        F: FnOnce(&mut TxnView<'_, K, V>) -> Result<(), E>,
+    {
+        let version = self.version;
+        let sink = self.op_log.take();
+        let mut txn = TxnView {
+            logged: sink.as_ref().map(|_| Vec::new()),
+            tree: self,
+            undo: Vec::new(),
+            version,
+            sink,
+            committed: false,
+        };
+        let result = f(&mut txn);
+        // Dropping the view rolls back unless committed, also if `f` panics
+        txn.committed = result.is_ok();
+        result
+    }
+
+    /// Put an entry back with the version stamp it had, without logging it.
+    fn restore_entry(&mut self, key: K, value: V, stamp: u64) {
+        let order = self.order.clone();
+        self.insert_with(key, move |_, leaf, key| {
+            leaf.insert_stamped_by(key, value, stamp, |a, b| order.cmp(a, b))
+        });
+    }
+
+    /// Version stamp of the entry for `key`, if present.
+    fn entry_stamp(&self, key: &K) -> Option<u64> {
+        let (leaf_id, index) = self.find_entry_position(key)?;
+        self.get_leaf(leaf_id)?.versions.get(index).copied()
+    }
+
+    /// Get multiple keys with detailed error reporting
+    pub fn get_many(&self, keys: &[K]) -> BTreeResult<Vec<&V>> {
+        let mut values = Vec::new();
+
+        for key in keys {
+            match self.get(key) {
+                Some(value) => values.push(value),
+                None => {
+                    return Err(BPlusTreeError::KeyNotFound);
+                }
+            }
+        }
+
+        Ok(values)
+    }
+
+    /// Look up each key in `keys`, returning `None` for the ones that are
+    /// missing instead of failing like [`get_many`](Self::get_many).
+    ///
+    /// The result lines up with `keys` index for index.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bplustree::BPlusTreeMap;
+    ///
+    /// let mut tree = BPlusTreeMap::new(4).unwrap();
+    /// tree.insert(1, "one");
+    /// tree.insert(3, "three");
+    /// assert_eq!(tree.get_batch(&[3, 2, 1]), vec![Some(&"three"), None, Some(&"one")]);
+    /// ```
+    pub fn get_batch<'a>(&'a self, keys: &[K]) -> Vec<Option<&'a V>> {
+        keys.iter().map(|key| self.get(key)).collect()
+    }
+
+    /// Check if tree is in a valid state for operations
+    pub fn validate_for_operation(&self, operation: &str) -> BTreeResult<()> {
+        self.check_invariants_detailed().map_err(|e| {
+            BPlusTreeError::DataIntegrityError(format!("Validation for {}: {}", operation, e))
+        })
+    }
+
+    // ============================================================================
+    // HELPERS FOR DELETE OPERATIONS
+    // ============================================================================
+
+    /// Recursively remove a key with proper arena access.
+    fn remove_recursive(&mut self, node: &NodeRef<K, V>, key: &K) -> RemoveResult<V> {
+        match node {
+            NodeRef::Leaf(id, _) => {
+                self.record_leaf_comparisons(*id, key);
+                let order = self.order.clone();
+                self.get_leaf_mut(*id)
+                    .map_or(RemoveResult::Updated(None, false), |leaf| {
+                        let removed_value = leaf.remove_by(key, |a, b| order.cmp(a, b));
+                        let is_underfull = leaf.is_underfull();
+                        RemoveResult::Updated(removed_value, is_underfull)
+                    })
+            }
+            NodeRef::Branch(id, _) => {
+                let id = *id;
+
+                // First get child info without mutable borrow
+                let (child_index, child_ref) = match self.get_child_for_key(id, key) {
+                    Some(info) => info,
+                    None => return RemoveResult::Updated(None, false),
+                };
+
+                // Recursively remove
+                let child_result = self.remove_recursive(&child_ref, key);
+
+                // Handle the result
+                match child_result {
+                    RemoveResult::Updated(removed_value, child_became_underfull) => {
                         // If child became underfull, try to rebalance
                         if removed_value.is_some() && child_became_underfull {
                             let _child_still_exists = self.rebalance_child(id, child_index);
                         }
+                        if removed_value.is_some() {
+                            self.refresh_subtree_len(id);
+                        }
 
                         // Check if this branch is now underfull after rebalancing
                         let is_underfull =
@@ -937,7 +2421,7 @@ impl<K: Ord + Clone, V: Clone> BPlusTreeMap<K, V> {
                             self.get_branch(*left_id)
                                 .zip(self.get_branch(*child_id))
                                 .map(|(left, child)| {
-                                    left.keys.len() + 1 + child.keys.len() <= self.capacity
+                                    left.keys.len() + 1 + child.keys.len() <= self.branch_capacity
                                 })
                                 .unwrap_or(false)
                         } else {
@@ -955,7 +2439,7 @@ impl<K: Ord + Clone, V: Clone> BPlusTreeMap<K, V> {
                             self.get_branch(*child_id)
                                 .zip(self.get_branch(*right_id))
                                 .map(|(child, right)| {
-                                    child.keys.len() + 1 + right.keys.len() <= self.capacity
+                                    child.keys.len() + 1 + right.keys.len() <= self.branch_capacity
                                 })
                                 .unwrap_or(false)
                         } else {
@@ -1024,8 +2508,8 @@ impl<K: Ord + Clone, V: Clone> BPlusTreeMap<K, V> {
             // First, extract content from child
             let (mut child_keys, mut child_children) = match self.get_branch_mut(child_id) {
                 Some(child_branch) => {
-                    let keys = std::mem::take(&mut child_branch.keys);
-                    let children = std::mem::take(&mut child_branch.children);
+                    let keys = core::mem::take(&mut child_branch.keys);
+                    let children = core::mem::take(&mut child_branch.children);
                     (keys, children)
                 }
                 None => return false,
@@ -1046,9 +2530,11 @@ impl<K: Ord + Clone, V: Clone> BPlusTreeMap<K, V> {
         };
         parent.children.remove(child_index);
         parent.keys.remove(child_index - 1);
+        self.refresh_subtree_len(left_id);
 
         // Deallocate the merged child
         self.deallocate_branch(child_id);
+        self.record_merge();
 
         false // Child was merged away
     }
@@ -1077,8 +2563,8 @@ impl<K: Ord + Clone, V: Clone> BPlusTreeMap<K, V> {
             // First, extract content from right
             let (mut right_keys, mut right_children) = match self.get_branch_mut(right_id) {
                 Some(right_branch) => {
-                    let keys = std::mem::take(&mut right_branch.keys);
-                    let children = std::mem::take(&mut right_branch.children);
+                    let keys = core::mem::take(&mut right_branch.keys);
+                    let children = core::mem::take(&mut right_branch.children);
                     (keys, children)
                 }
                 None => return false,
@@ -1099,9 +2585,11 @@ impl<K: Ord + Clone, V: Clone> BPlusTreeMap<K, V> {
         };
         parent.children.remove(child_index + 1);
         parent.keys.remove(child_index);
+        self.refresh_subtree_len(child_id);
 
         // Deallocate the merged right sibling
         self.deallocate_branch(right_id);
+        self.record_merge();
 
         true // Child still exists
     }
@@ -1144,6 +2632,8 @@ impl<K: Ord + Clone, V: Clone> BPlusTreeMap<K, V> {
             return false;
         };
         parent.keys[child_index - 1] = new_separator;
+        self.refresh_subtree_len(left_id);
+        self.refresh_subtree_len(child_id);
 
         true
     }
@@ -1186,6 +2676,8 @@ impl<K: Ord + Clone, V: Clone> BPlusTreeMap<K, V> {
             return false;
         };
         parent.keys[child_index] = new_separator;
+        self.refresh_subtree_len(child_id);
+        self.refresh_subtree_len(right_id);
 
         true
     }
@@ -1209,7 +2701,7 @@ impl<K: Ord + Clone, V: Clone> BPlusTreeMap<K, V> {
         };
 
         // Borrow from left leaf
-        let (key, value) = match self.get_leaf_mut(left_id) {
+        let (key, value, version) = match self.get_leaf_mut(left_id) {
             Some(left_leaf) => match left_leaf.borrow_last() {
                 Some(result) => result,
                 None => return false,
@@ -1221,7 +2713,7 @@ impl<K: Ord + Clone, V: Clone> BPlusTreeMap<K, V> {
         let Some(child_leaf) = self.get_leaf_mut(child_id) else {
             return false;
         };
-        child_leaf.accept_from_left(key.clone(), value);
+        child_leaf.accept_from_left(key.clone(), value, version);
 
         // Update separator in parent (second and final parent access)
         let Some(branch) = self.get_branch_mut(branch_id) else {
@@ -1251,7 +2743,7 @@ impl<K: Ord + Clone, V: Clone> BPlusTreeMap<K, V> {
         };
 
         // Borrow from right leaf
-        let (key, value) = match self.get_leaf_mut(right_id) {
+        let (key, value, version) = match self.get_leaf_mut(right_id) {
             Some(right_leaf) => match right_leaf.borrow_first() {
                 Some(result) => result,
                 None => return false,
@@ -1263,17 +2755,16 @@ impl<K: Ord + Clone, V: Clone> BPlusTreeMap<K, V> {
         let Some(child_leaf) = self.get_leaf_mut(child_id) else {
             return false;
         };
-        child_leaf.accept_from_right(key, value);
+        child_leaf.accept_from_right(key, value, version);
 
         // Update separator in parent (new first key of right sibling, second parent access)
         let new_separator = self
             .get_leaf(right_id)
             .and_then(|right_leaf| right_leaf.keys.first().cloned());
 
-        // Use Option combinators for nested conditional update
-        new_separator
-            .zip(self.get_branch_mut(branch_id))
-            .map(|(sep, branch)| branch.keys[child_index] = sep);
+        if let Some((sep, branch)) = new_separator.zip(self.get_branch_mut(branch_id)) {
+            branch.keys[child_index] = sep;
+        }
 
         true
     }
@@ -1297,10 +2788,11 @@ impl<K: Ord + Clone, V: Clone> BPlusTreeMap<K, V> {
         };
 
         // Extract all content from child
-        let (mut child_keys, mut child_values, child_next) = match self.get_leaf_mut(child_id) {
-            Some(child_leaf) => child_leaf.extract_all(),
-            None => return false,
-        };
+        let (mut child_keys, mut child_values, mut child_versions, child_next) =
+            match self.get_leaf_mut(child_id) {
+                Some(child_leaf) => child_leaf.extract_all(),
+                None => return false,
+            };
 
         // Merge into left leaf and update linked list - use early return for cleaner flow
         let Some(left_leaf) = self.get_leaf_mut(left_id) else {
@@ -1308,6 +2800,7 @@ impl<K: Ord + Clone, V: Clone> BPlusTreeMap<K, V> {
         };
         left_leaf.keys.append(&mut child_keys);
         left_leaf.values.append(&mut child_values);
+        left_leaf.versions.append(&mut child_versions);
         left_leaf.next = child_next;
 
         // Remove child from parent (second and final parent access)
@@ -1319,6 +2812,7 @@ impl<K: Ord + Clone, V: Clone> BPlusTreeMap<K, V> {
 
         // Deallocate the merged child
         self.deallocate_leaf(child_id);
+        self.record_merge();
 
         false // Child was merged away
     }
@@ -1345,15 +2839,11 @@ impl<K: Ord + Clone, V: Clone> BPlusTreeMap<K, V> {
         // Use a safer approach that avoids multiple mutable borrows
         {
             // First, extract content from right
-            let (mut right_keys, mut right_values, right_next) = match self.get_leaf_mut(right_id) {
-                Some(right_leaf) => {
-                    let keys = std::mem::take(&mut right_leaf.keys);
-                    let values = std::mem::take(&mut right_leaf.values);
-                    let next = right_leaf.next;
-                    (keys, values, next)
-                }
-                None => return false,
-            };
+            let (mut right_keys, mut right_values, mut right_versions, right_next) =
+                match self.get_leaf_mut(right_id) {
+                    Some(right_leaf) => right_leaf.extract_all(),
+                    None => return false,
+                };
 
             // Then merge into child
             let Some(child_leaf) = self.get_leaf_mut(child_id) else {
@@ -1361,6 +2851,7 @@ impl<K: Ord + Clone, V: Clone> BPlusTreeMap<K, V> {
             };
             child_leaf.keys.append(&mut right_keys);
             child_leaf.values.append(&mut right_values);
+            child_leaf.versions.append(&mut right_versions);
             child_leaf.next = right_next;
         }
 
@@ -1373,6 +2864,7 @@ impl<K: Ord + Clone, V: Clone> BPlusTreeMap<K, V> {
 
         // Deallocate the merged right sibling
         self.deallocate_leaf(right_id);
+        self.record_merge();
 
         true // Child still exists
     }
@@ -1416,18 +2908,160 @@ impl<K: Ord + Clone, V: Clone> BPlusTreeMap<K, V> {
                 }
                 None => {
                     // Handle missing branch or already leaf root
-                    root_branch_id
-                        .filter(|_| true) // Branch ID exists but branch is missing
-                        .map(|_| self.create_empty_root_leaf());
+                    if root_branch_id.is_some() {
+                        // Branch ID exists but branch is missing
+                        self.create_empty_root_leaf();
+                    }
                     break;
                 }
             }
         }
     }
 
+    /// Coalesce the leaves below a branch, then rebalance any child that the
+    /// merges left underfull. Returns the number of leaf merges performed.
+    fn coalesce_recursive(&mut self, branch_id: NodeId) -> usize {
+        let children = match self.get_branch(branch_id) {
+            Some(branch) => branch.children.clone(),
+            None => return 0,
+        };
+
+        let mut merges = 0;
+        for child in &children {
+            if let NodeRef::Branch(child_id, _) = child {
+                merges += self.coalesce_recursive(*child_id);
+            }
+        }
+
+        // Merge each leaf with its right sibling while the pair fits in one leaf
+        let mut index = 0;
+        loop {
+            let pair_fits = self.get_branch(branch_id).and_then(|branch| {
+                match (branch.children.get(index), branch.children.get(index + 1)) {
+                    (Some(NodeRef::Leaf(left, _)), Some(NodeRef::Leaf(right, _))) => {
+                        let left = self.get_leaf(*left)?.keys.len();
+                        let right = self.get_leaf(*right)?.keys.len();
+                        Some(left + right <= self.leaf_capacity)
+                    }
+                    _ => None,
+                }
+            });
+            match pair_fits {
+                Some(true) => {
+                    self.merge_with_right_leaf(branch_id, index);
+                    merges += 1;
+                }
+                Some(false) => index += 1,
+                None => break,
+            }
+        }
+
+        self.repair_underfull_children(branch_id);
+        merges
+    }
+
+    /// Rebalance every underfull child of a branch, as removal does.
+    /// Returns true if anything was borrowed or merged.
+    ///
+    /// A child may be several keys short, and one borrow only makes up one,
+    /// so each child is rebalanced until it is full enough or merged.
+    fn repair_underfull_children(&mut self, branch_id: NodeId) -> bool {
+        let mut changed = false;
+        let mut index = 0;
+        while let Some(branch) = self.get_branch(branch_id) {
+            let child_count = branch.children.len();
+            let Some(child) = branch.children.get(index).cloned() else {
+                break;
+            };
+            if child_count < 2 || !self.is_node_underfull(&child) {
+                index += 1;
+                continue;
+            }
+
+            let keys_before = self.node_key_count(&child);
+            self.rebalance_child(branch_id, index);
+            let shrank = self
+                .get_branch(branch_id)
+                .is_some_and(|branch| branch.children.len() < child_count);
+            if shrank {
+                // The merged node may sit one slot to the left; check it again
+                index = index.saturating_sub(1);
+                changed = true;
+            } else if self.node_key_count(&child) == keys_before {
+                // Nothing could be borrowed or merged; leave this child be
+                index += 1;
+            } else {
+                changed = true;
+            }
+        }
+        changed
+    }
+
+    /// Rebalance every underfull node below a branch, bottom-up. Returns
+    /// true if anything was borrowed or merged.
+    ///
+    /// A branch's only child cannot be repaired until the branch is merged
+    /// into a sibling, so callers repeat passes until nothing changes.
+    fn repair_underfull_below(&mut self, branch_id: NodeId) -> bool {
+        let children = match self.get_branch(branch_id) {
+            Some(branch) => branch.children.clone(),
+            None => return false,
+        };
+        let mut changed = false;
+        for child in children {
+            if let NodeRef::Branch(child_id, _) = child {
+                changed |= self.repair_underfull_below(child_id);
+            }
+        }
+        self.repair_underfull_children(branch_id) || changed
+    }
+
+    /// Free the leaves below a branch that were emptied in place, along
+    /// with any branch left without children, and recount the entries of
+    /// the rest. Returns true if the branch itself is now empty.
+    ///
+    /// The leaf chain must already bypass the emptied leaves. Nodes left
+    /// underfull are repaired afterwards by the caller.
+    fn prune_emptied(&mut self, branch_id: NodeId) -> bool {
+        let mut index = 0;
+        while let Some(child) = self
+            .get_branch(branch_id)
+            .and_then(|branch| branch.children.get(index).cloned())
+        {
+            let emptied = match child {
+                NodeRef::Leaf(id, _) => self.get_leaf(id).is_some_and(|leaf| leaf.keys.is_empty()),
+                NodeRef::Branch(id, _) => self.prune_emptied(id),
+            };
+            if !emptied {
+                index += 1;
+                continue;
+            }
+
+            match child {
+                NodeRef::Leaf(id, _) => drop(self.deallocate_leaf(id)),
+                NodeRef::Branch(id, _) => drop(self.deallocate_branch(id)),
+            }
+            if let Some(branch) = self.get_branch_mut(branch_id) {
+                branch.children.remove(index);
+                // The separator left of the child goes with it; the first
+                // child has none, so its right-hand separator goes instead
+                if !branch.keys.is_empty() {
+                    branch.keys.remove(index.saturating_sub(1));
+                }
+            }
+        }
+
+        self.refresh_subtree_len(branch_id);
+        self.get_branch(branch_id)
+            .is_none_or(|branch| branch.children.is_empty())
+    }
+
     /// Helper method to create empty root leaf
     fn create_empty_root_leaf(&mut self) {
-        let empty_id = self.allocate_leaf(LeafNode::new(self.capacity));
+        // The root being replaced has just been freed, so a slot is available
+        let empty_id = self
+            .allocate_leaf(LeafNode::new(self.leaf_capacity))
+            .expect("a leaf slot is free for the empty root");
         self.root = NodeRef::Leaf(empty_id, PhantomData);
     }
 
@@ -1436,25 +3070,11 @@ impl<K: Ord + Clone, V: Clone> BPlusTreeMap<K, V> {
     // ============================================================================
 
     /// Returns the number of elements in the tree.
+    ///
+    /// The root's entry count is kept up to date by every insert, removal
+    /// and rebuild, so this is O(1).
     pub fn len(&self) -> usize {
-        self.len_recursive(&self.root)
-    }
-
-    /// Recursively count elements with proper arena access.
-    fn len_recursive(&self, node: &NodeRef<K, V>) -> usize {
-        match node {
-            NodeRef::Leaf(id, _) => self.get_leaf(*id).map(|leaf| leaf.len()).unwrap_or(0),
-            NodeRef::Branch(id, _) => self
-                .get_branch(*id)
-                .map(|branch| {
-                    branch
-                        .children
-                        .iter()
-                        .map(|child| self.len_recursive(child))
-                        .sum()
-                })
-                .unwrap_or(0),
-        }
+        self.node_len(&self.root)
     }
 
     /// Returns true if the tree is empty.
@@ -1462,6 +3082,62 @@ impl<K: Ord + Clone, V: Clone> BPlusTreeMap<K, V> {
         self.len() == 0
     }
 
+    /// Returns the node capacity the tree was constructed with.
+    ///
+    /// For trees built with [`new_with_capacities`](Self::new_with_capacities)
+    /// this is the leaf capacity; see [`branch_capacity`](Self::branch_capacity).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bplustree::BPlusTreeMap;
+    ///
+    /// let tree = BPlusTreeMap::<i32, i32>::new(32).unwrap();
+    /// assert_eq!(tree.capacity(), 32);
+    /// ```
+    pub fn capacity(&self) -> usize {
+        self.leaf_capacity
+    }
+
+    /// Returns the maximum number of keys per branch node.
+    pub fn branch_capacity(&self) -> usize {
+        self.branch_capacity
+    }
+
+    /// Returns the number of levels in the tree; a lone root leaf is height 1.
+    ///
+    /// All leaves sit at the same depth, so this descends the leftmost spine
+    /// in O(height).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bplustree::BPlusTreeMap;
+    ///
+    /// let mut tree = BPlusTreeMap::new(4).unwrap();
+    /// assert_eq!(tree.height(), 1);
+    /// for i in 0..5 {
+    ///     tree.insert(i, i);
+    /// }
+    /// assert_eq!(tree.height(), 2);
+    /// ```
+    pub fn height(&self) -> usize {
+        let mut height = 1;
+        let mut node = &self.root;
+        while let NodeRef::Branch(id, _) = node {
+            let Some(child) = self
+                .branch_arena
+                .get(*id)
+                .and_then(|branch| branch.children.first())
+            else {
+                break;
+            };
+            height += 1;
+            node = child;
+        }
+        height
+    }
+
     /// Returns true if the root is a leaf node.
     pub fn is_leaf_root(&self) -> bool {
         matches!(self.root, NodeRef::Leaf(_, _))
@@ -1489,33 +3165,223 @@ impl<K: Ord + Clone, V: Clone> BPlusTreeMap<K, V> {
         }
     }
 
-    /// Clear all items from the tree.
-    pub fn clear(&mut self) {
-        // Clear all arenas and create a new root leaf
-        self.leaf_arena.clear();
-        self.branch_arena.clear();
-
-        // Create a new root leaf
-        let root_leaf = LeafNode::new(self.capacity);
-        let root_id = self.leaf_arena.allocate(root_leaf);
-        self.root = NodeRef::Leaf(root_id, PhantomData);
-    }
-
-    /// Returns an iterator over all key-value pairs in sorted order.
-    pub fn items(&self) -> ItemIterator<K, V> {
-        ItemIterator::new(self)
-    }
-
-    /// Returns an iterator over all keys in sorted order.
-    pub fn keys(&self) -> KeyIterator<K, V> {
-        KeyIterator::new(self)
+    /// Returns an iterator over all nodes in breadth-first (level) order.
+    ///
+    /// Each item is `(depth, node_id, is_leaf)`, with the root at depth 0.
+    /// Nodes on the same level are yielded left to right.
+    pub fn level_order(&self) -> LevelOrderIterator<'_, K, V> {
+        LevelOrderIterator::new(self)
     }
 
-    /// Returns an iterator over all values in key order.
-    pub fn values(&self) -> ValueIterator<K, V> {
+    /// Summarise the tree's shape: node counts, height, per-level widths and
+    /// how full the leaves and branches are.
+    ///
+    /// This walks every node once, so it is O(nodes); it is meant for tuning
+    /// and debugging, e.g. spotting a workload that leaves the tree sparse.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bplustree::BPlusTreeMap;
+    ///
+    /// let tree = BPlusTreeMap::bulk_load(4, (0..16).map(|i| (i, i))).unwrap();
+    /// let report = tree.structure_report();
+    /// assert_eq!(report.leaf_count, 4);
+    /// assert_eq!(report.nodes_per_level, vec![1, 4]);
+    /// assert_eq!(report.leaf_fill.avg, 4.0);
+    /// ```
+    pub fn structure_report(&self) -> StructureReport {
+        let mut leaf_sizes = Vec::new();
+        let mut branch_sizes = Vec::new();
+        let mut nodes_per_level: Vec<usize> = Vec::new();
+
+        for (depth, id, is_leaf) in self.level_order() {
+            if nodes_per_level.len() <= depth {
+                nodes_per_level.resize(depth + 1, 0);
+            }
+            nodes_per_level[depth] += 1;
+
+            if is_leaf {
+                leaf_sizes.push(self.get_leaf(id).map_or(0, |leaf| leaf.keys.len()));
+            } else {
+                branch_sizes.push(self.get_branch(id).map_or(0, |branch| branch.keys.len()));
+            }
+        }
+
+        StructureReport {
+            leaf_count: leaf_sizes.len(),
+            branch_count: branch_sizes.len(),
+            height: nodes_per_level.len(),
+            leaf_fill: FillStats::from_sizes(&leaf_sizes),
+            branch_fill: FillStats::from_sizes(&branch_sizes),
+            nodes_per_level,
+        }
+    }
+
+    /// Clear all items from the tree.
+    pub fn clear(&mut self) {
+        self.log_remove_all();
+        self.reset_empty();
+    }
+
+    /// Drop every node and start over with one empty root leaf.
+    fn reset_empty(&mut self) {
+        // Clear all arenas and create a new root leaf
+        self.leaf_arena.clear();
+        self.branch_arena.clear();
+
+        // Create a new root leaf
+        let root_leaf = LeafNode::new(self.leaf_capacity);
+        let root_id = self.leaf_arena.allocate(root_leaf);
+        self.root = NodeRef::Leaf(root_id, PhantomData);
+    }
+
+    /// Clear the tree and reload it from strictly increasing key-value pairs.
+    ///
+    /// The new contents are bulk-loaded bottom-up into densely packed nodes,
+    /// reusing the arenas' existing storage rather than reallocating it. This
+    /// suits periodic full-refresh workloads such as reloading a snapshot.
+    ///
+    /// # Errors
+    ///
+    /// Returns `DataIntegrityError` if the keys are not strictly increasing.
+    /// The tree is left empty in that case.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bplustree::BPlusTreeMap;
+    ///
+    /// let mut tree = BPlusTreeMap::new(4).unwrap();
+    /// tree.insert(100, "stale");
+    ///
+    /// tree.reset_with(vec![(1, "a"), (2, "b"), (3, "c")]).unwrap();
+    /// assert_eq!(tree.len(), 3);
+    /// assert_eq!(tree.get(&100), None);
+    /// ```
+    pub fn reset_with<I>(&mut self, sorted: I) -> BTreeResult<()>
+    where
+        I: IntoIterator<Item = (K, V)>,
+    {
+        self.log_remove_all();
+        let result = self.build_from_sorted(sorted);
+        match result {
+            Ok(()) => self.log_insert_all(),
+            Err(_) => self.reset_empty(),
+        }
+        result
+    }
+
+    /// Merge adjacent sibling leaves whose combined entries fit in one leaf.
+    ///
+    /// This tightens a tree left sparse by deletions without rebuilding it:
+    /// only leaves sharing a parent are merged, and any branch left underfull
+    /// is rebalanced the same way as after a removal. Returns the number of
+    /// leaf merges performed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bplustree::BPlusTreeMap;
+    ///
+    /// let mut tree = BPlusTreeMap::new(4).unwrap();
+    /// for i in 0..40 {
+    ///     tree.insert(i, i);
+    /// }
+    /// for i in (0..40).filter(|i| i % 4 == 0) {
+    ///     tree.remove(&i);
+    /// }
+    ///
+    /// let leaves_before = tree.leaf_count();
+    /// let merges = tree.coalesce_underfull();
+    /// assert_eq!(tree.leaf_count(), leaves_before - merges);
+    /// assert_eq!(tree.len(), 30);
+    /// ```
+    pub fn coalesce_underfull(&mut self) -> usize {
+        let merges = match self.root {
+            NodeRef::Branch(id, _) => self.coalesce_recursive(id),
+            NodeRef::Leaf(_, _) => 0,
+        };
+        if merges > 0 {
+            self.collapse_root_if_needed();
+        }
+        merges
+    }
+
+    /// Returns an iterator over all key-value pairs in sorted order.
+    pub fn items(&self) -> ItemIterator<'_, K, V> {
+        ItemIterator::new(self)
+    }
+
+    /// Returns an iterator over all key-value pairs that tolerates a damaged
+    /// leaf chain.
+    ///
+    /// Leaves are visited in tree-structure order instead of following `next`
+    /// pointers, and leaves missing from the arena are skipped rather than
+    /// ending iteration. Call `skipped_leaves()` on the iterator afterwards to
+    /// find out whether any data was unreachable.
+    pub fn items_lenient(&self) -> LenientItemIterator<'_, K, V> {
+        LenientItemIterator::new(self)
+    }
+
+    /// Returns an iterator over the leaves, yielding each leaf's keys and
+    /// values as parallel slices in key order.
+    ///
+    /// Useful for bulk work on contiguous runs of entries. Where one slice
+    /// ends and the next begins depends on the tree's split and merge
+    /// history and is not part of the API; only the concatenated order is.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bplustree::BPlusTreeMap;
+    ///
+    /// let mut tree = BPlusTreeMap::new(4).unwrap();
+    /// for i in 0..20 {
+    ///     tree.insert(i, i * 2);
+    /// }
+    /// let total: i32 = tree.leaves().map(|(_, values)| values.iter().sum::<i32>()).sum();
+    /// assert_eq!(total, 380);
+    /// ```
+    pub fn leaves(&self) -> LeafChunkIterator<'_, K, V> {
+        LeafChunkIterator::new(self)
+    }
+
+    /// Returns an iterator over all keys in sorted order.
+    pub fn keys(&self) -> KeyIterator<'_, K, V> {
+        KeyIterator::new(self)
+    }
+
+    /// Returns an iterator over all values in key order.
+    pub fn values(&self) -> ValueIterator<'_, K, V> {
         ValueIterator::new(self)
     }
 
+    /// Returns an iterator over mutable references to all values in key order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bplustree::BPlusTreeMap;
+    ///
+    /// let mut tree = BPlusTreeMap::new(4).unwrap();
+    /// for i in 0..10 {
+    ///     tree.insert(i, i);
+    /// }
+    /// for value in tree.values_mut() {
+    ///     *value *= 2;
+    /// }
+    /// assert_eq!(tree.get(&7), Some(&14));
+    /// ```
+    pub fn values_mut(&mut self) -> ValueIteratorMut<'_, K, V> {
+        let leaf_ids = self
+            .get_first_leaf_id()
+            .map(|first| self.leaf_chain_from(first, None))
+            .unwrap_or_default();
+        let version = self.next_version();
+        ValueIteratorMut::new(self.leaves_disjoint_mut(&leaf_ids), version)
+    }
+
     /// Returns an iterator over key-value pairs in a range.
     /// If start_key is None, starts from the beginning.
     /// If end_key is None, goes to the end.
@@ -1524,11 +3390,9 @@ impl<K: Ord + Clone, V: Clone> BPlusTreeMap<K, V> {
         start_key: Option<&K>,
         end_key: Option<&'a K>,
     ) -> RangeIterator<'a, K, V> {
-        let start_bound = start_key.map_or(Bound::Unbounded, |k| Bound::Included(k));
-        let end_bound = end_key.map_or(Bound::Unbounded, |k| Bound::Excluded(k));
-
-        let (start_info, skip_first, end_info) = self.resolve_range_bounds((start_bound, end_bound));
-        RangeIterator::new_with_skip_owned(self, start_info, skip_first, end_info)
+        let start_bound = start_key.map_or(Bound::Unbounded, Bound::Included);
+        let end_bound = end_key.map_or(Bound::Unbounded, Bound::Excluded);
+        self.range((start_bound, end_bound))
     }
 
     /// Returns an iterator over key-value pairs in a range using Rust's range syntax.
@@ -1566,612 +3430,4567 @@ impl<K: Ord + Clone, V: Clone> BPlusTreeMap<K, V> {
     where
         R: RangeBounds<K>,
     {
-        let (start_info, skip_first, end_info) = self.resolve_range_bounds(range);
-        RangeIterator::new_with_skip_owned(self, start_info, skip_first, end_info)
-    }
-
-    /// Returns the first key-value pair in the tree.
-    pub fn first(&self) -> Option<(&K, &V)> {
-        self.items().next()
-    }
-
-    /// Returns the last key-value pair in the tree.
-    pub fn last(&self) -> Option<(&K, &V)> {
-        self.items().last()
+        let (start, end) = self.range_endpoints(range);
+        RangeIterator::between(self, start, end)
     }
 
-    // ============================================================================
-    // RANGE QUERY HELPERS
-    // ============================================================================
-
-    fn resolve_range_bounds<R>(
-        &self,
+    /// Fold over a range, yielding the running state after each entry.
+    ///
+    /// Starting from `init`, each entry in the range produces
+    /// `f(&state, key, value)`, which becomes the new state and is yielded.
+    /// This gives prefix sums, running maxima and the like in one lazy pass.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bplustree::BPlusTreeMap;
+    ///
+    /// let mut tree = BPlusTreeMap::new(4).unwrap();
+    /// for i in 0..10 {
+    ///     tree.insert(i, i);
+    /// }
+    /// let totals: Vec<i32> = tree.range_scan(2..6, 0, |sum, _, v| sum + v).collect();
+    /// assert_eq!(totals, vec![2, 5, 9, 14]);
+    /// ```
+    pub fn range_scan<'a, R, S, F>(
+        &'a self,
         range: R,
-    ) -> (
-        Option<(NodeId, usize)>,
-        bool,
-        Option<(K, bool)>,
-    )
+        init: S,
+        mut f: F,
+    ) -> impl Iterator<Item = S> + 'a
     where
         R: RangeBounds<K>,
+        S: Clone + 'a,
+        F: FnMut(&S, &K, &V) -> S + 'a,
     {
-        // Optimize start bound resolution - eliminate redundant Option handling
-        let (start_info, skip_first) = match range.start_bound() {
-            Bound::Included(key) => (self.find_range_start(key), false),
-            Bound::Excluded(key) => (self.find_range_start(key), true),
-            Bound::Unbounded => (self.get_first_leaf_id().map(|id| (id, 0)), false),
-        };
-
-        // Avoid cloning end bound key when possible
-        let end_info = match range.end_bound() {
-            Bound::Included(key) => Some((key.clone(), true)),
-            Bound::Excluded(key) => Some((key.clone(), false)),
-            Bound::Unbounded => None,
-        };
-
-        (start_info, skip_first, end_info)
+        self.range(range).scan(init, move |state, (key, value)| {
+            *state = f(state, key, value);
+            Some(state.clone())
+        })
     }
 
-    // ============================================================================
-    // RANGE QUERY OPTIMIZATION HELPERS
-    // ============================================================================
-
-    /// Find the leaf node and index where a range should start
-    fn find_range_start(&self, start_key: &K) -> Option<(NodeId, usize)> {
-        let mut current = &self.root;
-
-        // Navigate down to leaf level
-        loop {
-            match current {
-                NodeRef::Leaf(leaf_id, _) => {
-                    let leaf = self.get_leaf(*leaf_id)?;
-                    
-                    // Use binary search instead of linear search for better performance
-                    let index = match leaf.keys.binary_search(start_key) {
-                        Ok(exact_index) => exact_index,     // Found exact key
-                        Err(insert_index) => insert_index,  // First key >= start_key
-                    };
-
-                    if index < leaf.keys.len() {
-                        return Some((*leaf_id, index));
-                    } else if leaf.next != NULL_NODE {
-                        // All keys in this leaf are < start_key, try next leaf
-                        // Check if next leaf exists and has keys without redundant arena lookup
-                        return Some((leaf.next, 0));
-                    } else {
-                        // No more leaves
-                        return None;
-                    }
-                }
-                NodeRef::Branch(branch_id, _) => {
-                    let branch = self.get_branch(*branch_id)?;
-                    let child_index = branch.find_child_index(start_key);
+    /// Locate both ends of a range as `(leaf_id, index)` positions.
+    ///
+    /// The first position is the first entry inside the range, or `None` if
+    /// the range holds no entries. The second is the first entry past the
+    /// range, or `None` if the range runs to the end of the tree. Iterating
+    /// from the first position up to, but not including, the second visits
+    /// exactly the entries in the range; this is how [`range`](Self::range)
+    /// stops without comparing each key against the end bound.
+    ///
+    /// Positions are only valid until the tree is next modified.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bplustree::BPlusTreeMap;
+    ///
+    /// let tree = BPlusTreeMap::bulk_load(4, (0..100).map(|i| (i, i))).unwrap();
+    /// let (start, end) = tree.range_endpoints(10..20);
+    /// let (start_leaf, start_index) = start.unwrap();
+    /// let (end_leaf, end_index) = end.unwrap();
+    /// assert_eq!(tree.get_leaf(start_leaf).unwrap().keys()[start_index], 10);
+    /// assert_eq!(tree.get_leaf(end_leaf).unwrap().keys()[end_index], 20);
+    ///
+    /// assert_eq!(tree.range_endpoints(90..).1, None);
+    /// assert_eq!(tree.range_endpoints(20..10).0, None);
+    /// ```
+    pub fn range_endpoints<R>(&self, range: R) -> (Option<LeafPosition>, Option<LeafPosition>)
+    where
+        R: RangeBounds<K>,
+    {
+        let start =
+            match range.start_bound() {
+                Bound::Included(key) => self.find_range_start(key),
+                Bound::Excluded(key) => self.find_range_start(key).and_then(|position| match self
+                    .key_at(position)
+                {
+                    Some(first) if self.compare(first, key).is_eq() => self.next_position(position),
+                    _ => Some(position),
+                }),
+                Bound::Unbounded => self.get_first_leaf_id().map(|id| (id, 0)),
+            };
+        let end = self.find_range_end(range.end_bound());
 
-                    if child_index < branch.children.len() {
-                        current = &branch.children[child_index];
-                    } else {
-                        return None;
-                    }
-                }
-            }
-        }
+        // The range is empty if it would start at or after its end
+        let start = start.filter(|&position| {
+            let Some(first) = self.key_at(position) else {
+                return false;
+            };
+            end.and_then(|end| self.key_at(end))
+                .is_none_or(|past_end| self.compare(first, past_end).is_lt())
+        });
+        (start, end)
     }
 
-    /// Get the ID of the first (leftmost) leaf in the tree
-    fn get_first_leaf_id(&self) -> Option<NodeId> {
-        let mut current = &self.root;
-
-        loop {
-            match current {
-                NodeRef::Leaf(leaf_id, _) => return Some(*leaf_id),
-                NodeRef::Branch(branch_id, _) => {
-                    if let Some(branch) = self.get_branch(*branch_id) {
-                        if !branch.children.is_empty() {
-                            current = &branch.children[0];
-                        } else {
-                            return None;
-                        }
-                    } else {
-                        return None;
-                    }
-                }
-            }
+    /// Returns an iterator over the keys in a range.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bplustree::BPlusTreeMap;
+    ///
+    /// let mut tree = BPlusTreeMap::new(4).unwrap();
+    /// for i in 0..10 {
+    ///     tree.insert(i, i * 10);
+    /// }
+    /// assert_eq!(tree.keys_range(3..6).copied().collect::<Vec<_>>(), vec![3, 4, 5]);
+    /// ```
+    pub fn keys_range<R>(&self, range: R) -> KeyRangeIterator<'_, K, V>
+    where
+        R: RangeBounds<K>,
+    {
+        KeyRangeIterator {
+            items: self.range(range),
         }
     }
 
-    // ============================================================================
-    // ENHANCED ARENA-BASED ALLOCATION FOR LEAF NODES
-    // ============================================================================
-
-    /// Allocate a new leaf node in the arena and return its ID.
-    pub fn allocate_leaf(&mut self, leaf: LeafNode<K, V>) -> NodeId {
-        self.leaf_arena.allocate(leaf)
-    }
-
-    /// Deallocate a leaf node from the arena.
-    pub fn deallocate_leaf(&mut self, id: NodeId) -> Option<LeafNode<K, V>> {
-        self.leaf_arena.deallocate(id)
+    /// Returns an iterator over the values whose keys fall in a range, in
+    /// key order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bplustree::BPlusTreeMap;
+    ///
+    /// let mut tree = BPlusTreeMap::new(4).unwrap();
+    /// for i in 0..10 {
+    ///     tree.insert(i, i * 10);
+    /// }
+    /// assert_eq!(tree.values_range(7..).copied().collect::<Vec<_>>(), vec![70, 80, 90]);
+    /// ```
+    pub fn values_range<R>(&self, range: R) -> ValueRangeIterator<'_, K, V>
+    where
+        R: RangeBounds<K>,
+    {
+        ValueRangeIterator {
+            items: self.range(range),
+        }
     }
 
-    /// Get a reference to a leaf node in the arena.
-    pub fn get_leaf(&self, id: NodeId) -> Option<&LeafNode<K, V>> {
-        self.leaf_arena.get(id)
-    }
+    /// Returns every `step`-th entry in a range, starting with the first.
+    ///
+    /// A `step` of 1 behaves like [`range`](Self::range). Skipped entries
+    /// are still walked over, so this costs the same as iterating the whole
+    /// range.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `step` is 0, like [`Iterator::step_by`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bplustree::BPlusTreeMap;
+    ///
+    /// let tree = BPlusTreeMap::bulk_load(4, (0..10).map(|i| (i, ()))).unwrap();
+    /// let keys: Vec<_> = tree.range_step(1..8, 3).map(|(k, _)| *k).collect();
+    /// assert_eq!(keys, vec![1, 4, 7]);
+    /// ```
+    pub fn range_step<R>(&self, range: R, step: usize) -> impl Iterator<Item = (&K, &V)> + '_
+    where
+        R: RangeBounds<K>,
+    {
+        assert!(step > 0, "range_step requires a step of at least 1");
+        self.range(range).step_by(step)
+    }
+
+    /// Returns an iterator over owned (cloned) key-value pairs in a range.
+    ///
+    /// Each pair is cloned lazily as it is yielded, so results can be kept
+    /// after the tree borrow ends without first collecting the whole range.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bplustree::BPlusTreeMap;
+    ///
+    /// let mut tree = BPlusTreeMap::new(16).unwrap();
+    /// for i in 0..10 {
+    ///     tree.insert(i, i.to_string());
+    /// }
+    ///
+    /// let owned: Vec<(i32, String)> = tree.range_owned(2..4).collect();
+    /// drop(tree);
+    /// assert_eq!(owned, vec![(2, "2".to_string()), (3, "3".to_string())]);
+    /// ```
+    pub fn range_owned<R>(&self, range: R) -> impl Iterator<Item = (K, V)> + '_
+    where
+        R: RangeBounds<K>,
+    {
+        self.range(range).map(|(k, v)| (k.clone(), v.clone()))
+    }
+
+    /// Returns an iterator over the entries in a range, with mutable
+    /// references to the values.
+    ///
+    /// Keys stay immutable since changing them could break the ordering.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bplustree::BPlusTreeMap;
+    ///
+    /// let mut tree = BPlusTreeMap::new(4).unwrap();
+    /// for i in 0..10 {
+    ///     tree.insert(i, i);
+    /// }
+    /// for (_, value) in tree.range_mut(3..=5) {
+    ///     *value *= 10;
+    /// }
+    /// assert_eq!(tree.values().copied().collect::<Vec<_>>(), vec![0, 1, 2, 30, 40, 50, 6, 7, 8, 9]);
+    /// ```
+    pub fn range_mut<R>(&mut self, range: R) -> RangeIteratorMut<'_, K, V>
+    where
+        R: RangeBounds<K>,
+    {
+        let version = self.next_version();
+        self.range_mut_stamped(range, Some(version))
+    }
+
+    /// Mutable range iteration that stamps each yielded entry with `stamp`,
+    /// or leaves the stamps alone when it is `None`.
+    fn range_mut_stamped<R>(&mut self, range: R, stamp: Option<u64>) -> RangeIteratorMut<'_, K, V>
+    where
+        R: RangeBounds<K>,
+    {
+        let (start_info, skip_first, end_info) = self.resolve_range_bounds(range);
+        let Some((start_leaf, start_index)) = start_info else {
+            return RangeIteratorMut::new(
+                Vec::new(),
+                0,
+                false,
+                end_info,
+                KeyOrder::default(),
+                stamp,
+            );
+        };
+        let leaf_ids = self.leaf_chain_from(start_leaf, end_info.as_ref().map(|(key, _)| key));
+        let order = self.order.clone();
+        let leaves = self.leaves_disjoint_mut(&leaf_ids);
+        RangeIteratorMut::new(leaves, start_index, skip_first, end_info, order, stamp)
+    }
+
+    /// Count the entries whose keys fall in `range`.
+    ///
+    /// The count is taken from the per-branch entry counts along the paths
+    /// to both ends of the range, so it is O(log n) however wide the range.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bplustree::BPlusTreeMap;
+    ///
+    /// let mut tree = BPlusTreeMap::new(4).unwrap();
+    /// for i in 0..100 {
+    ///     tree.insert(i, i);
+    /// }
+    /// assert_eq!(tree.range_count(10..20), 10);
+    /// assert_eq!(tree.range_count(..=49), 50);
+    /// assert_eq!(tree.range_count(200..), 0);
+    /// ```
+    pub fn range_count<R>(&self, range: R) -> usize
+    where
+        R: RangeBounds<K>,
+    {
+        self.count_range(&range)
+    }
+
+    /// Returns an iterator over a range that knows its exact length.
+    ///
+    /// The number of matching entries is counted up front from the spanned
+    /// leaves, so the iterator implements `ExactSizeIterator` and collecting
+    /// it allocates once.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bplustree::BPlusTreeMap;
+    ///
+    /// let mut tree = BPlusTreeMap::new(16).unwrap();
+    /// for i in 0..10 {
+    ///     tree.insert(i, i * 10);
+    /// }
+    ///
+    /// let range = tree.range_sized(2..6);
+    /// assert_eq!(range.len(), 4);
+    /// ```
+    pub fn range_sized<R>(&self, range: R) -> SizedRangeIterator<'_, K, V>
+    where
+        R: RangeBounds<K>,
+    {
+        let bounds = (range.start_bound().cloned(), range.end_bound().cloned());
+        let remaining = self.count_range(&bounds);
+        SizedRangeIterator {
+            inner: self.range(bounds),
+            remaining,
+        }
+    }
+
+    /// Apply `f` to every value whose key falls in `range`.
+    ///
+    /// The tree is descended once to the start of the range and the values are
+    /// then updated in a single walk along the leaf chain, so no key is looked
+    /// up again. Keys are never changed, so the structure stays valid.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bplustree::BPlusTreeMap;
+    ///
+    /// let mut tree = BPlusTreeMap::new(4).unwrap();
+    /// for i in 0..10 {
+    ///     tree.insert(i, 0);
+    /// }
+    /// tree.update_range(3..6, |_, value| *value += 1);
+    /// assert_eq!(tree.values().copied().collect::<Vec<_>>(), vec![0, 0, 0, 1, 1, 1, 0, 0, 0, 0]);
+    /// ```
+    pub fn update_range<R, F>(&mut self, range: R, mut f: F)
+    where
+        R: RangeBounds<K>,
+        F: FnMut(&K, &mut V),
+    {
+        let (start_info, mut skip_first, end_info) = self.resolve_range_bounds(range);
+        let Some((mut leaf_id, mut index)) = start_info else {
+            return;
+        };
+        let order = self.order.clone();
+        let version = self.next_version();
+        self.mark_sums_stale();
+
+        while let Some(leaf) = self.get_leaf_mut(leaf_id) {
+            for i in index..leaf.keys.len() {
+                if skip_first {
+                    skip_first = false;
+                    continue;
+                }
+                let key = &leaf.keys[i];
+                if let Some((end, inclusive)) = &end_info {
+                    if order.beyond_end(key, end, *inclusive) {
+                        return;
+                    }
+                }
+                leaf.versions[i] = version;
+                f(key, &mut leaf.values[i]);
+            }
+
+            if leaf.next == NULL_NODE {
+                return;
+            }
+            leaf_id = leaf.next;
+            index = 0;
+        }
+    }
+
+    /// Returns the first key-value pair in the tree.
+    ///
+    /// Descends the leftmost edge, so this is O(log n). This is the
+    /// non-consuming counterpart of [`pop_first`](Self::pop_first).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bplustree::BPlusTreeMap;
+    ///
+    /// let mut queue = BPlusTreeMap::new(4).unwrap();
+    /// queue.insert(3, "low");
+    /// queue.insert(1, "urgent");
+    /// assert_eq!(queue.first(), Some((&1, &"urgent")));
+    /// assert_eq!(queue.pop_first(), Some((1, "urgent")));
+    /// ```
+    #[doc(alias = "peek_min")]
+    pub fn first(&self) -> Option<(&K, &V)> {
+        let leaf = self.get_leaf(self.get_first_leaf_id()?)?;
+        Some((leaf.keys.first()?, leaf.values.first()?))
+    }
+
+    /// Returns the last key-value pair in the tree.
+    ///
+    /// Descends the rightmost edge, so this is O(log n) rather than a walk
+    /// of the whole leaf chain. This is the non-consuming counterpart of
+    /// [`pop_last`](Self::pop_last).
+    #[doc(alias = "peek_max")]
+    pub fn last(&self) -> Option<(&K, &V)> {
+        let leaf = self.get_leaf(self.get_last_leaf_id()?)?;
+        Some((leaf.keys.last()?, leaf.values.last()?))
+    }
+
+    /// Returns the smallest key in the tree.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bplustree::BPlusTreeMap;
+    ///
+    /// let tree = BPlusTreeMap::bulk_load(4, (0..100).map(|i| (i, ()))).unwrap();
+    /// assert_eq!(tree.min_key(), Some(&0));
+    /// ```
+    pub fn min_key(&self) -> Option<&K> {
+        self.first().map(|(key, _)| key)
+    }
+
+    /// Returns the largest key in the tree in O(log n).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bplustree::BPlusTreeMap;
+    ///
+    /// let tree = BPlusTreeMap::bulk_load(4, (0..100).map(|i| (i, ()))).unwrap();
+    /// assert_eq!(tree.max_key(), Some(&99));
+    /// ```
+    pub fn max_key(&self) -> Option<&K> {
+        self.last().map(|(key, _)| key)
+    }
+
+    /// Remove and return the entry with the smallest key.
+    ///
+    /// The key is found by descending the leftmost edge, so this is O(log n).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bplustree::BPlusTreeMap;
+    ///
+    /// let mut tree = BPlusTreeMap::new(4).unwrap();
+    /// tree.insert(2, "b");
+    /// tree.insert(1, "a");
+    /// assert_eq!(tree.pop_first(), Some((1, "a")));
+    /// assert_eq!(tree.pop_first(), Some((2, "b")));
+    /// assert_eq!(tree.pop_first(), None);
+    /// ```
+    pub fn pop_first(&mut self) -> Option<(K, V)> {
+        let leaf_id = self.get_first_leaf_id()?;
+        let key = self.get_leaf(leaf_id)?.keys.first()?.clone();
+        self.remove(&key).map(|value| (key, value))
+    }
+
+    /// Remove and return the entry with the largest key.
+    ///
+    /// The key is found by descending the rightmost edge, so this is O(log n).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bplustree::BPlusTreeMap;
+    ///
+    /// let mut tree = BPlusTreeMap::new(4).unwrap();
+    /// tree.insert(2, "b");
+    /// tree.insert(1, "a");
+    /// assert_eq!(tree.pop_last(), Some((2, "b")));
+    /// assert_eq!(tree.pop_last(), Some((1, "a")));
+    /// assert_eq!(tree.pop_last(), None);
+    /// ```
+    pub fn pop_last(&mut self) -> Option<(K, V)> {
+        let leaf_id = self.get_last_leaf_id()?;
+        let key = self.get_leaf(leaf_id)?.keys.last()?.clone();
+        self.remove(&key).map(|value| (key, value))
+    }
+
+    /// Gets the entry with the smallest key, for inspection, in-place
+    /// mutation or removal without a second lookup.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bplustree::BPlusTreeMap;
+    ///
+    /// let mut tasks = BPlusTreeMap::new(4).unwrap();
+    /// tasks.insert(10, "backup");
+    /// tasks.insert(5, "email");
+    /// if let Some(mut soonest) = tasks.first_entry() {
+    ///     assert_eq!(*soonest.key(), 5);
+    ///     *soonest.get_mut() = "email (retry)";
+    /// }
+    /// assert_eq!(tasks.first_entry().map(|e| e.remove()), Some("email (retry)"));
+    /// assert_eq!(tasks.len(), 1);
+    /// ```
+    pub fn first_entry(&mut self) -> Option<OccupiedEntry<'_, K, V>> {
+        let leaf_id = self.get_first_leaf_id()?;
+        if self.get_leaf(leaf_id)?.keys.is_empty() {
+            return None;
+        }
+        Some(OccupiedEntry {
+            tree: self,
+            leaf_id,
+            index: 0,
+        })
+    }
+
+    /// Gets the entry with the largest key; the counterpart of
+    /// [`first_entry`](Self::first_entry).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bplustree::BPlusTreeMap;
+    ///
+    /// let mut tree = BPlusTreeMap::new(4).unwrap();
+    /// tree.insert(1, "a");
+    /// tree.insert(2, "b");
+    /// assert_eq!(tree.last_entry().map(|e| e.remove_entry()), Some((2, "b")));
+    /// assert_eq!(tree.last_entry().map(|e| *e.key()), Some(1));
+    /// ```
+    pub fn last_entry(&mut self) -> Option<OccupiedEntry<'_, K, V>> {
+        let leaf_id = self.get_last_leaf_id()?;
+        let index = self.get_leaf(leaf_id)?.keys.len().checked_sub(1)?;
+        Some(OccupiedEntry {
+            tree: self,
+            leaf_id,
+            index,
+        })
+    }
+
+    /// Returns the entry with the largest key less than or equal to `key`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bplustree::BPlusTreeMap;
+    ///
+    /// let mut tree = BPlusTreeMap::new(4).unwrap();
+    /// tree.insert(10, "a");
+    /// tree.insert(20, "b");
+    /// assert_eq!(tree.floor(&15), Some((&10, &"a")));
+    /// assert_eq!(tree.floor(&20), Some((&20, &"b")));
+    /// assert_eq!(tree.floor(&5), None);
+    /// ```
+    pub fn floor(&self, key: &K) -> Option<(&K, &V)> {
+        self.entry_before_bound(key, |k| self.compare(k, key).is_le())
+    }
+
+    /// Returns the entry with the smallest key greater than or equal to `key`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bplustree::BPlusTreeMap;
+    ///
+    /// let mut tree = BPlusTreeMap::new(4).unwrap();
+    /// tree.insert(10, "a");
+    /// tree.insert(20, "b");
+    /// assert_eq!(tree.ceiling(&15), Some((&20, &"b")));
+    /// assert_eq!(tree.ceiling(&10), Some((&10, &"a")));
+    /// assert_eq!(tree.ceiling(&25), None);
+    /// ```
+    pub fn ceiling(&self, key: &K) -> Option<(&K, &V)> {
+        self.entry_from_bound(key, |k| self.compare(k, key).is_lt())
+    }
+
+    /// Returns the entry with the largest key strictly less than `key`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bplustree::BPlusTreeMap;
+    ///
+    /// let mut tree = BPlusTreeMap::new(4).unwrap();
+    /// tree.insert(10, "a");
+    /// tree.insert(20, "b");
+    /// assert_eq!(tree.predecessor(&20), Some((&10, &"a")));
+    /// assert_eq!(tree.predecessor(&10), None);
+    /// ```
+    pub fn predecessor(&self, key: &K) -> Option<(&K, &V)> {
+        self.entry_before_bound(key, |k| self.compare(k, key).is_lt())
+    }
+
+    /// Returns the entry with the smallest key strictly greater than `key`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bplustree::BPlusTreeMap;
+    ///
+    /// let mut tree = BPlusTreeMap::new(4).unwrap();
+    /// tree.insert(10, "a");
+    /// tree.insert(20, "b");
+    /// assert_eq!(tree.successor(&10), Some((&20, &"b")));
+    /// assert_eq!(tree.successor(&20), None);
+    /// ```
+    pub fn successor(&self, key: &K) -> Option<(&K, &V)> {
+        self.entry_from_bound(key, |k| self.compare(k, key).is_le())
+    }
+
+    /// Last entry whose key satisfies `below`, which must hold for a prefix
+    /// of the keys. Falls back to the previous leaf at a leaf boundary.
+    fn entry_before_bound<F>(&self, key: &K, below: F) -> Option<(&K, &V)>
+    where
+        F: Fn(&K) -> bool,
+    {
+        let leaf_id = self.find_leaf_for_key(key)?;
+        let leaf = self.get_leaf(leaf_id)?;
+        let index = leaf.keys.partition_point(|k| below(k));
+        if index > 0 {
+            return Some((&leaf.keys[index - 1], &leaf.values[index - 1]));
+        }
+        let prev = self.get_leaf(self.find_prev_leaf_id(leaf_id)?)?;
+        Some((prev.keys.last()?, prev.values.last()?))
+    }
+
+    /// First entry whose key does not satisfy `below`, which must hold for a
+    /// prefix of the keys. Follows the leaf chain at a leaf boundary.
+    fn entry_from_bound<F>(&self, key: &K, below: F) -> Option<(&K, &V)>
+    where
+        F: Fn(&K) -> bool,
+    {
+        let leaf = self.get_leaf(self.find_leaf_for_key(key)?)?;
+        let index = leaf.keys.partition_point(|k| below(k));
+        if index < leaf.keys.len() {
+            return Some((&leaf.keys[index], &leaf.values[index]));
+        }
+        let next = self.get_leaf(leaf.next)?;
+        Some((next.keys.first()?, next.values.first()?))
+    }
+
+    /// Returns the entry at position `n` in key order, counting from zero.
+    ///
+    /// Each branch knows how many entries lie below it, so the descent skips
+    /// whole subtrees and this is O(log n).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bplustree::BPlusTreeMap;
+    ///
+    /// let mut tree = BPlusTreeMap::new(4).unwrap();
+    /// for i in 0..100 {
+    ///     tree.insert(i * 10, i);
+    /// }
+    /// assert_eq!(tree.select(0), Some((&0, &0)));
+    /// assert_eq!(tree.select(42), Some((&420, &42)));
+    /// assert_eq!(tree.select(100), None);
+    /// ```
+    pub fn select(&self, n: usize) -> Option<(&K, &V)> {
+        let (leaf_id, index) = self.select_position(n)?;
+        let leaf = self.get_leaf(leaf_id)?;
+        Some((leaf.keys.get(index)?, leaf.values.get(index)?))
+    }
+
+    /// Find the leaf position of the entry at position `n` in key order.
+    fn select_position(&self, n: usize) -> Option<LeafPosition> {
+        let mut node = &self.root;
+        let mut n = n;
+        loop {
+            match node {
+                NodeRef::Leaf(id, _) => {
+                    let leaf = self.get_leaf(*id)?;
+                    return (n < leaf.keys.len()).then_some((*id, n));
+                }
+                NodeRef::Branch(id, _) => {
+                    let branch = self.get_branch(*id)?;
+                    let mut next = None;
+                    for child in &branch.children {
+                        let len = self.node_len(child);
+                        if n < len {
+                            next = Some(child);
+                            break;
+                        }
+                        n -= len;
+                    }
+                    node = next?;
+                }
+            }
+        }
+    }
+
+    /// Returns one page of entries: starting at the first key `>= start` (or
+    /// the smallest key), skip `offset` entries and take up to `limit`.
+    ///
+    /// The start of the page is found with one O(log n) descent using the
+    /// per-branch entry counts, however large `offset` is, and the page is
+    /// then read off the leaf chain. See [`scan_page`](Self::scan_page) for
+    /// cursor-based paging.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bplustree::BPlusTreeMap;
+    ///
+    /// let tree = BPlusTreeMap::bulk_load(4, (0..100).map(|i| (i, i))).unwrap();
+    /// let page: Vec<i32> = tree.scan(Some(&50), 10, 3).into_iter().map(|(k, _)| *k).collect();
+    /// assert_eq!(page, vec![60, 61, 62]);
+    /// assert!(tree.scan(None, 100, 10).is_empty());
+    /// ```
+    pub fn scan(&self, start: Option<&K>, offset: usize, limit: usize) -> Vec<(&K, &V)> {
+        self.scan_page(start, offset, limit).0
+    }
+
+    /// Like [`scan`](Self::scan), but also returns the key of the first
+    /// entry after the page, or `None` once the tree is exhausted.
+    ///
+    /// Passing that key back as `start` with an `offset` of zero fetches the
+    /// next page, which stays correct even if entries before it are inserted
+    /// or removed between calls.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bplustree::BPlusTreeMap;
+    ///
+    /// let tree = BPlusTreeMap::bulk_load(4, (0..10).map(|i| (i, i))).unwrap();
+    /// let mut cursor = None;
+    /// let mut pages = Vec::new();
+    /// loop {
+    ///     let (page, next) = tree.scan_page(cursor, 0, 4);
+    ///     pages.push(page.len());
+    ///     match next {
+    ///         Some(key) => cursor = Some(key),
+    ///         None => break,
+    ///     }
+    /// }
+    /// assert_eq!(pages, vec![4, 4, 2]);
+    /// ```
+    pub fn scan_page(
+        &self,
+        start: Option<&K>,
+        offset: usize,
+        limit: usize,
+    ) -> (Vec<(&K, &V)>, Option<&K>) {
+        let first = start.map_or(0, |key| self.rank(key)).saturating_add(offset);
+        let Some((leaf_id, index)) = self.select_position(first) else {
+            return (Vec::new(), None);
+        };
+
+        let mut items =
+            ItemIterator::new_from_position_with_bounds(self, leaf_id, index, Bound::Unbounded);
+        let mut page = Vec::with_capacity(limit.min(self.len() - first));
+        page.extend(items.by_ref().take(limit));
+        let next = items.next().map(|(key, _)| key);
+        (page, next)
+    }
+
+    /// Returns the number of keys strictly less than `key`.
+    ///
+    /// This is the position `key` has, or would have, in key order, so
+    /// `select(rank(k))` returns the entry for `k` when it is present.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bplustree::BPlusTreeMap;
+    ///
+    /// let mut tree = BPlusTreeMap::new(4).unwrap();
+    /// for i in 0..100 {
+    ///     tree.insert(i * 10, i);
+    /// }
+    /// assert_eq!(tree.rank(&420), 42);
+    /// assert_eq!(tree.rank(&425), 43);
+    /// assert_eq!(tree.rank(&-1), 0);
+    /// ```
+    pub fn rank(&self, key: &K) -> usize {
+        self.rank_by(key, false)
+    }
+
+    /// Count the keys below `key`, or at most `key` when `inclusive`.
+    fn rank_by(&self, key: &K, inclusive: bool) -> usize {
+        let mut node = &self.root;
+        let mut rank = 0;
+        loop {
+            match node {
+                NodeRef::Leaf(id, _) => {
+                    let Some(leaf) = self.get_leaf(*id) else {
+                        return rank;
+                    };
+                    return rank
+                        + leaf.keys.partition_point(|k| match self.compare(k, key) {
+                            Ordering::Less => true,
+                            Ordering::Equal => inclusive,
+                            Ordering::Greater => false,
+                        });
+                }
+                NodeRef::Branch(id, _) => {
+                    let Some(branch) = self.get_branch(*id) else {
+                        return rank;
+                    };
+                    // Every key left of the routed child is below `key`
+                    let index = branch.find_child_index_by(key, |a, b| self.compare(a, b));
+                    let Some(child) = branch.children.get(index) else {
+                        return rank;
+                    };
+                    rank += self.children_len(&branch.children[..index]);
+                    node = child;
+                }
+            }
+        }
+    }
+
+    /// Remove and return the `n` smallest entries in ascending order, or all
+    /// entries if the tree holds fewer than `n`.
+    ///
+    /// Small batches are drained straight out of the leftmost leaves, which
+    /// are freed as they empty, and the left edge is rebalanced once
+    /// afterwards. Once a batch covers half the tree or more, the tree is
+    /// instead drained in one pass and the remainder rebuilt.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bplustree::BPlusTreeMap;
+    ///
+    /// let mut tree = BPlusTreeMap::new(4).unwrap();
+    /// for i in 0..10 {
+    ///     tree.insert(i, i * 10);
+    /// }
+    /// assert_eq!(tree.pop_first_n(3), vec![(0, 0), (1, 10), (2, 20)]);
+    /// assert_eq!(tree.len(), 7);
+    /// ```
+    pub fn pop_first_n(&mut self, n: usize) -> Vec<(K, V)> {
+        let len = self.len();
+        let n = n.min(len);
+        if n == 0 {
+            return Vec::new();
+        }
+
+        if n >= len / 2 {
+            let mut entries = self.take_all_stamped();
+            let rest = entries.split_off(n);
+            self.rebuild_from_stamped(rest);
+            self.log_removals(entries.iter().map(|(key, _, _)| key));
+            self.auto_compact_if_needed();
+            self.validate_if_enabled();
+            return entries.into_iter().map(|(k, v, _)| (k, v)).collect();
+        }
+
+        self.pop_edge(n, false)
+    }
+
+    /// Remove and return the `n` largest entries in descending order, or all
+    /// entries if the tree holds fewer than `n`.
+    ///
+    /// This is the counterpart of [`pop_first_n`](Self::pop_first_n) and uses
+    /// the same strategies, working from the right edge.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bplustree::BPlusTreeMap;
+    ///
+    /// let mut tree = BPlusTreeMap::new(4).unwrap();
+    /// for i in 0..10 {
+    ///     tree.insert(i, i * 10);
+    /// }
+    /// assert_eq!(tree.pop_last_n(2), vec![(9, 90), (8, 80)]);
+    /// assert_eq!(tree.len(), 8);
+    /// ```
+    pub fn pop_last_n(&mut self, n: usize) -> Vec<(K, V)> {
+        let len = self.len();
+        let n = n.min(len);
+        if n == 0 {
+            return Vec::new();
+        }
+
+        if n >= len / 2 {
+            let mut entries = self.take_all_stamped();
+            let popped = entries.split_off(len - n);
+            self.rebuild_from_stamped(entries);
+            self.log_removals(popped.iter().rev().map(|(key, _, _)| key));
+            self.auto_compact_if_needed();
+            self.validate_if_enabled();
+            return popped.into_iter().rev().map(|(k, v, _)| (k, v)).collect();
+        }
+
+        self.pop_edge(n, true)
+    }
+
+    /// Split the tree in two at `key`.
+    ///
+    /// Entries with keys `< key` stay in `self`; entries with keys `>= key`
+    /// are moved into the returned tree, which uses the same node capacities
+    /// and settings. Both trees are rebuilt from their entries, so the split
+    /// is O(n).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bplustree::BPlusTreeMap;
+    ///
+    /// let mut tree = BPlusTreeMap::new(4).unwrap();
+    /// for i in 0..10 {
+    ///     tree.insert(i, i * 10);
+    /// }
+    /// let upper = tree.split_off(&6);
+    /// assert_eq!(tree.keys().copied().collect::<Vec<_>>(), vec![0, 1, 2, 3, 4, 5]);
+    /// assert_eq!(upper.keys().copied().collect::<Vec<_>>(), vec![6, 7, 8, 9]);
+    /// ```
+    pub fn split_off(&mut self, key: &K) -> BPlusTreeMap<K, V> {
+        let mut upper = self.empty_like();
+
+        let mut entries = self.take_all_stamped();
+        let split = entries.partition_point(|(k, _, _)| self.compare(k, key).is_lt());
+        let moved = entries.split_off(split);
+        self.rebuild_from_stamped(entries);
+        self.log_removals(moved.iter().map(|(key, _, _)| key));
+        upper.rebuild_from_stamped(moved);
+        self.auto_compact_if_needed();
+        self.validate_if_enabled();
+        upper.validate_if_enabled();
+        upper
+    }
+
+    /// Split the tree in two by position.
+    ///
+    /// The first `index` entries stay in `self` and the rest are moved into
+    /// the returned tree, which uses the same node capacities and settings.
+    /// An `index` past the end leaves everything in `self`. Like
+    /// `split_off`, both halves are rebuilt from their entries, so this is
+    /// O(n).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bplustree::BPlusTreeMap;
+    ///
+    /// let mut tree = BPlusTreeMap::new(4).unwrap();
+    /// for i in 0..10 {
+    ///     tree.insert(i * 10, i);
+    /// }
+    /// let page_two = tree.split_at_index(4);
+    /// assert_eq!(tree.len(), 4);
+    /// assert_eq!(page_two.first(), Some((&40, &4)));
+    /// ```
+    pub fn split_at_index(&mut self, index: usize) -> BPlusTreeMap<K, V> {
+        let mut upper = self.empty_like();
+
+        let mut entries = self.take_all_stamped();
+        let split = index.min(entries.len());
+        let moved = entries.split_off(split);
+        self.rebuild_from_stamped(entries);
+        self.log_removals(moved.iter().map(|(key, _, _)| key));
+        upper.rebuild_from_stamped(moved);
+        self.auto_compact_if_needed();
+        self.validate_if_enabled();
+        upper.validate_if_enabled();
+        upper
+    }
+
+    /// Move all entries from `other` into `self`, leaving `other` empty.
+    ///
+    /// When a key is present in both trees, the value from `other` wins.
+    /// Both entry sequences are already sorted, so they are merged in one
+    /// pass and `self` is rebuilt from the result.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bplustree::BPlusTreeMap;
+    ///
+    /// let mut a = BPlusTreeMap::new(4).unwrap();
+    /// a.insert(1, "a");
+    /// a.insert(2, "a");
+    /// let mut b = BPlusTreeMap::new(4).unwrap();
+    /// b.insert(2, "b");
+    /// b.insert(3, "b");
+    ///
+    /// a.append(&mut b);
+    /// assert!(b.is_empty());
+    /// assert_eq!(a.items().map(|(k, v)| (*k, *v)).collect::<Vec<_>>(), vec![(1, "a"), (2, "b"), (3, "b")]);
+    /// ```
+    pub fn append(&mut self, other: &mut BPlusTreeMap<K, V>) {
+        other.log_remove_all();
+        let theirs = other.take_all_entries();
+        other.reset_empty();
+        if theirs.is_empty() {
+            return;
+        }
+
+        // Entries from `other` are new writes here; ours keep their stamps
+        let version = self.next_version();
+        let theirs: Vec<(K, V, u64)> = theirs
+            .into_iter()
+            .map(|(key, value)| (key, value, version))
+            .collect();
+        let ours = self.take_all_stamped();
+        let mut merged = Vec::with_capacity(ours.len() + theirs.len());
+        let mut ours = ours.into_iter().peekable();
+        let mut theirs = theirs.into_iter().peekable();
+        loop {
+            let ordering = match (ours.peek(), theirs.peek()) {
+                (Some((a, _, _)), Some((b, _, _))) => self.compare(a, b),
+                (Some(_), None) => Ordering::Less,
+                (None, Some(_)) => Ordering::Greater,
+                (None, None) => break,
+            };
+            match ordering {
+                Ordering::Less => merged.extend(ours.next()),
+                Ordering::Greater => merged.extend(theirs.next()),
+                Ordering::Equal => {
+                    ours.next();
+                    merged.extend(theirs.next());
+                }
+            }
+        }
+        self.rebuild_from_stamped(merged);
+        if let Some(mut sink) = self.op_log.take() {
+            for (key, value, _) in self
+                .stamped_entries()
+                .filter(|(_, _, stamp)| *stamp == version)
+            {
+                sink(&Operation::Insert {
+                    key: key.clone(),
+                    value: value.clone(),
+                });
+            }
+            self.op_log = Some(sink);
+        }
+        self.validate_if_enabled();
+    }
+
+    /// Returns a new tree with the entries of both `self` and `other`.
+    ///
+    /// For a key present in both, the stored value is `combine(ours,
+    /// theirs)`; `combine` is not called for any other key. Both leaf chains
+    /// are walked once in step and the result is bulk-built, so this is
+    /// O(n + m). Keys are compared with `self`'s ordering, and the result
+    /// keeps `self`'s capacities and ordering.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bplustree::BPlusTreeMap;
+    ///
+    /// let a: BPlusTreeMap<&str, i32> = [("x", 1), ("y", 2)].into_iter().collect();
+    /// let b: BPlusTreeMap<&str, i32> = [("y", 10), ("z", 20)].into_iter().collect();
+    /// let total = a.union_with(&b, |ours, theirs| ours + theirs);
+    /// assert_eq!(
+    ///     total.items().map(|(k, v)| (*k, *v)).collect::<Vec<_>>(),
+    ///     vec![("x", 1), ("y", 12), ("z", 20)]
+    /// );
+    /// ```
+    pub fn union_with<F>(&self, other: &Self, combine: F) -> Self
+    where
+        F: Fn(&V, &V) -> V,
+    {
+        self.combine_entries(other, |ours, theirs| match (ours, theirs) {
+            (Some(ours), Some(theirs)) => Some(combine(ours, theirs)),
+            (ours, theirs) => ours.or(theirs).cloned(),
+        })
+    }
+
+    /// Returns a new tree with the entries of `self` whose keys are also in
+    /// `other`. Values come from `self`.
+    ///
+    /// Walks both trees once in step like [`union_with`](Self::union_with).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bplustree::BPlusTreeMap;
+    ///
+    /// let a: BPlusTreeMap<i32, char> = [(1, 'a'), (2, 'b'), (3, 'c')].into_iter().collect();
+    /// let b: BPlusTreeMap<i32, char> = [(2, 'x'), (3, 'y'), (4, 'z')].into_iter().collect();
+    /// let both = a.intersection(&b);
+    /// assert_eq!(both.items().map(|(k, v)| (*k, *v)).collect::<Vec<_>>(), vec![(2, 'b'), (3, 'c')]);
+    /// ```
+    pub fn intersection(&self, other: &Self) -> Self {
+        self.combine_entries(other, |ours, theirs| {
+            ours.filter(|_| theirs.is_some()).cloned()
+        })
+    }
+
+    /// Returns a new tree with the entries of `self` whose keys are not in
+    /// `other`.
+    ///
+    /// Walks both trees once in step like [`union_with`](Self::union_with).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bplustree::BPlusTreeMap;
+    ///
+    /// let a: BPlusTreeMap<i32, char> = [(1, 'a'), (2, 'b'), (3, 'c')].into_iter().collect();
+    /// let b: BPlusTreeMap<i32, char> = [(2, 'x'), (4, 'z')].into_iter().collect();
+    /// let only_a = a.difference(&b);
+    /// assert_eq!(only_a.items().map(|(k, v)| (*k, *v)).collect::<Vec<_>>(), vec![(1, 'a'), (3, 'c')]);
+    /// ```
+    pub fn difference(&self, other: &Self) -> Self {
+        self.combine_entries(other, |ours, theirs| {
+            ours.filter(|_| theirs.is_none()).cloned()
+        })
+    }
+
+    /// Remove all entries, yielding them in key order.
+    ///
+    /// The tree is empty once the iterator is dropped, even if it was not
+    /// fully consumed. Leaves are released as they are drained and the tree
+    /// keeps its capacities, so it can be refilled without reallocating.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bplustree::BPlusTreeMap;
+    ///
+    /// let mut tree = BPlusTreeMap::new(4).unwrap();
+    /// for i in 0..10 {
+    ///     tree.insert(i, i * 10);
+    /// }
+    /// let first_three: Vec<_> = tree.drain().take(3).collect();
+    /// assert_eq!(first_three, vec![(0, 0), (1, 10), (2, 20)]);
+    /// assert!(tree.is_empty());
+    /// ```
+    pub fn drain(&mut self) -> DrainIterator<'_, K, V> {
+        DrainIterator::new(self)
+    }
+
+    /// Remove every entry whose key falls in `range`, returning the removed
+    /// entries in ascending order.
+    ///
+    /// Like [`pop_first_n`](Self::pop_first_n), a range covering half the
+    /// tree or more is cut out of a single drain and the rest rebuilt;
+    /// smaller ranges are removed key by key.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bplustree::BPlusTreeMap;
+    ///
+    /// let mut tree = BPlusTreeMap::new(4).unwrap();
+    /// for i in 0..10 {
+    ///     tree.insert(i, i * 10);
+    /// }
+    /// assert_eq!(tree.remove_range(3..6), vec![(3, 30), (4, 40), (5, 50)]);
+    /// assert_eq!(tree.len(), 7);
+    /// assert!(!tree.contains_key(&4));
+    /// ```
+    pub fn remove_range<R>(&mut self, range: R) -> Vec<(K, V)>
+    where
+        R: RangeBounds<K>,
+    {
+        let count = self.count_range(&range);
+        if count == 0 {
+            return Vec::new();
+        }
+
+        if count >= self.len() / 2 {
+            let mut entries = self.take_all_stamped();
+            let start = entries.partition_point(|(key, _, _)| match range.start_bound() {
+                Bound::Included(start) => self.compare(key, start).is_lt(),
+                Bound::Excluded(start) => self.compare(key, start).is_le(),
+                Bound::Unbounded => false,
+            });
+            let removed: Vec<(K, V)> = entries
+                .drain(start..start + count)
+                .map(|(key, value, _)| (key, value))
+                .collect();
+            self.rebuild_from_stamped(entries);
+            self.log_removals(removed.iter().map(|(key, _)| key));
+            self.auto_compact_if_needed();
+            self.validate_if_enabled();
+            return removed;
+        }
+
+        let keys: Vec<K> = self.range(range).map(|(key, _)| key.clone()).collect();
+        self.remove_keys(keys)
+    }
+
+    /// Keep only the entries for which `f` returns `true`.
+    ///
+    /// Entries are visited once in key order and may be modified in place.
+    /// Each leaf is filtered where it is and emptied leaves are freed, then
+    /// the affected nodes are rebalanced in one bottom-up pass, so nothing is
+    /// copied out of the tree and untouched nodes keep their IDs.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bplustree::BPlusTreeMap;
+    ///
+    /// let mut tree = BPlusTreeMap::new(4).unwrap();
+    /// for i in 0..10 {
+    ///     tree.insert(i, i);
+    /// }
+    /// tree.retain(|k, v| {
+    ///     *v *= 2;
+    ///     k % 3 == 0
+    /// });
+    /// assert_eq!(tree.items().map(|(k, v)| (*k, *v)).collect::<Vec<_>>(), vec![(0, 0), (3, 6), (6, 12), (9, 18)]);
+    /// ```
+    pub fn retain<F>(&mut self, mut f: F)
+    where
+        F: FnMut(&K, &mut V) -> bool,
+    {
+        self.mark_sums_stale();
+        let logging = self.op_log.is_some();
+        let mut removed_keys = Vec::new();
+        let mut removed_any = false;
+        let mut last_kept = None;
+        let mut leaf_id = self.get_first_leaf_id().unwrap_or(NULL_NODE);
+        while let Some(leaf) = self.get_leaf_mut(leaf_id) {
+            let next = leaf.next;
+            let len = leaf.keys.len();
+            leaf.retain_entries(|key, value| {
+                let keep = f(key, value);
+                if !keep && logging {
+                    removed_keys.push(key.clone());
+                }
+                keep
+            });
+            removed_any |= leaf.keys.len() < len;
+
+            if !leaf.keys.is_empty() {
+                // Link past any emptied leaves in between
+                if let Some(last) = last_kept {
+                    self.set_leaf_next(last, leaf_id);
+                }
+                last_kept = Some(leaf_id);
+            }
+            leaf_id = next;
+        }
+        if !removed_any {
+            return;
+        }
+        if let Some(last) = last_kept {
+            self.set_leaf_next(last, NULL_NODE);
+        }
+
+        if let NodeRef::Branch(root_id, _) = self.root {
+            self.prune_emptied(root_id);
+        }
+        loop {
+            self.collapse_root_if_needed();
+            let NodeRef::Branch(root_id, _) = self.root else {
+                break;
+            };
+            if !self.repair_underfull_below(root_id) {
+                break;
+            }
+        }
+        self.log_removals(&removed_keys);
+        self.auto_compact_if_needed();
+        self.validate_if_enabled();
+    }
+
+    /// Keep only the entries in `range` for which `f` returns `true`,
+    /// leaving every entry outside the range untouched.
+    ///
+    /// Entries in the range are visited once in key order and may be
+    /// modified in place. Rejected entries are then removed one by one, so
+    /// the tree rebalances as usual and the cost scales with the window
+    /// rather than the whole tree.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bplustree::BPlusTreeMap;
+    ///
+    /// let mut tree = BPlusTreeMap::new(4).unwrap();
+    /// for i in 0..10 {
+    ///     tree.insert(i, i);
+    /// }
+    /// tree.retain_range(3..7, |_, v| *v % 2 == 0);
+    /// assert_eq!(tree.keys().copied().collect::<Vec<_>>(), vec![0, 1, 2, 4, 6, 7, 8, 9]);
+    /// ```
+    pub fn retain_range<R, F>(&mut self, range: R, mut f: F)
+    where
+        R: RangeBounds<K>,
+        F: FnMut(&K, &mut V) -> bool,
+    {
+        // Like `retain`, this keeps the entries' version stamps
+        let rejected: Vec<K> = self
+            .range_mut_stamped(range, None)
+            .filter_map(|(key, value)| (!f(key, value)).then(|| key.clone()))
+            .collect();
+        self.remove_keys(rejected);
+    }
+
+    // ============================================================================
+    // BULK LOADING HELPERS
+    // ============================================================================
+
+    /// Move every entry out of the leaves in key order.
+    ///
+    /// The tree is left structurally intact but with empty leaves, so callers
+    /// must rebuild it before using it again.
+    fn take_all_entries(&mut self) -> Vec<(K, V)> {
+        self.take_all_stamped()
+            .into_iter()
+            .map(|(key, value, _)| (key, value))
+            .collect()
+    }
+
+    /// Move every entry out of the leaves in key order, together with its
+    /// version stamp, so that a rebuild can keep the stamps.
+    fn take_all_stamped(&mut self) -> Vec<(K, V, u64)> {
+        let mut leaf_ids = Vec::new();
+        self.collect_leaf_ids(&self.root.clone(), &mut leaf_ids);
+
+        let mut entries = Vec::new();
+        for id in leaf_ids {
+            if let Some(leaf) = self.get_leaf_mut(id) {
+                let keys = core::mem::take(&mut leaf.keys);
+                let values = core::mem::take(&mut leaf.values);
+                let versions = core::mem::take(&mut leaf.versions);
+                entries.extend(
+                    keys.into_iter()
+                        .zip(values)
+                        .zip(versions)
+                        .map(|((key, value), version)| (key, value, version)),
+                );
+            }
+        }
+        entries
+    }
+
+    /// Rebuild the tree from new entries in strictly increasing key order,
+    /// stamping them all as written now.
+    fn rebuild_from_entries(&mut self, entries: Vec<(K, V)>) {
+        self.build_from_sorted(entries)
+            .expect("entries taken from the tree are strictly increasing");
+    }
+
+    /// Rebuild the tree from entries taken out of it with
+    /// [`take_all_stamped`](Self::take_all_stamped), keeping their stamps.
+    fn rebuild_from_stamped(&mut self, entries: Vec<(K, V, u64)>) {
+        self.build_from_stamped(entries)
+            .expect("entries taken from the tree are strictly increasing");
+    }
+
+    /// Walk the entries of `self` and `other` in step and build a tree from
+    /// the value `pick` returns for each key, given its value in each tree.
+    fn combine_entries<F>(&self, other: &Self, mut pick: F) -> Self
+    where
+        F: FnMut(Option<&V>, Option<&V>) -> Option<V>,
+    {
+        let mut entries = Vec::new();
+        let mut ours = self.items().peekable();
+        let mut theirs = other.items().peekable();
+        loop {
+            let ordering = match (ours.peek(), theirs.peek()) {
+                (Some((a, _)), Some((b, _))) => self.compare(a, b),
+                (Some(_), None) => Ordering::Less,
+                (None, Some(_)) => Ordering::Greater,
+                (None, None) => break,
+            };
+            let (key, ours_value, theirs_value) = match ordering {
+                Ordering::Less => ours.next().map(|(key, value)| (key, Some(value), None)),
+                Ordering::Greater => theirs.next().map(|(key, value)| (key, None, Some(value))),
+                Ordering::Equal => ours
+                    .next()
+                    .zip(theirs.next())
+                    .map(|((key, a), (_, b))| (key, Some(a), Some(b))),
+            }
+            .expect("peeked entries are present");
+            if let Some(value) = pick(ours_value, theirs_value) {
+                entries.push((key.clone(), value));
+            }
+        }
+
+        let mut tree = self.empty_like();
+        tree.rebuild_from_entries(entries);
+        tree
+    }
+
+    /// Remove each key in turn, collecting the removed entries.
+    fn remove_keys(&mut self, keys: Vec<K>) -> Vec<(K, V)> {
+        keys.into_iter()
+            .filter_map(|key| self.remove(&key).map(|value| (key, value)))
+            .collect()
+    }
+
+    /// Replace the tree's contents by building it bottom-up from strictly
+    /// increasing key-value pairs.
+    ///
+    /// Leaves are packed to capacity and chained as they fill; a trailing
+    /// underfull leaf is topped up from its left neighbour. Branch levels are
+    /// then built with children distributed evenly across the fewest branches.
+    ///
+    /// Every entry is stamped with one new version.
+    fn build_from_sorted<I>(&mut self, sorted: I) -> BTreeResult<()>
+    where
+        I: IntoIterator<Item = (K, V)>,
+    {
+        let version = self.next_version();
+        self.build_from_stamped(
+            sorted
+                .into_iter()
+                .map(move |(key, value)| (key, value, version)),
+        )
+    }
+
+    /// Like [`build_from_sorted`](Self::build_from_sorted), but each entry
+    /// carries its own version stamp.
+    fn build_from_stamped<I>(&mut self, sorted: I) -> BTreeResult<()>
+    where
+        I: IntoIterator<Item = (K, V, u64)>,
+    {
+        self.leaf_arena.clear();
+        self.branch_arena.clear();
+
+        // Each entry is a finished node plus the smallest key in its subtree
+        let mut level: Vec<(NodeRef<K, V>, K)> = Vec::new();
+        let mut leaf = LeafNode::new(self.leaf_capacity);
+
+        for (key, value, version) in sorted {
+            let previous_key = leaf.keys.last().or_else(|| {
+                level
+                    .last()
+                    .and_then(|(node, _)| self.get_leaf(node.id()))
+                    .and_then(|prev| prev.keys.last())
+            });
+            if previous_key.is_some_and(|previous| self.compare(previous, &key).is_ge()) {
+                return Err(BPlusTreeError::data_integrity(
+                    "Bulk load",
+                    "input keys must be strictly increasing",
+                ));
+            }
+
+            if leaf.is_full() {
+                let full = core::mem::replace(&mut leaf, LeafNode::new(self.leaf_capacity));
+                self.push_built_leaf(&mut level, full)?;
+            }
+            leaf.keys.push(key);
+            leaf.values.push(value);
+            leaf.versions.push(version);
+        }
+
+        if leaf.is_empty() {
+            // Empty input: the tree is a single empty root leaf
+            let root_id = self.allocate_leaf(leaf)?;
+            self.root = NodeRef::Leaf(root_id, PhantomData);
+            return Ok(());
+        }
+
+        // Top up a trailing underfull leaf from its full left neighbour
+        if leaf.is_underfull() {
+            if let Some(prev) = level
+                .last()
+                .and_then(|(node, _)| self.leaf_arena.get_mut(node.id()))
+            {
+                let total = prev.keys.len() + leaf.keys.len();
+                let keep = total - total / 2;
+                let mut keys = prev.keys.split_off(keep);
+                let mut values = prev.values.split_off(keep);
+                let mut versions = prev.versions.split_off(keep);
+                keys.append(&mut leaf.keys);
+                values.append(&mut leaf.values);
+                versions.append(&mut leaf.versions);
+                leaf.keys = keys;
+                leaf.values = values;
+                leaf.versions = versions;
+            }
+        }
+        self.push_built_leaf(&mut level, leaf)?;
+
+        // Build branch levels until a single root remains
+        while level.len() > 1 {
+            let max_children = self.branch_capacity + 1;
+            let group_count = level.len().div_ceil(max_children);
+            let base_size = level.len() / group_count;
+            let larger_groups = level.len() % group_count;
+
+            let mut children = level.into_iter();
+            let mut next_level = Vec::with_capacity(group_count);
+            for group in 0..group_count {
+                let size = base_size + usize::from(group < larger_groups);
+                let mut branch = BranchNode::new(self.branch_capacity);
+                let mut min_key = None;
+                for (child, child_min) in children.by_ref().take(size) {
+                    if min_key.is_none() {
+                        min_key = Some(child_min);
+                    } else {
+                        branch.keys.push(child_min);
+                    }
+                    branch.children.push(child);
+                }
+                let min_key = min_key.expect("branch groups are never empty");
+                let id = self.allocate_branch(branch)?;
+                next_level.push((NodeRef::Branch(id, PhantomData), min_key));
+            }
+            level = next_level;
+        }
+
+        if let Some((root, _)) = level.pop() {
+            self.root = root;
+        }
+        Ok(())
+    }
+
+    /// Allocate a finished leaf, link it after the previous one, and record it.
+    fn push_built_leaf(
+        &mut self,
+        level: &mut Vec<(NodeRef<K, V>, K)>,
+        leaf: LeafNode<K, V>,
+    ) -> BTreeResult<()> {
+        let Some(min_key) = leaf.keys.first().cloned() else {
+            return Ok(());
+        };
+        let id = self.allocate_leaf(leaf)?;
+        if let Some((prev, _)) = level.last() {
+            self.set_leaf_next(prev.id(), id);
+        }
+        level.push((NodeRef::Leaf(id, PhantomData), min_key));
+        Ok(())
+    }
+
+    // ============================================================================
+    // RANGE QUERY HELPERS
+    // ============================================================================
+
+    fn resolve_range_bounds<R>(&self, range: R) -> ResolvedRange<K>
+    where
+        R: RangeBounds<K>,
+    {
+        // Optimize start bound resolution - eliminate redundant Option handling
+        let (start_info, skip_first) = match range.start_bound() {
+            Bound::Included(key) => (self.find_range_start(key), false),
+            Bound::Excluded(key) => {
+                // Only skip the first entry when it is the excluded key itself
+                let start = self.find_range_start(key);
+                let skip_first = start
+                    .and_then(|(leaf_id, index)| self.get_leaf(leaf_id)?.keys.get(index))
+                    .is_some_and(|first| self.compare(first, key).is_eq());
+                (start, skip_first)
+            }
+            Bound::Unbounded => (self.get_first_leaf_id().map(|id| (id, 0)), false),
+        };
+
+        // Avoid cloning end bound key when possible
+        let end_info = match range.end_bound() {
+            Bound::Included(key) => Some((key.clone(), true)),
+            Bound::Excluded(key) => Some((key.clone(), false)),
+            Bound::Unbounded => None,
+        };
+
+        (start_info, skip_first, end_info)
+    }
+
+    // ============================================================================
+    // RANGE QUERY OPTIMIZATION HELPERS
+    // ============================================================================
+
+    /// Find the leaf node and index where a range should start
+    fn find_range_start(&self, start_key: &K) -> Option<(NodeId, usize)> {
+        let mut current = &self.root;
+
+        // Navigate down to leaf level
+        loop {
+            match current {
+                NodeRef::Leaf(leaf_id, _) => {
+                    let leaf = self.get_leaf(*leaf_id)?;
+
+                    // Use binary search instead of linear search for better performance
+                    let index = match leaf.keys.binary_search_by(|k| self.compare(k, start_key)) {
+                        Ok(exact_index) => exact_index,    // Found exact key
+                        Err(insert_index) => insert_index, // First key >= start_key
+                    };
+
+                    if index < leaf.keys.len() {
+                        return Some((*leaf_id, index));
+                    } else if leaf.next != NULL_NODE {
+                        // All keys in this leaf are < start_key, try next leaf
+                        // Check if next leaf exists and has keys without redundant arena lookup
+                        return Some((leaf.next, 0));
+                    } else {
+                        // No more leaves
+                        return None;
+                    }
+                }
+                NodeRef::Branch(branch_id, _) => {
+                    let branch = self.get_branch(*branch_id)?;
+                    let child_index =
+                        branch.find_child_index_by(start_key, |a, b| self.compare(a, b));
+
+                    if child_index < branch.children.len() {
+                        current = &branch.children[child_index];
+                    } else {
+                        return None;
+                    }
+                }
+            }
+        }
+    }
+
+    /// Find the position just past the entries within `end`: the first entry
+    /// whose key is beyond the bound, or `None` if there is no such entry.
+    fn find_range_end(&self, end: Bound<&K>) -> Option<LeafPosition> {
+        let (end_key, inclusive) = match end {
+            Bound::Included(key) => (key, true),
+            Bound::Excluded(key) => (key, false),
+            Bound::Unbounded => return None,
+        };
+        let leaf_id = self.find_leaf_for_key(end_key)?;
+        let leaf = self.get_leaf(leaf_id)?;
+        let index = leaf
+            .keys
+            .partition_point(|key| !self.order.beyond_end(key, end_key, inclusive));
+
+        if index < leaf.keys.len() {
+            Some((leaf_id, index))
+        } else if leaf.next != NULL_NODE {
+            // Every key here is within the bound, so the next leaf starts past it
+            Some((leaf.next, 0))
+        } else {
+            None
+        }
+    }
+
+    /// The key stored at a `(leaf_id, index)` position.
+    fn key_at(&self, (leaf_id, index): LeafPosition) -> Option<&K> {
+        self.get_leaf(leaf_id)?.keys.get(index)
+    }
+
+    /// The position after `(leaf_id, index)`, following the leaf chain.
+    fn next_position(&self, (leaf_id, index): LeafPosition) -> Option<LeafPosition> {
+        let leaf = self.get_leaf(leaf_id)?;
+        if index + 1 < leaf.keys.len() {
+            Some((leaf_id, index + 1))
+        } else if leaf.next != NULL_NODE {
+            Some((leaf.next, 0))
+        } else {
+            None
+        }
+    }
+
+    /// Count the entries in a range as the difference of two ranks.
+    fn count_range<R>(&self, range: &R) -> usize
+    where
+        R: RangeBounds<K>,
+    {
+        let start = match range.start_bound() {
+            Bound::Included(key) => self.rank_by(key, false),
+            Bound::Excluded(key) => self.rank_by(key, true),
+            Bound::Unbounded => 0,
+        };
+        let end = match range.end_bound() {
+            Bound::Included(key) => self.rank_by(key, true),
+            Bound::Excluded(key) => self.rank_by(key, false),
+            Bound::Unbounded => self.node_len(&self.root),
+        };
+        end.saturating_sub(start)
+    }
+
+    /// Get the ID of the first (leftmost) leaf in the tree
+    fn get_first_leaf_id(&self) -> Option<NodeId> {
+        let mut current = &self.root;
+
+        loop {
+            match current {
+                NodeRef::Leaf(leaf_id, _) => return Some(*leaf_id),
+                NodeRef::Branch(branch_id, _) => {
+                    if let Some(branch) = self.get_branch(*branch_id) {
+                        if !branch.children.is_empty() {
+                            current = &branch.children[0];
+                        } else {
+                            return None;
+                        }
+                    } else {
+                        return None;
+                    }
+                }
+            }
+        }
+    }
+
+    /// Get the ID of the last (rightmost) leaf in the tree
+    fn get_last_leaf_id(&self) -> Option<NodeId> {
+        self.rightmost_leaf_from(self.root.clone())
+    }
+
+    /// Descend along the last child of each branch below `node`.
+    fn rightmost_leaf_from(&self, mut node: NodeRef<K, V>) -> Option<NodeId> {
+        loop {
+            match node {
+                NodeRef::Leaf(id, _) => return Some(id),
+                NodeRef::Branch(id, _) => node = self.get_branch(id)?.children.last()?.clone(),
+            }
+        }
+    }
+
+    /// Find the leaf preceding `leaf_id` in key order.
+    ///
+    /// Leaves only link forward, so this re-descends from the root using the
+    /// leaf's first key and remembers the deepest left sibling on the way.
+    fn find_prev_leaf_id(&self, leaf_id: NodeId) -> Option<NodeId> {
+        let first_key = self.get_leaf(leaf_id)?.keys.first()?;
+        let mut node = self.root.clone();
+        let mut left_sibling = None;
+        while let NodeRef::Branch(id, _) = node {
+            let (index, child) = self.get_child_for_key(id, first_key)?;
+            if index > 0 {
+                left_sibling = Some(self.get_branch(id)?.children[index - 1].clone());
+            }
+            node = child;
+        }
+        self.rightmost_leaf_from(left_sibling?)
+    }
+
+    // ============================================================================
+    // ENHANCED ARENA-BASED ALLOCATION FOR LEAF NODES
+    // ============================================================================
+
+    /// Allocate a new leaf node in the arena and return its ID.
+    ///
+    /// Fails with `AllocationError` once every `NodeId` below `NULL_NODE`
+    /// is in use, rather than handing out an ID that aliases the sentinel.
+    pub fn allocate_leaf(&mut self, leaf: LeafNode<K, V>) -> BTreeResult<NodeId> {
+        self.leaf_arena
+            .try_allocate(leaf)
+            .ok_or_else(|| BPlusTreeError::allocation_error("leaf node", "NodeId space exhausted"))
+    }
+
+    /// Deallocate a leaf node from the arena.
+    pub fn deallocate_leaf(&mut self, id: NodeId) -> Option<LeafNode<K, V>> {
+        self.leaf_arena.deallocate(id)
+    }
+
+    /// Get a reference to a leaf node in the arena.
+    pub fn get_leaf(&self, id: NodeId) -> Option<&LeafNode<K, V>> {
+        self.record_visit();
+        self.leaf_arena.get(id)
+    }
+
+    /// Get a mutable reference to a leaf node in the arena.
+    pub fn get_leaf_mut(&mut self, id: NodeId) -> Option<&mut LeafNode<K, V>> {
+        self.record_visit();
+        self.leaf_arena.get_mut(id)
+    }
+
+    /// Get the number of free leaf nodes in the arena.
+    pub fn free_leaf_count(&self) -> usize {
+        self.leaf_arena.free_count()
+    }
+
+    /// Get the number of allocated leaf nodes in the arena.
+    pub fn allocated_leaf_count(&self) -> usize {
+        self.leaf_arena.allocated_count()
+    }
+
+    /// Get the leaf arena utilization ratio.
+    pub fn leaf_utilization(&self) -> f64 {
+        self.leaf_arena.utilization()
+    }
+
+    // ============================================================================
+    // ARENA STATISTICS
+    // ============================================================================
+
+    /// Get statistics for the leaf node arena.
+    pub fn leaf_arena_stats(&self) -> arena::ArenaStats {
+        self.leaf_arena.stats()
+    }
+
+    /// Get statistics for the branch node arena.
+    pub fn branch_arena_stats(&self) -> arena::ArenaStats {
+        self.branch_arena.stats()
+    }
+
+    /// Pre-size the arenas for `expected_entries` more entries.
+    ///
+    /// Reserves enough leaf and branch slots to hold that many entries with
+    /// every node at its minimum fill, which is the worst case for inserts
+    /// that split nodes in half. A large load that follows then doesn't
+    /// reallocate the arenas as it grows. Slots already free in the arenas are
+    /// not counted, so this may over-reserve on a fragmented tree.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bplustree::BPlusTreeMap;
+    ///
+    /// let mut tree = BPlusTreeMap::new(16).unwrap();
+    /// tree.reserve_nodes(10_000);
+    /// let reserved = tree.leaf_arena_stats().reserved_capacity;
+    /// for i in 0..10_000 {
+    ///     tree.insert(i, i);
+    /// }
+    /// assert_eq!(tree.leaf_arena_stats().reserved_capacity, reserved);
+    /// ```
+    pub fn reserve_nodes(&mut self, expected_entries: usize) {
+        let min_leaf_fill = (self.leaf_capacity / 2).max(1);
+        let min_branch_fanout = self.branch_capacity / 2 + 1;
+
+        let leaves = expected_entries.div_ceil(min_leaf_fill);
+        let mut branches = 0;
+        let mut level = leaves;
+        while level > 1 {
+            level = level.div_ceil(min_branch_fanout);
+            branches += level;
+        }
+
+        self.leaf_arena.reserve(leaves);
+        self.branch_arena.reserve(branches);
+    }
+
+    /// Fraction of arena slots, across both arenas, that are free.
+    ///
+    /// Free slots are reused by later allocations but still take up memory;
+    /// `compact` brings this back to zero.
+    pub fn fragmentation(&self) -> f64 {
+        let total = self.leaf_arena.total_capacity() + self.branch_arena.total_capacity();
+        if total == 0 {
+            return 0.0;
+        }
+        (self.leaf_arena.free_count() + self.branch_arena.free_count()) as f64 / total as f64
+    }
+
+    /// Estimate how much memory the tree is using.
+    ///
+    /// Counts every arena slot (free or not) at the size of its node type,
+    /// plus the allocated capacity of each live node's vectors. Memory owned
+    /// by the keys and values themselves (e.g. `String` contents) is not
+    /// included. This walks every node, so it is O(nodes).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bplustree::BPlusTreeMap;
+    ///
+    /// let mut tree = BPlusTreeMap::new(16).unwrap();
+    /// for i in 0..1000u64 {
+    ///     tree.insert(i, i);
+    /// }
+    /// let stats = tree.memory_stats();
+    /// assert_eq!(stats.leaf_nodes, tree.leaf_count());
+    /// assert!(stats.estimated_bytes >= 1000 * 2 * std::mem::size_of::<u64>());
+    /// ```
+    pub fn memory_stats(&self) -> MemoryStats {
+        let leaf_buffers: usize = self
+            .leaf_arena
+            .iter()
+            .map(|(_, leaf)| {
+                leaf.keys.capacity() * core::mem::size_of::<K>()
+                    + leaf.values.capacity() * core::mem::size_of::<V>()
+                    + leaf.versions.capacity() * core::mem::size_of::<u64>()
+            })
+            .sum();
+        let branch_buffers: usize = self
+            .branch_arena
+            .iter()
+            .map(|(_, branch)| {
+                branch.keys.capacity() * core::mem::size_of::<K>()
+                    + branch.children.capacity() * core::mem::size_of::<NodeRef<K, V>>()
+            })
+            .sum();
+        let slots = self.leaf_arena.total_capacity()
+            * core::mem::size_of::<Option<LeafNode<K, V>>>()
+            + self.branch_arena.total_capacity() * core::mem::size_of::<Option<BranchNode<K, V>>>();
+
+        MemoryStats {
+            leaf_nodes: self.leaf_arena.allocated_count(),
+            branch_nodes: self.branch_arena.allocated_count(),
+            free_leaf_slots: self.leaf_arena.free_count(),
+            free_branch_slots: self.branch_arena.free_count(),
+            estimated_bytes: core::mem::size_of::<Self>() + slots + leaf_buffers + branch_buffers,
+        }
+    }
+
+    /// Repack the tree bottom-up from its current contents.
+    ///
+    /// Unlike [`compact`](Self::compact), which keeps the node layout, this
+    /// refills every leaf to capacity and rebuilds the branch levels, so a
+    /// tree left sparse by heavy deletion ends up as dense and shallow as a
+    /// fresh `bulk_load`. All node IDs change. Runs in O(n).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bplustree::BPlusTreeMap;
+    ///
+    /// let mut tree = BPlusTreeMap::new(4).unwrap();
+    /// for i in 0..100 {
+    ///     tree.insert(i, i);
+    /// }
+    /// tree.retain(|k, _| k % 2 == 0);
+    /// tree.rebuild();
+    /// assert_eq!(tree.leaf_sizes().iter().sum::<usize>(), 50);
+    /// assert!(tree.leaf_sizes().len() <= 13);
+    /// assert_eq!(tree.len(), 50);
+    /// ```
+    pub fn rebuild(&mut self) {
+        let entries = self.take_all_stamped();
+        self.rebuild_from_stamped(entries);
+    }
+
+    /// Repack the tree like [`rebuild`](Self::rebuild), switching both leaf
+    /// and branch nodes to `new_capacity`.
+    ///
+    /// The capacity is validated the same way as in [`new`](Self::new); on
+    /// error the tree is left untouched.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bplustree::BPlusTreeMap;
+    ///
+    /// let mut tree = BPlusTreeMap::new(4).unwrap();
+    /// for i in 0..100 {
+    ///     tree.insert(i, i);
+    /// }
+    /// tree.rebuild_with_capacity(64).unwrap();
+    /// assert_eq!(tree.capacity(), 64);
+    /// assert_eq!(tree.leaf_count(), 2);
+    /// assert!(tree.rebuild_with_capacity(2).is_err());
+    /// ```
+    pub fn rebuild_with_capacity(&mut self, new_capacity: usize) -> InitResult<()> {
+        Self::check_capacities(new_capacity, new_capacity)?;
+        let entries = self.take_all_stamped();
+        self.leaf_capacity = new_capacity;
+        self.branch_capacity = new_capacity;
+        self.rebuild_from_stamped(entries);
+        Ok(())
+    }
+
+    /// Release spare capacity held by every node's vectors.
+    ///
+    /// Node vectors grow as entries are inserted and keep their capacity
+    /// when entries are removed, so a tree emptied by heavy deletion still
+    /// holds memory sized for its peak. This shrinks each node's key, value
+    /// and child vectors to their length, in O(nodes). Later inserts into a
+    /// shrunk node reallocate, so this suits long-lived, mostly-read trees.
+    /// Arena slots are left alone; see [`compact`](Self::compact).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bplustree::BPlusTreeMap;
+    ///
+    /// let mut tree = BPlusTreeMap::new(64).unwrap();
+    /// for i in 0..10_000 {
+    ///     tree.insert(i, i);
+    /// }
+    /// for i in 0..10_000 {
+    ///     if i % 10 != 0 {
+    ///         tree.remove(&i);
+    ///     }
+    /// }
+    /// let before = tree.memory_stats().estimated_bytes;
+    /// tree.shrink_to_fit();
+    /// assert!(tree.memory_stats().estimated_bytes < before);
+    /// ```
+    pub fn shrink_to_fit(&mut self) {
+        for (_, leaf) in self.leaf_arena.iter_mut() {
+            leaf.keys.shrink_to_fit();
+            leaf.values.shrink_to_fit();
+            leaf.versions.shrink_to_fit();
+        }
+        for (_, branch) in self.branch_arena.iter_mut() {
+            branch.keys.shrink_to_fit();
+            branch.children.shrink_to_fit();
+        }
+    }
+
+    /// Rebuild both arenas densely, dropping every free slot.
+    ///
+    /// Nodes are moved into fresh arenas in key order and every `NodeId` in
+    /// the tree (root, branch children, leaf `next` links) is remapped, then
+    /// the old storage is released. Afterwards `free_leaf_count()` and
+    /// `free_branch_count()` are zero. Runs in O(nodes); contents and shape
+    /// are unchanged.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bplustree::BPlusTreeMap;
+    ///
+    /// let mut tree = BPlusTreeMap::new(4).unwrap();
+    /// for i in 0..100 {
+    ///     tree.insert(i, i);
+    /// }
+    /// for i in 0..90 {
+    ///     tree.remove(&i);
+    /// }
+    /// tree.compact();
+    /// assert_eq!(tree.free_leaf_count(), 0);
+    /// assert_eq!(tree.get(&95), Some(&95));
+    /// ```
+    pub fn compact(&mut self) {
+        let mut old_leaves = core::mem::take(&mut self.leaf_arena);
+        let mut old_branches = core::mem::take(&mut self.branch_arena);
+        let mut leaf_ids = vec![NULL_NODE; old_leaves.total_capacity()];
+        let mut leaves = Arena::with_capacity(old_leaves.allocated_count());
+        let mut branches = Arena::with_capacity(old_branches.allocated_count());
+
+        let root = self.root.clone();
+        self.root = Self::compact_node(
+            root,
+            &mut old_leaves,
+            &mut old_branches,
+            &mut leaves,
+            &mut branches,
+            &mut leaf_ids,
+        );
+
+        for (_, leaf) in leaves.iter_mut() {
+            if leaf.next != NULL_NODE {
+                leaf.next = leaf_ids[leaf.next as usize];
+            }
+        }
+        self.leaf_arena = leaves;
+        self.branch_arena = branches;
+    }
+
+    /// Compact automatically whenever a removal leaves more than `ratio` of
+    /// the arena slots free, as measured by [`fragmentation`](Self::fragmentation).
+    ///
+    /// Every operation that removes entries checks the threshold, from
+    /// `remove` and entry removal to `remove_range`, `retain`, the batch
+    /// pops, `split_off` and `drain`. Compaction also waits until at least
+    /// [`AUTO_COMPACT_MIN_FREE_NODES`] slots are free, so small trees do not
+    /// compact on nearly every removal.
+    ///
+    /// Disabled by default. Node IDs change when compaction fires, exactly
+    /// as with a manual `compact` call.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bplustree::BPlusTreeMap;
+    ///
+    /// let mut tree = BPlusTreeMap::new(4).unwrap();
+    /// tree.set_auto_compact_threshold(0.25);
+    /// for i in 0..10_000 {
+    ///     tree.insert(i, i);
+    /// }
+    /// for i in 0..9_000 {
+    ///     tree.remove(&i);
+    /// }
+    /// assert!(tree.fragmentation() <= 0.25);
+    /// ```
+    pub fn set_auto_compact_threshold(&mut self, ratio: f64) {
+        self.auto_compact_threshold = Some(ratio);
+    }
+
+    /// Turn off automatic compaction.
+    pub fn disable_auto_compact(&mut self) {
+        self.auto_compact_threshold = None;
+    }
+
+    /// Compact if a threshold is set and the arenas have grown too sparse.
+    fn auto_compact_if_needed(&mut self) {
+        let Some(ratio) = self.auto_compact_threshold else {
+            return;
+        };
+        let free = self.leaf_arena.free_count() + self.branch_arena.free_count();
+        if free >= AUTO_COMPACT_MIN_FREE_NODES && self.fragmentation() > ratio {
+            self.compact();
+        }
+    }
+
+    /// Move `node` and everything below it into the new arenas, left to
+    /// right, recording each leaf's new ID in `leaf_ids`.
+    fn compact_node(
+        node: NodeRef<K, V>,
+        old_leaves: &mut Arena<LeafNode<K, V>>,
+        old_branches: &mut Arena<BranchNode<K, V>>,
+        leaves: &mut Arena<LeafNode<K, V>>,
+        branches: &mut Arena<BranchNode<K, V>>,
+        leaf_ids: &mut [NodeId],
+    ) -> NodeRef<K, V> {
+        match node {
+            NodeRef::Leaf(id, _) => {
+                let leaf = old_leaves
+                    .deallocate(id)
+                    .expect("reachable leaf must be allocated");
+                let new_id = leaves.allocate(leaf);
+                leaf_ids[id as usize] = new_id;
+                NodeRef::Leaf(new_id, PhantomData)
+            }
+            NodeRef::Branch(id, _) => {
+                let mut branch = old_branches
+                    .deallocate(id)
+                    .expect("reachable branch must be allocated");
+                branch.children = core::mem::take(&mut branch.children)
+                    .into_iter()
+                    .map(|child| {
+                        Self::compact_node(
+                            child,
+                            old_leaves,
+                            old_branches,
+                            leaves,
+                            branches,
+                            leaf_ids,
+                        )
+                    })
+                    .collect();
+                NodeRef::Branch(branches.allocate(branch), PhantomData)
+            }
+        }
+    }
+
+    // ============================================================================
+    // OPERATION LOG
+    // ============================================================================
+
+    /// Report every entry the tree gains, replaces or loses to `sink`, after
+    /// the tree has been updated.
+    ///
+    /// The records can be persisted to a write-ahead log and replayed onto
+    /// an empty tree to rebuild it. Every method that adds or removes
+    /// entries is recorded, bulk paths such as `retain`, `split_off`,
+    /// `append` or `clear` included, as one `Insert` or `Remove` per entry.
+    /// Values changed in place through mutable references, such as those
+    /// from `get_mut`, `values_mut` or a `retain` predicate, are not. With no
+    /// sink set, the only cost is a check for the missing sink. Clones of the
+    /// tree start without a sink.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bplustree::{BPlusTreeMap, Operation};
+    /// use std::sync::{Arc, Mutex};
+    ///
+    /// let log = Arc::new(Mutex::new(Vec::new()));
+    /// let sink = Arc::clone(&log);
+    ///
+    /// let mut tree = BPlusTreeMap::new(4).unwrap();
+    /// tree.set_op_log(move |op: &Operation<i32, &str>| sink.lock().unwrap().push(op.clone()));
+    /// tree.insert(1, "one");
+    /// tree.remove(&1);
+    /// tree.remove(&1);
+    ///
+    /// assert_eq!(
+    ///     *log.lock().unwrap(),
+    ///     vec![Operation::Insert { key: 1, value: "one" }, Operation::Remove { key: 1 }]
+    /// );
+    /// ```
+    pub fn set_op_log<F>(&mut self, sink: F)
+    where
+        F: FnMut(&Operation<K, V>) + Send + Sync + 'static,
+    {
+        self.op_log = Some(Box::new(sink));
+    }
+
+    /// Stop reporting operations and drop the sink.
+    pub fn clear_op_log(&mut self) {
+        self.op_log = None;
+    }
+
+    /// Pass an operation to the sink, building it only if a sink is set.
+    fn log_operation(&mut self, operation: impl FnOnce() -> Operation<K, V>) {
+        if let Some(sink) = self.op_log.as_mut() {
+            sink(&operation());
+        }
+    }
+
+    /// Report a `Remove` for each of `keys`, which are no longer in the tree.
+    fn log_removals<'a>(&mut self, keys: impl IntoIterator<Item = &'a K>)
+    where
+        K: 'a,
+    {
+        if let Some(sink) = self.op_log.as_mut() {
+            for key in keys {
+                sink(&Operation::Remove { key: key.clone() });
+            }
+        }
+    }
+
+    /// Report a `Remove` for every entry, ahead of emptying the tree.
+    fn log_remove_all(&mut self) {
+        if let Some(mut sink) = self.op_log.take() {
+            for key in self.keys() {
+                sink(&Operation::Remove { key: key.clone() });
+            }
+            self.op_log = Some(sink);
+        }
+    }
+
+    /// Report an `Insert` for every entry, after filling the tree anew.
+    fn log_insert_all(&mut self) {
+        if let Some(mut sink) = self.op_log.take() {
+            for (key, value) in self.items() {
+                sink(&Operation::Insert {
+                    key: key.clone(),
+                    value: value.clone(),
+                });
+            }
+            self.op_log = Some(sink);
+        }
+    }
+
+    // ============================================================================
+    // VALIDATION MODE
+    // ============================================================================
+
+    /// Check the tree's invariants after every mutation: single inserts and
+    /// removals, and the bulk operations such as `retain`, `append`,
+    /// `remove_range`, `split_off` and `insert_sorted_batch`.
+    ///
+    /// This is a debugging aid: each check walks the whole tree, so it makes
+    /// every mutation O(n). A violation is recorded and can be read back with
+    /// [`last_error`](Self::last_error), pinpointing the operation that
+    /// corrupted the tree. Off by default; when off, the only cost is a check
+    /// of the flag. Switching the mode clears any recorded error.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bplustree::BPlusTreeMap;
+    ///
+    /// let mut tree = BPlusTreeMap::new(4).unwrap();
+    /// tree.set_validation_mode(true);
+    /// for i in 0..100 {
+    ///     tree.insert(i, i);
+    /// }
+    /// tree.remove(&50);
+    /// assert!(tree.last_error().is_none());
+    /// ```
+    pub fn set_validation_mode(&mut self, on: bool) {
+        self.validation_mode = on;
+        self.last_error = None;
+    }
+
+    /// Returns the most recent invariant violation found in validation mode.
+    pub fn last_error(&self) -> Option<&BPlusTreeError> {
+        self.last_error.as_ref()
+    }
+
+    /// Record an invariant violation if validation mode is on.
+    fn validate_if_enabled(&mut self) {
+        if !self.validation_mode {
+            return;
+        }
+        if let Err(details) = self.check_invariants_detailed() {
+            self.last_error = Some(BPlusTreeError::DataIntegrityError(details));
+        }
+    }
+
+    // ============================================================================
+    // VERSION TRACKING
+    // ============================================================================
+
+    /// Returns the version stamp of the most recent write.
+    ///
+    /// Stamps start at 0 for a new tree and increase with every write. Save
+    /// the current stamp after syncing and pass it to
+    /// [`changed_since`](Self::changed_since) to pick up later changes.
+    pub fn version(&self) -> u64 {
+        self.version
+    }
+
+    /// Iterate, in key order, over the entries written after `version`.
+    ///
+    /// Every entry records the stamp of its last write. Inserts, overwrites,
+    /// `merge_with` and `OccupiedEntry::insert` restamp the entry they touch.
+    /// Anything that hands out a `&mut V` (`get_mut`, `values_mut`,
+    /// `range_mut`, `update_range`, `Entry::and_modify` and the like)
+    /// restamps each entry it hands out, whether or not the value is then
+    /// changed. Operations that move entries around without writing them
+    /// (`retain`, `split_off`, `pop_first_n`, `rebuild`, `clone`, and
+    /// `append` for the receiving tree's own entries) keep their stamps.
+    /// Edits made inside the predicates of `retain` and `retain_range` are
+    /// not tracked, and removed keys are never reported.
+    ///
+    /// This walks every leaf, filtering by stamp, so it is O(n).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bplustree::BPlusTreeMap;
+    ///
+    /// let mut tree = BPlusTreeMap::new(16).unwrap();
+    /// tree.insert(1, "a");
+    /// tree.insert(2, "b");
+    /// let synced = tree.version();
+    ///
+    /// tree.insert(3, "c");
+    /// tree.insert(1, "A");
+    /// let changed: Vec<_> = tree.changed_since(synced).map(|(k, v)| (*k, *v)).collect();
+    /// assert_eq!(changed, vec![(1, "A"), (3, "c")]);
+    /// ```
+    pub fn changed_since(&self, version: u64) -> impl Iterator<Item = (&K, &V)> + '_ {
+        self.stamped_entries()
+            .filter(move |(_, _, stamp)| *stamp > version)
+            .map(|(key, value, _)| (key, value))
+    }
+
+    /// Every entry with its version stamp, in key order.
+    fn stamped_entries(&self) -> impl Iterator<Item = (&K, &V, u64)> + '_ {
+        let mut next_leaf = self.get_first_leaf_id();
+        core::iter::from_fn(move || {
+            let leaf = self.get_leaf(next_leaf?)?;
+            next_leaf = (leaf.next != NULL_NODE).then_some(leaf.next);
+            Some(leaf)
+        })
+        .flat_map(|leaf| {
+            leaf.keys
+                .iter()
+                .zip(&leaf.values)
+                .zip(&leaf.versions)
+                .map(|((key, value), version)| (key, value, *version))
+        })
+    }
+
+    /// Stamp the entry at `index` of `leaf_id` as written now and return
+    /// its value for writing.
+    fn stamped_value_mut(&mut self, leaf_id: NodeId, index: usize) -> Option<&mut V> {
+        let version = self.next_version();
+        *self.get_leaf_mut(leaf_id)?.versions.get_mut(index)? = version;
+        self.writable_value(leaf_id, index)
+    }
+
+    /// Return the value at `index` of `leaf_id` for writing, first marking
+    /// the value sums above it stale.
+    fn writable_value(&mut self, leaf_id: NodeId, index: usize) -> Option<&mut V> {
+        if self.sum_ops.is_some() {
+            let key = self.leaf_arena.get(leaf_id)?.keys.get(index)?.clone();
+            self.mark_path_sums_stale(&key);
+        }
+        self.get_leaf_mut(leaf_id)?.values.get_mut(index)
+    }
+
+    /// Advance the version counter, returning the stamp for a new write.
+    fn next_version(&mut self) -> u64 {
+        self.version += 1;
+        self.version
+    }
+
+    // ============================================================================
+    // OPERATION COUNTERS
+    // ============================================================================
+
+    /// Start counting node visits, comparisons, splits and merges, from zero.
+    ///
+    /// Counting is off by default; while it is off the only cost on the hot
+    /// path is a check for the missing counters.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bplustree::BPlusTreeMap;
+    ///
+    /// let mut tree = BPlusTreeMap::new(4).unwrap();
+    /// tree.enable_counters();
+    /// for i in 0..100 {
+    ///     tree.insert(i, i);
+    /// }
+    /// assert!(tree.operation_counters().splits > 0);
+    /// ```
+    pub fn enable_counters(&mut self) {
+        self.counters = Some(Box::default());
+    }
+
+    /// Stop counting and discard the counts gathered so far.
+    pub fn disable_counters(&mut self) {
+        self.counters = None;
+    }
+
+    /// Reset the counts to zero without disabling counting.
+    pub fn reset_counters(&mut self) {
+        if self.counters.is_some() {
+            self.enable_counters();
+        }
+    }
+
+    /// Returns the counts gathered since counting was enabled or last reset,
+    /// or all zeros when counting is disabled.
+    pub fn operation_counters(&self) -> OpCounters {
+        self.counters
+            .as_ref()
+            .map(|cells| OpCounters {
+                node_visits: cells.node_visits.load(AtomicOrdering::Relaxed) as u64,
+                comparisons: cells.comparisons.load(AtomicOrdering::Relaxed) as u64,
+                splits: cells.splits.load(AtomicOrdering::Relaxed) as u64,
+                merges: cells.merges.load(AtomicOrdering::Relaxed) as u64,
+            })
+            .unwrap_or_default()
+    }
+
+    /// Returns how many key comparisons a lookup of `key` performs.
+    ///
+    /// This is the sum of the binary-search steps taken in each node on the
+    /// path from the root to the leaf, which makes the logarithmic cost of a
+    /// lookup directly observable. The tree and its counters are untouched.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bplustree::BPlusTreeMap;
+    ///
+    /// let mut tree = BPlusTreeMap::new(16).unwrap();
+    /// for i in 0..1000 {
+    ///     tree.insert(i, i);
+    /// }
+    /// let comparisons = tree.get_comparisons(&500);
+    /// assert!(comparisons > 0 && comparisons < 20);
+    /// ```
+    pub fn get_comparisons(&self, key: &K) -> usize {
+        let mut comparisons = 0;
+        let mut node = &self.root;
+        loop {
+            match node {
+                NodeRef::Leaf(id, _) => {
+                    if let Some(leaf) = self.leaf_arena.get(*id) {
+                        comparisons +=
+                            search_comparisons(&leaf.keys, key, |a, b| self.compare(a, b));
+                    }
+                    return comparisons;
+                }
+                NodeRef::Branch(id, _) => {
+                    let Some(branch) = self.branch_arena.get(*id) else {
+                        return comparisons;
+                    };
+                    comparisons += search_comparisons(&branch.keys, key, |a, b| self.compare(a, b));
+                    match branch
+                        .children
+                        .get(branch.find_child_index_by(key, |a, b| self.compare(a, b)))
+                    {
+                        Some(child) => node = child,
+                        None => return comparisons,
+                    }
+                }
+            }
+        }
+    }
+
+    fn record_visit(&self) {
+        if let Some(cells) = &self.counters {
+            cells.node_visits.fetch_add(1, AtomicOrdering::Relaxed);
+        }
+    }
+
+    fn record_split(&self) {
+        if let Some(cells) = &self.counters {
+            cells.splits.fetch_add(1, AtomicOrdering::Relaxed);
+        }
+    }
+
+    fn record_merge(&self) {
+        if let Some(cells) = &self.counters {
+            cells.merges.fetch_add(1, AtomicOrdering::Relaxed);
+        }
+    }
+
+    fn record_comparisons(&self, keys: &[K], key: &K) {
+        if let Some(cells) = &self.counters {
+            let comparisons = search_comparisons(keys, key, |a, b| self.compare(a, b));
+            cells
+                .comparisons
+                .fetch_add(comparisons, AtomicOrdering::Relaxed);
+        }
+    }
+
+    /// Count the search in a leaf that is about to be borrowed mutably.
+    fn record_leaf_comparisons(&self, id: NodeId, key: &K) {
+        if let Some(cells) = &self.counters {
+            if let Some(leaf) = self.leaf_arena.get(id) {
+                let comparisons = search_comparisons(&leaf.keys, key, |a, b| self.compare(a, b));
+                cells
+                    .comparisons
+                    .fetch_add(comparisons, AtomicOrdering::Relaxed);
+            }
+        }
+    }
+
+    /// Set the next pointer of a leaf node in the arena.
+    pub fn set_leaf_next(&mut self, id: NodeId, next_id: NodeId) -> bool {
+        self.get_leaf_mut(id)
+            .map(|leaf| {
+                leaf.next = next_id;
+                true
+            })
+            .unwrap_or(false)
+    }
+
+    /// Get the next pointer of a leaf node in the arena.
+    pub fn get_leaf_next(&self, id: NodeId) -> Option<NodeId> {
+        self.get_leaf(id).and_then(|leaf| {
+            if leaf.next == NULL_NODE {
+                None
+            } else {
+                Some(leaf.next)
+            }
+        })
+    }
+
+    // ============================================================================
+    // CHILD LOOKUP HELPERS (Phase 2)
+    // ============================================================================
+
+    /// Find the child index and `NodeRef` for `key` in the specified branch,
+    /// returning `None` if the branch does not exist or index is out of range.
+    pub fn find_child(&self, branch_id: NodeId, key: &K) -> Option<(usize, NodeRef<K, V>)> {
+        self.get_branch(branch_id).and_then(|branch| {
+            let idx = branch.find_child_index_by(key, |a, b| self.compare(a, b));
+            branch.children.get(idx).cloned().map(|child| (idx, child))
+        })
+    }
+
+    /// Mutable version of `find_child`.
+    pub fn find_child_mut(&mut self, branch_id: NodeId, key: &K) -> Option<(usize, NodeRef<K, V>)> {
+        let order = self.order.clone();
+        self.get_branch_mut(branch_id).and_then(|branch| {
+            let idx = branch.find_child_index_by(key, |a, b| order.cmp(a, b));
+            branch.children.get(idx).cloned().map(|child| (idx, child))
+        })
+    }
+
+    // ============================================================================
+    // ENHANCED ARENA-BASED ALLOCATION FOR BRANCH NODES
+    // ============================================================================
+
+    /// Allocate a new branch node in the arena and return its ID.
+    ///
+    /// Fails with `AllocationError` once the branch `NodeId` space is used up.
+    pub fn allocate_branch(&mut self, mut branch: BranchNode<K, V>) -> BTreeResult<NodeId> {
+        branch.subtree_len = self.children_len(&branch.children);
+        branch.subtree_sum = None;
+        let id = self.branch_arena.try_allocate(branch).ok_or_else(|| {
+            BPlusTreeError::allocation_error("branch node", "NodeId space exhausted")
+        })?;
+        self.refresh_subtree_sum(id);
+        Ok(id)
+    }
+
+    /// Make sure an insert can allocate every node it might need: one leaf,
+    /// plus a branch per level if the split propagates into a new root.
+    /// Checking up front means a failed insert leaves the tree untouched.
+    fn ensure_room_for_insert(&self) -> BTreeResult<()> {
+        if self.leaf_arena.remaining_ids() < 1 {
+            return Err(BPlusTreeError::allocation_error(
+                "leaf node",
+                "NodeId space exhausted",
+            ));
+        }
+        if self.branch_arena.remaining_ids() < self.height() {
+            return Err(BPlusTreeError::allocation_error(
+                "branch node",
+                "NodeId space exhausted",
+            ));
+        }
+        Ok(())
+    }
+
+    /// Set an exclusive upper bound on the node IDs of both arenas, to
+    /// simulate a tree whose `NodeId` space is nearly exhausted.
+    #[cfg(feature = "testing")]
+    pub fn limit_node_ids(&mut self, limit: NodeId) {
+        self.leaf_arena.set_id_limit(limit);
+        self.branch_arena.set_id_limit(limit);
+    }
+
+    /// Number of entries below `node`, read from the arenas directly so
+    /// operation counters are not touched.
+    fn node_len(&self, node: &NodeRef<K, V>) -> usize {
+        match node {
+            NodeRef::Leaf(id, _) => self.leaf_arena.get(*id).map_or(0, |leaf| leaf.keys.len()),
+            NodeRef::Branch(id, _) => self
+                .branch_arena
+                .get(*id)
+                .map_or(0, |branch| branch.subtree_len),
+        }
+    }
+
+    /// Total number of entries below `children`.
+    fn children_len(&self, children: &[NodeRef<K, V>]) -> usize {
+        children.iter().map(|child| self.node_len(child)).sum()
+    }
+
+    /// Recompute a branch's entry count, and its value sum if range sums
+    /// are enabled, from its children after they change.
+    fn refresh_subtree_len(&mut self, id: NodeId) {
+        let Some(branch) = self.branch_arena.get(id) else {
+            return;
+        };
+        let len = self.children_len(&branch.children);
+        if let Some(branch) = self.branch_arena.get_mut(id) {
+            branch.subtree_len = len;
+        }
+        self.refresh_subtree_sum(id);
+    }
+
+    /// Recompute a branch's value sum from its children, first recomputing
+    /// any child branch whose sum is stale. Does nothing while range sums
+    /// are disabled.
+    fn refresh_subtree_sum(&mut self, id: NodeId) -> Option<V> {
+        let ops = self.sum_ops?;
+        let child_count = self.branch_arena.get(id)?.children.len();
+        let mut sum = (ops.zero)();
+        for index in 0..child_count {
+            let child_sum = match self.branch_arena.get(id)?.children[index] {
+                NodeRef::Leaf(leaf_id, _) => {
+                    self.leaf_arena.get(leaf_id).map(|leaf| ops.sum_leaf(leaf))
+                }
+                NodeRef::Branch(child_id, _) => {
+                    match self.branch_arena.get(child_id)?.subtree_sum.clone() {
+                        Some(child_sum) => Some(child_sum),
+                        None => self.refresh_subtree_sum(child_id),
+                    }
+                }
+            };
+            if let Some(child_sum) = child_sum {
+                sum = (ops.add)(&sum, &child_sum);
+            }
+        }
+        self.branch_arena.get_mut(id)?.subtree_sum = Some(sum.clone());
+        Some(sum)
+    }
+
+    /// Forget every branch's value sum before values are handed out for
+    /// writing; each is recomputed the next time its branch is refreshed.
+    fn mark_sums_stale(&mut self) {
+        if self.sum_ops.is_some() {
+            for (_, branch) in self.branch_arena.iter_mut() {
+                branch.subtree_sum = None;
+            }
+        }
+    }
+
+    /// Forget the value sums of the branches above `key` before its value
+    /// is handed out for writing.
+    fn mark_path_sums_stale(&mut self, key: &K) {
+        if self.sum_ops.is_none() {
+            return;
+        }
+        let mut node = self.root.clone();
+        while let NodeRef::Branch(id, _) = node {
+            let Some(branch) = self.branch_arena.get_mut(id) else {
+                return;
+            };
+            branch.subtree_sum = None;
+            let index = branch.find_child_index_by(key, |a, b| self.order.cmp(a, b));
+            node = branch.children[index].clone();
+        }
+    }
+
+    /// Deallocate a branch node from the arena.
+    pub fn deallocate_branch(&mut self, id: NodeId) -> Option<BranchNode<K, V>> {
+        self.branch_arena.deallocate(id)
+    }
+
+    /// Get a reference to a branch node in the arena.
+    pub fn get_branch(&self, id: NodeId) -> Option<&BranchNode<K, V>> {
+        self.record_visit();
+        self.branch_arena.get(id)
+    }
+
+    /// Get a mutable reference to a branch node in the arena.
+    pub fn get_branch_mut(&mut self, id: NodeId) -> Option<&mut BranchNode<K, V>> {
+        self.record_visit();
+        self.branch_arena.get_mut(id)
+    }
+
+    /// Get the number of free branch nodes in the arena.
+    pub fn free_branch_count(&self) -> usize {
+        self.branch_arena.free_count()
+    }
+
+    // ============================================================================
+    // OTHER HELPERS (TEST HELPERS)
+    // ============================================================================
+
+    /// Check if the tree maintains B+ tree invariants.
+    /// Returns true if all invariants are satisfied.
+    pub fn check_invariants(&self) -> bool {
+        self.check_node_invariants(&self.root, None, None, true)
+            && self.check_subtree_lens(&self.root).is_ok()
+    }
+
+    /// Returns true if the root node is present in its arena.
+    ///
+    /// A missing root makes lookups return `None` as if the tree were empty,
+    /// so this cheap check tells the two situations apart.
+    pub fn root_is_valid(&self) -> bool {
+        match &self.root {
+            NodeRef::Leaf(id, _) => self.get_leaf(*id).is_some(),
+            NodeRef::Branch(id, _) => self.get_branch(*id).is_some(),
+        }
+    }
+
+    /// Check invariants with detailed error reporting.
+    pub fn check_invariants_detailed(&self) -> Result<(), String> {
+        // The root must exist before anything below it can be checked
+        if !self.root_is_valid() {
+            let (kind, id) = match &self.root {
+                NodeRef::Leaf(id, _) => ("leaf", *id),
+                NodeRef::Branch(id, _) => ("branch", *id),
+            };
+            return Err(BPlusTreeError::corrupted_tree(
+                "Root",
+                &format!("root {} node {} is missing from the arena", kind, id),
+            )
+            .to_string());
+        }
+
+        // First check the tree structure invariants
+        if !self.check_node_invariants(&self.root, None, None, true) {
+            return Err("Tree invariants violated".to_string());
+        }
+
+        // Then check the linked list invariants
+        self.check_linked_list_invariants()?;
+
+        // Order-statistic queries rely on every branch's entry count
+        self.check_subtree_lens(&self.root)?;
+
+        // Finally check arena-tree consistency
+        self.check_arena_tree_consistency()
+            .map_err(|e| e.to_string())?;
+        Ok(())
+    }
+
+    /// Scan the tree and fix the derived structure that can drift after a
+    /// bug or an unexpected panic, returning the number of corrections.
+    ///
+    /// Branch separators that no longer split their neighbouring subtrees
+    /// are reset to the right subtree's minimum, branch entry counts are
+    /// recounted, and the leaf `next` chain is relinked in key order. If the
+    /// tree still fails `check_invariants_detailed` afterwards (for example
+    /// because of underfull nodes), it is rebuilt from its entries, which
+    /// counts as one more correction.
+    ///
+    /// # Errors
+    ///
+    /// Returns `CorruptedTree` when the entries themselves are damaged:
+    /// a missing node, or leaves whose keys are out of order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bplustree::BPlusTreeMap;
+    ///
+    /// let mut tree = BPlusTreeMap::new(4).unwrap();
+    /// for i in 0..100 {
+    ///     tree.insert(i, i);
+    /// }
+    /// assert_eq!(tree.repair().unwrap(), 0);
+    /// ```
+    pub fn repair(&mut self) -> BTreeResult<usize> {
+        if !self.root_is_valid() {
+            return Err(BPlusTreeError::corrupted_tree(
+                "Root",
+                "root node is missing from the arena",
+            ));
+        }
+
+        let mut corrections = 0;
+        self.repair_node(&self.root.clone(), &mut corrections)?;
+
+        let mut leaf_ids = Vec::new();
+        self.collect_leaf_ids(&self.root.clone(), &mut leaf_ids);
+        let successors = leaf_ids
+            .iter()
+            .skip(1)
+            .copied()
+            .chain(core::iter::once(NULL_NODE));
+        for (&id, next) in leaf_ids.iter().zip(successors) {
+            if let Some(leaf) = self.leaf_arena.get_mut(id) {
+                if leaf.next != next {
+                    leaf.next = next;
+                    corrections += 1;
+                }
+            }
+        }
+
+        if self.check_invariants_detailed().is_err() {
+            let entries = self.take_all_stamped();
+            self.rebuild_from_stamped(entries);
+            corrections += 1;
+        }
+        Ok(corrections)
+    }
+
+    /// Fix the separators and entry counts below `node`, returning the
+    /// smallest and largest key it holds.
+    fn repair_node(
+        &mut self,
+        node: &NodeRef<K, V>,
+        corrections: &mut usize,
+    ) -> BTreeResult<Option<(K, K)>> {
+        let id = node.id();
+        let children = match node {
+            NodeRef::Leaf(..) => {
+                let leaf = self
+                    .leaf_arena
+                    .get(id)
+                    .ok_or_else(|| BPlusTreeError::corrupted_tree("Leaf", "node is missing"))?;
+                if leaf
+                    .keys
+                    .windows(2)
+                    .any(|pair| self.compare(&pair[0], &pair[1]).is_ge())
+                {
+                    return Err(BPlusTreeError::corrupted_tree(
+                        "Leaf",
+                        "keys are out of order",
+                    ));
+                }
+                return Ok(leaf.keys.first().cloned().zip(leaf.keys.last().cloned()));
+            }
+            NodeRef::Branch(..) => {
+                let branch = self
+                    .branch_arena
+                    .get(id)
+                    .ok_or_else(|| BPlusTreeError::corrupted_tree("Branch", "node is missing"))?;
+                if branch.keys.len() + 1 != branch.children.len() {
+                    return Err(BPlusTreeError::corrupted_tree(
+                        "Branch",
+                        "separator and child counts disagree",
+                    ));
+                }
+                branch.children.clone()
+            }
+        };
+
+        let mut bounds: Option<(K, K)> = None;
+        for (index, child) in children.iter().enumerate() {
+            let Some((child_min, child_max)) = self.repair_node(child, corrections)? else {
+                continue;
+            };
+            if let Some((_, max)) = &bounds {
+                if self.compare(max, &child_min).is_ge() {
+                    return Err(BPlusTreeError::corrupted_tree(
+                        "Branch",
+                        "child subtrees overlap",
+                    ));
+                }
+            }
+            if index > 0 {
+                let order = self.order.clone();
+                let branch = self.branch_arena.get_mut(id).expect("branch checked above");
+                let separator = &branch.keys[index - 1];
+                let splits = bounds
+                    .as_ref()
+                    .is_none_or(|(_, max)| order.cmp(max, separator).is_lt())
+                    && order.cmp(separator, &child_min).is_le();
+                if !splits {
+                    branch.keys[index - 1] = child_min.clone();
+                    *corrections += 1;
+                }
+            }
+            bounds = Some(match bounds {
+                Some((min, _)) => (min, child_max),
+                None => (child_min, child_max),
+            });
+        }
+
+        let len = self.children_len(&children);
+        if let Some(branch) = self.branch_arena.get_mut(id) {
+            if branch.subtree_len != len {
+                branch.subtree_len = len;
+                *corrections += 1;
+            }
+        }
+        self.refresh_subtree_sum(id);
+        Ok(bounds)
+    }
+
+    /// Check that each branch's stored entry count matches its leaves,
+    /// returning the number of entries below `node`.
+    fn check_subtree_lens(&self, node: &NodeRef<K, V>) -> Result<usize, String> {
+        match node {
+            NodeRef::Leaf(id, _) => Ok(self.leaf_arena.get(*id).map_or(0, |leaf| leaf.keys.len())),
+            NodeRef::Branch(id, _) => {
+                let Some(branch) = self.branch_arena.get(*id) else {
+                    return Ok(0);
+                };
+                let mut actual = 0;
+                for child in &branch.children {
+                    actual += self.check_subtree_lens(child)?;
+                }
+                if actual != branch.subtree_len {
+                    return Err(BPlusTreeError::corrupted_tree(
+                        "Branch",
+                        &format!(
+                            "branch {} records {} entries but holds {}",
+                            id, branch.subtree_len, actual
+                        ),
+                    )
+                    .to_string());
+                }
+                Ok(actual)
+            }
+        }
+    }
+
+    /// Check that arena allocation matches tree structure
+    fn check_arena_tree_consistency(&self) -> TreeResult<()> {
+        // Count nodes in the tree structure
+        let (tree_leaf_count, tree_branch_count) = self.count_nodes_in_tree();
+
+        // Get arena counts
+        let leaf_stats = self.leaf_arena_stats();
+        let branch_stats = self.branch_arena_stats();
+
+        // Check leaf node consistency
+        if tree_leaf_count != leaf_stats.allocated_count {
+            return Err(BPlusTreeError::arena_error(
+                "Leaf consistency check",
+                &format!(
+                    "{} in tree vs {} in arena",
+                    tree_leaf_count, leaf_stats.allocated_count
+                ),
+            ));
+        }
+
+        // Check branch node consistency
+        if tree_branch_count != branch_stats.allocated_count {
+            return Err(BPlusTreeError::arena_error(
+                "Branch consistency check",
+                &format!(
+                    "{} in tree vs {} in arena",
+                    tree_branch_count, branch_stats.allocated_count
+                ),
+            ));
+        }
+
+        // Check that all leaf nodes in tree are reachable via linked list
+        self.check_leaf_linked_list_completeness()?;
+
+        Ok(())
+    }
+
+    /// Check that the leaf linked list is properly ordered and complete.
+    fn check_linked_list_invariants(&self) -> Result<(), String> {
+        // Use the iterator to get all keys
+        let keys: Vec<&K> = self.keys().collect();
+
+        // Check that keys are sorted
+        for i in 1..keys.len() {
+            if self.compare(keys[i - 1], keys[i]).is_ge() {
+                return Err(format!("Iterator returned unsorted keys at index {}", i));
+            }
+        }
+
+        // Verify we got the right number of keys
+        if keys.len() != self.len() {
+            return Err(format!(
+                "Iterator returned {} keys but tree has {} items",
+                keys.len(),
+                self.len()
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// Count the number of leaf and branch nodes actually in the tree structure.
+    pub fn count_nodes_in_tree(&self) -> (usize, usize) {
+        if matches!(self.root, NodeRef::Leaf(_, _)) {
+            // Single leaf root
+            (1, 0)
+        } else {
+            self.count_nodes_recursive(&self.root)
+        }
+    }
+
+    /// Recursively count nodes in the tree.
+    fn count_nodes_recursive(&self, node: &NodeRef<K, V>) -> (usize, usize) {
+        match node {
+            NodeRef::Leaf(_, _) => (1, 0), // Found a leaf
+            NodeRef::Branch(id, _) => {
+                if let Some(branch) = self.get_branch(*id) {
+                    let mut total_leaves = 0;
+                    let mut total_branches = 1; // Count this branch
+
+                    // Recursively count in all children
+                    for child in &branch.children {
+                        let (child_leaves, child_branches) = self.count_nodes_recursive(child);
+                        total_leaves += child_leaves;
+                        total_branches += child_branches;
+                    }
+
+                    (total_leaves, total_branches)
+                } else {
+                    // Invalid branch reference
+                    (0, 0)
+                }
+            }
+        }
+    }
+
+    /// Check that all leaf nodes in the tree are reachable via the linked list.
+    fn check_leaf_linked_list_completeness(&self) -> TreeResult<()> {
+        // Collect all leaf IDs from the tree structure
+        let mut tree_leaf_ids = Vec::new();
+        self.collect_leaf_ids(&self.root, &mut tree_leaf_ids);
+        tree_leaf_ids.sort();
+
+        // Collect all leaf IDs from the linked list traversal
+        let mut linked_list_ids = Vec::new();
+        if let Some(first_id) = self.get_first_leaf_id() {
+            let mut current_id = Some(first_id);
+            while let Some(id) = current_id {
+                linked_list_ids.push(id);
+                current_id = self.get_leaf(id).and_then(|leaf| {
+                    if leaf.next == crate::NULL_NODE {
+                        None
+                    } else {
+                        Some(leaf.next)
+                    }
+                });
+            }
+        }
+        linked_list_ids.sort();
+
+        // Compare the two lists
+        if tree_leaf_ids != linked_list_ids {
+            return Err(BPlusTreeError::corrupted_tree(
+                "Linked list",
+                &format!(
+                    "tree has {:?}, linked list has {:?}",
+                    tree_leaf_ids, linked_list_ids
+                ),
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// Returns true if the leaf linked list does not reach every leaf.
+    ///
+    /// Detects `next` pointers to missing leaves, cycles, and chains that end
+    /// before all leaves in the tree structure have been visited. Iterators
+    /// that follow the chain silently stop early in these situations.
+    pub fn has_chain_break(&self) -> bool {
+        let (expected_leaves, _) = self.count_nodes_in_tree();
+        let mut visited = 0;
+        let mut current = self.get_first_leaf_id();
+
+        while let Some(id) = current {
+            let Some(leaf) = self.get_leaf(id) else {
+                return true; // Pointer to a leaf that is not in the arena
+            };
+            visited += 1;
+            if visited > expected_leaves {
+                return true; // More links than leaves means a cycle
+            }
+            current = (leaf.next != NULL_NODE).then_some(leaf.next);
+        }
+
+        visited != expected_leaves
+    }
+
+    /// Relink the leaf chain to follow the tree structure, returning how many
+    /// `next` pointers had to be corrected.
+    ///
+    /// The branch nodes are treated as the source of truth: leaves are
+    /// collected left to right from the tree and each one is pointed at its
+    /// successor, with the last leaf terminating the chain. This fixes the
+    /// breaks reported by [`has_chain_break`](Self::has_chain_break).
+    pub fn repair_leaf_chain(&mut self) -> usize {
+        let mut leaf_ids = Vec::new();
+        self.collect_leaf_ids(&self.root.clone(), &mut leaf_ids);
+
+        let successors = leaf_ids
+            .iter()
+            .skip(1)
+            .copied()
+            .chain(core::iter::once(NULL_NODE));
+        let mut corrected = 0;
+        for (id, next) in leaf_ids.iter().copied().zip(successors) {
+            let needs_fix = self.get_leaf(id).is_some_and(|leaf| leaf.next != next);
+            if needs_fix {
+                self.set_leaf_next(id, next);
+                corrected += 1;
+            }
+        }
+        corrected
+    }
+
+    /// Follow the leaf chain from `start`, collecting leaf IDs in key order
+    /// up to the first leaf that reaches `end`.
+    ///
+    /// The walk is capped at the arena size so a corrupted, cyclic chain
+    /// cannot loop forever.
+    fn leaf_chain_from(&self, start: NodeId, end: Option<&K>) -> Vec<NodeId> {
+        let mut ids = Vec::new();
+        let mut current = start;
+        while ids.len() < self.leaf_arena.total_capacity() {
+            let Some(leaf) = self.get_leaf(current) else {
+                break;
+            };
+            ids.push(current);
+            let reaches_end = end
+                .zip(leaf.keys.last())
+                .is_some_and(|(end, last)| self.compare(last, end).is_ge());
+            if reaches_end || leaf.next == NULL_NODE {
+                break;
+            }
+            current = leaf.next;
+        }
+        ids
+    }
+
+    /// Borrow the given distinct leaves mutably at the same time.
+    ///
+    /// Used by the mutable iterators, which hand out references into several
+    /// leaves at once. Yields no leaves if the IDs repeat or are missing,
+    /// which only happens when the leaf chain is corrupted.
+    fn leaves_disjoint_mut(&mut self, ids: &[NodeId]) -> Vec<&mut LeafNode<K, V>> {
+        self.mark_sums_stale();
+        self.leaf_arena.get_disjoint_mut(ids).unwrap_or_default()
+    }
+
+    /// Collect all leaf node IDs from the tree structure.
+    fn collect_leaf_ids(&self, node: &NodeRef<K, V>, ids: &mut Vec<NodeId>) {
+        match node {
+            NodeRef::Leaf(id, _) => ids.push(*id),
+            NodeRef::Branch(id, _) => {
+                if let Some(branch) = self.get_branch(*id) {
+                    for child in &branch.children {
+                        self.collect_leaf_ids(child, ids);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Alias for check_invariants_detailed (for test compatibility).
+    pub fn validate(&self) -> Result<(), String> {
+        self.check_invariants_detailed()
+    }
+
+    /// Returns all key-value pairs as a vector (for testing/debugging).
+    pub fn slice(&self) -> Vec<(&K, &V)> {
+        self.items().collect()
+    }
+
+    /// Consume the tree and return its entries in ascending key order.
+    ///
+    /// Keys and values are moved out of the leaves, so nothing is cloned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bplustree::BPlusTreeMap;
+    ///
+    /// let mut tree = BPlusTreeMap::new(4).unwrap();
+    /// tree.insert(2, "b".to_string());
+    /// tree.insert(1, "a".to_string());
+    /// assert_eq!(
+    ///     tree.into_sorted_vec(),
+    ///     vec![(1, "a".to_string()), (2, "b".to_string())]
+    /// );
+    /// ```
+    pub fn into_sorted_vec(mut self) -> Vec<(K, V)> {
+        self.take_all_entries()
+    }
+
+    /// Consume the tree, applying `f` to every key.
+    ///
+    /// `f` must be strictly increasing: `a < b` implies `f(a) < f(b)`, as with
+    /// shifting timestamps by a fixed offset. The mapped entries then stay in
+    /// order and the new tree is bulk-built in O(n) with the same node
+    /// capacities. The result is ordered by `K2`'s `Ord`; a custom comparator
+    /// is not carried over. Monotonicity is checked with a debug assertion;
+    /// in release builds a non-monotonic `f` falls back to inserting each
+    /// entry, with the last value winning for keys that collide.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bplustree::BPlusTreeMap;
+    ///
+    /// let tree: BPlusTreeMap<u64, &str> = [(100, "a"), (200, "b")].into_iter().collect();
+    /// let shifted = tree.map_keys(|ts| ts + 5);
+    /// assert_eq!(shifted.keys().copied().collect::<Vec<_>>(), vec![105, 205]);
+    /// ```
+    pub fn map_keys<K2, F>(self, f: F) -> BPlusTreeMap<K2, V>
+    where
+        F: Fn(K) -> K2,
+        K2: Ord + Clone,
+    {
+        let mut tree = BPlusTreeMap::new_with_capacities(self.leaf_capacity, self.branch_capacity)
+            .expect("capacities of an existing tree are valid");
+        let entries: Vec<(K2, V)> = self
+            .into_sorted_vec()
+            .into_iter()
+            .map(|(key, value)| (f(key), value))
+            .collect();
+
+        let monotonic = entries.windows(2).all(|pair| pair[0].0 < pair[1].0);
+        debug_assert!(
+            monotonic,
+            "map_keys requires a strictly monotonic key mapping"
+        );
+        if monotonic {
+            tree.build_from_sorted(entries)
+                .expect("monotonic mapping keeps keys strictly increasing");
+        } else {
+            for (key, value) in entries {
+                tree.insert(key, value);
+            }
+        }
+        tree
+    }
+
+    /// Consume the tree, applying `f` to every value.
+    ///
+    /// The node layout is reused as is: keys are moved, not cloned or
+    /// compared, and every node keeps its ID, so this is a single O(n) pass.
+    /// `f` is called in key order. Settings tied to the value type, such as
+    /// an operation log, are not carried over.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bplustree::BPlusTreeMap;
+    ///
+    /// let tree: BPlusTreeMap<i32, String> =
+    ///     [(1, "one".to_string()), (2, "three".to_string())].into_iter().collect();
+    /// let lengths = tree.map_values(|s| s.len());
+    /// assert_eq!(lengths.get(&2), Some(&5));
+    /// ```
+    pub fn map_values<V2, F>(mut self, mut f: F) -> BPlusTreeMap<K, V2>
+    where
+        F: FnMut(V) -> V2,
+        V2: Clone,
+    {
+        // Map along the leaf chain so `f` sees values in key order
+        let mut mapped: Vec<Vec<V2>> = (0..self.leaf_arena.total_capacity())
+            .map(|_| Vec::new())
+            .collect();
+        let mut leaf_id = self.get_first_leaf_id();
+        while let Some(id) = leaf_id {
+            let Some(leaf) = self.leaf_arena.get_mut(id) else {
+                break;
+            };
+            mapped[id as usize] = core::mem::take(&mut leaf.values)
+                .into_iter()
+                .map(&mut f)
+                .collect();
+            leaf_id = Some(leaf.next).filter(|&next| next != NULL_NODE);
+        }
+
+        let leaf_arena = self.leaf_arena.map(|id, leaf| LeafNode {
+            capacity: leaf.capacity,
+            keys: leaf.keys,
+            values: core::mem::take(&mut mapped[id as usize]),
+            versions: leaf.versions,
+            next: leaf.next,
+        });
+        let branch_arena = self.branch_arena.map(|_, branch| BranchNode {
+            capacity: branch.capacity,
+            keys: branch.keys,
+            children: branch.children.into_iter().map(NodeRef::cast).collect(),
+            subtree_len: branch.subtree_len,
+            subtree_sum: None,
+        });
+
+        BPlusTreeMap {
+            leaf_capacity: self.leaf_capacity,
+            branch_capacity: self.branch_capacity,
+            order: self.order,
+            root: self.root.cast(),
+            leaf_arena,
+            branch_arena,
+            sum_ops: None,
+            counters: self.counters,
+            auto_compact_threshold: self.auto_compact_threshold,
+            op_log: None,
+            validation_mode: self.validation_mode,
+            last_error: None,
+            version: self.version,
+        }
+    }
+
+    /// Returns the sizes of all leaf nodes (for testing/debugging).
+    pub fn leaf_sizes(&self) -> Vec<usize> {
+        let mut sizes = Vec::new();
+        self.collect_leaf_sizes(&self.root, &mut sizes);
+        sizes
+    }
+
+    /// Prints the node chain for debugging.
+    #[cfg(feature = "std")]
+    pub fn print_node_chain(&self) {
+        println!("Tree structure:");
+        self.print_node(&self.root, 0);
+    }
+
+    fn collect_leaf_sizes(&self, node: &NodeRef<K, V>, sizes: &mut Vec<usize>) {
+        match node {
+            NodeRef::Leaf(id, _) => {
+                let size = self.get_leaf(*id).map(|leaf| leaf.keys.len()).unwrap_or(0);
+                sizes.push(size);
+            }
+            NodeRef::Branch(id, _) => {
+                if let Some(branch) = self.get_branch(*id) {
+                    for child in &branch.children {
+                        self.collect_leaf_sizes(child, sizes);
+                    }
+                }
+                // Missing arena branch contributes no leaf sizes (do nothing)
+            }
+        }
+    }
+
+    #[cfg(feature = "std")]
+    fn print_node(&self, node: &NodeRef<K, V>, depth: usize) {
+        let indent = "  ".repeat(depth);
+        match node {
+            NodeRef::Leaf(id, _) => {
+                if let Some(leaf) = self.get_leaf(*id) {
+                    println!(
+                        "{}Leaf[id={}, cap={}]: {} keys",
+                        indent,
+                        id,
+                        leaf.capacity,
+                        leaf.keys.len()
+                    );
+                } else {
+                    println!("{}Leaf[id={}]: <missing>", indent, id);
+                }
+            }
+            NodeRef::Branch(id, _) => {
+                if let Some(branch) = self.get_branch(*id) {
+                    println!(
+                        "{}Branch[id={}, cap={}]: {} keys, {} children",
+                        indent,
+                        id,
+                        branch.capacity,
+                        branch.keys.len(),
+                        branch.children.len()
+                    );
+                    for child in &branch.children {
+                        self.print_node(child, depth + 1);
+                    }
+                } else {
+                    println!("{}Branch[id={}]: <missing>", indent, id);
+                }
+            }
+        }
+    }
+
+    /// Recursively check invariants for a node and its children.
+    fn check_node_invariants(
+        &self,
+        node: &NodeRef<K, V>,
+        min_key: Option<&K>,
+        max_key: Option<&K>,
+        _is_root: bool,
+    ) -> bool {
+        match node {
+            NodeRef::Leaf(id, _) => {
+                if let Some(leaf) = self.get_leaf(*id) {
+                    // Check leaf invariants
+                    if leaf.keys.len() != leaf.values.len()
+                        || leaf.keys.len() != leaf.versions.len()
+                    {
+                        return false; // Keys, values and versions must have same length
+                    }
+
+                    // Check that keys are sorted
+                    for i in 1..leaf.keys.len() {
+                        if self.compare(&leaf.keys[i - 1], &leaf.keys[i]).is_ge() {
+                            return false; // Keys must be in ascending order
+                        }
+                    }
+
+                    // Check capacity constraints
+                    if leaf.keys.len() > self.leaf_capacity {
+                        return false; // Node exceeds capacity
+                    }
+
+                    // Check minimum occupancy
+                    if !leaf.keys.is_empty() && leaf.is_underfull() {
+                        // For root nodes, allow fewer keys only if it's the only node
+                        if _is_root {
+                            // Root leaf can have any number of keys >= 1
+                            // (This is fine for leaf roots)
+                        } else {
+                            return false; // Non-root leaf is underfull
+                        }
+                    }
+
+                    // Check key bounds
+                    if let Some(min) = min_key {
+                        if !leaf.keys.is_empty() && self.compare(&leaf.keys[0], min).is_lt() {
+                            return false; // First key must be >= min_key
+                        }
+                    }
+                    if let Some(max) = max_key {
+                        if !leaf.keys.is_empty()
+                            && self.compare(&leaf.keys[leaf.keys.len() - 1], max).is_ge()
+                        {
+                            return false; // Last key must be < max_key
+                        }
+                    }
+
+                    true
+                } else {
+                    false // Missing arena leaf is invalid
+                }
+            }
+            NodeRef::Branch(id, _) => {
+                if let Some(branch) = self.get_branch(*id) {
+                    // Check branch invariants
+                    if branch.keys.len() + 1 != branch.children.len() {
+                        return false; // Must have one more child than keys
+                    }
+
+                    // Check that keys are sorted
+                    for i in 1..branch.keys.len() {
+                        if self.compare(&branch.keys[i - 1], &branch.keys[i]).is_ge() {
+                            return false; // Keys must be in ascending order
+                        }
+                    }
+
+                    // Check capacity constraints
+                    if branch.keys.len() > self.branch_capacity {
+                        return false; // Node exceeds capacity
+                    }
+
+                    // Check minimum occupancy
+                    if !branch.keys.is_empty() && branch.is_underfull() {
+                        if _is_root {
+                            // Root branch can have any number of keys >= 1 (as long as it has children)
+                            // The only requirement is that keys.len() + 1 == children.len()
+                            // This is already checked above, so root branches are always valid
+                        } else {
+                            return false; // Non-root branch is underfull
+                        }
+                    }
+
+                    // Check that branch has at least one child
+                    if branch.children.is_empty() {
+                        return false; // Branch must have at least one child
+                    }
+
+                    // Check children recursively
+                    for (i, child) in branch.children.iter().enumerate() {
+                        let child_min = if i == 0 {
+                            min_key
+                        } else {
+                            Some(&branch.keys[i - 1])
+                        };
+                        let child_max = if i == branch.keys.len() {
+                            max_key
+                        } else {
+                            Some(&branch.keys[i])
+                        };
 
-    /// Get a mutable reference to a leaf node in the arena.
-    pub fn get_leaf_mut(&mut self, id: NodeId) -> Option<&mut LeafNode<K, V>> {
-        self.leaf_arena.get_mut(id)
+                        if !self.check_node_invariants(child, child_min, child_max, false) {
+                            return false;
+                        }
+                    }
+
+                    true
+                } else {
+                    false // Missing arena branch is invalid
+                }
+            }
+        }
     }
+}
 
-    /// Get the number of free leaf nodes in the arena.
-    pub fn free_leaf_count(&self) -> usize {
-        self.leaf_arena.free_count()
+// ============================================================================
+// RANGE AGGREGATES
+// ============================================================================
+
+impl<K, V> BPlusTreeMap<K, V>
+where
+    K: Ord + Clone,
+    V: Clone + Copy + Default + Add<Output = V>,
+{
+    /// Keep a running sum of the values below every branch, so that
+    /// [`range_sum`](Self::range_sum) runs in O(log n).
+    ///
+    /// The sums are computed once here in O(n) and then updated alongside
+    /// the branches' entry counts by every insert, removal, split and merge.
+    /// Handing out values for writing, as `get_mut` or `values_mut` do,
+    /// marks the sums above them stale; they are recomputed the next time
+    /// those branches change, and until then `range_sum` scans below them.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bplustree::BPlusTreeMap;
+    ///
+    /// let mut tree = BPlusTreeMap::new(4).unwrap();
+    /// tree.enable_range_sums();
+    /// for i in 0..100 {
+    ///     tree.insert(i, i as u64);
+    /// }
+    /// *tree.get_mut(&10).unwrap() = 1000;
+    /// assert_eq!(tree.range_sum(..), (0..100).sum::<u64>() - 10 + 1000);
+    /// ```
+    pub fn enable_range_sums(&mut self) {
+        self.sum_ops = Some(SumOps {
+            zero: V::default,
+            add: |a, b| *a + *b,
+        });
+        self.mark_sums_stale();
+        if let NodeRef::Branch(id, _) = self.root {
+            self.refresh_subtree_sum(id);
+        }
     }
 
-    /// Get the number of allocated leaf nodes in the arena.
-    pub fn allocated_leaf_count(&self) -> usize {
-        self.leaf_arena.allocated_count()
+    /// Stop keeping branch sums; `range_sum` then visits every entry in
+    /// the range.
+    pub fn disable_range_sums(&mut self) {
+        self.mark_sums_stale();
+        self.sum_ops = None;
     }
 
-    /// Get the leaf arena utilization ratio.
-    pub fn leaf_utilization(&self) -> f64 {
-        self.leaf_arena.utilization()
+    /// Sum the values of all entries whose keys fall within `range`.
+    ///
+    /// With [`enable_range_sums`](Self::enable_range_sums), subtrees lying
+    /// entirely inside the range are added from their branch's stored sum
+    /// and only the two boundary paths are scanned, giving O(log n) queries.
+    /// Otherwise every entry in the range is visited.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bplustree::BPlusTreeMap;
+    ///
+    /// let mut tree = BPlusTreeMap::new(4).unwrap();
+    /// for i in 0..100 {
+    ///     tree.insert(i, i as u64);
+    /// }
+    /// assert_eq!(tree.range_sum(10..20), (10..20).sum());
+    /// assert_eq!(tree.range_sum(..), (0..100).sum());
+    /// ```
+    pub fn range_sum<R: RangeBounds<K>>(&self, range: R) -> V {
+        self.range_sum_recursive(&self.root, None, None, &range)
     }
 
-    // ============================================================================
-    // ARENA STATISTICS
-    // ============================================================================
+    /// Sum of every value below `node`, from the stored sum when it is
+    /// current.
+    fn node_sum(&self, node: &NodeRef<K, V>) -> V {
+        match node {
+            NodeRef::Leaf(id, _) => self
+                .get_leaf(*id)
+                .map(|leaf| leaf.values.iter().fold(V::default(), |acc, v| acc + *v))
+                .unwrap_or_default(),
+            NodeRef::Branch(id, _) => self
+                .get_branch(*id)
+                .map(|branch| {
+                    branch.subtree_sum.unwrap_or_else(|| {
+                        branch
+                            .children
+                            .iter()
+                            .fold(V::default(), |acc, child| acc + self.node_sum(child))
+                    })
+                })
+                .unwrap_or_default(),
+        }
+    }
 
-    /// Get statistics for the leaf node arena.
-    pub fn leaf_arena_stats(&self) -> arena::ArenaStats {
-        self.leaf_arena.stats()
+    /// Sum the part of `range` covered by `node`, whose keys lie in `[low, high)`.
+    fn range_sum_recursive<R: RangeBounds<K>>(
+        &self,
+        node: &NodeRef<K, V>,
+        low: Option<&K>,
+        high: Option<&K>,
+        range: &R,
+    ) -> V {
+        if self.range_covers(range, low, high) {
+            return self.node_sum(node);
+        }
+
+        match node {
+            NodeRef::Leaf(id, _) => self
+                .get_leaf(*id)
+                .map(|leaf| {
+                    leaf.keys
+                        .iter()
+                        .zip(&leaf.values)
+                        .filter(|(key, _)| self.range_contains(range, key))
+                        .fold(V::default(), |acc, (_, v)| acc + *v)
+                })
+                .unwrap_or_default(),
+            NodeRef::Branch(id, _) => self
+                .get_branch(*id)
+                .map(|branch| {
+                    let mut total = V::default();
+                    for (i, child) in branch.children.iter().enumerate() {
+                        let child_low = if i == 0 {
+                            low
+                        } else {
+                            Some(&branch.keys[i - 1])
+                        };
+                        let child_high = branch.keys.get(i).or(high);
+                        if self.range_overlaps(range, child_low, child_high) {
+                            total = total
+                                + self.range_sum_recursive(child, child_low, child_high, range);
+                        }
+                    }
+                    total
+                })
+                .unwrap_or_default(),
+        }
     }
 
-    /// Get statistics for the branch node arena.
-    pub fn branch_arena_stats(&self) -> arena::ArenaStats {
-        self.branch_arena.stats()
+    /// True if `key` lies inside `range` under the tree's ordering.
+    fn range_contains<R: RangeBounds<K>>(&self, range: &R, key: &K) -> bool {
+        let after_start = match range.start_bound() {
+            Bound::Included(start) => self.compare(key, start).is_ge(),
+            Bound::Excluded(start) => self.compare(key, start).is_gt(),
+            Bound::Unbounded => true,
+        };
+        let before_end = match range.end_bound() {
+            Bound::Included(end) => self.compare(key, end).is_le(),
+            Bound::Excluded(end) => self.compare(key, end).is_lt(),
+            Bound::Unbounded => true,
+        };
+        after_start && before_end
+    }
+
+    /// True if every key in `[low, high)` is guaranteed to lie inside `range`.
+    fn range_covers<R: RangeBounds<K>>(
+        &self,
+        range: &R,
+        low: Option<&K>,
+        high: Option<&K>,
+    ) -> bool {
+        let start_ok = match (range.start_bound(), low) {
+            (Bound::Unbounded, _) => true,
+            (_, None) => false,
+            (Bound::Included(start), Some(low)) => self.compare(low, start).is_ge(),
+            (Bound::Excluded(start), Some(low)) => self.compare(low, start).is_gt(),
+        };
+        let end_ok = match (range.end_bound(), high) {
+            (Bound::Unbounded, _) => true,
+            (_, None) => false,
+            (Bound::Included(end), Some(high)) | (Bound::Excluded(end), Some(high)) => {
+                self.compare(high, end).is_le()
+            }
+        };
+        start_ok && end_ok
+    }
+
+    /// False only if no key in `[low, high)` can lie inside `range`.
+    fn range_overlaps<R: RangeBounds<K>>(
+        &self,
+        range: &R,
+        low: Option<&K>,
+        high: Option<&K>,
+    ) -> bool {
+        let before_start = match (range.start_bound(), high) {
+            (Bound::Included(start), Some(high)) | (Bound::Excluded(start), Some(high)) => {
+                self.compare(high, start).is_le()
+            }
+            _ => false,
+        };
+        let after_end = match (range.end_bound(), low) {
+            (Bound::Included(end), Some(low)) => self.compare(low, end).is_gt(),
+            (Bound::Excluded(end), Some(low)) => self.compare(low, end).is_ge(),
+            _ => false,
+        };
+        !before_start && !after_end
+    }
+}
+
+// ============================================================================
+// INTEGER KEY HELPERS
+// ============================================================================
+
+impl<K, V> BPlusTreeMap<K, V>
+where
+    K: Ord + Clone + Copy + Add<Output = K> + From<u8>,
+    V: Clone,
+{
+    /// Find the longest run of consecutive integer keys present in the tree.
+    ///
+    /// Returns the first key of the run and its length, or `None` for an
+    /// empty tree. Ties go to the run with the smallest keys. This is a
+    /// single O(n) pass over the leaf chain.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bplustree::BPlusTreeMap;
+    ///
+    /// let mut tree = BPlusTreeMap::new(4).unwrap();
+    /// for key in [1, 2, 3, 7, 8, 100] {
+    ///     tree.insert(key, ());
+    /// }
+    /// assert_eq!(tree.longest_run(), Some((1, 3)));
+    /// ```
+    pub fn longest_run(&self) -> Option<(K, usize)> {
+        let mut keys = self.keys().copied();
+        let first = keys.next()?;
+
+        let mut best = (first, 1);
+        let (mut run_start, mut run_len, mut previous) = (first, 1, first);
+        for key in keys {
+            if key == previous + K::from(1) {
+                run_len += 1;
+            } else {
+                run_start = key;
+                run_len = 1;
+            }
+            if run_len > best.1 {
+                best = (run_start, run_len);
+            }
+            previous = key;
+        }
+
+        Some(best)
+    }
+}
+
+// ============================================================================
+// NUMERIC KEY HELPERS
+// ============================================================================
+
+impl<K, V> BPlusTreeMap<K, V>
+where
+    K: Ord + Clone + Add<Output = K> + Sub<Output = K>,
+    V: Clone,
+{
+    /// Returns an iterator over the entries whose keys lie within `radius`
+    /// of `center`, i.e. the inclusive range `[center - radius, center + radius]`.
+    ///
+    /// Useful for float keys wrapped in an ordered newtype, where matching an
+    /// exact endpoint is fragile. The caller must pick a radius for which
+    /// both endpoints are representable; integer keys follow the usual
+    /// overflow rules of their `Add`/`Sub` impls.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bplustree::BPlusTreeMap;
+    ///
+    /// let mut tree = BPlusTreeMap::new(4).unwrap();
+    /// for key in (0..100).step_by(10) {
+    ///     tree.insert(key, ());
+    /// }
+    /// let near: Vec<_> = tree.range_around(&40, &10).map(|(k, _)| *k).collect();
+    /// assert_eq!(near, vec![30, 40, 50]);
+    /// ```
+    pub fn range_around(&self, center: &K, radius: &K) -> RangeIterator<'_, K, V> {
+        let low = center.clone() - radius.clone();
+        let high = center.clone() + radius.clone();
+        self.range(low..=high)
+    }
+}
+
+// ============================================================================
+// STRING KEY HELPERS
+// ============================================================================
+
+impl<K, V> BPlusTreeMap<K, V>
+where
+    K: Ord + Clone + AsRef<str> + From<String>,
+    V: Clone,
+{
+    /// Returns an iterator over the entries whose keys start with `prefix`.
+    ///
+    /// This is the range `[prefix, successor)`, where the successor is the
+    /// smallest string greater than every string with the prefix, so the
+    /// tree is descended once and only matching entries are visited. It
+    /// relies on the default string ordering and is meaningless for trees
+    /// built with a different comparator.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bplustree::BPlusTreeMap;
+    ///
+    /// let mut tree = BPlusTreeMap::new(4).unwrap();
+    /// for word in ["apple", "apricot", "banana", "ape"] {
+    ///     tree.insert(word.to_string(), word.len());
+    /// }
+    /// let ap: Vec<&str> = tree.prefix_range(&"ap".to_string()).map(|(k, _)| k.as_str()).collect();
+    /// assert_eq!(ap, vec!["ape", "apple", "apricot"]);
+    /// ```
+    pub fn prefix_range(&self, prefix: &K) -> RangeIterator<'_, K, V> {
+        let upper = match prefix_successor(prefix.as_ref()) {
+            Some(successor) => Bound::Excluded(K::from(successor)),
+            None => Bound::Unbounded,
+        };
+        self.range((Bound::Included(prefix.clone()), upper))
+    }
+}
+
+/// The smallest string greater than every string starting with `prefix`,
+/// or `None` if no such string exists (empty prefix, or all `char::MAX`).
+///
+/// UTF-8 byte order matches code point order, so this increments the last
+/// character that can be incremented and drops everything after it,
+/// stepping over the surrogate range that `char` cannot represent.
+fn prefix_successor(prefix: &str) -> Option<String> {
+    let mut chars: Vec<char> = prefix.chars().collect();
+    while let Some(last) = chars.pop() {
+        let next = match last {
+            '\u{D7FF}' => Some('\u{E000}'),
+            char::MAX => None,
+            c => char::from_u32(c as u32 + 1),
+        };
+        if let Some(next) = next {
+            chars.push(next);
+            return Some(chars.into_iter().collect());
+        }
+    }
+    None
+}
+
+// ============================================================================
+// GRAPHVIZ EXPORT
+// ============================================================================
+
+impl<K: Ord + Clone + core::fmt::Debug, V: Clone> BPlusTreeMap<K, V> {
+    /// Render the tree structure as a GraphViz DOT graph.
+    ///
+    /// Branches are labelled with their separator keys and leaves with their
+    /// key range and size. Parent-child edges are solid, while the leaf
+    /// `next` chain is drawn with dashed edges that do not affect layout.
+    /// Render the output with e.g. `dot -Tpng tree.dot -o tree.png`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bplustree::BPlusTreeMap;
+    ///
+    /// let mut tree = BPlusTreeMap::new(4).unwrap();
+    /// for i in 0..10 {
+    ///     tree.insert(i, i);
+    /// }
+    /// let dot = tree.to_dot();
+    /// assert!(dot.starts_with("digraph BPlusTree {"));
+    /// ```
+    pub fn to_dot(&self) -> String {
+        use core::fmt::Write;
+
+        let mut dot = String::from("digraph BPlusTree {\n    node [shape=box];\n");
+        let mut leaf_ids = Vec::new();
+        let mut queue = VecDeque::from([self.root.clone()]);
+
+        // Writing to a String cannot fail, so the results are ignored
+        while let Some(node) = queue.pop_front() {
+            match node {
+                NodeRef::Leaf(id, _) => {
+                    let label = match self.leaf_arena.get(id) {
+                        Some(leaf) => match (leaf.keys.first(), leaf.keys.last()) {
+                            (Some(first), Some(last)) => format!(
+                                "{} .. {}\\n{} keys",
+                                escape_dot(&format!("{:?}", first)),
+                                escape_dot(&format!("{:?}", last)),
+                                leaf.keys.len()
+                            ),
+                            _ => "empty".to_string(),
+                        },
+                        None => "missing".to_string(),
+                    };
+                    let _ = writeln!(dot, "    L{} [style=rounded, label=\"{}\"];", id, label);
+                    leaf_ids.push(id);
+                }
+                NodeRef::Branch(id, _) => {
+                    let Some(branch) = self.branch_arena.get(id) else {
+                        let _ = writeln!(dot, "    B{} [label=\"missing\"];", id);
+                        continue;
+                    };
+                    let keys: Vec<String> = branch
+                        .keys
+                        .iter()
+                        .map(|key| escape_dot(&format!("{:?}", key)))
+                        .collect();
+                    let _ = writeln!(dot, "    B{} [label=\"{}\"];", id, keys.join(" | "));
+                    for child in &branch.children {
+                        let _ = match child {
+                            NodeRef::Leaf(child_id, _) => {
+                                writeln!(dot, "    B{} -> L{};", id, child_id)
+                            }
+                            NodeRef::Branch(child_id, _) => {
+                                writeln!(dot, "    B{} -> B{};", id, child_id)
+                            }
+                        };
+                        queue.push_back(child.clone());
+                    }
+                }
+            }
+        }
+
+        for id in leaf_ids {
+            if let Some(next) = self.leaf_arena.get(id).map(|leaf| leaf.next) {
+                if next != NULL_NODE {
+                    let _ = writeln!(
+                        dot,
+                        "    L{} -> L{} [style=dashed, constraint=false];",
+                        id, next
+                    );
+                }
+            }
+        }
+
+        dot.push_str("}\n");
+        dot
+    }
+}
+
+/// Escape a label for use inside a double-quoted DOT string.
+fn escape_dot(label: &str) -> String {
+    label.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+// ============================================================================
+// VEC-VALUED MAP HELPERS
+// ============================================================================
+
+impl<K: Ord + Clone, T: Clone> BPlusTreeMap<K, Vec<T>> {
+    /// Append `item` to the vector stored under `key`, creating the vector
+    /// if the key is absent.
+    ///
+    /// This takes a single descent, avoiding the usual `get_mut`-then-`insert`
+    /// dance when grouping items into per-key lists.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bplustree::BPlusTreeMap;
+    ///
+    /// let mut groups = BPlusTreeMap::new(16).unwrap();
+    /// groups.push_to_vec_value("fruit", "apple");
+    /// groups.push_to_vec_value("fruit", "pear");
+    /// groups.push_to_vec_value("veg", "leek");
+    /// assert_eq!(groups.get(&"fruit"), Some(&vec!["apple", "pear"]));
+    /// ```
+    pub fn push_to_vec_value(&mut self, key: K, item: T) {
+        let order = self.order.clone();
+        let version = self.next_version();
+        let logging = self.op_log.is_some();
+        let mut logged = None;
+        self.insert_with(key, |_, leaf, key| {
+            match leaf.keys.binary_search_by(|k| order.cmp(k, &key)) {
+                Ok(index) => {
+                    leaf.values[index].push(item);
+                    leaf.versions[index] = version;
+                    if logging {
+                        logged = Some(Operation::Insert {
+                            key,
+                            value: leaf.values[index].clone(),
+                        });
+                    }
+                    InsertResult::Updated(None)
+                }
+                Err(_) => {
+                    let value = vec![item];
+                    if logging {
+                        logged = Some(Operation::Insert {
+                            key: key.clone(),
+                            value: value.clone(),
+                        });
+                    }
+                    leaf.insert_stamped_by(key, value, version, |a, b| order.cmp(a, b))
+                }
+            }
+        });
+        if let Some(operation) = logged {
+            self.log_operation(|| operation);
+        }
+    }
+}
+
+// ============================================================================
+// K-WAY MERGE CONSTRUCTION
+// ============================================================================
+
+/// The current head of one sorted source during a k-way merge.
+///
+/// `key` is a `K` or a reference to one, compared with the merge's key
+/// order. Ordering is reversed so that `BinaryHeap` pops the smallest key
+/// first, with ties broken by source position.
+struct MergeHead<'o, K, Q, V> {
+    key: Q,
+    value: V,
+    source: usize,
+    order: &'o KeyOrder<K>,
+}
+
+impl<K: Ord, Q: Borrow<K>, V> PartialEq for MergeHead<'_, K, Q, V> {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
     }
+}
+
+impl<K: Ord, Q: Borrow<K>, V> Eq for MergeHead<'_, K, Q, V> {}
+
+impl<K: Ord, Q: Borrow<K>, V> PartialOrd for MergeHead<'_, K, Q, V> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<K: Ord, Q: Borrow<K>, V> Ord for MergeHead<'_, K, Q, V> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.order
+            .cmp(other.key.borrow(), self.key.borrow())
+            .then_with(|| other.source.cmp(&self.source))
+    }
+}
+
+impl<K: Ord + Clone, V: Clone> BPlusTreeMap<K, V> {
+    /// Build a tree by merging several individually sorted sources.
+    ///
+    /// The sources are merged through a binary heap holding one entry per
+    /// source and streamed straight into a bulk load, so the combined input
+    /// is never materialized. When a key appears more than once, `on_dup`
+    /// receives the key, the value merged so far and the next value (sources
+    /// earlier in `sources` come first) and returns the value to keep.
+    ///
+    /// # Errors
+    ///
+    /// Returns `InvalidCapacity` for a capacity below the minimum, and
+    /// `DataIntegrityError` if any source is not sorted by key.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bplustree::BPlusTreeMap;
+    ///
+    /// let runs = vec![
+    ///     vec![(1, 1), (4, 1)].into_iter(),
+    ///     vec![(2, 1), (4, 1)].into_iter(),
+    /// ];
+    /// let tree = BPlusTreeMap::from_sorted_sources(16, runs, |_, a, b| a + b).unwrap();
+    /// assert_eq!(tree.items().collect::<Vec<_>>(), vec![(&1, &1), (&2, &1), (&4, &2)]);
+    /// ```
+    pub fn from_sorted_sources<I, F>(
+        capacity: usize,
+        sources: Vec<I>,
+        mut on_dup: F,
+    ) -> BTreeResult<Self>
+    where
+        I: Iterator<Item = (K, V)>,
+        F: FnMut(&K, V, V) -> V,
+    {
+        let mut tree = Self::new(capacity)?;
+        let mut sources = sources;
+        let order = KeyOrder::default();
+
+        let mut heap = BinaryHeap::with_capacity(sources.len());
+        for (source, iter) in sources.iter_mut().enumerate() {
+            if let Some((key, value)) = iter.next() {
+                heap.push(MergeHead {
+                    key,
+                    value,
+                    source,
+                    order: &order,
+                });
+            }
+        }
+
+        // Hold back the latest key until the next one differs, folding
+        // duplicates into it as they surface
+        let mut pending: Option<(K, V)> = None;
+        let merged = core::iter::from_fn(|| loop {
+            let Some(head) = heap.pop() else {
+                return pending.take();
+            };
+            if let Some((key, value)) = sources[head.source].next() {
+                heap.push(MergeHead {
+                    key,
+                    value,
+                    source: head.source,
+                    order: head.order,
+                });
+            }
+
+            match pending.take() {
+                Some((key, value)) if key == head.key => {
+                    let value = on_dup(&key, value, head.value);
+                    pending = Some((key, value));
+                }
+                Some(done) => {
+                    pending = Some((head.key, head.value));
+                    return Some(done);
+                }
+                None => pending = Some((head.key, head.value)),
+            }
+        });
 
-    /// Set the next pointer of a leaf node in the arena.
-    pub fn set_leaf_next(&mut self, id: NodeId, next_id: NodeId) -> bool {
-        self.get_leaf_mut(id)
-            .map(|leaf| {
-                leaf.next = next_id;
-                true
-            })
-            .unwrap_or(false)
+        tree.build_from_sorted(merged)?;
+        Ok(tree)
     }
 
-    /// Get the next pointer of a leaf node in the arena.
-    pub fn get_leaf_next(&self, id: NodeId) -> Option<NodeId> {
-        self.get_leaf(id).and_then(|leaf| {
-            if leaf.next == NULL_NODE {
-                None
-            } else {
-                Some(leaf.next)
+    /// Iterate over the entries of several trees in globally sorted order.
+    ///
+    /// Each tree contributes a single cursor to a binary heap, so the merge
+    /// is lazy and holds O(trees) state. Keys are compared with the first
+    /// tree's key order, which is its comparator if it was built with
+    /// [`with_comparator`](Self::with_comparator). By default every entry is
+    /// yielded, with equal keys ordered by their tree's position in `trees`;
+    /// use [`MergeIterator::with_policy`] to keep only the first.
+    ///
+    /// # Panics
+    ///
+    /// The iterator panics if another tree turns out not to be sorted by
+    /// the first tree's order, as happens when merging trees that use
+    /// different comparators.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bplustree::{BPlusTreeMap, DuplicatePolicy};
+    ///
+    /// let newer = BPlusTreeMap::bulk_load(4, vec![(2, "new"), (3, "new")]).unwrap();
+    /// let older = BPlusTreeMap::bulk_load(4, vec![(1, "old"), (2, "old")]).unwrap();
+    ///
+    /// let merged: Vec<_> = BPlusTreeMap::merge_iter(&[&newer, &older])
+    ///     .with_policy(DuplicatePolicy::FirstWins)
+    ///     .collect();
+    /// assert_eq!(merged, vec![(&1, &"old"), (&2, &"new"), (&3, &"new")]);
+    /// ```
+    pub fn merge_iter<'a>(trees: &[&'a BPlusTreeMap<K, V>]) -> MergeIterator<'a, K, V> {
+        let mut sources: Vec<ItemIterator<'a, K, V>> =
+            trees.iter().map(|tree| tree.items()).collect();
+        let mut heap = BinaryHeap::with_capacity(sources.len());
+        for (source, iter) in sources.iter_mut().enumerate() {
+            if let Some((key, value)) = iter.next() {
+                heap.push(MergeHead {
+                    key,
+                    value,
+                    source,
+                    order: &trees[0].order,
+                });
             }
-        })
+        }
+        MergeIterator {
+            sources,
+            heap,
+            policy: DuplicatePolicy::YieldAll,
+            last_key: None,
+        }
     }
+}
 
-    // ============================================================================
-    // CHILD LOOKUP HELPERS (Phase 2)
-    // ============================================================================
+/// How [`MergeIterator`] handles a key present in more than one tree.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum DuplicatePolicy {
+    /// Yield every entry; equal keys come out in tree order.
+    #[default]
+    YieldAll,
+    /// Yield only the entry from the earliest tree holding the key.
+    FirstWins,
+}
 
-    /// Find the child index and `NodeRef` for `key` in the specified branch,
-    /// returning `None` if the branch does not exist or index is out of range.
-    pub fn find_child(&self, branch_id: NodeId, key: &K) -> Option<(usize, NodeRef<K, V>)> {
-        self.get_branch(branch_id).and_then(|branch| {
-            let idx = branch.find_child_index(key);
-            branch.children.get(idx).cloned().map(|child| (idx, child))
-        })
-    }
+/// Lazy k-way merge over several trees, created by
+/// [`BPlusTreeMap::merge_iter`].
+pub struct MergeIterator<'a, K, V> {
+    sources: Vec<ItemIterator<'a, K, V>>,
+    heap: BinaryHeap<MergeHead<'a, K, &'a K, &'a V>>,
+    policy: DuplicatePolicy,
+    last_key: Option<&'a K>,
+}
 
-    /// Mutable version of `find_child`.
-    pub fn find_child_mut(&mut self, branch_id: NodeId, key: &K) -> Option<(usize, NodeRef<K, V>)> {
-        self.get_branch_mut(branch_id).and_then(|branch| {
-            let idx = branch.find_child_index(key);
-            branch.children.get(idx).cloned().map(|child| (idx, child))
-        })
+impl<'a, K: Ord + Clone, V: Clone> MergeIterator<'a, K, V> {
+    /// Set how keys present in more than one tree are handled.
+    pub fn with_policy(mut self, policy: DuplicatePolicy) -> Self {
+        self.policy = policy;
+        self
     }
+}
 
-    // ============================================================================
-    // ENHANCED ARENA-BASED ALLOCATION FOR BRANCH NODES
-    // ============================================================================
+impl<'a, K: Ord + Clone, V: Clone> Iterator for MergeIterator<'a, K, V> {
+    type Item = (&'a K, &'a V);
 
-    /// Allocate a new branch node in the arena and return its ID.
-    pub fn allocate_branch(&mut self, branch: BranchNode<K, V>) -> NodeId {
-        self.branch_arena.allocate(branch)
-    }
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let head = self.heap.pop()?;
+            if let Some((key, value)) = self.sources[head.source].next() {
+                // A tree with a different order would otherwise interleave
+                // out of sequence without any sign
+                assert!(
+                    head.order.cmp(head.key, key).is_lt(),
+                    "merge_iter trees must all use the first tree's key order"
+                );
+                self.heap.push(MergeHead {
+                    key,
+                    value,
+                    source: head.source,
+                    order: head.order,
+                });
+            }
 
-    /// Deallocate a branch node from the arena.
-    pub fn deallocate_branch(&mut self, id: NodeId) -> Option<BranchNode<K, V>> {
-        self.branch_arena.deallocate(id)
+            let duplicate = self
+                .last_key
+                .is_some_and(|last| head.order.cmp(last, head.key).is_eq());
+            if self.policy == DuplicatePolicy::FirstWins && duplicate {
+                continue;
+            }
+            self.last_key = Some(head.key);
+            return Some((head.key, head.value));
+        }
     }
+}
 
-    /// Get a reference to a branch node in the arena.
-    pub fn get_branch(&self, id: NodeId) -> Option<&BranchNode<K, V>> {
-        self.branch_arena.get(id)
+impl<K: Ord + Clone, V: Clone> Default for BPlusTreeMap<K, V> {
+    /// Create a B+ tree with default capacity (16).
+    fn default() -> Self {
+        Self::new(16).expect("Default capacity should be valid")
     }
+}
 
-    /// Get a mutable reference to a branch node in the arena.
-    pub fn get_branch_mut(&mut self, id: NodeId) -> Option<&mut BranchNode<K, V>> {
-        self.branch_arena.get_mut(id)
+impl<K: Ord + Clone, V: Clone> Clone for BPlusTreeMap<K, V> {
+    /// Deep-copy the tree into fresh arenas.
+    ///
+    /// The copy is bulk loaded from the entries in key order, so it is
+    /// densely packed and its node IDs are unrelated to the original's.
+    /// Range sums, the auto-compaction threshold and validation mode carry
+    /// over; operation counters, the operation log and any recorded
+    /// validation error do not.
+    fn clone(&self) -> Self {
+        let mut tree = self.empty_like();
+        tree.rebuild_from_stamped(
+            self.stamped_entries()
+                .map(|(key, value, version)| (key.clone(), value.clone(), version))
+                .collect(),
+        );
+        tree
     }
+}
 
-    
-
-    // ============================================================================
-    // OTHER HELPERS (TEST HELPERS)
-    // ============================================================================
+impl<K: Ord + Clone + core::fmt::Debug, V: Clone + core::fmt::Debug> core::fmt::Debug
+    for BPlusTreeMap<K, V>
+{
+    /// Formats the entries in key order, like `BTreeMap`.
+    ///
+    /// Use `print_node_chain` to inspect the node layout instead.
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_map().entries(self.items()).finish()
+    }
+}
 
-    /// Check if the tree maintains B+ tree invariants.
-    /// Returns true if all invariants are satisfied.
-    pub fn check_invariants(&self) -> bool {
-        self.check_node_invariants(&self.root, None, None, true)
+impl<K: Ord + Clone, V: Clone + PartialEq> PartialEq for BPlusTreeMap<K, V> {
+    /// Trees are equal when they hold the same entries in the same order,
+    /// regardless of capacity or node layout.
+    fn eq(&self, other: &Self) -> bool {
+        self.items().eq(other.items())
     }
+}
 
-    /// Check invariants with detailed error reporting.
-    pub fn check_invariants_detailed(&self) -> Result<(), String> {
-        // First check the tree structure invariants
-        if !self.check_node_invariants(&self.root, None, None, true) {
-            return Err("Tree invariants violated".to_string());
-        }
+impl<K: Ord + Clone, V: Clone + Eq> Eq for BPlusTreeMap<K, V> {}
 
-        // Then check the linked list invariants
-        self.check_linked_list_invariants()?;
+impl<K: Ord + Clone, V: Clone> IntoIterator for BPlusTreeMap<K, V> {
+    type Item = (K, V);
+    type IntoIter = IntoIter<K, V>;
 
-        // Finally check arena-tree consistency
-        self.check_arena_tree_consistency()
-            .map_err(|e| e.to_string())?;
-        Ok(())
+    /// Consume the tree, yielding owned entries in sorted key order.
+    fn into_iter(self) -> Self::IntoIter {
+        IntoIter::new(self)
     }
+}
 
-    /// Check that arena allocation matches tree structure
-    fn check_arena_tree_consistency(&self) -> TreeResult<()> {
-        // Count nodes in the tree structure
-        let (tree_leaf_count, tree_branch_count) = self.count_nodes_in_tree();
-
-        // Get arena counts
-        let leaf_stats = self.leaf_arena_stats();
-        let branch_stats = self.branch_arena_stats();
-
-        // Check leaf node consistency
-        if tree_leaf_count != leaf_stats.allocated_count {
-            return Err(BPlusTreeError::arena_error(
-                "Leaf consistency check",
-                &format!(
-                    "{} in tree vs {} in arena",
-                    tree_leaf_count, leaf_stats.allocated_count
-                ),
-            ));
+impl<K: Ord + Clone, V: Clone> FromIterator<(K, V)> for BPlusTreeMap<K, V> {
+    /// Build a tree with default capacity (16) from key-value pairs.
+    ///
+    /// Later pairs overwrite earlier ones with the same key. Input that is
+    /// already strictly increasing is bulk loaded instead of inserted.
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        let entries: Vec<(K, V)> = iter.into_iter().collect();
+        let mut tree = Self::default();
+
+        if entries.windows(2).all(|pair| pair[0].0 < pair[1].0) {
+            tree.rebuild_from_entries(entries);
+        } else {
+            for (key, value) in entries {
+                tree.insert(key, value);
+            }
         }
+        tree
+    }
+}
 
-        // Check branch node consistency
-        if tree_branch_count != branch_stats.allocated_count {
-            return Err(BPlusTreeError::arena_error(
-                "Branch consistency check",
-                &format!(
-                    "{} in tree vs {} in arena",
-                    tree_branch_count, branch_stats.allocated_count
-                ),
-            ));
+impl<K: Ord + Clone, V: Clone> Extend<(K, V)> for BPlusTreeMap<K, V> {
+    /// Insert each pair, overwriting the values of existing keys.
+    fn extend<I: IntoIterator<Item = (K, V)>>(&mut self, iter: I) {
+        for (key, value) in iter {
+            self.insert(key, value);
         }
+    }
+}
 
-        // Check that all leaf nodes in tree are reachable via linked list
-        self.check_leaf_linked_list_completeness()?;
-
-        Ok(())
+impl<'a, K: Ord + Clone, V: Clone> Extend<(&'a K, &'a V)> for BPlusTreeMap<K, V> {
+    /// Insert clones of each borrowed pair, e.g. from another tree's `items()`.
+    fn extend<I: IntoIterator<Item = (&'a K, &'a V)>>(&mut self, iter: I) {
+        self.extend(
+            iter.into_iter()
+                .map(|(key, value)| (key.clone(), value.clone())),
+        );
     }
+}
 
-    /// Check that the leaf linked list is properly ordered and complete.
-    fn check_linked_list_invariants(&self) -> Result<(), String> {
-        // Use the iterator to get all keys
-        let keys: Vec<&K> = self.keys().collect();
+// ============================================================================
+// ENTRY API
+// ============================================================================
 
-        // Check that keys are sorted
-        for i in 1..keys.len() {
-            if keys[i - 1] >= keys[i] {
-                return Err(format!("Iterator returned unsorted keys at index {}", i));
-            }
-        }
+/// A view into a single entry of a tree, which is either vacant or occupied.
+///
+/// Created by [`BPlusTreeMap::entry`].
+pub enum Entry<'a, K, V> {
+    /// The key is not in the tree.
+    Vacant(VacantEntry<'a, K, V>),
+    /// The key is in the tree.
+    Occupied(OccupiedEntry<'a, K, V>),
+}
 
-        // Verify we got the right number of keys
-        if keys.len() != self.len() {
-            return Err(format!(
-                "Iterator returned {} keys but tree has {} items",
-                keys.len(),
-                self.len()
-            ));
-        }
+/// A view into an absent key, holding the key until a value is inserted.
+///
+/// The entry remembers the leaf and slot where the key belongs. Inserting
+/// still descends the tree to keep the branches up to date, but reuses that
+/// slot instead of searching the leaf, and finds the new value without a
+/// lookup.
+pub struct VacantEntry<'a, K, V> {
+    tree: &'a mut BPlusTreeMap<K, V>,
+    key: K,
+    leaf_id: NodeId,
+    index: usize,
+}
 
-        Ok(())
-    }
+/// A view into a present key, located by its leaf and slot.
+///
+/// The entry borrows the tree mutably, so the position cannot go stale.
+pub struct OccupiedEntry<'a, K, V> {
+    tree: &'a mut BPlusTreeMap<K, V>,
+    leaf_id: NodeId,
+    index: usize,
+}
 
-    /// Count the number of leaf and branch nodes actually in the tree structure.
-    pub fn count_nodes_in_tree(&self) -> (usize, usize) {
-        if matches!(self.root, NodeRef::Leaf(_, _)) {
-            // Single leaf root
-            (1, 0)
-        } else {
-            self.count_nodes_recursive(&self.root)
+impl<'a, K: Ord + Clone, V: Clone> Entry<'a, K, V> {
+    /// Returns the entry's key.
+    pub fn key(&self) -> &K {
+        match self {
+            Entry::Vacant(entry) => entry.key(),
+            Entry::Occupied(entry) => entry.key(),
         }
     }
 
-    /// Recursively count nodes in the tree.
-    fn count_nodes_recursive(&self, node: &NodeRef<K, V>) -> (usize, usize) {
-        match node {
-            NodeRef::Leaf(_, _) => (1, 0), // Found a leaf
-            NodeRef::Branch(id, _) => {
-                if let Some(branch) = self.get_branch(*id) {
-                    let mut total_leaves = 0;
-                    let mut total_branches = 1; // Count this branch
+    /// Inserts `default` if the entry is vacant, and returns a mutable
+    /// reference to the value.
+    pub fn or_insert(self, default: V) -> &'a mut V {
+        match self {
+            Entry::Vacant(entry) => entry.insert(default),
+            Entry::Occupied(entry) => entry.into_mut(),
+        }
+    }
 
-                    // Recursively count in all children
-                    for child in &branch.children {
-                        let (child_leaves, child_branches) = self.count_nodes_recursive(child);
-                        total_leaves += child_leaves;
-                        total_branches += child_branches;
-                    }
+    /// Inserts the result of `default` if the entry is vacant, and returns a
+    /// mutable reference to the value. `default` is only called when vacant.
+    pub fn or_insert_with<F: FnOnce() -> V>(self, default: F) -> &'a mut V {
+        match self {
+            Entry::Vacant(entry) => entry.insert(default()),
+            Entry::Occupied(entry) => entry.into_mut(),
+        }
+    }
 
-                    (total_leaves, total_branches)
-                } else {
-                    // Invalid branch reference
-                    (0, 0)
-                }
+    /// Like [`or_insert_with`](Self::or_insert_with), but `default` receives
+    /// the key.
+    pub fn or_insert_with_key<F: FnOnce(&K) -> V>(self, default: F) -> &'a mut V {
+        match self {
+            Entry::Vacant(entry) => {
+                let value = default(entry.key());
+                entry.insert(value)
             }
+            Entry::Occupied(entry) => entry.into_mut(),
         }
     }
 
-    /// Check that all leaf nodes in the tree are reachable via the linked list.
-    fn check_leaf_linked_list_completeness(&self) -> TreeResult<()> {
-        // Collect all leaf IDs from the tree structure
-        let mut tree_leaf_ids = Vec::new();
-        self.collect_leaf_ids(&self.root, &mut tree_leaf_ids);
-        tree_leaf_ids.sort();
+    /// Inserts `V::default()` if the entry is vacant, and returns a mutable
+    /// reference to the value.
+    pub fn or_default(self) -> &'a mut V
+    where
+        V: Default,
+    {
+        self.or_insert_with(V::default)
+    }
 
-        // Collect all leaf IDs from the linked list traversal
-        let mut linked_list_ids = Vec::new();
-        if let Some(first_id) = self.get_first_leaf_id() {
-            let mut current_id = Some(first_id);
-            while let Some(id) = current_id {
-                linked_list_ids.push(id);
-                current_id = self.get_leaf(id).and_then(|leaf| {
-                    if leaf.next == crate::NULL_NODE {
-                        None
-                    } else {
-                        Some(leaf.next)
-                    }
-                });
+    /// Applies `f` to the value if the entry is occupied, then returns the
+    /// entry for further chaining.
+    pub fn and_modify<F: FnOnce(&mut V)>(self, f: F) -> Self {
+        match self {
+            Entry::Occupied(mut entry) => {
+                f(entry.get_mut());
+                Entry::Occupied(entry)
             }
+            Entry::Vacant(entry) => Entry::Vacant(entry),
         }
-        linked_list_ids.sort();
-
-        // Compare the two lists
-        if tree_leaf_ids != linked_list_ids {
-            return Err(BPlusTreeError::corrupted_tree(
-                "Linked list",
-                &format!(
-                    "tree has {:?}, linked list has {:?}",
-                    tree_leaf_ids, linked_list_ids
-                ),
-            ));
-        }
-
-        Ok(())
     }
+}
 
-    /// Collect all leaf node IDs from the tree structure.
-    fn collect_leaf_ids(&self, node: &NodeRef<K, V>, ids: &mut Vec<NodeId>) {
-        match node {
-            NodeRef::Leaf(id, _) => ids.push(*id),
-            NodeRef::Branch(id, _) => {
-                if let Some(branch) = self.get_branch(*id) {
-                    for child in &branch.children {
-                        self.collect_leaf_ids(child, ids);
-                    }
+impl<'a, K: Ord + Clone, V: Clone> VacantEntry<'a, K, V> {
+    /// Returns the key that would be inserted.
+    pub fn key(&self) -> &K {
+        &self.key
+    }
+
+    /// Takes ownership of the key.
+    pub fn into_key(self) -> K {
+        self.key
+    }
+
+    /// Inserts `value` under the entry's key and returns a mutable reference
+    /// to it.
+    pub fn insert(self, value: V) -> &'a mut V {
+        let (found_leaf, found_index) = (self.leaf_id, self.index);
+        let order = self.tree.order.clone();
+        let version = self.tree.next_version();
+        let mut slot = None;
+        // This is a second full descent through `insert_with`, which keeps
+        // the ancestors' entry counts and any split up to date. Only the
+        // search within the leaf is skipped, and only if the descent reaches
+        // the leaf `entry` found.
+        self.tree.insert_with(self.key, |leaf_id, leaf, key| {
+            let index = if leaf_id == found_leaf {
+                found_index
+            } else {
+                match leaf.keys.binary_search_by(|k| order.cmp(k, &key)) {
+                    Ok(index) | Err(index) => index,
                 }
-            }
-        }
+            };
+            let result = leaf.insert_absent(index, key, value, version);
+            slot = Some((leaf_id, index, leaf.keys.len()));
+            result
+        });
+        let (leaf_id, index, leaf_len) = slot.expect("insert_with reaches a leaf");
+        self.tree.inserted_value_mut(leaf_id, index, leaf_len)
     }
+}
 
-    /// Alias for check_invariants_detailed (for test compatibility).
-    pub fn validate(&self) -> Result<(), String> {
-        self.check_invariants_detailed()
+impl<'a, K: Ord + Clone, V: Clone> OccupiedEntry<'a, K, V> {
+    fn leaf(&self) -> &LeafNode<K, V> {
+        self.tree
+            .get_leaf(self.leaf_id)
+            .expect("occupied entry points at a live leaf")
     }
 
-    /// Returns all key-value pairs as a vector (for testing/debugging).
-    pub fn slice(&self) -> Vec<(&K, &V)> {
-        self.items().collect()
+    /// Returns the entry's key.
+    pub fn key(&self) -> &K {
+        &self.leaf().keys[self.index]
     }
 
-    /// Returns the sizes of all leaf nodes (for testing/debugging).
-    pub fn leaf_sizes(&self) -> Vec<usize> {
-        let mut sizes = Vec::new();
-        self.collect_leaf_sizes(&self.root, &mut sizes);
-        sizes
+    /// Returns a reference to the entry's value.
+    pub fn get(&self) -> &V {
+        &self.leaf().values[self.index]
     }
 
-    /// Prints the node chain for debugging.
-    pub fn print_node_chain(&self) {
-        println!("Tree structure:");
-        self.print_node(&self.root, 0);
+    /// Returns a mutable reference to the entry's value, stamping the entry
+    /// as written.
+    pub fn get_mut(&mut self) -> &mut V {
+        self.tree
+            .stamped_value_mut(self.leaf_id, self.index)
+            .expect("occupied entry points at a live leaf")
     }
 
-    fn collect_leaf_sizes(&self, node: &NodeRef<K, V>, sizes: &mut Vec<usize>) {
-        match node {
-            NodeRef::Leaf(id, _) => {
-                let size = self.get_leaf(*id).map(|leaf| leaf.keys.len()).unwrap_or(0);
-                sizes.push(size);
-            }
-            NodeRef::Branch(id, _) => {
-                if let Some(branch) = self.get_branch(*id) {
-                    for child in &branch.children {
-                        self.collect_leaf_sizes(child, sizes);
-                    }
-                }
-                // Missing arena branch contributes no leaf sizes (do nothing)
-            }
-        }
+    /// Converts the entry into a mutable reference to its value that lives
+    /// as long as the tree borrow, stamping the entry as written.
+    pub fn into_mut(self) -> &'a mut V {
+        self.tree
+            .stamped_value_mut(self.leaf_id, self.index)
+            .expect("occupied entry points at a live leaf")
     }
 
-    fn print_node(&self, node: &NodeRef<K, V>, depth: usize) {
-        let indent = "  ".repeat(depth);
-        match node {
-            NodeRef::Leaf(id, _) => {
-                if let Some(leaf) = self.get_leaf(*id) {
-                    println!(
-                        "{}Leaf[id={}, cap={}]: {} keys",
-                        indent,
-                        id,
-                        leaf.capacity,
-                        leaf.keys.len()
-                    );
-                } else {
-                    println!("{}Leaf[id={}]: <missing>", indent, id);
-                }
-            }
-            NodeRef::Branch(id, _) => {
-                if let Some(branch) = self.get_branch(*id) {
-                    println!(
-                        "{}Branch[id={}, cap={}]: {} keys, {} children",
-                        indent,
-                        id,
-                        branch.capacity,
-                        branch.keys.len(),
-                        branch.children.len()
-                    );
-                    for child in &branch.children {
-                        self.print_node(child, depth + 1);
-                    }
-                } else {
-                    println!("{}Branch[id={}]: <missing>", indent, id);
-                }
-            }
+    /// Replaces the entry's value, returning the old one.
+    pub fn insert(&mut self, value: V) -> V {
+        let old_value = core::mem::replace(self.get_mut(), value);
+        if self.tree.op_log.is_some() {
+            let operation = Operation::Insert {
+                key: self.key().clone(),
+                value: self.get().clone(),
+            };
+            self.tree.log_operation(|| operation);
         }
+        old_value
     }
 
-    /// Recursively check invariants for a node and its children.
-    fn check_node_invariants(
-        &self,
-        node: &NodeRef<K, V>,
-        min_key: Option<&K>,
-        max_key: Option<&K>,
-        _is_root: bool,
-    ) -> bool {
-        match node {
-            NodeRef::Leaf(id, _) => {
-                if let Some(leaf) = self.get_leaf(*id) {
-                    // Check leaf invariants
-                    if leaf.keys.len() != leaf.values.len() {
-                        return false; // Keys and values must have same length
-                    }
-
-                    // Check that keys are sorted
-                    for i in 1..leaf.keys.len() {
-                        if leaf.keys[i - 1] >= leaf.keys[i] {
-                            return false; // Keys must be in ascending order
-                        }
-                    }
-
-                    // Check capacity constraints
-                    if leaf.keys.len() > self.capacity {
-                        return false; // Node exceeds capacity
-                    }
+    /// Removes the entry from the tree, returning its value.
+    pub fn remove(self) -> V {
+        self.remove_entry().1
+    }
 
-                    // Check minimum occupancy
-                    if !leaf.keys.is_empty() && leaf.is_underfull() {
-                        // For root nodes, allow fewer keys only if it's the only node
-                        if _is_root {
-                            // Root leaf can have any number of keys >= 1
-                            // (This is fine for leaf roots)
-                        } else {
-                            return false; // Non-root leaf is underfull
-                        }
-                    }
+    /// Removes the entry from the tree, returning its key and value.
+    pub fn remove_entry(self) -> (K, V) {
+        let key = self.key().clone();
+        let value = self
+            .tree
+            .remove(&key)
+            .expect("occupied entry key is in the tree");
+        (key, value)
+    }
+}
 
-                    // Check key bounds
-                    if let Some(min) = min_key {
-                        if !leaf.keys.is_empty() && &leaf.keys[0] < min {
-                            return false; // First key must be >= min_key
-                        }
-                    }
-                    if let Some(max) = max_key {
-                        if !leaf.keys.is_empty() && &leaf.keys[leaf.keys.len() - 1] >= max {
-                            return false; // Last key must be < max_key
-                        }
-                    }
+// ============================================================================
+// TRANSACTIONS
+// ============================================================================
 
-                    true
-                } else {
-                    false // Missing arena leaf is invalid
-                }
-            }
-            NodeRef::Branch(id, _) => {
-                if let Some(branch) = self.get_branch(*id) {
-                    // Check branch invariants
-                    if branch.keys.len() + 1 != branch.children.len() {
-                        return false; // Must have one more child than keys
-                    }
+/// Mutable access to a tree inside [`BPlusTreeMap::transaction`].
+///
+/// Mutations go straight to the tree; each one also records the key's prior
+/// value and version stamp so the transaction can be undone.
+///
+/// The view is also the transaction's guard: when it is dropped without
+/// having committed, including while unwinding from a panic, it rolls the
+/// changes back. Either way it hands the operation log back to the tree.
+pub struct TxnView<'a, K: Ord + Clone, V: Clone> {
+    tree: &'a mut BPlusTreeMap<K, V>,
+    /// Each touched key with the value and stamp it held before, in
+    /// mutation order.
+    undo: Vec<(K, Option<(V, u64)>)>,
+    /// Operations held back from the tree's log until commit, if it has one.
+    logged: Option<Vec<Operation<K, V>>>,
+    /// The tree's version when the transaction began.
+    version: u64,
+    /// The tree's operation log, held here while the transaction runs.
+    sink: Option<OpLogSink<K, V>>,
+    /// Whether the transaction's closure returned `Ok`.
+    committed: bool,
+}
 
-                    // Check that keys are sorted
-                    for i in 1..branch.keys.len() {
-                        if branch.keys[i - 1] >= branch.keys[i] {
-                            return false; // Keys must be in ascending order
-                        }
-                    }
+impl<K: Ord + Clone, V: Clone> TxnView<'_, K, V> {
+    /// Insert a key-value pair, returning the previous value.
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        let stamp = self.tree.entry_stamp(&key);
+        if let Some(logged) = self.logged.as_mut() {
+            logged.push(Operation::Insert {
+                key: key.clone(),
+                value: value.clone(),
+            });
+        }
+        let old_value = self.tree.insert(key.clone(), value);
+        self.undo.push((key, old_value.clone().zip(stamp)));
+        old_value
+    }
 
-                    // Check capacity constraints
-                    if branch.keys.len() > self.capacity {
-                        return false; // Node exceeds capacity
-                    }
+    /// Remove a key, returning its value if it was present.
+    pub fn remove(&mut self, key: &K) -> Option<V> {
+        let stamp = self.tree.entry_stamp(key)?;
+        let old_value = self.tree.remove(key)?;
+        if let Some(logged) = self.logged.as_mut() {
+            logged.push(Operation::Remove { key: key.clone() });
+        }
+        self.undo
+            .push((key.clone(), Some((old_value.clone(), stamp))));
+        Some(old_value)
+    }
 
-                    // Check minimum occupancy
-                    if !branch.keys.is_empty() && branch.is_underfull() {
-                        if _is_root {
-                            // Root branch can have any number of keys >= 1 (as long as it has children)
-                            // The only requirement is that keys.len() + 1 == children.len()
-                            // This is already checked above, so root branches are always valid
-                        } else {
-                            return false; // Non-root branch is underfull
-                        }
-                    }
+    /// Get a reference to the value for a key, including uncommitted changes.
+    pub fn get(&self, key: &K) -> Option<&V> {
+        self.tree.get(key)
+    }
 
-                    // Check that branch has at least one child
-                    if branch.children.is_empty() {
-                        return false; // Branch must have at least one child
-                    }
+    /// Returns true if the key is present, including uncommitted changes.
+    pub fn contains_key(&self, key: &K) -> bool {
+        self.tree.contains_key(key)
+    }
 
-                    // Check children recursively
-                    for (i, child) in branch.children.iter().enumerate() {
-                        let child_min = if i == 0 {
-                            min_key
-                        } else {
-                            Some(&branch.keys[i - 1])
-                        };
-                        let child_max = if i == branch.keys.len() {
-                            max_key
-                        } else {
-                            Some(&branch.keys[i])
-                        };
+    /// Returns the number of entries, including uncommitted changes.
+    pub fn len(&self) -> usize {
+        self.tree.len()
+    }
 
-                        if !self.check_node_invariants(child, child_min, child_max, false) {
-                            return false;
-                        }
-                    }
+    /// Returns true if the tree is currently empty.
+    pub fn is_empty(&self) -> bool {
+        self.tree.is_empty()
+    }
 
-                    true
-                } else {
-                    false // Missing arena branch is invalid
+    /// Restore every touched key, newest change first, then the tree's
+    /// version. Nothing is logged or stamped anew.
+    fn rollback(&mut self) {
+        while let Some((key, old_entry)) = self.undo.pop() {
+            match old_entry {
+                Some((value, stamp)) => self.tree.restore_entry(key, value, stamp),
+                None => {
+                    self.tree.remove_unlogged(&key);
                 }
             }
         }
+        self.tree.version = self.version;
     }
 }
 
-impl<K: Ord + Clone, V: Clone> Default for BPlusTreeMap<K, V> {
-    /// Create a B+ tree with default capacity (16).
-    fn default() -> Self {
-        Self::new(16).expect("Default capacity should be valid")
+impl<K: Ord + Clone, V: Clone> Drop for TxnView<'_, K, V> {
+    /// Give the operation log back to the tree first, so it survives even a
+    /// rollback that panics, then either log the committed operations or
+    /// roll back, which logs nothing.
+    fn drop(&mut self) {
+        self.tree.op_log = self.sink.take();
+        if self.committed {
+            for operation in self.logged.take().into_iter().flatten() {
+                self.tree.log_operation(|| operation);
+            }
+        } else {
+            self.rollback();
+        }
     }
 }
 
@@ -2184,6 +8003,8 @@ pub struct LeafNode<K, V> {
     keys: Vec<K>,
     /// List of values corresponding to keys.
     values: Vec<V>,
+    /// Version stamp of each entry's last write, parallel to `values`.
+    versions: Vec<u64>,
     /// Next leaf node in the linked list (for range queries).
     next: NodeId,
 }
@@ -2197,6 +8018,11 @@ pub struct BranchNode<K, V> {
     keys: Vec<K>,
     /// List of child nodes (leaves or other branches).
     children: Vec<NodeRef<K, V>>,
+    /// Number of entries stored in the leaves below this branch.
+    subtree_len: usize,
+    /// Sum of the values below this branch while range sums are enabled;
+    /// `None` when disabled or while stale.
+    subtree_sum: Option<V>,
 }
 
 impl<K: Ord + Clone, V: Clone> LeafNode<K, V> {
@@ -2210,6 +8036,7 @@ impl<K: Ord + Clone, V: Clone> LeafNode<K, V> {
             capacity,
             keys: Vec::new(),
             values: Vec::new(),
+            versions: Vec::new(),
             next: NULL_NODE,
         }
     }
@@ -2235,7 +8062,15 @@ impl<K: Ord + Clone, V: Clone> LeafNode<K, V> {
 
     /// Get value for a key from this leaf node.
     pub fn get(&self, key: &K) -> Option<&V> {
-        match self.keys.binary_search(key) {
+        self.get_by(key, K::cmp)
+    }
+
+    /// Get value for a key, using `cmp` to order keys.
+    pub fn get_by<F>(&self, key: &K, cmp: F) -> Option<&V>
+    where
+        F: Fn(&K, &K) -> Ordering,
+    {
+        match self.keys.binary_search_by(|k| cmp(k, key)) {
             Ok(index) => Some(&self.values[index]),
             Err(_) => None,
         }
@@ -2243,7 +8078,15 @@ impl<K: Ord + Clone, V: Clone> LeafNode<K, V> {
 
     /// Get a mutable reference to the value for a key from this leaf node.
     pub fn get_mut(&mut self, key: &K) -> Option<&mut V> {
-        match self.keys.binary_search(key) {
+        self.get_mut_by(key, K::cmp)
+    }
+
+    /// Mutable version of `get_by`.
+    pub fn get_mut_by<F>(&mut self, key: &K, cmp: F) -> Option<&mut V>
+    where
+        F: Fn(&K, &K) -> Ordering,
+    {
+        match self.keys.binary_search_by(|k| cmp(k, key)) {
             Ok(index) => Some(&mut self.values[index]),
             Err(_) => None,
         }
@@ -2260,54 +8103,71 @@ impl<K: Ord + Clone, V: Clone> LeafNode<K, V> {
 
     /// Insert a key-value pair and handle splitting if necessary.
     pub fn insert(&mut self, key: K, value: V) -> InsertResult<K, V> {
+        self.insert_by(key, value, K::cmp)
+    }
+
+    /// Insert a key-value pair, using `cmp` to order keys.
+    pub fn insert_by<F>(&mut self, key: K, value: V, cmp: F) -> InsertResult<K, V>
+    where
+        F: Fn(&K, &K) -> Ordering,
+    {
+        self.insert_stamped_by(key, value, 0, cmp)
+    }
+
+    /// Insert a key-value pair, recording `version` as the entry's stamp.
+    fn insert_stamped_by<F>(&mut self, key: K, value: V, version: u64, cmp: F) -> InsertResult<K, V>
+    where
+        F: Fn(&K, &K) -> Ordering,
+    {
         // Do binary search once and use the result throughout
-        match self.keys.binary_search(&key) {
+        match self.keys.binary_search_by(|k| cmp(k, &key)) {
             Ok(index) => {
                 // Key already exists, update the value
-                let old_value = std::mem::replace(&mut self.values[index], value);
+                let old_value = core::mem::replace(&mut self.values[index], value);
+                self.versions[index] = version;
                 InsertResult::Updated(Some(old_value))
             }
-            Err(index) => {
-                // Key doesn't exist, need to insert
-                // Check if split is needed BEFORE inserting
-                if !self.is_full() {
-                    // Room to insert without splitting
-                    self.insert_at_index(index, key, value);
-                    // Simple insertion - no split needed
-                    return InsertResult::Updated(None);
-                }
-                // Leaf is at capacity, split first then insert
-                let mut new_leaf_data = self.split();
-                let separator_key = new_leaf_data.keys[0].clone();
-
-                // Determine which leaf should receive the new key
-                if key < separator_key {
-                    // Insert into the current (left) leaf
-                    self.insert_at_index(index, key, value);
-                } else {
-                    // Insert into the new (right) leaf
-                    match new_leaf_data.keys.binary_search(&key) {
-                        Ok(_) => {
-                            // This should never happen with correct split logic
-                            // Return error instead of panic to maintain stability
-                            return InsertResult::Error(BPlusTreeError::data_integrity(
-                                "Leaf split operation",
-                                "Key unexpectedly found in new leaf after split",
-                            ));
-                        }
-                        Err(new_index) => {
-                            new_leaf_data.insert_at_index(new_index, key, value);
-                        }
-                    }
-                }
+            // Key doesn't exist, need to insert
+            Err(index) => self.insert_absent(index, key, value, version),
+        }
+    }
 
-                // Return the leaf data for arena allocation
-                InsertResult::Split {
-                    old_value: None,
-                    new_node_data: SplitNodeData::Leaf(new_leaf_data),
-                    separator_key,
-                }
-            }
+    /// Insert an absent key at `index`, splitting first if the leaf is full.
+    ///
+    /// If the leaf splits, the entry ends up in this leaf when `index` is
+    /// below its new length, and otherwise at `index - self.keys.len()` in
+    /// the new right leaf.
+    fn insert_absent(
+        &mut self,
+        index: usize,
+        key: K,
+        value: V,
+        version: u64,
+    ) -> InsertResult<K, V> {
+        // Check if split is needed BEFORE inserting
+        if !self.is_full() {
+            // Room to insert without splitting
+            self.insert_at_index(index, key, value, version);
+            // Simple insertion - no split needed
+            return InsertResult::Updated(None);
+        }
+        // Leaf is at capacity. `index` already says where the key goes, so
+        // the split runs without consulting the comparator again: a
+        // comparator panic can only happen before any entry has moved.
+        let mid = self.split_point();
+        let mut new_leaf_data = self.split();
+        if index > mid {
+            new_leaf_data.insert_at_index(index - mid, key, value, version);
+        } else {
+            self.insert_at_index(index, key, value, version);
+        }
+        let separator_key = new_leaf_data.keys[0].clone();
+
+        // Return the leaf data for arena allocation
+        InsertResult::Split {
+            old_value: None,
+            new_node_data: SplitNodeData::Leaf(new_leaf_data),
+            separator_key,
         }
     }
 
@@ -2316,13 +8176,14 @@ impl<K: Ord + Clone, V: Clone> LeafNode<K, V> {
     // ============================================================================
 
     /// Insert a key-value pair at the specified index.
-    fn insert_at_index(&mut self, index: usize, key: K, value: V) {
+    fn insert_at_index(&mut self, index: usize, key: K, value: V, version: u64) {
         self.keys.insert(index, key);
         self.values.insert(index, value);
+        self.versions.insert(index, version);
     }
 
-    /// Split this leaf node, returning the new right node.
-    pub fn split(&mut self) -> LeafNode<K, V> {
+    /// Index of the first key that `split` moves into the new right node.
+    fn split_point(&self) -> usize {
         // For B+ trees, we need to ensure both resulting nodes have at least min_keys
         // When splitting a full node (capacity keys), we want to distribute them
         // so that both nodes have at least min_keys
@@ -2334,7 +8195,14 @@ impl<K: Ord + Clone, V: Clone> LeafNode<K, V> {
         let mid = total_keys.div_ceil(2); // Round up for odd numbers
 
         // Ensure the split point respects minimum requirements
-        let mid = mid.max(min_keys).min(total_keys - min_keys);
+        mid.max(min_keys).min(total_keys - min_keys)
+    }
+
+    /// Split this leaf node, returning the new right node.
+    pub fn split(&mut self) -> LeafNode<K, V> {
+        let min_keys = self.min_keys();
+        let total_keys = self.keys.len();
+        let mid = self.split_point();
 
         // Verify this split is valid
         debug_assert!(mid >= min_keys, "Left side would be underfull");
@@ -2349,6 +8217,7 @@ impl<K: Ord + Clone, V: Clone> LeafNode<K, V> {
         // Move right half of keys/values to new leaf
         new_leaf.keys = self.keys.split_off(mid);
         new_leaf.values = self.values.split_off(mid);
+        new_leaf.versions = self.versions.split_off(mid);
 
         // Maintain the linked list: new leaf inherits our next pointer
         new_leaf.next = self.next;
@@ -2364,9 +8233,18 @@ impl<K: Ord + Clone, V: Clone> LeafNode<K, V> {
 
     /// Remove a key from this leaf node.
     pub fn remove(&mut self, key: &K) -> Option<V> {
-        match self.keys.binary_search(key) {
+        self.remove_by(key, K::cmp)
+    }
+
+    /// Remove a key, using `cmp` to order keys.
+    pub fn remove_by<F>(&mut self, key: &K, cmp: F) -> Option<V>
+    where
+        F: Fn(&K, &K) -> Ordering,
+    {
+        match self.keys.binary_search_by(|k| cmp(k, key)) {
             Ok(index) => {
                 self.keys.remove(index);
+                self.versions.remove(index);
                 Some(self.values.remove(index))
             }
             Err(_) => None,
@@ -2423,50 +8301,81 @@ impl<K: Ord + Clone, V: Clone> LeafNode<K, V> {
     // BORROWING AND MERGING HELPERS
     // ============================================================================
 
-    /// Borrow the last key-value pair from this leaf (used when this is the left sibling)
-    pub fn borrow_last(&mut self) -> Option<(K, V)> {
+    /// Borrow the last entry and its version stamp from this leaf (used when this is the left sibling)
+    pub fn borrow_last(&mut self) -> Option<(K, V, u64)> {
         if self.keys.is_empty() || !self.can_donate() {
             return None;
         }
-        Some((self.keys.pop().unwrap(), self.values.pop().unwrap()))
+        Some((
+            self.keys.pop().unwrap(),
+            self.values.pop().unwrap(),
+            self.versions.pop().unwrap(),
+        ))
     }
 
-    /// Borrow the first key-value pair from this leaf (used when this is the right sibling)
-    pub fn borrow_first(&mut self) -> Option<(K, V)> {
+    /// Borrow the first entry and its version stamp from this leaf (used when this is the right sibling)
+    pub fn borrow_first(&mut self) -> Option<(K, V, u64)> {
         if self.keys.is_empty() || !self.can_donate() {
             return None;
         }
-        Some((self.keys.remove(0), self.values.remove(0)))
+        Some((
+            self.keys.remove(0),
+            self.values.remove(0),
+            self.versions.remove(0),
+        ))
     }
 
-    /// Accept a borrowed key-value pair at the beginning (from left sibling)
-    pub fn accept_from_left(&mut self, key: K, value: V) {
+    /// Accept a borrowed entry at the beginning (from left sibling)
+    pub fn accept_from_left(&mut self, key: K, value: V, version: u64) {
         self.keys.insert(0, key);
         self.values.insert(0, value);
+        self.versions.insert(0, version);
     }
 
-    /// Accept a borrowed key-value pair at the end (from right sibling)
-    pub fn accept_from_right(&mut self, key: K, value: V) {
+    /// Accept a borrowed entry at the end (from right sibling)
+    pub fn accept_from_right(&mut self, key: K, value: V, version: u64) {
         self.keys.push(key);
         self.values.push(value);
+        self.versions.push(version);
+    }
+
+    /// Keep only the entries for which `f` returns `true`, in order.
+    fn retain_entries<F>(&mut self, mut f: F)
+    where
+        F: FnMut(&K, &mut V) -> bool,
+    {
+        let mut kept = 0;
+        for i in 0..self.keys.len() {
+            if f(&self.keys[i], &mut self.values[i]) {
+                self.keys.swap(kept, i);
+                self.values.swap(kept, i);
+                self.versions.swap(kept, i);
+                kept += 1;
+            }
+        }
+        self.keys.truncate(kept);
+        self.values.truncate(kept);
+        self.versions.truncate(kept);
     }
 
     /// Merge all content from another leaf into this one, returning the other's next pointer
     pub fn merge_from(&mut self, other: &mut LeafNode<K, V>) -> NodeId {
         self.keys.append(&mut other.keys);
         self.values.append(&mut other.values);
+        self.versions.append(&mut other.versions);
         let other_next = other.next;
         other.next = NULL_NODE; // Clear the other's next pointer
         other_next
     }
 
     /// Extract all content from this leaf (used for merging)
-    pub fn extract_all(&mut self) -> (Vec<K>, Vec<V>, NodeId) {
-        let keys = std::mem::take(&mut self.keys);
-        let values = std::mem::take(&mut self.values);
+    pub fn extract_all(&mut self) -> (Vec<K>, Vec<V>, Vec<u64>, NodeId) {
+        let keys = core::mem::take(&mut self.keys);
+        let values = core::mem::take(&mut self.values);
+        let versions = core::mem::take(&mut self.versions);
         let next = self.next;
         self.next = NULL_NODE;
-        (keys, values, next)
+        (keys, values, versions, next)
     }
 }
 
@@ -2481,9 +8390,33 @@ impl<K: Ord + Clone, V: Clone> BranchNode<K, V> {
             capacity,
             keys: Vec::new(),
             children: Vec::new(),
+            subtree_len: 0,
+            subtree_sum: None,
         }
     }
 
+    /// Get a reference to the separator keys in this branch node.
+    pub fn keys(&self) -> &Vec<K> {
+        &self.keys
+    }
+
+    /// Mutable access to the separator keys, for corrupting a tree in tests.
+    #[cfg(feature = "testing")]
+    pub fn keys_mut(&mut self) -> &mut Vec<K> {
+        &mut self.keys
+    }
+
+    /// Mutable access to the child pointers, for corrupting a tree in tests.
+    #[cfg(feature = "testing")]
+    pub fn children_mut(&mut self) -> &mut Vec<NodeRef<K, V>> {
+        &mut self.children
+    }
+
+    /// Number of entries stored in the leaves below this branch.
+    pub fn subtree_len(&self) -> usize {
+        self.subtree_len
+    }
+
     // ============================================================================
     // GET OPERATIONS
     // ============================================================================
@@ -2513,11 +8446,37 @@ impl<K: Ord + Clone, V: Clone> BranchNode<K, V> {
 
     /// Find the child index where the given key should be located.
     pub fn find_child_index(&self, key: &K) -> usize {
-        // Binary search to find the appropriate child
-        match self.keys.binary_search(key) {
-            Ok(index) => index + 1, // Key found, go to right child
-            Err(index) => index,    // Key not found, insert position is the child index
+        self.find_child_index_by(key, K::cmp)
+    }
+
+    /// Find the child index for `key` using `cmp` to order keys.
+    ///
+    /// The index is the number of separators not greater than `key`, so an
+    /// exact match routes to the right child. The search is branchless: each
+    /// step halves the window with a conditional select instead of an early
+    /// exit, which keeps large branches free of mispredicted jumps.
+    pub fn find_child_index_by<F>(&self, key: &K, cmp: F) -> usize
+    where
+        F: Fn(&K, &K) -> Ordering,
+    {
+        let keys = &self.keys;
+        if keys.is_empty() {
+            return 0;
+        }
+
+        let mut base = 0;
+        let mut size = keys.len();
+        while size > 1 {
+            let half = size / 2;
+            let mid = base + half;
+            base = if cmp(&keys[mid], key) == Ordering::Greater {
+                base
+            } else {
+                mid
+            };
+            size -= half;
         }
+        base + usize::from(cmp(&keys[base], key) != Ordering::Greater)
     }
 
     // ============================================================================
@@ -2697,251 +8656,944 @@ impl<K: Ord + Clone, V: Clone> BranchNode<K, V> {
     }
 }
 
-/// Iterator over key-value pairs in the B+ tree using the leaf linked list.
-pub struct ItemIterator<'a, K, V> {
-    tree: &'a BPlusTreeMap<K, V>,
-    current_leaf_id: Option<NodeId>,
-    current_leaf_index: usize,
-    end_key: Option<&'a K>,
-    end_bound_key: Option<K>,
-    end_inclusive: bool,
-    finished: bool,
+/// Iterator over key-value pairs in the B+ tree using the leaf linked list.
+pub struct ItemIterator<'a, K, V> {
+    tree: &'a BPlusTreeMap<K, V>,
+    current_leaf_id: Option<NodeId>,
+    current_leaf_index: usize,
+    end_key: Option<&'a K>,
+    end_bound_key: Option<K>,
+    end_inclusive: bool,
+    finished: bool,
+    // Back cursor: leaf and one past the next index to yield, set on first next_back
+    back: Option<(NodeId, usize)>,
+}
+
+impl<'a, K: Ord + Clone, V: Clone> ItemIterator<'a, K, V> {
+    fn new(tree: &'a BPlusTreeMap<K, V>) -> Self {
+        // Start with the first (leftmost) leaf in the tree
+        let leftmost_id = tree.get_first_leaf_id();
+
+        Self {
+            tree,
+            current_leaf_id: leftmost_id,
+            current_leaf_index: 0,
+            end_key: None,
+            end_bound_key: None,
+            end_inclusive: false,
+            finished: false,
+            back: None,
+        }
+    }
+
+    /// Start from specific position with full bound control using owned keys
+    fn new_from_position_with_bounds(
+        tree: &'a BPlusTreeMap<K, V>,
+        start_leaf_id: NodeId,
+        start_index: usize,
+        end_bound: Bound<&K>,
+    ) -> Self {
+        let (end_bound_key, end_inclusive) = match end_bound {
+            Bound::Included(key) => (Some(key.clone()), true),
+            Bound::Excluded(key) => (Some(key.clone()), false),
+            Bound::Unbounded => (None, false),
+        };
+
+        Self {
+            tree,
+            current_leaf_id: Some(start_leaf_id),
+            current_leaf_index: start_index,
+            end_key: None,
+            end_bound_key,
+            end_inclusive,
+            finished: false,
+            back: None,
+        }
+    }
+}
+
+impl<'a, K: Ord + Clone, V: Clone> Iterator for ItemIterator<'a, K, V> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.finished {
+            return None;
+        }
+
+        loop {
+            // Use Option::and_then to chain operations cleanly
+            let result = self
+                .current_leaf_id
+                .and_then(|leaf_id| self.tree.get_leaf(leaf_id))
+                .and_then(|leaf| self.try_get_next_item(leaf));
+
+            match result {
+                Some(item) => return Some(item),
+                None => {
+                    // Either no current leaf or no more items in current leaf
+                    if self.finished || !self.advance_to_next_leaf().unwrap_or(false) {
+                        self.finished = true;
+                        return None;
+                    }
+                    // Continue loop with next leaf
+                }
+            }
+        }
+    }
+}
+
+impl<'a, K: Ord + Clone, V: Clone> ItemIterator<'a, K, V> {
+    /// Helper method to try getting the next item from the current leaf
+    fn try_get_next_item(&mut self, leaf: &'a LeafNode<K, V>) -> Option<(&'a K, &'a V)> {
+        // Check if we have more items in the current leaf
+        if self.current_leaf_index >= leaf.keys.len() {
+            return None;
+        }
+
+        let key = &leaf.keys[self.current_leaf_index];
+        let value = &leaf.values[self.current_leaf_index];
+
+        // Check if we've reached the end bound using Option combinators
+        let order = &self.tree.order;
+        let beyond_end = self
+            .end_key
+            .map(|end| order.beyond_end(key, end, false))
+            .or_else(|| {
+                self.end_bound_key
+                    .as_ref()
+                    .map(|end| order.beyond_end(key, end, self.end_inclusive))
+            })
+            .unwrap_or(false);
+
+        if beyond_end || self.meets_back_cursor() {
+            self.finished = true;
+            return None;
+        }
+
+        self.current_leaf_index += 1;
+        Some((key, value))
+    }
+
+    /// Whether the front cursor has caught up with the back cursor.
+    fn meets_back_cursor(&self) -> bool {
+        matches!(self.back, Some((leaf_id, end))
+            if Some(leaf_id) == self.current_leaf_id && self.current_leaf_index >= end)
+    }
+
+    /// Position the back cursor after the last item within the end bound.
+    fn init_back_cursor(&mut self) -> Option<(NodeId, usize)> {
+        let end = self.end_key.or(self.end_bound_key.as_ref());
+        let inclusive = self.end_key.is_none() && self.end_inclusive;
+        let cursor = match end {
+            Some(end) => {
+                let leaf_id = self.tree.find_leaf_for_key(end)?;
+                let leaf = self.tree.get_leaf(leaf_id)?;
+                let index = leaf
+                    .keys
+                    .partition_point(|k| !self.tree.order.beyond_end(k, end, inclusive));
+                (leaf_id, index)
+            }
+            None => {
+                let leaf_id = self.tree.get_last_leaf_id()?;
+                (leaf_id, self.tree.get_leaf(leaf_id)?.keys.len())
+            }
+        };
+        self.back = Some(cursor);
+        Some(cursor)
+    }
+
+    /// Advance the iterator to the first remaining entry whose key is
+    /// `>= key`.
+    ///
+    /// The new position is found by descending from the root, so skipping
+    /// ahead costs O(log n) no matter how many entries are passed over.
+    /// Seeking never moves backwards: if the next entry already has a key
+    /// `>= key`, this does nothing. End bounds and entries already taken
+    /// from the back are still respected.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bplustree::BPlusTreeMap;
+    ///
+    /// let tree = BPlusTreeMap::bulk_load(4, (0..1000).map(|i| (i * 2, i))).unwrap();
+    /// let mut items = tree.items();
+    /// items.seek(&501);
+    /// assert_eq!(items.next(), Some((&502, &251)));
+    /// items.seek(&10);
+    /// assert_eq!(items.next(), Some((&504, &252)));
+    /// ```
+    pub fn seek(&mut self, key: &K) {
+        if self.finished {
+            return;
+        }
+        match self.front_key() {
+            Some(front) if self.tree.compare(front, key).is_lt() => {}
+            _ => return,
+        }
+
+        let Some((leaf_id, index)) = self.tree.find_range_start(key) else {
+            self.finished = true;
+            return;
+        };
+        self.current_leaf_id = Some(leaf_id);
+        self.current_leaf_index = index;
+
+        // Jumping may carry the front cursor past the back cursor
+        if let Some((back_leaf, back_end)) = self.back {
+            let taken_from_back = self
+                .tree
+                .get_leaf(back_leaf)
+                .and_then(|leaf| leaf.keys.get(back_end));
+            if let (Some(front), Some(back)) = (self.front_key(), taken_from_back) {
+                if self.tree.compare(front, back).is_ge() {
+                    self.finished = true;
+                }
+            }
+        }
+    }
+
+    /// Key of the next entry at or after the front cursor, ignoring bounds.
+    fn front_key(&self) -> Option<&'a K> {
+        let tree = self.tree;
+        let mut leaf = tree.get_leaf(self.current_leaf_id?)?;
+        let mut index = self.current_leaf_index;
+        while index >= leaf.keys.len() {
+            if leaf.next == NULL_NODE {
+                return None;
+            }
+            leaf = tree.get_leaf(leaf.next)?;
+            index = 0;
+        }
+        Some(&leaf.keys[index])
+    }
+
+    /// Helper method to advance to the next leaf
+    /// Returns Some(true) if successfully advanced, Some(false) if no more leaves, None if invalid leaf
+    fn advance_to_next_leaf(&mut self) -> Option<bool> {
+        let current_leaf = self.current_leaf_id?;
+        let leaf = self.tree.get_leaf(current_leaf)?;
+
+        self.current_leaf_id = (leaf.next != NULL_NODE).then_some(leaf.next);
+        self.current_leaf_index = 0;
+
+        Some(self.current_leaf_id.is_some())
+    }
+}
+
+impl<'a, K: Ord + Clone, V: Clone> DoubleEndedIterator for ItemIterator<'a, K, V> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.finished {
+            return None;
+        }
+
+        let (mut leaf_id, mut end) = match self.back {
+            Some(cursor) => cursor,
+            None => match self.init_back_cursor() {
+                Some(cursor) => cursor,
+                None => {
+                    self.finished = true;
+                    return None;
+                }
+            },
+        };
+
+        loop {
+            // Stop once the back cursor reaches the front cursor
+            let in_front_leaf = Some(leaf_id) == self.current_leaf_id;
+            let floor = if in_front_leaf {
+                self.current_leaf_index
+            } else {
+                0
+            };
+
+            if end > floor {
+                let leaf = self.tree.get_leaf(leaf_id)?;
+                end -= 1;
+                self.back = Some((leaf_id, end));
+                return Some((&leaf.keys[end], &leaf.values[end]));
+            }
+
+            if in_front_leaf {
+                self.finished = true;
+                return None;
+            }
+
+            match self.tree.find_prev_leaf_id(leaf_id) {
+                Some(prev_id) => {
+                    leaf_id = prev_id;
+                    end = self.tree.get_leaf(prev_id)?.keys.len();
+                    self.back = Some((leaf_id, end));
+                }
+                None => {
+                    self.finished = true;
+                    return None;
+                }
+            }
+        }
+    }
+}
+
+/// Iterator over keys in the B+ tree.
+pub struct KeyIterator<'a, K, V> {
+    items: ItemIterator<'a, K, V>,
+}
+
+impl<'a, K: Ord + Clone, V: Clone> KeyIterator<'a, K, V> {
+    fn new(tree: &'a BPlusTreeMap<K, V>) -> Self {
+        Self {
+            items: ItemIterator::new(tree),
+        }
+    }
+}
+
+impl<'a, K: Ord + Clone, V: Clone> Iterator for KeyIterator<'a, K, V> {
+    type Item = &'a K;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.items.next().map(|(k, _)| k)
+    }
+}
+
+impl<'a, K: Ord + Clone, V: Clone> DoubleEndedIterator for KeyIterator<'a, K, V> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.items.next_back().map(|(k, _)| k)
+    }
+}
+
+/// Iterator over values in the B+ tree.
+pub struct ValueIterator<'a, K, V> {
+    items: ItemIterator<'a, K, V>,
+}
+
+impl<'a, K: Ord + Clone, V: Clone> ValueIterator<'a, K, V> {
+    fn new(tree: &'a BPlusTreeMap<K, V>) -> Self {
+        Self {
+            items: ItemIterator::new(tree),
+        }
+    }
+}
+
+impl<'a, K: Ord + Clone, V: Clone> Iterator for ValueIterator<'a, K, V> {
+    type Item = &'a V;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.items.next().map(|(_, v)| v)
+    }
+}
+
+impl<'a, K: Ord + Clone, V: Clone> DoubleEndedIterator for ValueIterator<'a, K, V> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.items.next_back().map(|(_, v)| v)
+    }
+}
+
+/// Iterator over mutable references to the values in the B+ tree.
+pub struct ValueIteratorMut<'a, K, V> {
+    leaves: alloc::vec::IntoIter<&'a mut LeafNode<K, V>>,
+    current: core::iter::Zip<core::slice::IterMut<'a, V>, core::slice::IterMut<'a, u64>>,
+    /// Stamp given to each entry as its value is handed out.
+    version: u64,
+}
+
+impl<'a, K: Ord + Clone, V: Clone> ValueIteratorMut<'a, K, V> {
+    fn new(leaves: Vec<&'a mut LeafNode<K, V>>, version: u64) -> Self {
+        Self {
+            leaves: leaves.into_iter(),
+            current: <&mut [V]>::default()
+                .iter_mut()
+                .zip(<&mut [u64]>::default().iter_mut()),
+            version,
+        }
+    }
+}
+
+impl<'a, K: Ord + Clone, V: Clone> Iterator for ValueIteratorMut<'a, K, V> {
+    type Item = &'a mut V;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some((value, stamp)) = self.current.next() {
+                *stamp = self.version;
+                return Some(value);
+            }
+            let leaf = self.leaves.next()?;
+            self.current = leaf.values.iter_mut().zip(leaf.versions.iter_mut());
+        }
+    }
+}
+
+/// Owning iterator over the entries of a B+ tree, created by `into_iter`.
+///
+/// Leaves are taken out of the arena as they are reached, so their slots are
+/// released while iteration is still in progress.
+pub struct IntoIter<K, V> {
+    leaves: Arena<LeafNode<K, V>>,
+    next_leaf: NodeId,
+    current: core::iter::Zip<alloc::vec::IntoIter<K>, alloc::vec::IntoIter<V>>,
+}
+
+impl<K: Ord + Clone, V: Clone> IntoIter<K, V> {
+    fn new(mut tree: BPlusTreeMap<K, V>) -> Self {
+        let first_leaf = tree.get_first_leaf_id().unwrap_or(NULL_NODE);
+        // Branches hold no entries, so they can go straight away
+        tree.branch_arena.clear();
+
+        Self {
+            leaves: core::mem::take(&mut tree.leaf_arena),
+            next_leaf: first_leaf,
+            current: Vec::new().into_iter().zip(Vec::new()),
+        }
+    }
+}
+
+impl<K: Ord + Clone, V: Clone> Iterator for IntoIter<K, V> {
+    type Item = (K, V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(entry) = self.current.next() {
+                return Some(entry);
+            }
+            if self.next_leaf == NULL_NODE {
+                return None;
+            }
+            let leaf = self.leaves.deallocate(self.next_leaf)?;
+            self.next_leaf = leaf.next;
+            self.current = leaf.keys.into_iter().zip(leaf.values);
+        }
+    }
+}
+
+/// Draining iterator over the entries of a B+ tree, created by `drain`.
+///
+/// The leaves are detached up front, leaving the tree empty and valid even
+/// if the iterator is leaked. Dropping it discards whatever was not yielded
+/// and hands the leaf storage back to the tree.
+pub struct DrainIterator<'a, K: Ord + Clone, V: Clone> {
+    tree: &'a mut BPlusTreeMap<K, V>,
+    leaves: Arena<LeafNode<K, V>>,
+    next_leaf: NodeId,
+    current: core::iter::Zip<alloc::vec::IntoIter<K>, alloc::vec::IntoIter<V>>,
 }
 
-impl<'a, K: Ord + Clone, V: Clone> ItemIterator<'a, K, V> {
-    fn new(tree: &'a BPlusTreeMap<K, V>) -> Self {
-        // Start with the first (leftmost) leaf in the tree
-        let leftmost_id = tree.get_first_leaf_id();
-
+impl<'a, K: Ord + Clone, V: Clone> DrainIterator<'a, K, V> {
+    fn new(tree: &'a mut BPlusTreeMap<K, V>) -> Self {
+        tree.log_remove_all();
+        let first_leaf = tree.get_first_leaf_id().unwrap_or(NULL_NODE);
+        let leaves = core::mem::take(&mut tree.leaf_arena);
+        tree.reset_empty();
         Self {
             tree,
-            current_leaf_id: leftmost_id,
-            current_leaf_index: 0,
-            end_key: None,
-            end_bound_key: None,
-            end_inclusive: false,
-            finished: false,
+            leaves,
+            next_leaf: first_leaf,
+            current: Vec::new().into_iter().zip(Vec::new()),
         }
     }
+}
 
+impl<'a, K: Ord + Clone, V: Clone> Iterator for DrainIterator<'a, K, V> {
+    type Item = (K, V);
 
-    /// Start from specific position with full bound control using owned keys
-    fn new_from_position_with_bounds(
-        tree: &'a BPlusTreeMap<K, V>,
-        start_leaf_id: NodeId,
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(entry) = self.current.next() {
+                return Some(entry);
+            }
+            if self.next_leaf == NULL_NODE {
+                return None;
+            }
+            let leaf = self.leaves.deallocate(self.next_leaf)?;
+            self.next_leaf = leaf.next;
+            self.current = leaf.keys.into_iter().zip(leaf.values);
+        }
+    }
+}
+
+impl<'a, K: Ord + Clone, V: Clone> Drop for DrainIterator<'a, K, V> {
+    fn drop(&mut self) {
+        // Give the drained arena's storage back and reset to one empty root
+        core::mem::swap(&mut self.tree.leaf_arena, &mut self.leaves);
+        self.tree.reset_empty();
+        self.tree.auto_compact_if_needed();
+    }
+}
+
+/// Keys, values and version stamps of one leaf, zipped for mutable iteration.
+type LeafEntriesMut<'a, K, V> = core::iter::Zip<
+    core::iter::Zip<core::slice::Iter<'a, K>, core::slice::IterMut<'a, V>>,
+    core::slice::IterMut<'a, u64>,
+>;
+
+/// Iterator over a range of entries with mutable references to the values.
+pub struct RangeIteratorMut<'a, K, V> {
+    leaves: alloc::vec::IntoIter<&'a mut LeafNode<K, V>>,
+    current: LeafEntriesMut<'a, K, V>,
+    end: Option<(K, bool)>, // (end_key, is_inclusive)
+    order: KeyOrder<K>,
+    stamp: Option<u64>, // version given to each yielded entry
+}
+
+impl<'a, K: Ord + Clone, V: Clone> RangeIteratorMut<'a, K, V> {
+    fn new(
+        leaves: Vec<&'a mut LeafNode<K, V>>,
         start_index: usize,
-        end_bound: Bound<&K>,
+        skip_first: bool,
+        end: Option<(K, bool)>,
+        order: KeyOrder<K>,
+        stamp: Option<u64>,
     ) -> Self {
-        let (end_bound_key, end_inclusive) = match end_bound {
-            Bound::Included(key) => (Some(key.clone()), true),
-            Bound::Excluded(key) => (Some(key.clone()), false),
-            Bound::Unbounded => (None, false),
+        let mut iter = Self {
+            leaves: leaves.into_iter(),
+            current: Self::no_entries(),
+            end,
+            order,
+            stamp,
         };
 
-        Self {
-            tree,
-            current_leaf_id: Some(start_leaf_id),
-            current_leaf_index: start_index,
-            end_key: None,
-            end_bound_key,
-            end_inclusive,
-            finished: false,
+        // Position inside the first leaf, dropping an excluded start key
+        if let Some(leaf) = iter.leaves.next() {
+            let start = start_index.min(leaf.keys.len());
+            iter.current = Self::entries(leaf, start);
+            if skip_first {
+                iter.current.next();
+            }
         }
+        iter
+    }
+
+    fn no_entries() -> LeafEntriesMut<'a, K, V> {
+        <&[K]>::default()
+            .iter()
+            .zip(<&mut [V]>::default().iter_mut())
+            .zip(<&mut [u64]>::default().iter_mut())
+    }
+
+    fn entries(leaf: &'a mut LeafNode<K, V>, start: usize) -> LeafEntriesMut<'a, K, V> {
+        let LeafNode {
+            keys,
+            values,
+            versions,
+            ..
+        } = leaf;
+        keys[start..]
+            .iter()
+            .zip(values[start..].iter_mut())
+            .zip(versions[start..].iter_mut())
     }
 }
 
-impl<'a, K: Ord + Clone, V: Clone> Iterator for ItemIterator<'a, K, V> {
-    type Item = (&'a K, &'a V);
+impl<'a, K: Ord + Clone, V: Clone> Iterator for RangeIteratorMut<'a, K, V> {
+    type Item = (&'a K, &'a mut V);
 
     fn next(&mut self) -> Option<Self::Item> {
-        if self.finished {
-            return None;
-        }
-
         loop {
-            // Use Option::and_then to chain operations cleanly
-            let result = self
-                .current_leaf_id
-                .and_then(|leaf_id| self.tree.get_leaf(leaf_id))
-                .and_then(|leaf| self.try_get_next_item(leaf));
-
-            match result {
-                Some(item) => return Some(item),
-                None => {
-                    // Either no current leaf or no more items in current leaf
-                    if !self.advance_to_next_leaf().unwrap_or(false) {
-                        self.finished = true;
+            if let Some(((key, value), version)) = self.current.next() {
+                if let Some((end, inclusive)) = &self.end {
+                    if self.order.beyond_end(key, end, *inclusive) {
+                        // Past the end bound: stop for good
+                        self.leaves = Vec::new().into_iter();
+                        self.current = Self::no_entries();
                         return None;
                     }
-                    // Continue loop with next leaf
                 }
+                if let Some(stamp) = self.stamp {
+                    *version = stamp;
+                }
+                return Some((key, value));
             }
+            self.current = Self::entries(self.leaves.next()?, 0);
         }
     }
 }
 
-impl<'a, K: Ord + Clone, V: Clone> ItemIterator<'a, K, V> {
-    /// Helper method to try getting the next item from the current leaf
-    fn try_get_next_item(&mut self, leaf: &'a LeafNode<K, V>) -> Option<(&'a K, &'a V)> {
-        // Check if we have more items in the current leaf
-        if self.current_leaf_index >= leaf.keys.len() {
-            return None;
+/// Optimized iterator over a range of key-value pairs in the B+ tree.
+/// Uses tree navigation to find start, then linked list traversal for efficiency.
+pub struct RangeIterator<'a, K, V> {
+    iterator: Option<ItemIterator<'a, K, V>>,
+}
+
+impl<'a, K: Ord + Clone, V: Clone> RangeIterator<'a, K, V> {
+    /// Iterate from `start` up to, but not including, `end`, as returned by
+    /// [`BPlusTreeMap::range_endpoints`].
+    fn between(
+        tree: &'a BPlusTreeMap<K, V>,
+        start: Option<LeafPosition>,
+        end: Option<LeafPosition>,
+    ) -> Self {
+        let iterator = start.map(|(leaf_id, index)| {
+            let mut iterator =
+                ItemIterator::new_from_position_with_bounds(tree, leaf_id, index, Bound::Unbounded);
+            // The end position doubles as a back cursor, so the front stops
+            // there without comparing keys
+            iterator.back = end;
+            iterator
+        });
+        Self { iterator }
+    }
+}
+
+impl<K: Ord + Clone, V: Clone> RangeIterator<'_, K, V> {
+    /// Advance to the first remaining entry in the range whose key is
+    /// `>= key`, re-descending the tree instead of stepping through the
+    /// skipped entries. See [`ItemIterator::seek`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bplustree::BPlusTreeMap;
+    ///
+    /// let tree = BPlusTreeMap::bulk_load(4, (0..1000).map(|i| (i, i))).unwrap();
+    /// let mut range = tree.range(100..200);
+    /// range.seek(&150);
+    /// assert_eq!(range.next(), Some((&150, &150)));
+    /// range.seek(&500);
+    /// assert_eq!(range.next(), None);
+    /// ```
+    pub fn seek(&mut self, key: &K) {
+        if let Some(iterator) = self.iterator.as_mut() {
+            iterator.seek(key);
         }
+    }
+}
 
-        let key = &leaf.keys[self.current_leaf_index];
-        let value = &leaf.values[self.current_leaf_index];
+impl<'a, K: Ord + Clone, V: Clone> Iterator for RangeIterator<'a, K, V> {
+    type Item = (&'a K, &'a V);
 
-        // Check if we've reached the end bound using Option combinators
-        let beyond_end = self
-            .end_key
-            .map(|end| key >= end)
-            .or_else(|| {
-                self.end_bound_key.as_ref().map(|end| {
-                    if self.end_inclusive {
-                        key > end
-                    } else {
-                        key >= end
-                    }
-                })
-            })
-            .unwrap_or(false);
+    fn next(&mut self) -> Option<Self::Item> {
+        self.iterator.as_mut()?.next()
+    }
+}
 
-        if beyond_end {
-            self.finished = true;
-            return None;
-        }
+/// Range iterator with a known length, created by `range_sized`.
+pub struct SizedRangeIterator<'a, K, V> {
+    inner: RangeIterator<'a, K, V>,
+    remaining: usize,
+}
 
-        self.current_leaf_index += 1;
-        Some((key, value))
+impl<'a, K: Ord + Clone, V: Clone> Iterator for SizedRangeIterator<'a, K, V> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let item = self.inner.next()?;
+        self.remaining = self.remaining.saturating_sub(1);
+        Some(item)
     }
 
-    /// Helper method to advance to the next leaf
-    /// Returns Some(true) if successfully advanced, Some(false) if no more leaves, None if invalid leaf
-    fn advance_to_next_leaf(&mut self) -> Option<bool> {
-        let current_leaf = self.current_leaf_id?;
-        let leaf = self.tree.get_leaf(current_leaf)?;
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
 
-        self.current_leaf_id = (leaf.next != NULL_NODE).then_some(leaf.next);
-        self.current_leaf_index = 0;
+impl<'a, K: Ord + Clone, V: Clone> ExactSizeIterator for SizedRangeIterator<'a, K, V> {}
 
-        Some(self.current_leaf_id.is_some())
+/// Iterator over the keys in a range, created by `keys_range`.
+pub struct KeyRangeIterator<'a, K, V> {
+    items: RangeIterator<'a, K, V>,
+}
+
+impl<'a, K: Ord + Clone, V: Clone> Iterator for KeyRangeIterator<'a, K, V> {
+    type Item = &'a K;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.items.next().map(|(k, _)| k)
     }
 }
 
-/// Iterator over keys in the B+ tree.
-pub struct KeyIterator<'a, K, V> {
-    items: ItemIterator<'a, K, V>,
+/// Iterator over the values in a range, created by `values_range`.
+pub struct ValueRangeIterator<'a, K, V> {
+    items: RangeIterator<'a, K, V>,
 }
 
-impl<'a, K: Ord + Clone, V: Clone> KeyIterator<'a, K, V> {
+impl<'a, K: Ord + Clone, V: Clone> Iterator for ValueRangeIterator<'a, K, V> {
+    type Item = &'a V;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.items.next().map(|(_, v)| v)
+    }
+}
+
+/// Breadth-first iterator over the nodes of the B+ tree.
+/// Yields `(depth, node_id, is_leaf)` starting from the root.
+pub struct LevelOrderIterator<'a, K, V> {
+    tree: &'a BPlusTreeMap<K, V>,
+    queue: VecDeque<(usize, NodeRef<K, V>)>,
+}
+
+impl<'a, K: Ord + Clone, V: Clone> LevelOrderIterator<'a, K, V> {
     fn new(tree: &'a BPlusTreeMap<K, V>) -> Self {
-        Self {
-            items: ItemIterator::new(tree),
-        }
+        let mut queue = VecDeque::new();
+        queue.push_back((0, tree.root.clone()));
+        Self { tree, queue }
     }
 }
 
-impl<'a, K: Ord + Clone, V: Clone> Iterator for KeyIterator<'a, K, V> {
-    type Item = &'a K;
+impl<'a, K: Ord + Clone, V: Clone> Iterator for LevelOrderIterator<'a, K, V> {
+    type Item = (usize, NodeId, bool);
 
     fn next(&mut self) -> Option<Self::Item> {
-        self.items.next().map(|(k, _)| k)
+        let (depth, node) = self.queue.pop_front()?;
+
+        if let NodeRef::Branch(id, _) = &node {
+            if let Some(branch) = self.tree.get_branch(*id) {
+                self.queue.extend(
+                    branch
+                        .children
+                        .iter()
+                        .map(|child| (depth + 1, child.clone())),
+                );
+            }
+        }
+
+        Some((depth, node.id(), node.is_leaf()))
     }
 }
 
-/// Iterator over values in the B+ tree.
-pub struct ValueIterator<'a, K, V> {
-    items: ItemIterator<'a, K, V>,
+/// Iterator over the leaves of the B+ tree, yielding each leaf's keys and
+/// values as parallel slices in key order. Created by `leaves`.
+pub struct LeafChunkIterator<'a, K, V> {
+    tree: &'a BPlusTreeMap<K, V>,
+    next_leaf: Option<NodeId>,
+    // Leaves left before the walk is cut off, guarding against a cyclic chain
+    budget: usize,
 }
 
-impl<'a, K: Ord + Clone, V: Clone> ValueIterator<'a, K, V> {
+impl<'a, K: Ord + Clone, V: Clone> LeafChunkIterator<'a, K, V> {
     fn new(tree: &'a BPlusTreeMap<K, V>) -> Self {
         Self {
-            items: ItemIterator::new(tree),
+            tree,
+            next_leaf: tree.get_first_leaf_id(),
+            budget: tree.leaf_arena.total_capacity(),
         }
     }
 }
 
-impl<'a, K: Ord + Clone, V: Clone> Iterator for ValueIterator<'a, K, V> {
-    type Item = &'a V;
+impl<'a, K: Ord + Clone, V: Clone> Iterator for LeafChunkIterator<'a, K, V> {
+    type Item = (&'a [K], &'a [V]);
 
     fn next(&mut self) -> Option<Self::Item> {
-        self.items.next().map(|(_, v)| v)
+        loop {
+            let leaf_id = self.next_leaf.take()?;
+            self.budget = self.budget.checked_sub(1)?;
+            let leaf = self.tree.get_leaf(leaf_id)?;
+            self.next_leaf = (leaf.next != NULL_NODE).then_some(leaf.next);
+            if !leaf.keys.is_empty() {
+                return Some((&leaf.keys, &leaf.values));
+            }
+        }
     }
 }
 
-/// Optimized iterator over a range of key-value pairs in the B+ tree.
-/// Uses tree navigation to find start, then linked list traversal for efficiency.
-pub struct RangeIterator<'a, K, V> {
-    iterator: Option<ItemIterator<'a, K, V>>,
-    skip_first: bool,
-    first_key: Option<K>,
+/// Iterator over key-value pairs that walks leaves in tree-structure order,
+/// skipping leaves that are missing from the arena instead of stopping.
+pub struct LenientItemIterator<'a, K, V> {
+    tree: &'a BPlusTreeMap<K, V>,
+    leaf_ids: alloc::vec::IntoIter<NodeId>,
+    current: Option<&'a LeafNode<K, V>>,
+    index: usize,
+    skipped: usize,
 }
 
-impl<'a, K: Ord + Clone, V: Clone> RangeIterator<'a, K, V> {
-    fn new_with_skip_owned(
-        tree: &'a BPlusTreeMap<K, V>,
-        start_info: Option<(NodeId, usize)>,
-        skip_first: bool,
-        end_info: Option<(K, bool)>, // (end_key, is_inclusive)
-    ) -> Self {
-        let (iterator, first_key) = start_info
-            .map(|(leaf_id, index)| {
-                // Create iterator with appropriate end bound using Option combinators
-                let end_bound = end_info
-                    .as_ref()
-                    .map(|(key, is_inclusive)| {
-                        if *is_inclusive {
-                            Bound::Included(key)
-                        } else {
-                            Bound::Excluded(key)
-                        }
-                    })
-                    .unwrap_or(Bound::Unbounded);
-
-                let iter =
-                    ItemIterator::new_from_position_with_bounds(tree, leaf_id, index, end_bound);
-
-                // Extract first key if needed for skipping, avoid redundant arena lookup
-                let first_key = if skip_first {
-                    tree.get_leaf(leaf_id)
-                        .and_then(|leaf| leaf.keys.get(index))
-                        .cloned()
-                } else {
-                    None
-                };
-
-                (Some(iter), first_key)
-            })
-            .unwrap_or((None, None));
+impl<'a, K: Ord + Clone, V: Clone> LenientItemIterator<'a, K, V> {
+    fn new(tree: &'a BPlusTreeMap<K, V>) -> Self {
+        let mut leaf_ids = Vec::new();
+        tree.collect_leaf_ids(&tree.root, &mut leaf_ids);
 
         Self {
-            iterator,
-            skip_first,
-            first_key,
+            tree,
+            leaf_ids: leaf_ids.into_iter(),
+            current: None,
+            index: 0,
+            skipped: 0,
         }
     }
+
+    /// Number of leaves skipped so far because they were missing from the arena.
+    pub fn skipped_leaves(&self) -> usize {
+        self.skipped
+    }
 }
 
-impl<'a, K: Ord + Clone, V: Clone> Iterator for RangeIterator<'a, K, V> {
+impl<'a, K: Ord + Clone, V: Clone> Iterator for LenientItemIterator<'a, K, V> {
     type Item = (&'a K, &'a V);
 
     fn next(&mut self) -> Option<Self::Item> {
         loop {
-            let item = self.iterator.as_mut()?.next()?;
-
-            // Handle excluded start bound on first iteration
-            if self.skip_first {
-                self.skip_first = false;
-                if let Some(ref first_key) = self.first_key {
-                    if item.0 == first_key {
-                        // Skip this item and continue to next
-                        continue;
+            if let Some(leaf) = self.current {
+                if self.index < leaf.keys.len() {
+                    let item = (&leaf.keys[self.index], &leaf.values[self.index]);
+                    self.index += 1;
+                    return Some(item);
+                }
+            }
+
+            let id = self.leaf_ids.next()?;
+            self.current = self.tree.get_leaf(id);
+            self.index = 0;
+            if self.current.is_none() {
+                self.skipped += 1;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    impl BPlusTreeMap<i32, i32> {
+        /// Build a tree with an exact shape, bypassing insert-driven splits.
+        ///
+        /// `levels_spec[0]` lists the key count of each leaf from left to right;
+        /// every later entry lists the child count of each node on the next
+        /// level up, and the last level must be a single root. Keys run
+        /// `0, 1, 2, ...` across the leaves and each value is `key * 10`.
+        fn build_shape(capacity: usize, levels_spec: &[&[usize]]) -> Self {
+            let mut tree = BPlusTreeMap::new(capacity).unwrap();
+            let (leaf_sizes, branch_levels) = levels_spec
+                .split_first()
+                .expect("shape needs at least a leaf level");
+            assert!(!leaf_sizes.is_empty(), "shape needs at least one leaf");
+            tree.leaf_arena.clear();
+
+            let mut next_key = 0;
+            let mut level: Vec<(NodeRef<i32, i32>, i32)> = Vec::new();
+            for &size in leaf_sizes.iter() {
+                assert!(size <= capacity, "leaf of {} keys overflows", size);
+                let mut leaf = LeafNode::new(capacity);
+                for key in next_key..next_key + size as i32 {
+                    leaf.keys.push(key);
+                    leaf.values.push(key * 10);
+                    leaf.versions.push(0);
+                }
+                let min_key = next_key;
+                next_key += size as i32;
+
+                let id = tree.allocate_leaf(leaf).unwrap();
+                if let Some((prev, _)) = level.last() {
+                    tree.set_leaf_next(prev.id(), id);
+                }
+                level.push((NodeRef::Leaf(id, PhantomData), min_key));
+            }
+
+            for fan_outs in branch_levels.iter() {
+                assert_eq!(
+                    fan_outs.iter().sum::<usize>(),
+                    level.len(),
+                    "fan-outs must cover every node on the level below"
+                );
+                let mut children = level.into_iter();
+                let mut next_level = Vec::with_capacity(fan_outs.len());
+                for &fan_out in fan_outs.iter() {
+                    assert!(
+                        (1..=capacity + 1).contains(&fan_out),
+                        "branch with {} children is out of range",
+                        fan_out
+                    );
+                    let mut branch = BranchNode::new(capacity);
+                    let mut min_key = None;
+                    for (child, child_min) in children.by_ref().take(fan_out) {
+                        if min_key.is_none() {
+                            min_key = Some(child_min);
+                        } else {
+                            branch.keys.push(child_min);
+                        }
+                        branch.children.push(child);
                     }
+                    let id = tree.allocate_branch(branch).unwrap();
+                    next_level.push((NodeRef::Branch(id, PhantomData), min_key.unwrap()));
                 }
+                level = next_level;
             }
 
-            return Some(item);
+            assert_eq!(level.len(), 1, "top level of a shape must be a single root");
+            tree.root = level.pop().unwrap().0;
+            assert_eq!(tree.leaf_sizes(), leaf_sizes.to_vec());
+            tree
+        }
+
+        /// Keys of each leaf in chain order.
+        fn leaf_keys(&self) -> Vec<Vec<i32>> {
+            let mut leaf_ids = Vec::new();
+            self.collect_leaf_ids(&self.root, &mut leaf_ids);
+            leaf_ids
+                .into_iter()
+                .map(|id| self.get_leaf(id).unwrap().keys.clone())
+                .collect()
         }
     }
+
+    #[test]
+    fn test_build_shape_produces_requested_layout() {
+        let tree = BPlusTreeMap::build_shape(4, &[&[2, 3, 4, 2, 2, 2], &[3, 3], &[2]]);
+
+        assert!(tree.check_invariants());
+        assert_eq!(tree.leaf_sizes(), vec![2, 3, 4, 2, 2, 2]);
+        assert_eq!(tree.leaf_count(), 6);
+        assert_eq!(tree.len(), 15);
+        assert_eq!(tree.get(&6), Some(&60));
+        assert_eq!(tree.items().count(), 15);
+    }
+
+    #[test]
+    fn test_rebalance_borrows_from_left_leaf() {
+        let mut tree = BPlusTreeMap::build_shape(4, &[&[3, 2, 2], &[3]]);
+
+        assert_eq!(tree.remove(&3), Some(30));
+
+        assert!(tree.check_invariants());
+        assert_eq!(tree.leaf_keys(), vec![vec![0, 1], vec![2, 4], vec![5, 6]]);
+    }
+
+    #[test]
+    fn test_rebalance_borrows_from_right_leaf() {
+        let mut tree = BPlusTreeMap::build_shape(4, &[&[2, 2, 3], &[3]]);
+
+        assert_eq!(tree.remove(&2), Some(20));
+
+        assert!(tree.check_invariants());
+        assert_eq!(tree.leaf_keys(), vec![vec![0, 1], vec![3, 4], vec![5, 6]]);
+    }
+
+    #[test]
+    fn test_rebalance_merges_with_left_leaf() {
+        let mut tree = BPlusTreeMap::build_shape(4, &[&[2, 2, 2], &[3]]);
+
+        assert_eq!(tree.remove(&2), Some(20));
+
+        assert!(tree.check_invariants());
+        assert_eq!(tree.leaf_keys(), vec![vec![0, 1, 3], vec![4, 5]]);
+    }
+
+    #[test]
+    fn test_rebalance_merges_with_right_leaf() {
+        let mut tree = BPlusTreeMap::build_shape(4, &[&[2, 2, 2], &[3]]);
+
+        assert_eq!(tree.remove(&0), Some(0));
+
+        assert!(tree.check_invariants());
+        assert_eq!(tree.leaf_keys(), vec![vec![1, 2, 3], vec![4, 5]]);
+    }
+
+    #[test]
+    fn test_coalesce_rebalances_branches_left_underfull() {
+        let mut tree = BPlusTreeMap::build_shape(4, &[&[2, 2, 2, 2, 2, 2], &[3, 3], &[2]]);
+
+        assert_eq!(tree.coalesce_underfull(), 2);
+
+        // Both branches dropped to one key and were merged, collapsing the root
+        assert!(tree.check_invariants());
+        assert!(matches!(tree.root, NodeRef::Branch(_, _)));
+        assert_eq!(
+            tree.level_order()
+                .filter(|(_, _, is_leaf)| !is_leaf)
+                .count(),
+            1
+        );
+        assert_eq!(tree.leaf_sizes(), vec![4, 2, 4, 2]);
+        assert_eq!(
+            tree.keys().copied().collect::<Vec<_>>(),
+            (0..12).collect::<Vec<_>>()
+        );
+    }
 }
@@ -206,7 +206,7 @@ macro_rules! stress_test {
 #[macro_export]
 macro_rules! process_range_bounds {
     ($range:expr) => {{
-        use std::ops::Bound;
+        use ::core::ops::Bound;
         
         let start = match $range.start_bound() {
             Bound::Included(key) => Some(key),
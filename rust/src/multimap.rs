@@ -0,0 +1,132 @@
+//! Sorted multimap built on top of [`BPlusTreeMap`].
+
+#[cfg(not(feature = "std"))]
+use alloc::vec::Vec;
+use core::ops::RangeBounds;
+
+use crate::{BPlusTreeMap, InitResult};
+
+/// A sorted map that keeps every value inserted under a key.
+///
+/// Values are stored in a per-key bucket inside a `BPlusTreeMap<K, Vec<V>>`,
+/// so each key occupies one tree entry no matter how many values it has.
+/// Iteration visits keys in order and, within a key, values in insertion
+/// order; a key is yielded once per value.
+///
+/// # Examples
+///
+/// ```
+/// use bplustree::BPlusTreeMultiMap;
+///
+/// let mut tags = BPlusTreeMultiMap::new(16).unwrap();
+/// tags.insert("rust", 1);
+/// tags.insert("db", 2);
+/// tags.insert("rust", 3);
+///
+/// assert_eq!(tags.get_all(&"rust"), &[1, 3]);
+/// assert_eq!(tags.len(), 3);
+/// assert_eq!(
+///     tags.iter().collect::<Vec<_>>(),
+///     vec![(&"db", &2), (&"rust", &1), (&"rust", &3)]
+/// );
+/// ```
+pub struct BPlusTreeMultiMap<K, V> {
+    map: BPlusTreeMap<K, Vec<V>>,
+    /// Total number of values across all buckets.
+    len: usize,
+}
+
+impl<K: Ord + Clone, V: Clone> BPlusTreeMultiMap<K, V> {
+    /// Create an empty multimap whose nodes hold up to `capacity` keys.
+    pub fn new(capacity: usize) -> InitResult<Self> {
+        Ok(Self {
+            map: BPlusTreeMap::new(capacity)?,
+            len: 0,
+        })
+    }
+
+    /// Add `value` under `key`, after any values already stored there.
+    pub fn insert(&mut self, key: K, value: V) {
+        self.map.push_to_vec_value(key, value);
+        self.len += 1;
+    }
+
+    /// Returns every value stored under `key` in insertion order, or an
+    /// empty slice if the key is absent.
+    pub fn get_all(&self, key: &K) -> &[V] {
+        self.map.get(key).map_or(&[], Vec::as_slice)
+    }
+
+    /// Remove `key` and return all of its values in insertion order.
+    pub fn remove_all(&mut self, key: &K) -> Vec<V> {
+        let values = self.map.remove(key).unwrap_or_default();
+        self.len -= values.len();
+        values
+    }
+
+    /// Returns true if at least one value is stored under `key`.
+    pub fn contains_key(&self, key: &K) -> bool {
+        self.map.contains_key(key)
+    }
+
+    /// Returns the total number of values.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns the number of distinct keys.
+    pub fn key_count(&self) -> usize {
+        self.map.len()
+    }
+
+    /// Returns true if the multimap holds no values.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Remove every value.
+    pub fn clear(&mut self) {
+        self.map.clear();
+        self.len = 0;
+    }
+
+    /// Iterate over all key-value pairs, repeating a key once per value.
+    pub fn iter(&self) -> impl Iterator<Item = (&K, &V)> + '_ {
+        self.map
+            .items()
+            .flat_map(|(key, values)| values.iter().map(move |value| (key, value)))
+    }
+
+    /// Iterate over the distinct keys in order.
+    pub fn keys(&self) -> impl Iterator<Item = &K> + '_ {
+        self.map.keys()
+    }
+
+    /// Iterate over the key-value pairs whose keys fall in `range`.
+    pub fn range<R>(&self, range: R) -> impl Iterator<Item = (&K, &V)> + '_
+    where
+        R: RangeBounds<K>,
+    {
+        self.map
+            .range(range)
+            .flat_map(|(key, values)| values.iter().map(move |value| (key, value)))
+    }
+}
+
+impl<K: Ord + Clone, V: Clone> Clone for BPlusTreeMultiMap<K, V> {
+    fn clone(&self) -> Self {
+        Self {
+            map: self.map.clone(),
+            len: self.len,
+        }
+    }
+}
+
+impl<K: Ord + Clone + core::fmt::Debug, V: Clone + core::fmt::Debug> core::fmt::Debug
+    for BPlusTreeMultiMap<K, V>
+{
+    /// Formats each key with its bucket of values, e.g. `{1: [a, b]}`.
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_map().entries(self.map.items()).finish()
+    }
+}
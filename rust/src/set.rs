@@ -0,0 +1,224 @@
+//! Sorted set built on top of [`BPlusTreeMap`].
+
+use core::cmp::Ordering;
+use core::iter::Peekable;
+use core::ops::RangeBounds;
+
+use crate::{BPlusTreeMap, InitResult, KeyIterator, KeyRangeIterator};
+
+/// A sorted set of keys, stored as a `BPlusTreeMap<K, ()>`.
+///
+/// Set operations between two sets are lazy: [`union`](Self::union),
+/// [`intersection`](Self::intersection) and [`difference`](Self::difference)
+/// walk both key sequences once in step and yield keys in ascending order.
+///
+/// # Examples
+///
+/// ```
+/// use bplustree::BPlusTreeSet;
+///
+/// let mut a = BPlusTreeSet::new(16).unwrap();
+/// let mut b = BPlusTreeSet::new(16).unwrap();
+/// for i in 0..5 {
+///     a.insert(i);
+///     b.insert(i + 3);
+/// }
+///
+/// assert_eq!(a.union(&b).copied().collect::<Vec<_>>(), vec![0, 1, 2, 3, 4, 5, 6, 7]);
+/// assert_eq!(a.intersection(&b).copied().collect::<Vec<_>>(), vec![3, 4]);
+/// assert_eq!(a.difference(&b).copied().collect::<Vec<_>>(), vec![0, 1, 2]);
+/// ```
+pub struct BPlusTreeSet<K> {
+    map: BPlusTreeMap<K, ()>,
+}
+
+impl<K: Ord + Clone> BPlusTreeSet<K> {
+    /// Create an empty set whose nodes hold up to `capacity` keys.
+    pub fn new(capacity: usize) -> InitResult<Self> {
+        Ok(Self {
+            map: BPlusTreeMap::new(capacity)?,
+        })
+    }
+
+    /// Add `key` to the set. Returns true if it was not already present.
+    pub fn insert(&mut self, key: K) -> bool {
+        self.map.insert(key, ()).is_none()
+    }
+
+    /// Returns true if the set contains `key`.
+    pub fn contains(&self, key: &K) -> bool {
+        self.map.contains_key(key)
+    }
+
+    /// Remove `key` from the set. Returns true if it was present.
+    pub fn remove(&mut self, key: &K) -> bool {
+        self.map.remove(key).is_some()
+    }
+
+    /// Returns the number of keys in the set.
+    pub fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    /// Returns true if the set holds no keys.
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+
+    /// Remove every key.
+    pub fn clear(&mut self) {
+        self.map.clear();
+    }
+
+    /// Iterate over the keys in ascending order.
+    pub fn iter(&self) -> KeyIterator<'_, K, ()> {
+        self.map.keys()
+    }
+
+    /// Iterate over the keys that fall in `range`, in ascending order.
+    pub fn range<R>(&self, range: R) -> KeyRangeIterator<'_, K, ()>
+    where
+        R: RangeBounds<K>,
+    {
+        self.map.keys_range(range)
+    }
+
+    /// Lazily yield the keys in `self`, `other`, or both, each once.
+    pub fn union<'a>(&'a self, other: &'a Self) -> Union<'a, K> {
+        Union {
+            a: self.iter().peekable(),
+            b: other.iter().peekable(),
+        }
+    }
+
+    /// Lazily yield the keys present in both `self` and `other`.
+    pub fn intersection<'a>(&'a self, other: &'a Self) -> Intersection<'a, K> {
+        Intersection {
+            a: self.iter(),
+            b: other.iter().peekable(),
+        }
+    }
+
+    /// Lazily yield the keys in `self` that are not in `other`.
+    pub fn difference<'a>(&'a self, other: &'a Self) -> Difference<'a, K> {
+        Difference {
+            a: self.iter(),
+            b: other.iter().peekable(),
+        }
+    }
+}
+
+impl<K: Ord + Clone> Clone for BPlusTreeSet<K> {
+    fn clone(&self) -> Self {
+        Self {
+            map: self.map.clone(),
+        }
+    }
+}
+
+impl<K: Ord + Clone> Default for BPlusTreeSet<K> {
+    fn default() -> Self {
+        Self {
+            map: BPlusTreeMap::default(),
+        }
+    }
+}
+
+impl<K: Ord + Clone + core::fmt::Debug> core::fmt::Debug for BPlusTreeSet<K> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_set().entries(self.iter()).finish()
+    }
+}
+
+impl<K: Ord + Clone> PartialEq for BPlusTreeSet<K> {
+    fn eq(&self, other: &Self) -> bool {
+        self.iter().eq(other.iter())
+    }
+}
+
+impl<K: Ord + Clone> Eq for BPlusTreeSet<K> {}
+
+impl<K: Ord + Clone> FromIterator<K> for BPlusTreeSet<K> {
+    /// Build a set with the default node capacity.
+    fn from_iter<I: IntoIterator<Item = K>>(iter: I) -> Self {
+        Self {
+            map: iter.into_iter().map(|key| (key, ())).collect(),
+        }
+    }
+}
+
+impl<K: Ord + Clone> Extend<K> for BPlusTreeSet<K> {
+    fn extend<I: IntoIterator<Item = K>>(&mut self, iter: I) {
+        self.map.extend(iter.into_iter().map(|key| (key, ())));
+    }
+}
+
+/// Lazy iterator over the union of two sets. Created by
+/// [`BPlusTreeSet::union`].
+pub struct Union<'a, K: Ord + Clone> {
+    a: Peekable<KeyIterator<'a, K, ()>>,
+    b: Peekable<KeyIterator<'a, K, ()>>,
+}
+
+impl<'a, K: Ord + Clone> Iterator for Union<'a, K> {
+    type Item = &'a K;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let order = match (self.a.peek(), self.b.peek()) {
+            (Some(a), Some(b)) => a.cmp(b),
+            (Some(_), None) => Ordering::Less,
+            (None, _) => return self.b.next(),
+        };
+        match order {
+            Ordering::Less => self.a.next(),
+            Ordering::Greater => self.b.next(),
+            Ordering::Equal => {
+                self.b.next();
+                self.a.next()
+            }
+        }
+    }
+}
+
+/// Lazy iterator over the keys common to two sets. Created by
+/// [`BPlusTreeSet::intersection`].
+pub struct Intersection<'a, K: Ord + Clone> {
+    a: KeyIterator<'a, K, ()>,
+    b: Peekable<KeyIterator<'a, K, ()>>,
+}
+
+impl<'a, K: Ord + Clone> Iterator for Intersection<'a, K> {
+    type Item = &'a K;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        for key in self.a.by_ref() {
+            while self.b.next_if(|other| *other < key).is_some() {}
+            self.b.peek()?;
+            if self.b.next_if_eq(&key).is_some() {
+                return Some(key);
+            }
+        }
+        None
+    }
+}
+
+/// Lazy iterator over the keys of one set that are missing from another.
+/// Created by [`BPlusTreeSet::difference`].
+pub struct Difference<'a, K: Ord + Clone> {
+    a: KeyIterator<'a, K, ()>,
+    b: Peekable<KeyIterator<'a, K, ()>>,
+}
+
+impl<'a, K: Ord + Clone> Iterator for Difference<'a, K> {
+    type Item = &'a K;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        for key in self.a.by_ref() {
+            while self.b.next_if(|other| *other < key).is_some() {}
+            if self.b.next_if_eq(&key).is_none() {
+                return Some(key);
+            }
+        }
+        None
+    }
+}
@@ -0,0 +1,107 @@
+//! Immutable, shareable copies of a [`BPlusTreeMap`].
+
+use alloc::sync::Arc;
+use core::ops::RangeBounds;
+
+use crate::{BPlusTreeMap, ItemIterator, KeyIterator, RangeIterator};
+
+/// A read-only copy of a tree taken by [`BPlusTreeMap::snapshot`].
+///
+/// The copy is made once, up front, and then shared behind an `Arc`: cloning
+/// a snapshot is cheap, and later changes to the source tree are not seen.
+/// A snapshot is `Send + Sync` whenever `K` and `V` are, so one writer can
+/// keep mutating the tree while any number of reader threads query a
+/// snapshot.
+///
+/// # Examples
+///
+/// ```
+/// use bplustree::BPlusTreeMap;
+///
+/// let mut tree = BPlusTreeMap::new(16).unwrap();
+/// tree.insert(1, "one");
+/// let snapshot = tree.snapshot();
+/// tree.insert(2, "two");
+///
+/// assert_eq!(snapshot.get(&1), Some(&"one"));
+/// assert_eq!(snapshot.get(&2), None);
+/// assert_eq!(snapshot.len(), 1);
+/// ```
+pub struct Snapshot<K, V> {
+    tree: Arc<BPlusTreeMap<K, V>>,
+}
+
+impl<K: Ord + Clone, V: Clone> Snapshot<K, V> {
+    /// Get a reference to the value stored under `key`.
+    pub fn get(&self, key: &K) -> Option<&V> {
+        self.tree.get(key)
+    }
+
+    /// Returns true if the snapshot contains `key`.
+    pub fn contains_key(&self, key: &K) -> bool {
+        self.tree.contains_key(key)
+    }
+
+    /// Returns the number of entries.
+    pub fn len(&self) -> usize {
+        self.tree.len()
+    }
+
+    /// Returns true if the snapshot has no entries.
+    pub fn is_empty(&self) -> bool {
+        self.tree.is_empty()
+    }
+
+    /// Iterate over all entries in key order.
+    pub fn items(&self) -> ItemIterator<'_, K, V> {
+        self.tree.items()
+    }
+
+    /// Iterate over all keys in order.
+    pub fn keys(&self) -> KeyIterator<'_, K, V> {
+        self.tree.keys()
+    }
+
+    /// Iterate over the entries whose keys fall in `range`.
+    pub fn range<R>(&self, range: R) -> RangeIterator<'_, K, V>
+    where
+        R: RangeBounds<K>,
+    {
+        self.tree.range(range)
+    }
+
+    /// Copy the snapshot back into an independent, mutable tree.
+    pub fn to_tree(&self) -> BPlusTreeMap<K, V> {
+        BPlusTreeMap::clone(&self.tree)
+    }
+}
+
+impl<K, V> Clone for Snapshot<K, V> {
+    /// Share the same frozen copy; no entries are cloned.
+    fn clone(&self) -> Self {
+        Self {
+            tree: Arc::clone(&self.tree),
+        }
+    }
+}
+
+impl<K: Ord + Clone + core::fmt::Debug, V: Clone + core::fmt::Debug> core::fmt::Debug
+    for Snapshot<K, V>
+{
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_map().entries(self.items()).finish()
+    }
+}
+
+impl<K: Ord + Clone, V: Clone> BPlusTreeMap<K, V> {
+    /// Take an immutable copy of the tree that can be shared across threads.
+    ///
+    /// This deep-copies every entry once (see `Clone`), so it costs O(n);
+    /// the returned [`Snapshot`] is then cheap to clone and unaffected by
+    /// later mutations of `self`.
+    pub fn snapshot(&self) -> Snapshot<K, V> {
+        Snapshot {
+            tree: Arc::new(self.clone()),
+        }
+    }
+}
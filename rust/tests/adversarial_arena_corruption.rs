@@ -97,7 +97,7 @@ fn test_free_list_corruption_attack() {
     );
 
     // Step 2: Delete in a pattern that creates a specific free list state
-    for i in vec![3, 9, 15, 21, 27, 33, 39, 45] {
+    for i in [3, 9, 15, 21, 27, 33, 39, 45] {
         tree.remove(&i);
     }
 
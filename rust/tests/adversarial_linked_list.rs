@@ -327,22 +327,22 @@ fn test_force_linked_list_corruption() {
     for round in 0..20 {
         // Fill to capacity
         for i in 0..capacity * 3 {
-            tree.insert(round * 100 + i as i32, format!("round_{}_{}", round, i));
+            tree.insert(round * 100 + i, format!("round_{}_{}", round, i));
         }
 
         // Delete first and last items (boundary stress)
         tree.remove(&(round * 100));
-        tree.remove(&(round * 100 + capacity as i32 * 3 - 1));
+        tree.remove(&(round * 100 + capacity * 3 - 1));
 
         // Delete middle items to force merges
         for i in capacity..capacity * 2 {
-            tree.remove(&(round * 100 + i as i32));
+            tree.remove(&(round * 100 + i));
         }
 
         // Reinsert with different keys to force splits
         for i in 0..capacity {
             tree.insert(
-                round * 100 + i as i32 * 3 / 2,
+                round * 100 + i * 3 / 2,
                 format!("reused_{}_{}", round, i),
             );
         }
@@ -0,0 +1,75 @@
+use bplustree::BPlusTreeMap;
+use std::io::{Cursor, ErrorKind};
+
+#[test]
+fn test_binary_round_trip_through_cursor() {
+    let mut tree = BPlusTreeMap::new(4).unwrap();
+    for i in (0..1000i64).rev() {
+        tree.insert(i * 3 - 500, format!("value-{}", i));
+    }
+
+    let mut cursor = Cursor::new(Vec::new());
+    tree.write_to(&mut cursor).unwrap();
+    cursor.set_position(0);
+
+    let loaded = BPlusTreeMap::<i64, String>::read_from(16, &mut cursor).unwrap();
+    assert_eq!(loaded, tree);
+    assert!(loaded.check_invariants());
+    assert_eq!(cursor.position() as usize, cursor.get_ref().len());
+}
+
+#[test]
+fn test_binary_round_trip_empty_and_unit_values() {
+    let empty = BPlusTreeMap::<u32, ()>::new(4).unwrap();
+    let mut bytes = Vec::new();
+    empty.write_to(&mut bytes).unwrap();
+    let loaded = BPlusTreeMap::<u32, ()>::read_from(4, &mut Cursor::new(bytes)).unwrap();
+    assert!(loaded.is_empty());
+
+    let keys: BPlusTreeMap<String, ()> = ["b", "a", "c"]
+        .iter()
+        .map(|s| (s.to_string(), ()))
+        .collect();
+    let mut bytes = Vec::new();
+    keys.write_to(&mut bytes).unwrap();
+    let loaded = BPlusTreeMap::<String, ()>::read_from(4, &mut Cursor::new(bytes)).unwrap();
+    assert_eq!(loaded, keys);
+}
+
+#[test]
+fn test_binary_read_rejects_bad_input() {
+    let tree: BPlusTreeMap<u16, u16> = (0..50).map(|i| (i, i)).collect();
+    let mut bytes = Vec::new();
+    tree.write_to(&mut bytes).unwrap();
+
+    let truncated = &bytes[..bytes.len() - 1];
+    let err = BPlusTreeMap::<u16, u16>::read_from(4, &mut Cursor::new(truncated)).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::UnexpectedEof);
+
+    let mut bad_magic = bytes.clone();
+    bad_magic[0] = b'X';
+    let err = BPlusTreeMap::<u16, u16>::read_from(4, &mut Cursor::new(bad_magic)).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidData);
+
+    // Reading with a wider key type sees the wrong byte lengths
+    let err = BPlusTreeMap::<u32, u16>::read_from(4, &mut Cursor::new(&bytes)).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidData);
+
+    let err = BPlusTreeMap::<u16, u16>::read_from(2, &mut Cursor::new(&bytes)).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidInput);
+}
+
+#[test]
+fn test_binary_read_rejects_unsorted_keys() {
+    // Hand-built dump whose keys go 2 then 1
+    let mut bytes = b"BPT1".to_vec();
+    bytes.extend_from_slice(&2u64.to_le_bytes());
+    for key in [2u8, 1] {
+        bytes.extend_from_slice(&1u32.to_le_bytes());
+        bytes.push(key);
+        bytes.extend_from_slice(&0u32.to_le_bytes());
+    }
+
+    let err = BPlusTreeMap::<u8, ()>::read_from(4, &mut Cursor::new(bytes)).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidData);
+}
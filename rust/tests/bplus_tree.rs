@@ -1,5 +1,8 @@
-use bplustree::{BPlusTreeError, BPlusTreeMap, NodeRef};
+use bplustree::{
+    BPlusTreeError, BPlusTreeMap, Entry, FillStats, NodeRef, Operation, AUTO_COMPACT_MIN_FREE_NODES,
+};
 use std::marker::PhantomData;
+use std::sync::{Arc, Mutex};
 
 mod test_utils;
 use test_utils::*;
@@ -860,9 +863,9 @@ fn test_leaf_allocation() {
     let leaf3 = bplustree::LeafNode::new(4);
 
     // Test allocation
-    let id1 = tree.allocate_leaf(leaf1);
-    let id2 = tree.allocate_leaf(leaf2);
-    let id3 = tree.allocate_leaf(leaf3);
+    let id1 = tree.allocate_leaf(leaf1).unwrap();
+    let id2 = tree.allocate_leaf(leaf2).unwrap();
+    let id3 = tree.allocate_leaf(leaf3).unwrap();
 
     // IDs should be sequential starting from 1 (since 0 is the initial arena leaf)
     assert_eq!(id1, 1, "First allocation should get ID 1");
@@ -915,7 +918,7 @@ fn test_leaf_allocation() {
 
     // Test reuse of deallocated ID
     let leaf4 = bplustree::LeafNode::new(4);
-    let id4 = tree.allocate_leaf(leaf4);
+    let id4 = tree.allocate_leaf(leaf4).unwrap();
     assert_eq!(id4, id2, "Should reuse the deallocated ID");
     assert!(
         tree.get_leaf(id4).is_some(),
@@ -946,9 +949,9 @@ fn test_leaf_linked_list() {
     let leaf2 = bplustree::LeafNode::new(4);
     let leaf3 = bplustree::LeafNode::new(4);
 
-    let id1 = tree.allocate_leaf(leaf1);
-    let id2 = tree.allocate_leaf(leaf2);
-    let id3 = tree.allocate_leaf(leaf3);
+    let id1 = tree.allocate_leaf(leaf1).unwrap();
+    let id2 = tree.allocate_leaf(leaf2).unwrap();
+    let id3 = tree.allocate_leaf(leaf3).unwrap();
 
     // Initially, all next pointers should be NULL
     assert_eq!(tree.get_leaf_next(id1), None, "Initial next should be None");
@@ -1070,6 +1073,25 @@ fn test_invalid_capacity_error() {
     let _tree = BPlusTreeMap::<i32, String>::new(4).unwrap();
 }
 
+#[test]
+fn test_capacity_above_maximum_is_rejected() {
+    use bplustree::{BPlusTreeError, MAX_CAPACITY};
+
+    for capacity in [usize::MAX, MAX_CAPACITY + 1] {
+        assert!(matches!(
+            BPlusTreeMap::<i32, i32>::new(capacity),
+            Err(BPlusTreeError::InvalidCapacity(_))
+        ));
+    }
+    assert!(BPlusTreeMap::<i32, i32>::new_with_capacities(16, usize::MAX).is_err());
+    assert!(BPlusTreeMap::<i32, i32>::new_with_capacities(usize::MAX, 16).is_err());
+    assert!(BPlusTreeMap::bulk_load(usize::MAX, vec![(1, 1)]).is_err());
+
+    let mut tree = BPlusTreeMap::new(MAX_CAPACITY).unwrap();
+    tree.insert(1, 1);
+    assert_eq!(tree.capacity(), MAX_CAPACITY);
+}
+
 // ============================================================================
 // STRESS TESTS - These will be implemented after basic functionality works
 // ============================================================================
@@ -2103,10 +2125,10 @@ fn test_range_syntax_support() {
         ]
     );
 
-    let range3: Vec<_> = tree.range(5..).map(|(k, v)| *k).collect();
+    let range3: Vec<_> = tree.range(5..).map(|(k, _v)| *k).collect();
     assert_eq!(range3, vec![5, 6, 7, 8, 9]);
 
-    let range4: Vec<_> = tree.range(..5).map(|(k, v)| *k).collect();
+    let range4: Vec<_> = tree.range(..5).map(|(k, _v)| *k).collect();
     assert_eq!(range4, vec![0, 1, 2, 3, 4]);
 
     let range5: Vec<_> = tree.range(..).map(|(k, _v)| *k).collect();
@@ -2243,24 +2265,14 @@ fn test_batch_insert() {
     let mut tree = BPlusTreeMap::new(4).unwrap();
 
     // Successful batch insert
-    let items = vec![(1, "one"), (2, "two"), (3, "three")];
-    let result = tree.batch_insert(
-        items
-            .iter()
-            .map(|(k, v)| (*k, v.to_string()))
-            .collect(),
-    );
+    let items = [(1, "one"), (2, "two"), (3, "three")];
+    let result = tree.batch_insert(items.iter().map(|(k, v)| (*k, v.to_string())).collect());
     assert!(result.is_ok());
     assert_eq!(tree.len(), 3);
 
     // Batch insert with duplicates
-    let items2 = vec![(4, "four"), (2, "TWO"), (5, "five")];
-    let result2 = tree.batch_insert(
-        items2
-            .iter()
-            .map(|(k, v)| (*k, v.to_string()))
-            .collect(),
-    );
+    let items2 = [(4, "four"), (2, "TWO"), (5, "five")];
+    let result2 = tree.batch_insert(items2.iter().map(|(k, v)| (*k, v.to_string())).collect());
     assert!(result2.is_ok());
     assert_eq!(tree.len(), 5);
     assert_eq!(tree.get(&2), Some(&"TWO".to_string()));
@@ -2277,7 +2289,10 @@ fn test_get_many() {
     let keys = vec![1, 3];
     let result = tree.get_many(&keys);
     assert!(result.is_ok());
-    assert_eq!(result.unwrap(), vec![&"one".to_string(), &"three".to_string()]);
+    assert_eq!(
+        result.unwrap(),
+        vec![&"one".to_string(), &"three".to_string()]
+    );
 
     // get_many with missing key
     let keys2 = vec![1, 4, 2];
@@ -2292,4 +2307,4688 @@ fn test_validate_for_operation() {
 
     tree.insert(1, "one".to_string());
     assert!(tree.validate_for_operation("after insert").is_ok());
-}
\ No newline at end of file
+}
+// ============================================================================
+// LEVEL-ORDER TRAVERSAL TESTS
+// ============================================================================
+
+#[test]
+fn test_level_order_traversal() {
+    let tree = create_tree_4_with_data(50);
+
+    let nodes: Vec<_> = tree.level_order().collect();
+
+    // Root comes first at depth 0 and is a branch for this many items
+    assert_eq!(nodes[0].0, 0);
+    assert!(!nodes[0].2);
+
+    // Depths never decrease in breadth-first order
+    for pair in nodes.windows(2) {
+        assert!(pair[0].0 <= pair[1].0, "depths out of order: {:?}", pair);
+    }
+
+    // Every node is visited exactly once
+    let (leaf_count, branch_count) = tree.count_nodes_in_tree();
+    assert_eq!(nodes.len(), leaf_count + branch_count);
+    assert_eq!(
+        nodes.iter().filter(|(_, _, is_leaf)| *is_leaf).count(),
+        leaf_count
+    );
+
+    // All leaves sit on the deepest level
+    let max_depth = nodes.iter().map(|(depth, _, _)| *depth).max().unwrap();
+    assert!(nodes
+        .iter()
+        .filter(|(_, _, is_leaf)| *is_leaf)
+        .all(|(depth, _, _)| *depth == max_depth));
+}
+
+#[test]
+fn test_level_order_single_leaf_root() {
+    let tree = create_tree_4();
+    let nodes: Vec<_> = tree.level_order().collect();
+    assert_eq!(nodes.len(), 1);
+    assert_eq!(nodes[0].0, 0);
+    assert!(nodes[0].2);
+}
+
+// ============================================================================
+// OWNED RANGE ITERATION TESTS
+// ============================================================================
+
+#[test]
+fn test_range_owned_matches_borrowing_range() {
+    let tree = create_tree_4_with_data(100);
+
+    let borrowed: Vec<(i32, String)> = tree.range(20..=60).map(|(k, v)| (*k, v.clone())).collect();
+    let owned: Vec<(i32, String)> = tree.range_owned(20..=60).collect();
+    assert_eq!(owned, borrowed);
+
+    let all: Vec<(i32, String)> = tree.range_owned(..).collect();
+    assert_eq!(all.len(), 100);
+}
+
+#[test]
+fn test_range_owned_is_lazy_and_outlives_tree() {
+    let owned: Vec<(i32, String)> = {
+        let tree = create_tree_4_with_data(10_000);
+        let mut iter = tree.range_owned(500..);
+        let first_three: Vec<_> = iter.by_ref().take(3).collect();
+        assert_eq!(
+            first_three.iter().map(|(k, _)| *k).collect::<Vec<_>>(),
+            vec![500, 501, 502]
+        );
+        // The iterator resumes where it stopped
+        assert_eq!(iter.next().map(|(k, _)| k), Some(503));
+        first_three
+    };
+    assert_eq!(owned[0].0, 500);
+}
+
+// ============================================================================
+// EXACT-SIZE RANGE TESTS
+// ============================================================================
+
+#[test]
+fn test_range_sized_len_matches_yielded_count() {
+    use std::ops::Bound::{self, Excluded, Included, Unbounded};
+
+    let mut tree = BPlusTreeMap::new(4).unwrap();
+    for i in 0..200 {
+        tree.insert(i * 2, i);
+    }
+
+    let points = [-5, 0, 1, 37, 38, 200, 201, 398, 399, 500];
+    let mut bounds: Vec<(Bound<i32>, Bound<i32>)> = vec![(Unbounded, Unbounded)];
+    for &a in &points {
+        bounds.push((Included(a), Unbounded));
+        bounds.push((Excluded(a), Unbounded));
+        bounds.push((Unbounded, Included(a)));
+        bounds.push((Unbounded, Excluded(a)));
+        for &b in &points {
+            bounds.push((Included(a), Included(b)));
+            bounds.push((Included(a), Excluded(b)));
+            bounds.push((Excluded(a), Included(b)));
+            bounds.push((Excluded(a), Excluded(b)));
+        }
+    }
+
+    for range in bounds {
+        let sized = tree.range_sized(range);
+        let expected = tree.range(range).count();
+        assert_eq!(sized.len(), expected, "length mismatch for {:?}", range);
+        assert_eq!(sized.count(), expected, "yield mismatch for {:?}", range);
+    }
+}
+
+#[test]
+fn test_range_sized_reports_exact_remaining() {
+    let tree = create_tree_4_with_data(100);
+
+    let mut iter = tree.range_sized(10..60);
+    assert_eq!(iter.size_hint(), (50, Some(50)));
+    iter.next();
+    iter.next();
+    assert_eq!(iter.len(), 48);
+
+    let rest: Vec<_> = iter.collect();
+    assert_eq!(rest.len(), 48);
+    assert_eq!(rest.first().map(|(k, _)| **k), Some(12));
+    #[allow(clippy::reversed_empty_ranges)]
+    let empty = tree.range_sized(60..10);
+    assert_eq!(empty.len(), 0);
+}
+
+// ============================================================================
+// SEPARATE LEAF/BRANCH CAPACITY TESTS
+// ============================================================================
+
+#[test]
+fn test_new_with_capacities_rejects_small_capacities() {
+    assert!(matches!(
+        BPlusTreeMap::<i32, i32>::new_with_capacities(3, 16),
+        Err(BPlusTreeError::InvalidCapacity(_))
+    ));
+    assert!(matches!(
+        BPlusTreeMap::<i32, i32>::new_with_capacities(16, 2),
+        Err(BPlusTreeError::InvalidCapacity(_))
+    ));
+    assert!(BPlusTreeMap::<i32, i32>::new_with_capacities(4, 4).is_ok());
+}
+
+#[test]
+fn test_large_leaves_small_branches() {
+    let mut tree = BPlusTreeMap::new_with_capacities(64, 4).unwrap();
+
+    for i in 0..5000 {
+        tree.insert((i * 7919) % 5000, i);
+    }
+    assert_eq!(tree.len(), 5000);
+    assert_invariants_int(&tree, "large leaves, small branches");
+
+    // Leaves are wide, so there are far fewer of them than with capacity 4
+    assert!(tree.leaf_count() < 5000 / 16);
+
+    for i in 0..5000 {
+        assert!(tree.get(&i).is_some(), "key {} not routed correctly", i);
+    }
+
+    for i in (0..5000).step_by(2) {
+        tree.remove(&i);
+    }
+    assert_eq!(tree.len(), 2500);
+    assert_invariants_int(&tree, "after removals");
+    assert_eq!(
+        tree.keys().copied().collect::<Vec<_>>(),
+        (1..5000).step_by(2).collect::<Vec<_>>()
+    );
+}
+
+#[test]
+fn test_small_leaves_large_branches() {
+    let mut tree = BPlusTreeMap::new_with_capacities(4, 64).unwrap();
+
+    for i in 0..5000 {
+        tree.insert(i, i * 2);
+    }
+    assert_invariants_int(&tree, "small leaves, large branches");
+    assert_eq!(tree.get(&4321), Some(&8642));
+
+    for i in 0..4900 {
+        tree.remove(&i);
+    }
+    assert_eq!(tree.len(), 100);
+    assert_invariants_int(&tree, "after shrinking");
+}
+
+// ============================================================================
+// RESET WITH SORTED DATA TESTS
+// ============================================================================
+
+#[test]
+fn test_reset_with_reloads_repeatedly() {
+    let mut tree = create_tree_4_int();
+    for i in 0..100 {
+        tree.insert(i * 3, i);
+    }
+
+    for (round, size) in [1000, 37, 0, 5, 1000].into_iter().enumerate() {
+        let offset = round as i32 * 10_000;
+        tree.reset_with((0..size).map(|i| (offset + i, i))).unwrap();
+
+        assert_eq!(tree.len(), size as usize, "round {}", round);
+        assert_invariants_int(&tree, &format!("reload round {}", round));
+        assert_eq!(
+            tree.items().map(|(k, v)| (*k, *v)).collect::<Vec<_>>(),
+            (0..size).map(|i| (offset + i, i)).collect::<Vec<_>>()
+        );
+
+        // Storage is packed densely with no free slots left behind
+        let leaf_stats = tree.leaf_arena_stats();
+        let branch_stats = tree.branch_arena_stats();
+        assert_eq!(leaf_stats.free_count, 0);
+        assert_eq!(branch_stats.free_count, 0);
+        assert_eq!(leaf_stats.total_capacity, tree.leaf_count());
+    }
+}
+
+#[test]
+fn test_reset_with_packs_leaves() {
+    let mut tree = create_tree_capacity_int(16);
+    tree.reset_with((0..1000).map(|i| (i, i))).unwrap();
+
+    let sizes = tree.leaf_sizes();
+    assert_eq!(sizes.iter().sum::<usize>(), 1000);
+    // Every leaf but the last two is completely full
+    assert!(sizes[..sizes.len() - 2].iter().all(|&size| size == 16));
+    assert!(sizes.iter().all(|&size| size >= 8));
+
+    // The tree keeps working normally after a reload
+    tree.insert(-1, -1);
+    tree.remove(&500);
+    assert_invariants_int(&tree, "mutations after reload");
+}
+
+#[test]
+fn test_reset_with_rejects_unsorted_input() {
+    let mut tree = create_tree_4_int_with_data(20);
+
+    let result = tree.reset_with(vec![(1, 1), (3, 3), (2, 2)]);
+    assert!(matches!(result, Err(BPlusTreeError::DataIntegrityError(_))));
+    assert!(tree.is_empty());
+    assert_invariants_int(&tree, "after rejected reload");
+
+    let result = tree.reset_with(vec![(1, 1), (1, 2)]);
+    assert!(matches!(result, Err(BPlusTreeError::DataIntegrityError(_))));
+    assert!(tree.is_empty());
+}
+
+// ============================================================================
+// LONGEST RUN TESTS
+// ============================================================================
+
+#[test]
+fn test_longest_run() {
+    let mut tree = create_tree_4_int();
+    for key in [1, 2, 3, 7, 8, 100] {
+        tree.insert(key, key);
+    }
+    assert_eq!(tree.longest_run(), Some((1, 3)));
+
+    // A longer run spanning several leaves wins
+    for key in 50..70 {
+        tree.insert(key, key);
+    }
+    assert_eq!(tree.longest_run(), Some((50, 20)));
+
+    // Negative keys are handled
+    for key in -30..-5 {
+        tree.insert(key, key);
+    }
+    assert_eq!(tree.longest_run(), Some((-30, 25)));
+}
+
+#[test]
+fn test_longest_run_edge_cases() {
+    let mut tree = create_tree_4_int();
+    assert_eq!(tree.longest_run(), None);
+
+    tree.insert(42, 0);
+    assert_eq!(tree.longest_run(), Some((42, 1)));
+
+    // Ties go to the smallest run
+    tree.insert(10, 0);
+    assert_eq!(tree.longest_run(), Some((10, 1)));
+
+    let mut tree: BPlusTreeMap<u8, ()> = BPlusTreeMap::new(4).unwrap();
+    tree.insert(254, ());
+    tree.insert(255, ());
+    assert_eq!(tree.longest_run(), Some((254, 2)));
+}
+
+// ============================================================================
+// VEC-VALUED MAP TESTS
+// ============================================================================
+
+#[test]
+fn test_push_to_vec_value_groups_items() {
+    let mut groups: BPlusTreeMap<i32, Vec<i32>> = BPlusTreeMap::new(4).unwrap();
+
+    // Group 0..200 by remainder mod 7, interleaving keys to force splits
+    for item in 0..200 {
+        groups.push_to_vec_value(item % 7, item);
+    }
+    for item in 0..50 {
+        groups.push_to_vec_value(100 + item, item);
+    }
+
+    assert_eq!(groups.len(), 57);
+    for remainder in 0..7 {
+        let expected: Vec<i32> = (0..200).filter(|i| i % 7 == remainder).collect();
+        assert_eq!(groups.get(&remainder), Some(&expected));
+    }
+    assert_eq!(groups.get(&149), Some(&vec![49]));
+    assert!(groups.check_invariants());
+}
+
+// ============================================================================
+// COALESCE UNDERFULL LEAVES TESTS
+// ============================================================================
+
+#[test]
+fn test_coalesce_underfull_merges_sparse_leaves() {
+    let mut tree = BPlusTreeMap::new(8).unwrap();
+    for i in 0..400 {
+        tree.insert(i, i * 10);
+    }
+    // Thin every leaf down towards minimum fill without triggering merges
+    for i in (0..400).filter(|i| i % 3 != 0) {
+        tree.remove(&i);
+    }
+    assert!(tree.check_invariants());
+
+    let before: Vec<(i32, i32)> = tree.items().map(|(k, v)| (*k, *v)).collect();
+    let leaves_before = tree.leaf_count();
+
+    let merges = tree.coalesce_underfull();
+
+    assert!(merges > 0);
+    assert_eq!(tree.leaf_count(), leaves_before - merges);
+    assert!(tree.check_invariants());
+    assert_eq!(tree.len(), before.len());
+    let after: Vec<(i32, i32)> = tree.items().map(|(k, v)| (*k, *v)).collect();
+    assert_eq!(after, before);
+    for (k, v) in &before {
+        assert_eq!(tree.get(k), Some(v));
+    }
+
+    // Nothing fits together any more
+    assert_eq!(tree.coalesce_underfull(), 0);
+}
+
+#[test]
+fn test_coalesce_underfull_on_small_trees() {
+    let mut tree = BPlusTreeMap::new(4).unwrap();
+    assert_eq!(tree.coalesce_underfull(), 0);
+
+    for i in 0..6 {
+        tree.insert(i, i);
+    }
+    tree.remove(&5);
+    tree.remove(&4);
+    tree.coalesce_underfull();
+    assert!(tree.check_invariants());
+    assert_eq!(tree.keys().copied().collect::<Vec<_>>(), vec![0, 1, 2, 3]);
+}
+
+#[test]
+fn test_coalesce_underfull_keeps_invariants_under_random_removals() {
+    use rand::prelude::*;
+
+    let mut rng = StdRng::seed_from_u64(446);
+    for capacity in [7, 8, 9, 16] {
+        for round in 0..20 {
+            let mut tree = BPlusTreeMap::new(capacity).unwrap();
+            let mut reference = std::collections::BTreeMap::new();
+            for _ in 0..rng.gen_range(50..2_000) {
+                let key = rng.gen_range(0..5_000);
+                tree.insert(key, key);
+                reference.insert(key, key);
+            }
+
+            // Alternate thinning passes with coalescing, which merges many
+            // leaves under one branch at once
+            for pass in 0..4 {
+                let keep = rng.gen_range(0.2..0.9);
+                let doomed: Vec<i32> = reference
+                    .keys()
+                    .copied()
+                    .filter(|_| !rng.gen_bool(keep))
+                    .collect();
+                for key in doomed {
+                    assert_eq!(tree.remove(&key), reference.remove(&key));
+                }
+
+                tree.coalesce_underfull();
+                assert!(
+                    tree.check_invariants(),
+                    "capacity {} round {} pass {}",
+                    capacity,
+                    round,
+                    pass
+                );
+                assert!(tree.items().map(|(k, v)| (*k, *v)).eq(reference.clone()));
+            }
+        }
+    }
+}
+
+// ============================================================================
+// ROOT VALIDITY TESTS
+// ============================================================================
+
+#[test]
+fn test_missing_root_is_distinguishable_from_empty_tree() {
+    let empty = BPlusTreeMap::<i32, i32>::new(4).unwrap();
+    assert!(empty.root_is_valid());
+    assert!(empty.check_invariants_detailed().is_ok());
+
+    let mut tree = BPlusTreeMap::new(4).unwrap();
+    tree.insert(1, 10);
+    tree.insert(2, 20);
+    let (_, root_id, is_leaf) = tree.level_order().next().unwrap();
+    assert!(is_leaf);
+
+    assert!(tree.deallocate_leaf(root_id).is_some());
+
+    assert!(!tree.root_is_valid());
+    assert_eq!(tree.get(&1), None);
+    let error = tree.check_invariants_detailed().unwrap_err();
+    assert!(
+        error.contains("root leaf node"),
+        "unexpected error: {}",
+        error
+    );
+    assert!(error.contains("missing"), "unexpected error: {}", error);
+}
+
+#[test]
+fn test_root_is_valid_for_branch_roots() {
+    let tree = create_tree_4_with_data(100);
+    assert!(!tree.is_leaf_root());
+    assert!(tree.root_is_valid());
+}
+
+// ============================================================================
+// K-WAY MERGE CONSTRUCTION TESTS
+// ============================================================================
+
+#[test]
+fn test_from_sorted_sources_merges_overlapping_runs() {
+    let run = |keys: std::ops::Range<i32>| keys.map(|k| (k, 1));
+    let sources = vec![run(0..100), run(50..150), run(90..200)];
+
+    let tree = BPlusTreeMap::from_sorted_sources(8, sources, |_, a, b| a + b).unwrap();
+
+    assert!(tree.check_invariants());
+    let keys: Vec<i32> = tree.keys().copied().collect();
+    assert_eq!(keys, (0..200).collect::<Vec<_>>());
+    assert_eq!(tree.get(&10), Some(&1));
+    assert_eq!(tree.get(&60), Some(&2));
+    assert_eq!(tree.get(&95), Some(&3));
+    assert_eq!(tree.get(&120), Some(&2));
+    assert_eq!(tree.get(&180), Some(&1));
+
+    // Output is bulk-loaded densely: every leaf is full
+    assert_eq!(tree.leaf_count(), 25);
+    assert!(tree.leaf_sizes().iter().all(|&size| size == 8));
+}
+
+#[test]
+fn test_from_sorted_sources_passes_values_in_source_order() {
+    let sources = vec![
+        vec![(1, "a"), (3, "a")].into_iter(),
+        vec![(3, "b")].into_iter(),
+        vec![].into_iter(),
+        vec![(2, "c"), (3, "c")].into_iter(),
+    ];
+
+    let mut seen = Vec::new();
+    let tree = BPlusTreeMap::from_sorted_sources(4, sources, |key, kept, next| {
+        seen.push((*key, kept, next));
+        next
+    })
+    .unwrap();
+
+    assert_eq!(seen, vec![(3, "a", "b"), (3, "b", "c")]);
+    assert_eq!(
+        tree.items().collect::<Vec<_>>(),
+        vec![(&1, &"a"), (&2, &"c"), (&3, &"c")]
+    );
+}
+
+#[test]
+fn test_from_sorted_sources_rejects_unsorted_input_and_bad_capacity() {
+    let unsorted = vec![vec![(5, 0), (1, 0)].into_iter()];
+    assert!(matches!(
+        BPlusTreeMap::from_sorted_sources(4, unsorted, |_, a, _| a),
+        Err(BPlusTreeError::DataIntegrityError(_))
+    ));
+
+    let empty: Vec<std::vec::IntoIter<(i32, i32)>> = Vec::new();
+    let tree = BPlusTreeMap::from_sorted_sources(4, empty, |_, a, _| a).unwrap();
+    assert!(tree.is_empty());
+
+    let sources = vec![vec![(1, 1)].into_iter()];
+    assert!(matches!(
+        BPlusTreeMap::from_sorted_sources(2, sources, |_, a, _| a),
+        Err(BPlusTreeError::InvalidCapacity(_))
+    ));
+}
+
+// ============================================================================
+// OPERATION COUNTER TESTS
+// ============================================================================
+
+fn tree_height<K: Ord + Clone, V: Clone>(tree: &BPlusTreeMap<K, V>) -> u64 {
+    tree.level_order().map(|(depth, _, _)| depth).max().unwrap() as u64 + 1
+}
+
+#[test]
+fn test_counters_are_off_by_default() {
+    let mut tree = create_tree_4_with_data(50);
+    tree.get(&10);
+    tree.insert(100, "x".to_string());
+    assert_eq!(tree.operation_counters(), bplustree::OpCounters::default());
+
+    tree.enable_counters();
+    tree.get(&10);
+    assert!(tree.operation_counters().node_visits > 0);
+
+    tree.disable_counters();
+    assert_eq!(tree.operation_counters(), bplustree::OpCounters::default());
+}
+
+#[test]
+fn test_point_lookup_visits_one_node_per_level() {
+    let mut tree = create_tree_4_with_data(500);
+    let height = tree_height(&tree);
+    assert!(height >= 3);
+
+    tree.enable_counters();
+    assert!(tree.get(&250).is_some());
+
+    let counters = tree.operation_counters();
+    assert_eq!(counters.node_visits, height);
+    assert!(counters.comparisons >= height);
+    // Each node search is a binary search over at most 4 keys
+    assert!(counters.comparisons <= height * 3);
+    assert_eq!(counters.splits, 0);
+    assert_eq!(counters.merges, 0);
+}
+
+#[test]
+fn test_counters_track_splits_and_merges() {
+    let mut tree = BPlusTreeMap::new(4).unwrap();
+    for i in 0..4 {
+        tree.insert(i, i);
+    }
+    tree.enable_counters();
+
+    // The root leaf is full, so this insert splits it
+    tree.insert(4, 4);
+    assert_eq!(tree.operation_counters().splits, 1);
+    tree.insert(5, 5);
+    assert_eq!(tree.operation_counters().splits, 1);
+
+    tree.reset_counters();
+    assert_eq!(tree.operation_counters(), bplustree::OpCounters::default());
+
+    // Both leaves are at minimum fill: removing drives a merge
+    tree.remove(&5);
+    tree.remove(&4);
+    tree.remove(&0);
+    assert!(tree.check_invariants());
+    assert_eq!(tree.operation_counters().merges, 1);
+    assert_eq!(tree.operation_counters().splits, 0);
+}
+
+#[test]
+fn test_delete_reads_nodes_proportional_to_height() {
+    for capacity in [4, 16, 64] {
+        let mut tree = BPlusTreeMap::new(capacity).unwrap();
+        for i in 0..50_000 {
+            tree.insert(i, i);
+        }
+        let height = tree_height(&tree);
+        let leaves = tree.leaf_count() as u64;
+        tree.enable_counters();
+
+        // Deleting every third key drives borrows and merges at all levels
+        for i in (0..50_000).step_by(3) {
+            tree.reset_counters();
+            assert!(tree.remove(&i).is_some());
+            let visits = tree.operation_counters().node_visits;
+            // Each level reads its node, at most two siblings and its parent
+            // a bounded number of times; a tree-wide separator repair would
+            // read every node instead
+            assert!(
+                visits <= height * 16 && visits < leaves,
+                "capacity {}: delete of {} read {} nodes (height {}, {} leaves)",
+                capacity,
+                i,
+                visits,
+                height,
+                leaves
+            );
+        }
+        assert!(tree.check_invariants_detailed().is_ok());
+    }
+}
+
+// ============================================================================
+// BRANCH ROUTING TESTS
+// ============================================================================
+
+#[test]
+fn test_find_child_index_by_matches_ord_routing() {
+    use rand::prelude::*;
+
+    let mut rng = StdRng::seed_from_u64(42);
+    for capacity in [4, 5, 16, 64, 256] {
+        let mut tree = BPlusTreeMap::new(capacity).unwrap();
+        for _ in 0..20_000 {
+            let key: i32 = rng.gen_range(0..1_000_000);
+            tree.insert(key, ());
+        }
+
+        let branch_ids: Vec<_> = tree
+            .level_order()
+            .filter(|(_, _, is_leaf)| !is_leaf)
+            .map(|(_, id, _)| id)
+            .collect();
+        assert!(!branch_ids.is_empty());
+
+        for id in branch_ids {
+            let branch = tree.get_branch(id).unwrap();
+            let mut probes: Vec<i32> = (0..200).map(|_| rng.gen_range(-10..1_000_010)).collect();
+            probes.extend(branch.keys().iter().copied());
+            probes.extend(branch.keys().iter().map(|k| k - 1));
+
+            for key in probes {
+                let expected = match branch.keys().binary_search(&key) {
+                    Ok(index) => index + 1,
+                    Err(index) => index,
+                };
+                assert_eq!(branch.find_child_index_by(&key, i32::cmp), expected);
+                assert_eq!(branch.find_child_index(&key), expected);
+            }
+        }
+    }
+}
+
+#[test]
+fn test_find_child_index_by_honours_custom_ordering() {
+    let mut tree = BPlusTreeMap::new(4).unwrap();
+    for i in 0..50 {
+        tree.insert(i, i);
+    }
+    let (_, root_id, _) = tree.level_order().next().unwrap();
+    let branch = tree.get_branch(root_id).unwrap();
+
+    // Under a reversed comparator a large key sorts before every separator
+    // and a small key after all of them
+    let reversed = |a: &i32, b: &i32| b.cmp(a);
+    assert_eq!(branch.find_child_index_by(&1000, reversed), 0);
+    assert_eq!(
+        branch.find_child_index_by(&-1000, reversed),
+        branch.keys().len()
+    );
+}
+
+// ============================================================================
+// LOOKUP COMPARISON COUNT TESTS
+// ============================================================================
+
+#[test]
+fn test_get_comparisons_grows_logarithmically() {
+    let capacity: usize = 8;
+    // Binary search over at most `capacity` keys: ceil(log2(capacity + 1)) steps
+    let max_per_node = (usize::BITS - capacity.leading_zeros()) as usize;
+
+    let mut averages = Vec::new();
+    for size in [10, 100, 1_000, 10_000, 100_000] {
+        let mut tree = BPlusTreeMap::new(capacity).unwrap();
+        for i in 0..size {
+            tree.insert(i, i);
+        }
+        let height = tree_height(&tree) as usize;
+
+        let mut total = 0;
+        for key in (0..size).step_by((size / 100).max(1) as usize) {
+            let comparisons = tree.get_comparisons(&key);
+            assert!(comparisons >= 1);
+            assert!(
+                comparisons <= height * max_per_node,
+                "{} comparisons exceed bound for height {}",
+                comparisons,
+                height
+            );
+            total += comparisons;
+        }
+        averages.push(total as f64 / 100.0_f64.min(size as f64));
+    }
+
+    // Each tenfold growth adds a roughly constant number of comparisons
+    for pair in averages.windows(2) {
+        assert!(pair[1] >= pair[0]);
+        assert!(
+            pair[1] - pair[0] <= 2.0 * max_per_node as f64,
+            "{:?}",
+            averages
+        );
+    }
+    assert!(averages[4] < 10.0 * averages[0]);
+}
+
+#[test]
+fn test_get_comparisons_matches_counted_lookup() {
+    let mut tree = create_tree_4_with_data(300);
+    tree.enable_counters();
+    for key in [0, 17, 150, 299, 1000] {
+        tree.reset_counters();
+        tree.get(&key);
+        assert_eq!(
+            tree.get_comparisons(&key) as u64,
+            tree.operation_counters().comparisons
+        );
+    }
+}
+
+// ============================================================================
+// GRAPHVIZ EXPORT TESTS
+// ============================================================================
+
+#[test]
+fn test_to_dot_describes_small_tree() {
+    let mut tree = BPlusTreeMap::new(4).unwrap();
+    for i in 0..10 {
+        tree.insert(i, i * 10);
+    }
+    let leaves = tree.leaf_count();
+    let nodes = tree.level_order().count();
+    assert_eq!(leaves, 4);
+    assert_eq!(nodes, 5);
+
+    let dot = tree.to_dot();
+    let lines: Vec<&str> = dot.lines().map(str::trim).collect();
+
+    assert_eq!(lines.first(), Some(&"digraph BPlusTree {"));
+    assert_eq!(lines.last(), Some(&"}"));
+    let node_lines = lines
+        .iter()
+        .filter(|l| l.contains("[label=") || l.contains("style=rounded"));
+    assert_eq!(node_lines.count(), nodes);
+    let edges: Vec<&&str> = lines.iter().filter(|l| l.contains("->")).collect();
+    let next_edges = edges.iter().filter(|l| l.contains("style=dashed")).count();
+    assert_eq!(next_edges, leaves - 1);
+    assert_eq!(edges.len() - next_edges, nodes - 1);
+
+    assert!(dot.contains("0 .. 1\\n2 keys"), "{}", dot);
+}
+
+#[test]
+fn test_to_dot_escapes_string_keys() {
+    let mut tree = BPlusTreeMap::new(4).unwrap();
+    tree.insert("say \"hi\"".to_string(), 1);
+
+    let dot = tree.to_dot();
+    assert!(dot.contains(r#"\"say \\\"hi\\\"\""#), "{}", dot);
+}
+
+// ============================================================================
+// BATCH POP TESTS
+// ============================================================================
+
+#[test]
+fn test_pop_first_n_drains_in_batches() {
+    let mut tree = BPlusTreeMap::new(4).unwrap();
+    for i in 0..200 {
+        tree.insert(i, i * 10);
+    }
+
+    let mut expected_next = 0;
+    for batch in [1, 7, 3, 50, 20, 100, 5, 30] {
+        let popped = tree.pop_first_n(batch);
+        let wanted = batch.min(200 - expected_next as usize);
+        assert_eq!(popped.len(), wanted);
+        for (offset, (k, v)) in popped.iter().enumerate() {
+            assert_eq!(*k, expected_next + offset as i32);
+            assert_eq!(*v, k * 10);
+        }
+        expected_next += wanted as i32;
+
+        assert!(tree.check_invariants_detailed().is_ok());
+        assert_eq!(tree.len(), 200 - expected_next as usize);
+        assert_eq!(
+            tree.first().map(|(k, _)| *k),
+            (expected_next < 200).then_some(expected_next)
+        );
+    }
+    assert!(tree.is_empty());
+    assert!(tree.pop_first_n(3).is_empty());
+}
+
+#[test]
+fn test_pop_last_n_drains_in_batches() {
+    let mut tree = BPlusTreeMap::new(5).unwrap();
+    for i in 0..150 {
+        tree.insert(i, i.to_string());
+    }
+
+    let mut remaining = 150;
+    for batch in [2, 10, 1, 60, 9, 40, 100] {
+        let popped = tree.pop_last_n(batch);
+        let wanted = batch.min(remaining);
+        let keys: Vec<i32> = popped.iter().map(|(k, _)| *k).collect();
+        let expected: Vec<i32> = (0..remaining as i32).rev().take(wanted).collect();
+        assert_eq!(keys, expected);
+        remaining -= wanted;
+
+        assert!(tree.check_invariants_detailed().is_ok());
+        assert_eq!(tree.len(), remaining);
+    }
+    assert!(tree.is_empty());
+}
+
+#[test]
+fn test_pop_from_both_ends() {
+    let mut tree = create_tree_4_with_data(100);
+
+    assert_eq!(tree.pop_first_n(0), vec![]);
+    let low = tree.pop_first_n(10);
+    let high = tree.pop_last_n(10);
+    assert_eq!(low.last().map(|(k, _)| *k), Some(9));
+    assert_eq!(high.last().map(|(k, _)| *k), Some(90));
+
+    let keys: Vec<i32> = tree.keys().copied().collect();
+    assert_eq!(keys, (10..90).collect::<Vec<_>>());
+    assert!(tree.check_invariants_detailed().is_ok());
+}
+
+#[test]
+fn test_small_pops_match_reference_and_keep_invariants() {
+    use rand::prelude::*;
+
+    let mut rng = StdRng::seed_from_u64(453);
+    for capacity in [4, 5, 7, 16] {
+        let mut tree = BPlusTreeMap::new(capacity).unwrap();
+        let mut reference = std::collections::BTreeMap::new();
+        for _ in 0..3_000 {
+            let key = rng.gen_range(0..10_000);
+            tree.insert(key, key * 2);
+            reference.insert(key, key * 2);
+        }
+
+        while !reference.is_empty() {
+            // Stay below half the tree so the in-place edge path is taken
+            let n = rng.gen_range(1..=(reference.len() / 3).max(1));
+            if rng.gen_bool(0.5) {
+                let expected: Vec<(i32, i32)> =
+                    (0..n).map_while(|_| reference.pop_first()).collect();
+                assert_eq!(tree.pop_first_n(n), expected);
+            } else {
+                let expected: Vec<(i32, i32)> =
+                    (0..n).map_while(|_| reference.pop_last()).collect();
+                assert_eq!(tree.pop_last_n(n), expected);
+            }
+            assert!(
+                tree.check_invariants_detailed().is_ok(),
+                "capacity {} with {} left",
+                capacity,
+                reference.len()
+            );
+            assert_eq!(tree.len(), reference.len());
+            assert!(tree.items().map(|(k, v)| (*k, *v)).eq(reference.clone()));
+        }
+    }
+}
+
+/// Key that remembers whether it is a clone; ordering ignores the flag.
+#[derive(Debug)]
+struct TracedKey {
+    value: i32,
+    cloned: bool,
+}
+
+impl Clone for TracedKey {
+    fn clone(&self) -> Self {
+        TracedKey {
+            value: self.value,
+            cloned: true,
+        }
+    }
+}
+
+impl PartialEq for TracedKey {
+    fn eq(&self, other: &Self) -> bool {
+        self.value == other.value
+    }
+}
+
+impl Eq for TracedKey {}
+
+impl PartialOrd for TracedKey {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for TracedKey {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.value.cmp(&other.value)
+    }
+}
+
+#[test]
+fn test_small_pops_return_the_stored_keys() {
+    let mut tree = BPlusTreeMap::new(4).unwrap();
+    for value in 0..100 {
+        let key = TracedKey {
+            value,
+            cloned: false,
+        };
+        tree.insert(key, value);
+    }
+
+    let low = tree.pop_first_n(5);
+    let high = tree.pop_last_n(5);
+
+    assert!(low.iter().chain(&high).all(|(key, _)| !key.cloned));
+    assert_eq!(
+        low.iter().map(|(k, _)| k.value).collect::<Vec<_>>(),
+        vec![0, 1, 2, 3, 4]
+    );
+    assert_eq!(
+        high.iter().map(|(k, _)| k.value).collect::<Vec<_>>(),
+        vec![99, 98, 97, 96, 95]
+    );
+    assert!(tree.check_invariants());
+}
+
+// ============================================================================
+// RANGE UPDATE TESTS
+// ============================================================================
+
+#[test]
+fn test_update_range_touches_only_keys_in_range() {
+    let mut tree = BPlusTreeMap::new(4).unwrap();
+    for i in 0..1000 {
+        tree.insert(i, i);
+    }
+
+    let mut visited = Vec::new();
+    tree.update_range(100..200, |key, value| {
+        visited.push(*key);
+        *value += 1;
+    });
+
+    assert_eq!(visited, (100..200).collect::<Vec<_>>());
+    for i in 0..1000 {
+        let expected = if (100..200).contains(&i) { i + 1 } else { i };
+        assert_eq!(tree.get(&i), Some(&expected));
+    }
+    assert!(tree.check_invariants());
+}
+
+#[test]
+fn test_update_range_respects_bound_kinds() {
+    use std::ops::Bound::{Excluded, Included, Unbounded};
+
+    let mut tree = BPlusTreeMap::new(4).unwrap();
+    for i in 0..20 {
+        tree.insert(i * 2, 0);
+    }
+
+    tree.update_range((Excluded(4), Included(10)), |_, v| *v += 1);
+    tree.update_range((Excluded(5), Excluded(8)), |_, v| *v += 10);
+    tree.update_range((Included(34), Unbounded), |_, v| *v += 100);
+    tree.update_range(..=0, |_, v| *v += 1000);
+    tree.update_range(50..60, |_, v| *v += 5);
+
+    let changed: Vec<(i32, i32)> = tree
+        .items()
+        .filter(|(_, v)| **v != 0)
+        .map(|(k, v)| (*k, *v))
+        .collect();
+    assert_eq!(
+        changed,
+        vec![
+            (0, 1000),
+            (6, 11),
+            (8, 1),
+            (10, 1),
+            (34, 100),
+            (36, 100),
+            (38, 100)
+        ]
+    );
+}
+
+#[test]
+fn test_update_range_is_a_single_traversal() {
+    let mut tree = BPlusTreeMap::new(4).unwrap();
+    for i in 0..1000 {
+        tree.insert(i, i);
+    }
+    let height = tree_height(&tree);
+
+    tree.enable_counters();
+    tree.update_range(100..200, |_, v| *v *= 2);
+    let counters = tree.operation_counters();
+
+    // One descent, then one visit per leaf spanned (each holds at least 2 keys)
+    assert!(
+        counters.node_visits <= height + 100 / 2 + 1,
+        "{:?}",
+        counters
+    );
+    assert_eq!(tree.get(&150), Some(&300));
+}
+
+// ============================================================================
+// MUTABLE VALUE ITERATION TESTS
+// ============================================================================
+
+#[test]
+fn test_values_mut_doubles_every_value() {
+    let mut tree = BPlusTreeMap::new(4).unwrap();
+    for i in 0..500 {
+        tree.insert(i, i);
+    }
+
+    let mut visited = 0;
+    for value in tree.values_mut() {
+        *value *= 2;
+        visited += 1;
+    }
+
+    assert_eq!(visited, 500);
+    assert!(tree.items().all(|(k, v)| *v == k * 2));
+    assert!(tree.check_invariants());
+}
+
+#[test]
+fn test_values_mut_yields_in_key_order() {
+    let mut tree = BPlusTreeMap::new(5).unwrap();
+    for i in (0..100).rev() {
+        tree.insert(i, 0);
+    }
+    // Stamp each value with its position in iteration order
+    for (position, value) in tree.values_mut().enumerate() {
+        *value = position as i32;
+    }
+    assert!(tree.items().all(|(k, v)| k == v));
+
+    let mut empty: BPlusTreeMap<i32, i32> = BPlusTreeMap::new(4).unwrap();
+    assert_eq!(empty.values_mut().count(), 0);
+}
+
+#[test]
+fn test_range_mut_inclusive_end() {
+    let mut tree = BPlusTreeMap::new(4).unwrap();
+    for i in 0..100 {
+        tree.insert(i, i);
+    }
+
+    let keys: Vec<i32> = tree
+        .range_mut(20..=60)
+        .map(|(k, v)| {
+            *v = -*v;
+            *k
+        })
+        .collect();
+
+    assert_eq!(keys, (20..=60).collect::<Vec<_>>());
+    for (k, v) in tree.items() {
+        let expected = if (20..=60).contains(k) { -k } else { *k };
+        assert_eq!(*v, expected);
+    }
+    assert!(tree.check_invariants());
+}
+
+#[test]
+fn test_range_mut_exclusive_bounds() {
+    let mut tree = BPlusTreeMap::new(4).unwrap();
+    for i in 0..50 {
+        tree.insert(i * 2, 0);
+    }
+
+    // Excluded start and end both land on existing keys
+    let bounds = (std::ops::Bound::Excluded(10), std::ops::Bound::Excluded(30));
+    for (_, v) in tree.range_mut(bounds) {
+        *v = 1;
+    }
+    let touched: Vec<i32> = tree
+        .items()
+        .filter(|(_, v)| **v == 1)
+        .map(|(k, _)| *k)
+        .collect();
+    assert_eq!(touched, (12..30).step_by(2).collect::<Vec<_>>());
+
+    // Unbounded start, exclusive end between keys
+    assert_eq!(tree.range_mut(..7).count(), 4);
+    // Unbounded end walks to the last leaf
+    assert_eq!(tree.range_mut(91..).count(), 4);
+}
+
+#[test]
+fn test_range_mut_empty_ranges() {
+    let mut tree = BPlusTreeMap::new(4).unwrap();
+    for i in 0..40 {
+        tree.insert(i * 10, i);
+    }
+
+    assert_eq!(tree.range_mut(11..19).count(), 0);
+    assert_eq!(tree.range_mut(1000..).count(), 0);
+    assert_eq!(tree.range_mut(50..50).count(), 0);
+
+    let mut empty: BPlusTreeMap<i32, i32> = BPlusTreeMap::new(4).unwrap();
+    assert_eq!(empty.range_mut(..).count(), 0);
+    assert!(tree.values().enumerate().all(|(i, v)| *v == i as i32));
+}
+
+// ============================================================================
+// REVERSE ITERATION TESTS
+// ============================================================================
+
+#[test]
+fn test_items_rev_is_descending() {
+    let mut tree = BPlusTreeMap::new(4).unwrap();
+    for i in 0..300 {
+        tree.insert((i * 37) % 300, i);
+    }
+
+    let reversed: Vec<i32> = tree.items().rev().map(|(k, _)| *k).collect();
+    assert_eq!(reversed, (0..300).rev().collect::<Vec<_>>());
+
+    let largest: Vec<i32> = tree.keys().rev().take(3).copied().collect();
+    assert_eq!(largest, vec![299, 298, 297]);
+    assert_eq!(
+        tree.values().rev().copied().collect::<Vec<_>>(),
+        tree.values()
+            .copied()
+            .collect::<Vec<_>>()
+            .into_iter()
+            .rev()
+            .collect::<Vec<_>>()
+    );
+
+    let empty: BPlusTreeMap<i32, i32> = BPlusTreeMap::new(4).unwrap();
+    assert_eq!(empty.items().next_back(), None);
+}
+
+#[test]
+fn test_items_double_ended_meet_in_middle() {
+    for size in [0, 1, 2, 7, 8, 9, 64, 101] {
+        let mut tree = BPlusTreeMap::new(4).unwrap();
+        for i in 0..size {
+            tree.insert(i, i);
+        }
+
+        // Alternate ends until exhausted; every key must appear exactly once
+        let mut iter = tree.keys();
+        let mut front = Vec::new();
+        let mut back = Vec::new();
+        while let Some(k) = iter.next() {
+            front.push(*k);
+            match iter.next_back() {
+                Some(k) => back.push(*k),
+                None => break,
+            }
+        }
+        assert_eq!(iter.next(), None);
+        assert_eq!(iter.next_back(), None);
+
+        back.reverse();
+        front.extend(back);
+        assert_eq!(front, (0..size).collect::<Vec<_>>(), "size {}", size);
+    }
+}
+
+#[test]
+fn test_items_back_then_front_across_leaves() {
+    let mut tree = BPlusTreeMap::new(4).unwrap();
+    for i in 0..40 {
+        tree.insert(i, i);
+    }
+
+    // Consume most of the tree from the back, then finish from the front
+    let mut iter = tree.items();
+    let back: Vec<i32> = iter.by_ref().rev().take(35).map(|(k, _)| *k).collect();
+    assert_eq!(back, (5..40).rev().collect::<Vec<_>>());
+    let rest: Vec<i32> = iter.map(|(k, _)| *k).collect();
+    assert_eq!(rest, vec![0, 1, 2, 3, 4]);
+}
+
+// ============================================================================
+// OWNED ITERATION TESTS
+// ============================================================================
+
+#[test]
+fn test_into_iter_moves_string_values() {
+    let mut tree = BPlusTreeMap::new(4).unwrap();
+    for i in (0..200).rev() {
+        tree.insert(i, format!("value_{}", i));
+    }
+
+    let collected: Vec<(i32, String)> = tree.into_iter().collect();
+    let expected: Vec<(i32, String)> = (0..200).map(|i| (i, format!("value_{}", i))).collect();
+    assert_eq!(collected, expected);
+}
+
+#[test]
+fn test_into_iter_for_loop_and_partial_consumption() {
+    let mut tree = BPlusTreeMap::new(5).unwrap();
+    for i in 0..50 {
+        tree.insert(i, vec![i; 3]);
+    }
+
+    let mut sum = 0;
+    for (k, v) in tree {
+        assert_eq!(v, vec![k; 3]);
+        sum += k;
+    }
+    assert_eq!(sum, (0..50).sum());
+
+    // Dropping a partially consumed iterator releases the remaining leaves
+    let mut tree = BPlusTreeMap::new(4).unwrap();
+    for i in 0..100 {
+        tree.insert(i, i.to_string());
+    }
+    let mut iter = tree.into_iter();
+    assert_eq!(iter.next(), Some((0, "0".to_string())));
+    drop(iter);
+
+    let empty: BPlusTreeMap<i32, String> = BPlusTreeMap::new(4).unwrap();
+    assert_eq!(empty.into_iter().count(), 0);
+}
+
+// ============================================================================
+// COLLECT TESTS
+// ============================================================================
+
+#[test]
+fn test_collect_from_sorted_pairs() {
+    let tree: BPlusTreeMap<i32, String> = (0..500).map(|i| (i, i.to_string())).collect();
+
+    assert_eq!(tree.len(), 500);
+    assert!(tree.check_invariants());
+    assert!(tree.items().map(|(k, _)| *k).eq(0..500));
+    assert_eq!(tree.get(&250), Some(&"250".to_string()));
+}
+
+#[test]
+fn test_collect_from_unsorted_pairs_with_duplicates() {
+    let pairs = vec![(5, "a"), (1, "b"), (9, "c"), (5, "d"), (3, "e"), (1, "f")];
+    let tree: BPlusTreeMap<i32, &str> = pairs.into_iter().collect();
+
+    assert_eq!(tree.len(), 4);
+    assert!(tree.check_invariants());
+    // Later duplicates win, like BTreeMap
+    assert_eq!(
+        tree.items().map(|(k, v)| (*k, *v)).collect::<Vec<_>>(),
+        vec![(1, "f"), (3, "e"), (5, "d"), (9, "c")]
+    );
+
+    let reversed: BPlusTreeMap<i32, i32> = (0..300).rev().map(|i| (i, i)).collect();
+    assert_eq!(reversed.len(), 300);
+    assert!(reversed.keys().copied().eq(0..300));
+
+    let empty: BPlusTreeMap<i32, i32> = std::iter::empty().collect();
+    assert!(empty.is_empty());
+}
+
+#[test]
+fn test_extend_overwrites_and_adds() {
+    let mut tree: BPlusTreeMap<i32, String> = (0..50).map(|i| (i, "old".to_string())).collect();
+
+    tree.extend((40..80).map(|i| (i, "new".to_string())));
+
+    assert_eq!(tree.len(), 80);
+    assert!(tree.check_invariants());
+    assert!(tree.range(..40).all(|(_, v)| v == "old"));
+    assert!(tree.range(40..).all(|(_, v)| v == "new"));
+}
+
+#[test]
+fn test_extend_from_borrowed_items() {
+    let source: BPlusTreeMap<i32, i32> = (0..100).map(|i| (i * 2, i)).collect();
+    let mut tree: BPlusTreeMap<i32, i32> = (0..100).map(|i| (i * 3, -1)).collect();
+
+    tree.extend(source.items());
+
+    assert!(tree.check_invariants());
+    for i in 0..100 {
+        assert_eq!(tree.get(&(i * 2)), Some(&i));
+    }
+    assert_eq!(tree.get(&3), Some(&-1));
+    assert_eq!(tree.get(&6), Some(&3));
+    assert_eq!(source.len(), 100);
+}
+
+// ============================================================================
+// SPLIT OFF TESTS
+// ============================================================================
+
+fn assert_split(
+    tree: &BPlusTreeMap<i32, i32>,
+    upper: &BPlusTreeMap<i32, i32>,
+    at: i32,
+    total: i32,
+) {
+    assert!(tree.check_invariants());
+    assert!(upper.check_invariants());
+    assert!(tree.keys().copied().eq((0..total).filter(|k| *k < at)));
+    assert!(upper.keys().copied().eq((0..total).filter(|k| *k >= at)));
+    // The leaf chain is walked by items(); lengths must agree with it
+    assert_eq!(upper.items().count(), upper.len());
+    assert!(upper.items().all(|(k, v)| *v == k * 10));
+}
+
+#[test]
+fn test_split_off_middle_of_leaf() {
+    let mut tree = BPlusTreeMap::new(4).unwrap();
+    for i in 0..100 {
+        tree.insert(i, i * 10);
+    }
+    let leaf_start = tree.leaf_sizes()[0] as i32;
+
+    // One past a leaf boundary is guaranteed to be inside a leaf
+    let upper = tree.split_off(&(leaf_start + 1));
+    assert_split(&tree, &upper, leaf_start + 1, 100);
+}
+
+#[test]
+fn test_split_off_on_separator_key() {
+    let mut tree = BPlusTreeMap::new(4).unwrap();
+    for i in 0..100 {
+        tree.insert(i, i * 10);
+    }
+    // The first key of the second leaf is a separator in its parent
+    let separator = tree.leaf_sizes()[0] as i32;
+    let upper = tree.split_off(&separator);
+    assert_split(&tree, &upper, separator, 100);
+
+    // The returned tree keeps working as a normal tree
+    let mut upper = upper;
+    upper.insert(1000, 10000);
+    assert!(upper.check_invariants());
+    assert_eq!(upper.len(), (100 - separator) as usize + 1);
+}
+
+#[test]
+fn test_split_off_below_and_above_everything() {
+    let mut tree = BPlusTreeMap::new(5).unwrap();
+    for i in 0..60 {
+        tree.insert(i, i * 10);
+    }
+
+    let upper = tree.split_off(&-5);
+    assert!(tree.is_empty());
+    assert_split(&tree, &upper, -5, 60);
+    assert_eq!(upper.len(), 60);
+
+    let mut upper = upper;
+    let nothing = upper.split_off(&1000);
+    assert!(nothing.is_empty());
+    assert_eq!(upper.len(), 60);
+    assert!(upper.check_invariants());
+}
+
+// ============================================================================
+// APPEND TESTS
+// ============================================================================
+
+#[test]
+fn test_append_disjoint_ranges() {
+    let mut low: BPlusTreeMap<i32, i32> = (0..100).map(|i| (i, i)).collect();
+    let mut high: BPlusTreeMap<i32, i32> = (100..250).map(|i| (i, i)).collect();
+
+    low.append(&mut high);
+
+    assert!(high.is_empty());
+    assert!(high.check_invariants());
+    assert_eq!(low.len(), 250);
+    assert!(low.check_invariants());
+    assert!(low.keys().copied().eq(0..250));
+
+    // The emptied tree is reusable
+    high.insert(1, 1);
+    assert_eq!(high.len(), 1);
+}
+
+#[test]
+fn test_append_overlapping_ranges_other_wins() {
+    let mut tree = BPlusTreeMap::new(4).unwrap();
+    let mut other = BPlusTreeMap::new(6).unwrap();
+    for i in 0..100 {
+        tree.insert(i, "self");
+        other.insert(i * 2, "other");
+    }
+
+    tree.append(&mut other);
+
+    assert!(other.is_empty());
+    assert!(tree.check_invariants());
+    assert_eq!(tree.len(), 150);
+    for (k, v) in tree.items() {
+        let expected = if k % 2 == 0 { "other" } else { "self" };
+        assert_eq!(*v, expected, "key {}", k);
+    }
+}
+
+#[test]
+fn test_append_empty_trees() {
+    let mut tree: BPlusTreeMap<i32, i32> = (0..30).map(|i| (i, i)).collect();
+    let mut empty = BPlusTreeMap::new(4).unwrap();
+
+    tree.append(&mut empty);
+    assert_eq!(tree.len(), 30);
+    assert!(tree.check_invariants());
+
+    empty.append(&mut tree);
+    assert!(tree.is_empty());
+    assert_eq!(empty.len(), 30);
+    assert!(empty.check_invariants());
+    assert!(empty.keys().copied().eq(0..30));
+}
+
+// ============================================================================
+// RETAIN TESTS
+// ============================================================================
+
+#[test]
+fn test_retain_even_keys() {
+    let mut tree = BPlusTreeMap::new(4).unwrap();
+    for i in 0..100 {
+        tree.insert(i, i * 10);
+    }
+
+    tree.retain(|k, _| k % 2 == 0);
+
+    assert_eq!(tree.len(), 50);
+    assert!(tree.check_invariants());
+    assert!(tree.keys().copied().eq((0..100).step_by(2)));
+    assert_eq!(tree.get(&3), None);
+    assert_eq!(tree.get(&42), Some(&420));
+    assert_eq!(tree.items().count(), tree.len());
+}
+
+#[test]
+fn test_retain_heavy_removal_and_mutation() {
+    let mut tree = BPlusTreeMap::new(5).unwrap();
+    for i in 0..1000 {
+        tree.insert(i, i);
+    }
+
+    let mut visited = Vec::new();
+    tree.retain(|k, v| {
+        visited.push(*k);
+        *v += 1;
+        k % 97 == 0
+    });
+
+    // Each entry is visited exactly once, in order
+    assert!(visited.into_iter().eq(0..1000));
+    assert!(tree.check_invariants());
+    assert_eq!(tree.len(), 11);
+    assert!(tree.items().all(|(k, v)| *v == k + 1));
+
+    tree.retain(|_, _| false);
+    assert!(tree.is_empty());
+    assert!(tree.check_invariants());
+    tree.insert(5, 5);
+    assert_eq!(tree.len(), 1);
+}
+
+#[test]
+fn test_retain_keeping_everything_leaves_nodes_in_place() {
+    let mut tree = BPlusTreeMap::new(4).unwrap();
+    for i in 0..500 {
+        tree.insert(i, i);
+    }
+    let nodes_before: Vec<_> = tree.level_order().collect();
+
+    tree.retain(|_, _| true);
+
+    assert_eq!(tree.level_order().collect::<Vec<_>>(), nodes_before);
+    assert_eq!(tree.len(), 500);
+}
+
+#[test]
+fn test_retain_matches_btreemap_for_varied_patterns() {
+    let patterns: [fn(i32) -> bool; 6] = [
+        |k| !(200..700).contains(&k),
+        |k| k < 300,
+        |k| k >= 650,
+        |k| k % 7 == 0,
+        |k| (k * 37) % 11 < 3,
+        |k| k == 512,
+    ];
+
+    for capacity in [4, 5, 8] {
+        for keep in patterns {
+            let mut tree = BPlusTreeMap::new(capacity).unwrap();
+            let mut expected = std::collections::BTreeMap::new();
+            for i in 0..1000 {
+                tree.insert(i, i);
+                expected.insert(i, i);
+            }
+
+            tree.retain(|k, _| keep(*k));
+            expected.retain(|k, _| keep(*k));
+
+            tree.check_invariants_detailed().unwrap();
+            assert_eq!(tree.len(), expected.len());
+            assert!(tree.items().map(|(k, v)| (*k, *v)).eq(expected.clone()));
+            assert!(tree
+                .items()
+                .rev()
+                .map(|(k, _)| *k)
+                .eq(expected.keys().rev().copied()));
+        }
+    }
+}
+
+#[test]
+fn test_retain_range_only_filters_inside_window() {
+    let mut tree = BPlusTreeMap::new(4).unwrap();
+    for i in 0..40 {
+        tree.insert(i, i * 3);
+    }
+
+    let mut visited = Vec::new();
+    tree.retain_range(10..20, |k, v| {
+        visited.push(*k);
+        *v % 2 == 0
+    });
+
+    assert_eq!(visited, (10..20).collect::<Vec<_>>());
+    assert!(tree.check_invariants());
+    for i in 0..40 {
+        let inside = (10..20).contains(&i);
+        let expected = (!inside || (i * 3) % 2 == 0).then_some(i * 3);
+        assert_eq!(tree.get(&i).copied(), expected, "key {}", i);
+    }
+    assert_eq!(tree.len(), 35);
+
+    // Dropping everything in a window rebalances, and empty windows are no-ops
+    tree.retain_range(..=30, |_, _| false);
+    assert!(tree.check_invariants());
+    assert_eq!(
+        tree.keys().copied().collect::<Vec<_>>(),
+        (31..40).collect::<Vec<_>>()
+    );
+    tree.retain_range(100.., |_, _| false);
+    assert_eq!(tree.len(), 9);
+}
+
+// ============================================================================
+// KEY-VALUE LOOKUP TESTS
+// ============================================================================
+
+/// A key whose ordering only looks at `id`, ignoring `payload`.
+#[derive(Clone, Debug)]
+struct TaggedKey {
+    id: i32,
+    payload: String,
+}
+
+impl PartialEq for TaggedKey {
+    fn eq(&self, other: &Self) -> bool {
+        self.id == other.id
+    }
+}
+
+impl Eq for TaggedKey {}
+
+impl PartialOrd for TaggedKey {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for TaggedKey {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.id.cmp(&other.id)
+    }
+}
+
+#[test]
+fn test_get_key_value_returns_stored_key() {
+    let mut tree = BPlusTreeMap::new(4).unwrap();
+    for id in 0..50 {
+        let key = TaggedKey {
+            id,
+            payload: format!("stored_{}", id),
+        };
+        tree.insert(key, id * 10);
+    }
+
+    let probe = TaggedKey {
+        id: 37,
+        payload: "probe".to_string(),
+    };
+    let (key, value) = tree.get_key_value(&probe).unwrap();
+    assert_eq!(key.payload, "stored_37");
+    assert_eq!(*value, 370);
+
+    let missing = TaggedKey {
+        id: 99,
+        payload: String::new(),
+    };
+    assert!(tree.get_key_value(&missing).is_none());
+}
+
+// ============================================================================
+// POP FIRST/LAST TESTS
+// ============================================================================
+
+#[test]
+fn test_pop_first_drains_in_ascending_order() {
+    let mut tree = BPlusTreeMap::new(4).unwrap();
+    for i in (0..300).rev() {
+        tree.insert(i, i * 2);
+    }
+
+    let mut popped = Vec::new();
+    while let Some((k, v)) = tree.pop_first() {
+        assert_eq!(v, k * 2);
+        popped.push(k);
+        if popped.len() % 25 == 0 {
+            assert!(tree.check_invariants(), "after {} pops", popped.len());
+            assert_eq!(tree.len(), 300 - popped.len());
+        }
+    }
+
+    assert!(popped.into_iter().eq(0..300));
+    assert!(tree.is_empty());
+    assert!(tree.check_invariants());
+    assert_eq!(tree.pop_first(), None);
+}
+
+#[test]
+fn test_pop_last_drains_in_descending_order() {
+    let mut tree = BPlusTreeMap::new(5).unwrap();
+    for i in 0..300 {
+        tree.insert(i, i);
+    }
+
+    let mut popped = Vec::new();
+    while let Some((k, _)) = tree.pop_last() {
+        popped.push(k);
+        if popped.len() % 25 == 0 {
+            assert!(tree.check_invariants(), "after {} pops", popped.len());
+        }
+    }
+
+    assert!(popped.into_iter().eq((0..300).rev()));
+    assert!(tree.is_empty());
+    assert_eq!(tree.pop_last(), None);
+}
+
+#[test]
+fn test_pop_first_and_last_interleaved() {
+    let mut tree: BPlusTreeMap<i32, i32> = (0..101).map(|i| (i, i)).collect();
+
+    for i in 0..50 {
+        assert_eq!(tree.pop_first(), Some((i, i)));
+        assert_eq!(tree.pop_last(), Some((100 - i, 100 - i)));
+    }
+    assert_eq!(tree.len(), 1);
+    assert_eq!(tree.pop_last(), Some((50, 50)));
+    assert!(tree.check_invariants());
+}
+
+// ============================================================================
+// NEAREST KEY LOOKUP TESTS
+// ============================================================================
+
+#[test]
+fn test_floor_ceiling_predecessor_successor_with_gaps() {
+    let mut tree = BPlusTreeMap::new(4).unwrap();
+    for i in 0..100 {
+        tree.insert(i * 10, i);
+    }
+    let key_of = |entry: Option<(&i32, &i32)>| entry.map(|(k, _)| *k);
+
+    // Probe every value between and on the stored keys, across leaf boundaries
+    for probe in -5i32..1005 {
+        let below_or_eq = (probe >= 0).then(|| (probe.min(990) / 10) * 10);
+        let above_or_eq = (probe <= 990).then(|| ((probe.max(0) + 9) / 10) * 10);
+        let below = (probe > 0).then(|| ((probe.min(1000) - 1) / 10) * 10);
+        let above = (probe < 990).then(|| (probe.div_euclid(10) + 1) * 10);
+
+        assert_eq!(key_of(tree.floor(&probe)), below_or_eq, "floor({})", probe);
+        assert_eq!(
+            key_of(tree.ceiling(&probe)),
+            above_or_eq,
+            "ceiling({})",
+            probe
+        );
+        assert_eq!(
+            key_of(tree.predecessor(&probe)),
+            below,
+            "predecessor({})",
+            probe
+        );
+        assert_eq!(
+            key_of(tree.successor(&probe)),
+            above,
+            "successor({})",
+            probe
+        );
+    }
+
+    assert_eq!(tree.floor(&55), Some((&50, &5)));
+    assert_eq!(tree.ceiling(&55), Some((&60, &6)));
+}
+
+#[test]
+fn test_nearest_lookups_on_empty_and_single() {
+    let mut tree: BPlusTreeMap<i32, i32> = BPlusTreeMap::new(4).unwrap();
+    assert_eq!(tree.floor(&0), None);
+    assert_eq!(tree.ceiling(&0), None);
+    assert_eq!(tree.predecessor(&0), None);
+    assert_eq!(tree.successor(&0), None);
+
+    tree.insert(7, 70);
+    assert_eq!(tree.floor(&7), Some((&7, &70)));
+    assert_eq!(tree.ceiling(&7), Some((&7, &70)));
+    assert_eq!(tree.predecessor(&7), None);
+    assert_eq!(tree.successor(&7), None);
+    assert_eq!(tree.predecessor(&8), Some((&7, &70)));
+    assert_eq!(tree.successor(&6), Some((&7, &70)));
+}
+
+// ============================================================================
+// CLONE TESTS
+// ============================================================================
+
+#[test]
+fn test_clone_is_independent_deep_copy() {
+    let mut original = BPlusTreeMap::new(4).unwrap();
+    for i in 0..500 {
+        original.insert(i, format!("v{}", i));
+    }
+    assert!(tree_height(&original) > 2);
+
+    let mut copy = original.clone();
+    assert!(copy.check_invariants_detailed().is_ok());
+    assert!(copy.items().eq(original.items()));
+
+    for i in 0..250 {
+        copy.remove(&(i * 2));
+    }
+    for i in 0..20 {
+        copy.insert(1000 + i, "new".to_string());
+    }
+    if let Some(v) = copy.get_mut(&1) {
+        v.push_str("-changed");
+    }
+    assert!(copy.check_invariants_detailed().is_ok());
+
+    assert_eq!(original.len(), 500);
+    assert!(original.check_invariants_detailed().is_ok());
+    assert!(original.items().all(|(k, v)| *v == format!("v{}", k)));
+    assert_eq!(copy.len(), 270);
+    assert_eq!(copy.get(&1), Some(&"v1-changed".to_string()));
+}
+
+#[test]
+fn test_clone_empty_tree() {
+    let empty: BPlusTreeMap<i32, i32> = BPlusTreeMap::new(8).unwrap();
+    let mut copy = empty.clone();
+    assert!(copy.is_empty());
+    copy.insert(1, 1);
+    assert!(empty.is_empty());
+}
+
+#[test]
+fn test_clone_and_split_off_keep_auto_compact_threshold() {
+    let mut tree = create_tree_4_int();
+    tree.set_auto_compact_threshold(0.25);
+    let mut copy = tree.clone();
+    for i in 0..10_000 {
+        copy.insert(i, i);
+    }
+    let mut upper = copy.split_off(&5_000);
+
+    for i in 0..4_500 {
+        copy.remove(&i);
+    }
+    for i in 5_000..9_500 {
+        upper.remove(&i);
+    }
+    assert!(copy.fragmentation() <= 0.25);
+    assert!(upper.fragmentation() <= 0.25);
+}
+
+// ============================================================================
+// EQUALITY TESTS
+// ============================================================================
+
+#[test]
+fn test_equality_ignores_insertion_order_and_capacity() {
+    let mut ascending = BPlusTreeMap::new(4).unwrap();
+    let mut descending = BPlusTreeMap::new(16).unwrap();
+    let mut shuffled = BPlusTreeMap::new_with_capacities(7, 5).unwrap();
+    for i in 0..300 {
+        ascending.insert(i, i * 3);
+        descending.insert(299 - i, (299 - i) * 3);
+        shuffled.insert((i * 7) % 300, ((i * 7) % 300) * 3);
+    }
+
+    assert_eq!(ascending, descending);
+    assert_eq!(descending, shuffled);
+    assert_eq!(ascending, ascending.clone());
+
+    // Trees shaped differently by deletions are still equal by content
+    for i in 300..400 {
+        ascending.insert(i, 0);
+    }
+    for i in 300..400 {
+        ascending.remove(&i);
+    }
+    assert_eq!(ascending, shuffled);
+}
+
+#[test]
+fn test_inequality_on_values_keys_and_length() {
+    let base: BPlusTreeMap<i32, i32> = (0..50).map(|i| (i, i)).collect();
+
+    let mut different_value = base.clone();
+    different_value.insert(25, -1);
+    assert_ne!(base, different_value);
+
+    let mut missing_key = base.clone();
+    missing_key.remove(&49);
+    assert_ne!(base, missing_key);
+    assert_ne!(missing_key, base);
+
+    let mut different_key = missing_key.clone();
+    different_key.insert(1000, 49);
+    assert_ne!(base, different_key);
+
+    let empty_a: BPlusTreeMap<i32, i32> = BPlusTreeMap::new(4).unwrap();
+    let empty_b: BPlusTreeMap<i32, i32> = BPlusTreeMap::new(32).unwrap();
+    assert_eq!(empty_a, empty_b);
+}
+
+// ============================================================================
+// BULK LOAD TESTS
+// ============================================================================
+
+#[test]
+fn test_bulk_load_matches_inserts_with_denser_leaves() {
+    let count = 100_000;
+    let loaded = BPlusTreeMap::bulk_load(16, (0..count).map(|i| (i, i * 2))).unwrap();
+
+    let mut inserted = BPlusTreeMap::new(16).unwrap();
+    for i in 0..count {
+        inserted.insert(i, i * 2);
+    }
+
+    assert!(loaded.check_invariants_detailed().is_ok());
+    assert_eq!(loaded.len(), count as usize);
+    assert_eq!(loaded, inserted);
+    assert!(loaded.items().all(|(k, v)| *v == k * 2));
+
+    // Sequential inserts leave half-full leaves behind every split
+    assert!(
+        loaded.leaf_count() < inserted.leaf_count(),
+        "bulk load used {} leaves, inserts used {}",
+        loaded.leaf_count(),
+        inserted.leaf_count()
+    );
+}
+
+#[test]
+fn test_bulk_load_rejects_unsorted_and_duplicate_input() {
+    let out_of_order = BPlusTreeMap::bulk_load(4, vec![(1, 'a'), (3, 'c'), (2, 'b')]);
+    assert!(matches!(
+        out_of_order,
+        Err(BPlusTreeError::DataIntegrityError(_))
+    ));
+
+    let duplicate = BPlusTreeMap::bulk_load(4, vec![(1, 'a'), (1, 'b')]);
+    assert!(matches!(
+        duplicate,
+        Err(BPlusTreeError::DataIntegrityError(_))
+    ));
+
+    let bad_capacity = BPlusTreeMap::bulk_load(2, vec![(1, 'a')]);
+    assert!(matches!(
+        bad_capacity,
+        Err(BPlusTreeError::InvalidCapacity(_))
+    ));
+
+    let empty = BPlusTreeMap::<i32, i32>::bulk_load(4, Vec::new()).unwrap();
+    assert!(empty.is_empty());
+    assert!(empty.check_invariants());
+}
+
+/// Value type that fails the test if it is ever cloned.
+#[derive(Debug, PartialEq)]
+struct NoClone(u32);
+
+impl Clone for NoClone {
+    fn clone(&self) -> Self {
+        panic!("value {} was cloned", self.0)
+    }
+}
+
+#[test]
+fn test_from_sorted_vec_moves_values() {
+    let pairs: Vec<_> = (0..1000).map(|i| (i, NoClone(i))).collect();
+    let tree = BPlusTreeMap::from_sorted_vec(8, pairs).unwrap();
+
+    assert_eq!(tree.len(), 1000);
+    assert!(tree.check_invariants());
+    assert_eq!(tree.get(&0), Some(&NoClone(0)));
+    assert_eq!(tree.get(&999), Some(&NoClone(999)));
+    assert!(tree.keys().copied().eq(0..1000));
+}
+
+#[test]
+fn test_from_sorted_vec_rejects_unsorted_input() {
+    let unsorted = BPlusTreeMap::from_sorted_vec(4, vec![(1, 'a'), (3, 'c'), (2, 'b')]);
+    match unsorted {
+        Err(BPlusTreeError::DataIntegrityError(message)) => assert!(message.contains("index 2")),
+        other => panic!("expected DataIntegrityError, got {:?}", other),
+    }
+
+    let duplicate = BPlusTreeMap::from_sorted_vec(4, vec![(1, 'a'), (1, 'b')]);
+    assert!(matches!(
+        duplicate,
+        Err(BPlusTreeError::DataIntegrityError(_))
+    ));
+
+    let empty = BPlusTreeMap::<i32, i32>::from_sorted_vec(4, Vec::new()).unwrap();
+    assert!(empty.is_empty());
+}
+
+// ============================================================================
+// RANGE COUNT TESTS
+// ============================================================================
+
+#[test]
+fn test_range_count_matches_range_iteration() {
+    use std::ops::Bound::{self, Excluded, Included, Unbounded};
+
+    let mut tree = BPlusTreeMap::new(4).unwrap();
+    for i in 0..200 {
+        tree.insert(i * 3, i);
+    }
+
+    let probes = [-10, 0, 1, 3, 44, 45, 46, 300, 597, 598, 1000];
+    for &start in &probes {
+        for &end in &probes {
+            let bounds: [(Bound<i32>, Bound<i32>); 6] = [
+                (Included(start), Included(end)),
+                (Included(start), Excluded(end)),
+                (Excluded(start), Included(end)),
+                (Excluded(start), Excluded(end)),
+                (Included(start), Unbounded),
+                (Unbounded, Excluded(end)),
+            ];
+            for range in bounds {
+                if start > end && !matches!(range, (Unbounded, _) | (_, Unbounded)) {
+                    continue;
+                }
+                assert_eq!(
+                    tree.range_count(range),
+                    tree.range(range).count(),
+                    "{:?}",
+                    range
+                );
+            }
+        }
+    }
+
+    assert_eq!(tree.range_count(..), 200);
+    let empty: BPlusTreeMap<i32, i32> = BPlusTreeMap::new(4).unwrap();
+    assert_eq!(empty.range_count(..), 0);
+}
+
+// ============================================================================
+// ORDER STATISTIC TESTS
+// ============================================================================
+
+#[test]
+fn test_select_rank_round_trip() {
+    let mut tree = BPlusTreeMap::new(4).unwrap();
+    for i in 0..1000 {
+        tree.insert((i * 7919) % 1000 * 2, i);
+    }
+    assert!(tree.check_invariants_detailed().is_ok());
+
+    for (position, (key, value)) in tree.items().enumerate() {
+        assert_eq!(tree.rank(key), position);
+        assert_eq!(tree.select(position), Some((key, value)));
+        assert_eq!(tree.select(tree.rank(key)), tree.get_key_value(key));
+    }
+    assert_eq!(tree.select(1000), None);
+
+    // Absent keys rank between their neighbours
+    assert_eq!(tree.rank(&-1), 0);
+    assert_eq!(tree.rank(&1), 1);
+    assert_eq!(tree.rank(&999), 500);
+    assert_eq!(tree.rank(&5000), 1000);
+}
+
+#[test]
+fn test_order_statistics_survive_rebalancing() {
+    let mut tree = BPlusTreeMap::new(4).unwrap();
+    let mut reference = std::collections::BTreeMap::new();
+    for i in 0..2000 {
+        let key = (i * 37) % 1500;
+        tree.insert(key, i);
+        reference.insert(key, i);
+    }
+    // Deletions drive borrows and merges at every level
+    for i in 0..1200 {
+        let key = (i * 53) % 1500;
+        assert_eq!(tree.remove(&key), reference.remove(&key));
+        if i % 100 == 0 {
+            assert!(
+                tree.check_invariants_detailed().is_ok(),
+                "after {} removals",
+                i
+            );
+        }
+    }
+
+    assert!(tree.check_invariants_detailed().is_ok());
+    for (position, (key, value)) in reference.iter().enumerate() {
+        assert_eq!(tree.select(position), Some((key, value)));
+        assert_eq!(tree.rank(key), position);
+    }
+    assert_eq!(tree.select(reference.len()), None);
+
+    let empty: BPlusTreeMap<i32, i32> = BPlusTreeMap::new(4).unwrap();
+    assert_eq!(empty.select(0), None);
+    assert_eq!(empty.rank(&10), 0);
+}
+
+// ============================================================================
+// REMOVE RANGE TESTS
+// ============================================================================
+
+fn remove_range_case<R>(range: R, expected_removed: std::ops::Range<i32>)
+where
+    R: std::ops::RangeBounds<i32>,
+{
+    let mut tree = BPlusTreeMap::new(4).unwrap();
+    for i in 0..200 {
+        tree.insert(i, i * 10);
+    }
+
+    let removed = tree.remove_range(range);
+
+    let expected: Vec<(i32, i32)> = expected_removed.clone().map(|i| (i, i * 10)).collect();
+    assert_eq!(removed, expected);
+    assert!(tree.check_invariants_detailed().is_ok());
+    assert_eq!(tree.len(), 200 - expected.len());
+    assert!(tree
+        .keys()
+        .copied()
+        .eq((0..200).filter(|k| !expected_removed.contains(k))));
+}
+
+#[test]
+fn test_remove_range_middle() {
+    remove_range_case(90..110, 90..110);
+    remove_range_case(20..=179, 20..180);
+    remove_range_case(
+        (std::ops::Bound::Excluded(49), std::ops::Bound::Included(60)),
+        50..61,
+    );
+}
+
+#[test]
+fn test_remove_range_prefix_and_suffix() {
+    remove_range_case(..30, 0..30);
+    remove_range_case(..=150, 0..151);
+    remove_range_case(170.., 170..200);
+    remove_range_case(10.., 10..200);
+}
+
+#[test]
+fn test_remove_range_whole_tree_and_empty_ranges() {
+    remove_range_case(.., 0..200);
+    remove_range_case(-50..=1000, 0..200);
+    remove_range_case(500..600, 0..0);
+
+    let mut tree: BPlusTreeMap<i32, i32> = (0..100).map(|i| (i, i)).collect();
+    tree.remove_range(..);
+    assert!(tree.is_empty());
+    assert!(tree.is_leaf_root());
+    assert!(tree.check_invariants_detailed().is_ok());
+    tree.insert(1, 1);
+    assert_eq!(tree.len(), 1);
+}
+
+// ============================================================================
+// DRAIN TESTS
+// ============================================================================
+
+#[test]
+fn test_drain_full_consumption() {
+    let mut tree = BPlusTreeMap::new(4).unwrap();
+    for i in (0..300).rev() {
+        tree.insert(i, format!("v{}", i));
+    }
+
+    let drained: Vec<(i32, String)> = tree.drain().collect();
+
+    let expected: Vec<(i32, String)> = (0..300).map(|i| (i, format!("v{}", i))).collect();
+    assert_eq!(drained, expected);
+    assert!(tree.is_empty());
+    assert!(tree.is_leaf_root());
+    assert!(tree.check_invariants_detailed().is_ok());
+
+    // The tree is reusable afterwards
+    tree.insert(5, "again".to_string());
+    assert_eq!(tree.len(), 1);
+    assert!(tree.check_invariants_detailed().is_ok());
+}
+
+#[test]
+fn test_drain_early_drop_leaves_tree_empty() {
+    let mut tree = BPlusTreeMap::new(5).unwrap();
+    for i in 0..200 {
+        tree.insert(i, i);
+    }
+
+    {
+        let mut drain = tree.drain();
+        assert_eq!(drain.next(), Some((0, 0)));
+        assert_eq!(drain.next(), Some((1, 1)));
+    }
+    assert!(tree.is_empty());
+    assert!(tree.check_invariants_detailed().is_ok());
+
+    for i in 0..50 {
+        tree.insert(i, i);
+    }
+    drop(tree.drain());
+    assert!(tree.is_empty());
+    assert!(tree.check_invariants_detailed().is_ok());
+
+    // Leaking the iterator still leaves a valid, empty tree
+    for i in 0..50 {
+        tree.insert(i, i);
+    }
+    std::mem::forget(tree.drain());
+    assert!(tree.is_empty());
+    assert!(tree.check_invariants_detailed().is_ok());
+}
+
+// ============================================================================
+// LENGTH TESTS
+// ============================================================================
+
+#[test]
+fn test_len_tracks_mixed_operations() {
+    let mut tree = BPlusTreeMap::new(4).unwrap();
+    let mut reference = std::collections::BTreeMap::new();
+
+    for i in 0..3000 {
+        let key = (i * 7) % 1000;
+        match i % 5 {
+            0..=2 => {
+                assert_eq!(tree.insert(key, i), reference.insert(key, i));
+            }
+            3 => {
+                assert_eq!(tree.remove(&key), reference.remove(&key));
+            }
+            _ => {
+                // Overwrites must not change the count
+                if let Some(v) = tree.get_mut(&key) {
+                    *v += 1;
+                }
+                if let Some(v) = reference.get_mut(&key) {
+                    *v += 1;
+                }
+            }
+        }
+        assert_eq!(tree.len(), reference.len(), "after op {}", i);
+    }
+    assert_eq!(tree.len(), tree.items().count());
+
+    tree.pop_first();
+    tree.pop_last();
+    tree.remove_range(100..200);
+    tree.retain(|k, _| k % 2 == 0);
+    assert_eq!(tree.len(), tree.items().count());
+
+    tree.clear();
+    assert_eq!(tree.len(), 0);
+}
+
+#[test]
+fn test_len_does_not_scale_with_tree_size() {
+    for size in [10, 10_000, 200_000] {
+        let mut tree = BPlusTreeMap::new(8).unwrap();
+        for i in 0..size {
+            tree.insert(i, ());
+        }
+        tree.enable_counters();
+        assert_eq!(tree.len(), size as usize);
+        // The count comes from the root alone, however many nodes there are
+        assert_eq!(tree.operation_counters().node_visits, 0);
+    }
+}
+
+// ============================================================================
+// VALUE SCAN TESTS
+// ============================================================================
+
+#[test]
+fn test_find_keys_by_value_across_leaves() {
+    let mut tree = BPlusTreeMap::new(4).unwrap();
+    for i in (0..200).rev() {
+        tree.insert(i, i % 7);
+    }
+
+    let matches: Vec<i32> = tree.find_keys_by_value(&3).into_iter().copied().collect();
+    assert_eq!(matches, (0..200).filter(|k| k % 7 == 3).collect::<Vec<_>>());
+    // Matches are spread over many leaves
+    assert!(matches.len() > 4 * 4);
+
+    assert!(tree.contains_value(&6));
+    assert!(!tree.contains_value(&7));
+    assert!(tree.find_keys_by_value(&7).is_empty());
+
+    let empty: BPlusTreeMap<i32, i32> = BPlusTreeMap::new(4).unwrap();
+    assert!(!empty.contains_value(&0));
+}
+
+// ============================================================================
+// CAPACITY TESTS
+// ============================================================================
+
+#[test]
+fn test_capacity_getter() {
+    assert_eq!(BPlusTreeMap::<i32, i32>::new(32).unwrap().capacity(), 32);
+
+    let tree = BPlusTreeMap::<i32, i32>::new_with_capacities(64, 8).unwrap();
+    assert_eq!(tree.capacity(), 64);
+    assert_eq!(tree.branch_capacity(), 8);
+
+    let mut source = BPlusTreeMap::new(6).unwrap();
+    for i in 0..50 {
+        source.insert(i, i);
+    }
+    let tail = source.split_off(&25);
+    assert_eq!(tail.capacity(), source.capacity());
+}
+
+// ============================================================================
+// MEMORY STATS TESTS
+// ============================================================================
+
+#[test]
+fn test_memory_stats_estimate_is_bounded() {
+    let mut tree = BPlusTreeMap::new(16).unwrap();
+    let n = 10_000u64;
+    for i in 0..n {
+        tree.insert(i, i);
+    }
+
+    let stats = tree.memory_stats();
+    assert_eq!(stats.leaf_nodes, tree.leaf_count());
+    assert_eq!(stats.leaf_nodes, tree.leaf_arena_stats().allocated_count);
+    assert_eq!(
+        stats.branch_nodes,
+        tree.branch_arena_stats().allocated_count
+    );
+
+    // At least the raw entries, and no more than a small multiple of them
+    // given leaves are at least half full.
+    let payload = n as usize * 2 * std::mem::size_of::<u64>();
+    assert!(stats.estimated_bytes >= payload);
+    assert!(
+        stats.estimated_bytes <= payload * 8,
+        "estimate {} too large for payload {}",
+        stats.estimated_bytes,
+        payload
+    );
+
+    // Removing entries frees slots but keeps them in the estimate.
+    for i in 0..n / 2 {
+        tree.remove(&i);
+    }
+    let after = tree.memory_stats();
+    assert!(after.free_leaf_slots > 0);
+    assert!(after.leaf_nodes < stats.leaf_nodes);
+}
+
+// ============================================================================
+// COMPACTION TESTS
+// ============================================================================
+
+#[test]
+fn test_compact_reclaims_free_slots() {
+    let mut tree = BPlusTreeMap::new(8).unwrap();
+    for i in 0..10_000 {
+        tree.insert(i, i * 2);
+    }
+    for i in 0..10_000 {
+        if i % 10 != 0 {
+            tree.remove(&i);
+        }
+    }
+    let leaf_slots_before = tree.leaf_arena_stats().total_capacity;
+    let branch_slots_before = tree.branch_arena_stats().total_capacity;
+    assert!(tree.free_leaf_count() > 0);
+
+    tree.compact();
+
+    assert_eq!(tree.free_leaf_count(), 0);
+    assert_eq!(tree.free_branch_count(), 0);
+    assert!(tree.leaf_arena_stats().total_capacity * 4 < leaf_slots_before);
+    assert!(tree.branch_arena_stats().total_capacity * 4 < branch_slots_before);
+    tree.check_invariants_detailed().unwrap();
+
+    assert_eq!(tree.len(), 1_000);
+    for i in (0..10_000).step_by(10) {
+        assert_eq!(tree.get(&i), Some(&(i * 2)));
+    }
+    let keys: Vec<i32> = tree.keys().copied().collect();
+    assert_eq!(keys, (0..10_000).step_by(10).collect::<Vec<_>>());
+
+    // The compacted tree keeps working normally.
+    tree.insert(5, 10);
+    tree.remove(&0);
+    tree.check_invariants_detailed().unwrap();
+    assert_eq!(tree.first(), Some((&5, &10)));
+}
+
+// ============================================================================
+// NON-DEBUG TYPE TESTS
+// ============================================================================
+
+/// Key and value types that deliberately do not implement `Debug`.
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord)]
+struct OpaqueKey(u32);
+
+#[derive(Clone, PartialEq)]
+struct OpaqueValue(String);
+
+#[test]
+fn test_core_api_without_debug_bounds() {
+    let mut tree = BPlusTreeMap::new(4).unwrap();
+    for i in 0..50 {
+        assert!(tree
+            .insert(OpaqueKey(i), OpaqueValue(i.to_string()))
+            .is_none());
+    }
+
+    assert!(tree.get(&OpaqueKey(7)) == Some(&OpaqueValue("7".into())));
+    if let Some(value) = tree.get_mut(&OpaqueKey(8)) {
+        value.0.push('!');
+    }
+    assert!(tree.remove(&OpaqueKey(9)).is_some());
+    assert!(!tree.contains_key(&OpaqueKey(9)));
+
+    assert_eq!(tree.items().count(), 49);
+    assert_eq!(tree.keys().next_back().map(|k| k.0), Some(49));
+    assert_eq!(tree.values().filter(|v| v.0.ends_with('!')).count(), 1);
+    assert_eq!(tree.range(OpaqueKey(10)..OpaqueKey(20)).count(), 10);
+    for (_, value) in tree.range_mut(OpaqueKey(40)..) {
+        value.0.clear();
+    }
+    assert_eq!(
+        tree.find_keys_by_value(&OpaqueValue(String::new())).len(),
+        10
+    );
+
+    let copy = tree.clone();
+    assert!(copy == tree);
+    assert_eq!(tree.into_iter().count(), 49);
+}
+
+// ============================================================================
+// CUSTOM COMPARATOR TESTS
+// ============================================================================
+
+#[test]
+fn test_reverse_comparator_iterates_descending() {
+    use std::ops::Bound::{Excluded, Included};
+
+    let mut tree = BPlusTreeMap::with_comparator(4, |a: &i32, b: &i32| b.cmp(a)).unwrap();
+    for i in 0..500 {
+        tree.insert((i * 37) % 500, i);
+    }
+    tree.check_invariants_detailed().unwrap();
+
+    let keys: Vec<i32> = tree.keys().copied().collect();
+    assert_eq!(keys, (0..500).rev().collect::<Vec<_>>());
+    assert_eq!(tree.first().map(|(k, _)| *k), Some(499));
+    assert_eq!(tree.keys().next_back(), Some(&0));
+
+    // Ranges run from the larger key down to the smaller one
+    let range: Vec<i32> = tree
+        .range((Included(100), Included(95)))
+        .map(|(k, _)| *k)
+        .collect();
+    assert_eq!(range, vec![100, 99, 98, 97, 96, 95]);
+    assert_eq!(tree.range_count((Included(100), Excluded(95))), 5);
+    assert_eq!(tree.floor(&-1), Some((&0, tree.get(&0).unwrap())));
+    assert_eq!(tree.rank(&490), 9);
+
+    for (_, value) in tree.range_mut((Included(10), Included(1))) {
+        *value = -1;
+    }
+    assert_eq!(tree.values().filter(|v| **v == -1).count(), 10);
+
+    for i in (0..500).step_by(2) {
+        assert!(tree.remove(&i).is_some());
+    }
+    tree.check_invariants_detailed().unwrap();
+    let keys: Vec<i32> = tree.keys().copied().collect();
+    assert_eq!(keys, (1..500).step_by(2).rev().collect::<Vec<_>>());
+
+    // Derived trees keep the comparator
+    let copy = tree.clone();
+    assert_eq!(copy.keys().next(), Some(&499));
+    let lower = tree.split_off(&250);
+    assert_eq!(tree.keys().next_back(), Some(&251));
+    assert_eq!(lower.keys().next(), Some(&249));
+    let mut lower = lower;
+    lower.insert(1000, 0);
+    lower.insert(-5, 0);
+    assert_eq!(lower.keys().next(), Some(&1000));
+    assert_eq!(lower.keys().next_back(), Some(&-5));
+    lower.check_invariants_detailed().unwrap();
+}
+
+#[test]
+fn test_case_insensitive_comparator() {
+    let mut tree = BPlusTreeMap::with_comparator(4, |a: &String, b: &String| {
+        a.to_lowercase().cmp(&b.to_lowercase())
+    })
+    .unwrap();
+
+    tree.insert("Banana".to_string(), 1);
+    tree.insert("apple".to_string(), 2);
+    tree.insert("Cherry".to_string(), 3);
+    assert_eq!(tree.insert("APPLE".to_string(), 4), Some(2));
+
+    assert_eq!(tree.len(), 3);
+    assert_eq!(tree.get(&"BANANA".to_string()), Some(&1));
+    let keys: Vec<&str> = tree.keys().map(String::as_str).collect();
+    assert_eq!(keys, vec!["apple", "Banana", "Cherry"]);
+    assert_eq!(tree.remove(&"cherry".to_string()), Some(3));
+    assert!(!tree.contains_key(&"CHERRY".to_string()));
+}
+
+// ============================================================================
+// GET MANY MUT TESTS
+// ============================================================================
+
+#[test]
+fn test_get_many_mut_disjoint_keys() {
+    let mut tree = BPlusTreeMap::new(4).unwrap();
+    for i in 0..100 {
+        tree.insert(i, i);
+    }
+
+    // Keys in the same leaf and in different leaves, in any order
+    let [a, b, c] = tree.get_many_mut([&90, &0, &1]).unwrap();
+    std::mem::swap(a, b);
+    *c += 1000;
+    assert_eq!(tree.get(&0), Some(&90));
+    assert_eq!(tree.get(&90), Some(&0));
+    assert_eq!(tree.get(&1), Some(&1001));
+
+    let [] = tree.get_many_mut([]).unwrap();
+    tree.check_invariants_detailed().unwrap();
+}
+
+#[test]
+fn test_get_many_mut_rejects_duplicates_and_missing() {
+    let mut tree = BPlusTreeMap::new(4).unwrap();
+    for i in 0..100 {
+        tree.insert(i, i);
+    }
+
+    assert!(tree.get_many_mut([&5, &6, &5]).is_none());
+    assert!(tree.get_many_mut([&5, &500]).is_none());
+    assert!(tree.get_many_mut([&-1]).is_none());
+
+    // Nothing was modified by the rejected calls
+    assert_eq!(tree.get(&5), Some(&5));
+    assert_eq!(tree.len(), 100);
+}
+
+// ============================================================================
+// LEAF CHUNK ITERATOR TESTS
+// ============================================================================
+
+#[test]
+fn test_leaves_concatenate_to_keys() {
+    let mut tree = BPlusTreeMap::new(5).unwrap();
+    assert_eq!(tree.leaves().count(), 0);
+
+    for i in 0..300 {
+        tree.insert((i * 13) % 300, i);
+    }
+    for i in (0..300).step_by(7) {
+        tree.remove(&i);
+    }
+
+    let chunks: Vec<(&[i32], &[i32])> = tree.leaves().collect();
+    assert_eq!(chunks.len(), tree.leaf_count());
+    assert!(chunks
+        .iter()
+        .all(|(keys, values)| keys.len() == values.len()));
+
+    let keys: Vec<i32> = chunks
+        .iter()
+        .flat_map(|(keys, _)| keys.iter().copied())
+        .collect();
+    assert_eq!(keys, tree.keys().copied().collect::<Vec<_>>());
+    let values: Vec<i32> = chunks
+        .iter()
+        .flat_map(|(_, values)| values.iter().copied())
+        .collect();
+    assert_eq!(values, tree.values().copied().collect::<Vec<_>>());
+}
+
+// ============================================================================
+// BATCH GET TESTS
+// ============================================================================
+
+#[test]
+fn test_get_batch_aligns_with_input() {
+    let mut tree = BPlusTreeMap::new(4).unwrap();
+    for i in (0..100).step_by(3) {
+        tree.insert(i, i * 10);
+    }
+
+    let keys = vec![99, 1, 0, 50, 51, 200, 3, 3];
+    let results = tree.get_batch(&keys);
+    assert_eq!(results.len(), keys.len());
+    for (key, result) in keys.iter().zip(&results) {
+        assert_eq!(*result, tree.get(key), "mismatch for key {}", key);
+    }
+    assert_eq!(
+        results,
+        vec![
+            Some(&990),
+            None,
+            Some(&0),
+            None,
+            Some(&510),
+            None,
+            Some(&30),
+            Some(&30)
+        ]
+    );
+    assert!(tree.get_batch(&[]).is_empty());
+}
+
+// ============================================================================
+// HEIGHT TESTS
+// ============================================================================
+
+#[test]
+fn test_height_matches_hand_computed_levels() {
+    // Bulk loading packs leaves with 4 keys and branches with 5 children:
+    // 100 entries -> 25 leaves -> 5 branches -> 1 root
+    let tree = BPlusTreeMap::bulk_load(4, (0..100).map(|i| (i, i))).unwrap();
+    assert_eq!(tree.leaf_count(), 25);
+    assert_eq!(tree.height(), 3);
+
+    // 101 entries -> 26 leaves -> 6 branches -> 2 branches -> 1 root
+    let tree = BPlusTreeMap::bulk_load(4, (0..101).map(|i| (i, i))).unwrap();
+    assert_eq!(tree.height(), 4);
+    assert_eq!(tree.height() as u64, tree_height(&tree));
+
+    let mut tree = BPlusTreeMap::new(4).unwrap();
+    assert_eq!(tree.height(), 1);
+    for i in 0..1000 {
+        tree.insert(i, i);
+        assert_eq!(tree.height() as u64, tree_height(&tree));
+    }
+    while let Some((key, _)) = tree.pop_first() {
+        assert_eq!(
+            tree.height() as u64,
+            tree_height(&tree),
+            "after popping {}",
+            key
+        );
+    }
+    assert_eq!(tree.height(), 1);
+}
+
+// ============================================================================
+// INSERT IF ABSENT TESTS
+// ============================================================================
+
+#[test]
+fn test_insert_if_absent_first_writer_wins() {
+    let mut tree = BPlusTreeMap::new(4).unwrap();
+    for i in 0..100 {
+        assert_eq!(tree.insert_if_absent(i, format!("first {}", i)), Ok(()));
+    }
+    tree.check_invariants_detailed().unwrap();
+
+    for i in 0..100 {
+        let second = format!("second {}", i);
+        assert_eq!(tree.insert_if_absent(i, second.clone()), Err((i, second)));
+    }
+    assert_eq!(tree.len(), 100);
+    assert!(tree.items().all(|(k, v)| *v == format!("first {}", k)));
+
+    // Counters show a collision does no structural work
+    tree.enable_counters();
+    assert!(tree.insert_if_absent(50, String::new()).is_err());
+    assert_eq!(tree.operation_counters().splits, 0);
+    assert_eq!(tree.get(&50).map(String::as_str), Some("first 50"));
+}
+
+// ============================================================================
+// MERGE WITH TESTS
+// ============================================================================
+
+#[test]
+fn test_merge_with_accumulates_repeated_keys() {
+    let mut tree = BPlusTreeMap::new(4).unwrap();
+    let mut expected = std::collections::BTreeMap::new();
+    for i in 0..2000u64 {
+        let key = (i * 7919) % 97;
+        tree.merge_with(key, i, |total, value| *total += value);
+        *expected.entry(key).or_insert(0) += i;
+    }
+    tree.check_invariants_detailed().unwrap();
+
+    assert_eq!(tree.len(), expected.len());
+    for (key, total) in &expected {
+        assert_eq!(tree.get(key), Some(total));
+    }
+
+    // Merging into an existing key touches one node per level
+    tree.enable_counters();
+    tree.merge_with(5, 1, |total, value| *total += value);
+    assert_eq!(tree.operation_counters().node_visits, tree.height() as u64);
+    assert_eq!(tree.get(&5), Some(&(expected[&5] + 1)));
+}
+
+// ============================================================================
+// DEBUG FORMAT TESTS
+// ============================================================================
+
+#[test]
+fn test_debug_formats_entries_as_map() {
+    let mut tree = BPlusTreeMap::new(4).unwrap();
+    for key in [3, 1, 2, 5, 4] {
+        tree.insert(key, format!("v{}", key));
+    }
+    assert_eq!(
+        format!("{:?}", tree),
+        r#"{1: "v1", 2: "v2", 3: "v3", 4: "v4", 5: "v5"}"#
+    );
+
+    let empty: BPlusTreeMap<i32, i32> = BPlusTreeMap::new(4).unwrap();
+    assert_eq!(format!("{:?}", empty), "{}");
+}
+
+// ============================================================================
+// MIN/MAX KEY TESTS
+// ============================================================================
+
+#[test]
+fn test_min_and_max_key() {
+    let mut tree = BPlusTreeMap::new(4).unwrap();
+    assert_eq!(tree.min_key(), None);
+    assert_eq!(tree.max_key(), None);
+    assert_eq!(tree.last(), None);
+
+    for key in [50, 10, 90, 30, 70] {
+        tree.insert(key, key * 2);
+    }
+    assert_eq!(tree.min_key(), Some(&10));
+    assert_eq!(tree.max_key(), Some(&90));
+    assert_eq!(tree.first(), Some((&10, &20)));
+    assert_eq!(tree.last(), Some((&90, &180)));
+
+    tree.remove(&90);
+    tree.remove(&10);
+    assert_eq!(tree.min_key(), Some(&30));
+    assert_eq!(tree.max_key(), Some(&70));
+}
+
+#[test]
+fn test_last_descends_instead_of_walking_leaves() {
+    let mut tree = BPlusTreeMap::bulk_load(4, (0..10_000).map(|i| (i, i))).unwrap();
+    tree.enable_counters();
+    assert_eq!(tree.last(), Some((&9_999, &9_999)));
+    assert_eq!(tree.operation_counters().node_visits, tree.height() as u64);
+
+    tree.reset_counters();
+    assert_eq!(tree.first(), Some((&0, &0)));
+    assert_eq!(tree.operation_counters().node_visits, tree.height() as u64);
+}
+
+// ============================================================================
+// REBUILD TESTS
+// ============================================================================
+
+#[test]
+fn test_rebuild_packs_sparse_leaves() {
+    let capacity = 8;
+    let mut tree = BPlusTreeMap::new(capacity).unwrap();
+    for i in 0..1000 {
+        tree.insert(i, i * 3);
+    }
+    for i in (0..1000).step_by(2) {
+        tree.remove(&i);
+    }
+
+    let sparse = tree.leaf_sizes();
+    let sparse_average = 500.0 / sparse.len() as f64;
+    assert!(sparse_average < capacity as f64 * 0.8);
+
+    let height_before = tree.height();
+    tree.rebuild();
+    tree.check_invariants_detailed().unwrap();
+
+    let packed = tree.leaf_sizes();
+    assert_eq!(packed.iter().sum::<usize>(), 500);
+    assert_eq!(packed.len(), 500usize.div_ceil(capacity));
+    // Only the last two leaves may share a remainder
+    assert!(packed[..packed.len() - 2].iter().all(|&n| n == capacity));
+    assert!(tree.height() <= height_before);
+
+    let expected: Vec<(i32, i32)> = (1..1000).step_by(2).map(|i| (i, i * 3)).collect();
+    let actual: Vec<(i32, i32)> = tree.items().map(|(k, v)| (*k, *v)).collect();
+    assert_eq!(actual, expected);
+}
+
+#[test]
+fn test_rebuild_empty_and_single_leaf_trees() {
+    let mut tree: BPlusTreeMap<i32, i32> = BPlusTreeMap::new(4).unwrap();
+    tree.rebuild();
+    assert!(tree.is_empty());
+    tree.check_invariants_detailed().unwrap();
+
+    tree.insert(1, 1);
+    tree.rebuild();
+    assert_eq!(tree.get(&1), Some(&1));
+    assert!(tree.is_leaf_root());
+}
+
+#[test]
+fn test_rebuild_with_capacity_up_and_down() {
+    let mut tree = BPlusTreeMap::new(16).unwrap();
+    for i in 0..500 {
+        tree.insert(i, format!("v{}", i));
+    }
+    let expected: Vec<(i32, String)> = tree.items().map(|(k, v)| (*k, v.clone())).collect();
+
+    for capacity in [4, 128, 5, 16] {
+        tree.rebuild_with_capacity(capacity).unwrap();
+        assert_eq!(tree.capacity(), capacity);
+        assert_eq!(tree.branch_capacity(), capacity);
+        tree.check_invariants_detailed().unwrap();
+        assert!(tree.leaf_sizes().iter().all(|&n| n <= capacity));
+        let actual: Vec<(i32, String)> = tree.items().map(|(k, v)| (*k, v.clone())).collect();
+        assert_eq!(actual, expected);
+    }
+
+    // Inserts and removes keep working at the new capacity
+    tree.rebuild_with_capacity(4).unwrap();
+    tree.insert(1000, "v1000".to_string());
+    assert_eq!(tree.remove(&0), Some("v0".to_string()));
+    tree.check_invariants_detailed().unwrap();
+}
+
+#[test]
+fn test_rebuild_with_invalid_capacity_leaves_tree_untouched() {
+    use bplustree::MAX_CAPACITY;
+
+    let mut tree = BPlusTreeMap::new(8).unwrap();
+    for i in 0..50 {
+        tree.insert(i, i);
+    }
+    assert!(tree.rebuild_with_capacity(3).is_err());
+    assert!(tree.rebuild_with_capacity(MAX_CAPACITY + 1).is_err());
+    assert_eq!(tree.capacity(), 8);
+    assert_eq!(tree.len(), 50);
+    tree.check_invariants_detailed().unwrap();
+}
+
+// ============================================================================
+// TRANSACTION TESTS
+// ============================================================================
+
+#[test]
+fn test_transaction_rolls_back_on_error() {
+    let mut tree = BPlusTreeMap::new(4).unwrap();
+    for i in 0..20 {
+        tree.insert(i, format!("v{}", i));
+    }
+    let before: Vec<(i32, String)> = tree.items().map(|(k, v)| (*k, v.clone())).collect();
+
+    // Enough inserts to force splits, plus overwrites and removals that merge
+    let result: Result<(), &str> = tree.transaction(|txn| {
+        for i in 100..150 {
+            txn.insert(i, format!("new{}", i));
+        }
+        txn.insert(5, "overwritten".to_string());
+        txn.insert(5, "overwritten twice".to_string());
+        for i in 0..15 {
+            txn.remove(&i);
+        }
+        txn.insert(3, "reinserted".to_string());
+        assert_eq!(txn.len(), 5 + 50 + 1);
+        Err("abort")
+    });
+
+    assert_eq!(result, Err("abort"));
+    let after: Vec<(i32, String)> = tree.items().map(|(k, v)| (*k, v.clone())).collect();
+    assert_eq!(after, before);
+    assert_eq!(tree.len(), 20);
+    tree.check_invariants_detailed().unwrap();
+}
+
+#[test]
+fn test_transaction_rollback_restores_versions_and_logs_nothing() {
+    let log = Arc::new(Mutex::new(Vec::new()));
+    let sink = Arc::clone(&log);
+
+    let mut tree = create_tree_4();
+    for i in 0..20 {
+        tree.insert(i, format!("v{}", i));
+    }
+    let midway = tree.version();
+    for i in 10..30 {
+        tree.insert(i, format!("w{}", i));
+    }
+    tree.set_op_log(move |op| sink.lock().unwrap().push(op.clone()));
+    let version = tree.version();
+    let changed: Vec<(i32, String)> = tree
+        .changed_since(midway)
+        .map(|(k, v)| (*k, v.clone()))
+        .collect();
+
+    let result: Result<(), &str> = tree.transaction(|txn| {
+        for i in 100..140 {
+            txn.insert(i, format!("new{}", i));
+        }
+        txn.insert(5, "overwritten".to_string());
+        txn.insert(15, "overwritten".to_string());
+        for i in 0..12 {
+            txn.remove(&i);
+        }
+        txn.insert(3, "reinserted".to_string());
+        Err("abort")
+    });
+
+    assert_eq!(result, Err("abort"));
+    assert_eq!(tree.version(), version);
+    let changed_after: Vec<(i32, String)> = tree
+        .changed_since(midway)
+        .map(|(k, v)| (*k, v.clone()))
+        .collect();
+    assert_eq!(changed_after, changed);
+    assert_eq!(tree.changed_since(version).count(), 0);
+    assert!(log.lock().unwrap().is_empty());
+    tree.check_invariants_detailed().unwrap();
+
+    // The log is still attached and sees the next change
+    tree.insert(200, "after".to_string());
+    assert_eq!(log.lock().unwrap().len(), 1);
+}
+
+#[test]
+fn test_transaction_rolls_back_and_keeps_log_when_closure_panics() {
+    let log = Arc::new(Mutex::new(Vec::new()));
+    let sink = Arc::clone(&log);
+
+    let mut tree = create_tree_4_with_data(20);
+    tree.set_op_log(move |op| sink.lock().unwrap().push(op.clone()));
+    let before: Vec<(i32, String)> = tree.items().map(|(k, v)| (*k, v.clone())).collect();
+    let version = tree.version();
+
+    let panicked = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+        let _: Result<(), ()> = tree.transaction(|txn| {
+            for i in 100..140 {
+                txn.insert(i, format!("new{}", i));
+            }
+            for i in 0..10 {
+                txn.remove(&i);
+            }
+            panic!("closure failed mid-transaction");
+        });
+    }))
+    .is_err();
+
+    assert!(panicked);
+    let after: Vec<(i32, String)> = tree.items().map(|(k, v)| (*k, v.clone())).collect();
+    assert_eq!(after, before);
+    assert_eq!(tree.version(), version);
+    tree.check_invariants_detailed().unwrap();
+    assert!(log.lock().unwrap().is_empty());
+
+    tree.insert(500, "after".to_string());
+    assert_eq!(
+        *log.lock().unwrap(),
+        vec![Operation::Insert {
+            key: 500,
+            value: "after".to_string()
+        }]
+    );
+}
+
+#[test]
+fn test_transaction_logs_its_operations_on_commit() {
+    let log = Arc::new(Mutex::new(Vec::new()));
+    let sink = Arc::clone(&log);
+
+    let mut tree = create_tree_4();
+    tree.insert(1, "one".to_string());
+    tree.set_op_log(move |op| sink.lock().unwrap().push(op.clone()));
+
+    let result: Result<(), ()> = tree.transaction(|txn| {
+        txn.insert(2, "two".to_string());
+        txn.remove(&1);
+        txn.remove(&99);
+        // Nothing reaches the log before the transaction commits
+        assert!(log.lock().unwrap().is_empty());
+        Ok(())
+    });
+
+    assert!(result.is_ok());
+    assert_eq!(
+        *log.lock().unwrap(),
+        vec![
+            Operation::Insert {
+                key: 2,
+                value: "two".to_string()
+            },
+            Operation::Remove { key: 1 },
+        ]
+    );
+}
+
+#[test]
+fn test_transaction_commits_on_ok() {
+    let mut tree = BPlusTreeMap::new(4).unwrap();
+    tree.insert(1, 10);
+
+    let result: Result<(), ()> = tree.transaction(|txn| {
+        txn.insert(2, 20);
+        assert_eq!(txn.remove(&1), Some(10));
+        assert_eq!(txn.remove(&99), None);
+        assert!(txn.contains_key(&2));
+        Ok(())
+    });
+
+    assert!(result.is_ok());
+    assert_eq!(tree.get(&1), None);
+    assert_eq!(tree.get(&2), Some(&20));
+}
+
+// ============================================================================
+// SEEK TESTS
+// ============================================================================
+
+#[test]
+fn test_item_iterator_seek_forward() {
+    let mut tree = BPlusTreeMap::new(4).unwrap();
+    for i in 0..10_000 {
+        tree.insert(i * 3, i);
+    }
+
+    let mut items = tree.items();
+    assert_eq!(items.next(), Some((&0, &0)));
+    items.seek(&20_000);
+    assert_eq!(items.next(), Some((&20_001, &6_667)));
+    items.seek(&21_000);
+    assert_eq!(items.next(), Some((&21_000, &7_000)));
+    // Seeking backwards or to the current position does nothing
+    items.seek(&0);
+    items.seek(&21_003);
+    assert_eq!(items.next(), Some((&21_003, &7_001)));
+    items.seek(&1_000_000);
+    assert_eq!(items.next(), None);
+}
+
+#[test]
+fn test_seek_skips_without_visiting_every_leaf() {
+    let mut tree = BPlusTreeMap::bulk_load(4, (0..100_000).map(|i| (i, i))).unwrap();
+    tree.enable_counters();
+    let mut items = tree.items();
+    items.next();
+    items.seek(&90_000);
+    assert_eq!(items.next(), Some((&90_000, &90_000)));
+    assert!(tree.operation_counters().node_visits < 50);
+}
+
+#[test]
+fn test_range_iterator_seek_respects_bounds() {
+    let tree = BPlusTreeMap::bulk_load(4, (0..1000).map(|i| (i, i))).unwrap();
+
+    let mut range = tree.range(100..200);
+    range.seek(&150);
+    assert_eq!(range.next(), Some((&150, &150)));
+    range.seek(&199);
+    assert_eq!(range.next(), Some((&199, &199)));
+    assert_eq!(range.next(), None);
+
+    let mut range = tree.range(100..200);
+    range.seek(&200);
+    assert_eq!(range.next(), None);
+}
+
+#[test]
+fn test_seek_stops_at_back_cursor() {
+    let tree = BPlusTreeMap::bulk_load(4, (0..100).map(|i| (i, i))).unwrap();
+    let mut items = tree.items();
+    assert_eq!(items.next_back(), Some((&99, &99)));
+    assert_eq!(items.next_back(), Some((&98, &98)));
+    items.seek(&97);
+    assert_eq!(items.next(), Some((&97, &97)));
+    assert_eq!(items.next(), None);
+
+    let mut items = tree.items();
+    items.next_back();
+    items.seek(&99);
+    assert_eq!(items.next(), None);
+}
+
+// ============================================================================
+// GET OR INSERT WITH TESTS
+// ============================================================================
+
+#[test]
+fn test_get_or_insert_with_inserts_then_mutates() {
+    let mut tree: BPlusTreeMap<i32, Vec<i32>> = BPlusTreeMap::new(4).unwrap();
+    for i in 0..100 {
+        tree.get_or_insert_with(i % 10, Vec::new).push(i);
+    }
+    tree.check_invariants_detailed().unwrap();
+    assert_eq!(tree.len(), 10);
+    assert_eq!(tree.get(&3).unwrap().len(), 10);
+    assert_eq!(tree.get(&3).unwrap()[..3], [3, 13, 23]);
+}
+
+#[test]
+fn test_get_or_insert_with_skips_default_for_existing_key() {
+    let mut tree = BPlusTreeMap::new(4).unwrap();
+    for i in 0..50 {
+        tree.insert(i, i);
+    }
+    tree.enable_counters();
+    let value = tree.get_or_insert_with(25, || panic!("default must not run"));
+    *value += 100;
+    // One descent plus re-borrowing the leaf mutably
+    assert_eq!(
+        tree.operation_counters().node_visits,
+        tree.height() as u64 + 1
+    );
+    assert_eq!(tree.get(&25), Some(&125));
+    assert_eq!(tree.len(), 50);
+}
+
+#[test]
+fn test_get_or_insert_with_descends_once_for_new_key() {
+    let mut tree = BPlusTreeMap::new(4).unwrap();
+    let mut plain = BPlusTreeMap::new(4).unwrap();
+    for i in 0..50 {
+        tree.insert(i * 2, i * 2);
+        plain.insert(i * 2, i * 2);
+    }
+
+    // Odd keys fill the gaps, splitting full leaves along the way
+    for key in (1..100).step_by(2) {
+        plain.enable_counters();
+        plain.insert(key, key);
+        let insert_visits = plain.operation_counters().node_visits;
+
+        tree.enable_counters();
+        *tree.get_or_insert_with(key, || 0) = key;
+        // The insert's own descent, then the leaf holding the value and,
+        // after a split, its successor; no second descent
+        let visits = tree.operation_counters().node_visits;
+        assert!(visits <= insert_visits + 2, "key {}", key);
+    }
+
+    tree.check_invariants_detailed().unwrap();
+    assert!(tree
+        .items()
+        .map(|(k, v)| (*k, *v))
+        .eq((0..100).map(|i| (i, i))));
+}
+
+// ============================================================================
+// MERGE ITERATOR TESTS
+// ============================================================================
+
+#[test]
+fn test_merge_iter_three_overlapping_trees() {
+    use bplustree::DuplicatePolicy;
+
+    let a = BPlusTreeMap::bulk_load(4, (0..100).step_by(2).map(|k| (k, 'a'))).unwrap();
+    let b = BPlusTreeMap::bulk_load(4, (0..100).step_by(3).map(|k| (k, 'b'))).unwrap();
+    let c = BPlusTreeMap::bulk_load(4, (50..150).map(|k| (k, 'c'))).unwrap();
+    let trees = [&a, &b, &c];
+
+    let all: Vec<(i32, char)> = BPlusTreeMap::merge_iter(&trees)
+        .map(|(k, v)| (*k, *v))
+        .collect();
+    assert_eq!(all.len(), a.len() + b.len() + c.len());
+    assert!(all.windows(2).all(|w| w[0].0 <= w[1].0));
+    // Equal keys come out in tree order
+    let sixty: Vec<char> = all
+        .iter()
+        .filter(|(k, _)| *k == 60)
+        .map(|(_, v)| *v)
+        .collect();
+    assert_eq!(sixty, vec!['a', 'b', 'c']);
+
+    let first_wins: Vec<(i32, char)> = BPlusTreeMap::merge_iter(&trees)
+        .with_policy(DuplicatePolicy::FirstWins)
+        .map(|(k, v)| (*k, *v))
+        .collect();
+    let mut expected = std::collections::BTreeMap::new();
+    for tree in trees.iter().rev() {
+        for (k, v) in tree.items() {
+            expected.insert(*k, *v);
+        }
+    }
+    assert_eq!(first_wins, expected.into_iter().collect::<Vec<_>>());
+}
+
+#[test]
+fn test_merge_iter_with_empty_inputs() {
+    let empty: BPlusTreeMap<i32, i32> = BPlusTreeMap::new(4).unwrap();
+    let one = BPlusTreeMap::bulk_load(4, vec![(1, 1)]).unwrap();
+    assert_eq!(BPlusTreeMap::<i32, i32>::merge_iter(&[]).count(), 0);
+    assert_eq!(BPlusTreeMap::merge_iter(&[&empty, &empty]).count(), 0);
+    assert_eq!(
+        BPlusTreeMap::merge_iter(&[&empty, &one]).collect::<Vec<_>>(),
+        vec![(&1, &1)]
+    );
+}
+
+#[test]
+fn test_merge_iter_follows_custom_comparator() {
+    use bplustree::DuplicatePolicy;
+
+    let mut evens = BPlusTreeMap::with_comparator(4, |a: &i32, b: &i32| b.cmp(a)).unwrap();
+    for i in (0..40).step_by(2) {
+        evens.insert(i, 'e');
+    }
+    let mut odds = BPlusTreeMap::with_comparator(4, |a: &i32, b: &i32| b.cmp(a)).unwrap();
+    for i in (1..40).step_by(2) {
+        odds.insert(i, 'o');
+    }
+    odds.insert(10, 'o');
+
+    let keys: Vec<i32> = BPlusTreeMap::merge_iter(&[&evens, &odds])
+        .map(|(k, _)| *k)
+        .collect();
+    let mut expected: Vec<i32> = (0..40).chain([10]).collect();
+    expected.sort_by(|a, b| b.cmp(a));
+    assert_eq!(keys, expected);
+
+    // Duplicates are detected with the comparator too
+    let first_wins: Vec<(i32, char)> = BPlusTreeMap::merge_iter(&[&evens, &odds])
+        .with_policy(DuplicatePolicy::FirstWins)
+        .map(|(k, v)| (*k, *v))
+        .collect();
+    assert_eq!(first_wins.len(), 40);
+    assert!(first_wins.contains(&(10, 'e')));
+}
+
+#[test]
+#[should_panic(expected = "first tree's key order")]
+fn test_merge_iter_rejects_trees_with_different_orders() {
+    let mut descending = BPlusTreeMap::with_comparator(4, |a: &i32, b: &i32| b.cmp(a)).unwrap();
+    let mut ascending = BPlusTreeMap::new(4).unwrap();
+    for i in 0..10 {
+        descending.insert(i, i);
+        ascending.insert(i, i);
+    }
+    BPlusTreeMap::merge_iter(&[&descending, &ascending]).for_each(drop);
+}
+
+// ============================================================================
+// NODE ID EXHAUSTION TESTS
+// ============================================================================
+
+#[cfg(feature = "testing")]
+#[test]
+fn test_exhausted_node_ids_surface_allocation_error() {
+    let mut tree = BPlusTreeMap::new(4).unwrap();
+    for i in 0..20 {
+        tree.insert(i, i);
+    }
+    let before: Vec<(i32, i32)> = tree.items().map(|(k, v)| (*k, *v)).collect();
+
+    // No fresh IDs beyond the ones already in use
+    tree.limit_node_ids(0);
+    let mut failed = false;
+    for i in 20..40 {
+        match tree.try_insert(i, i) {
+            Ok(_) => {}
+            Err(BPlusTreeError::AllocationError(_)) => {
+                failed = true;
+                break;
+            }
+            Err(other) => panic!("unexpected error: {:?}", other),
+        }
+    }
+    assert!(failed, "inserts must fail once no node IDs are left");
+    tree.check_invariants_detailed().unwrap();
+    assert!(before.iter().all(|(k, v)| tree.get(k) == Some(v)));
+
+    // Freed slots become usable again
+    for i in 0..10 {
+        tree.remove(&i);
+    }
+    assert!(tree.try_insert(100, 100).is_ok());
+}
+
+#[cfg(feature = "testing")]
+#[test]
+#[should_panic(expected = "NodeId space exhausted")]
+fn test_insert_panics_when_node_ids_run_out() {
+    let mut tree = BPlusTreeMap::new(4).unwrap();
+    tree.limit_node_ids(1);
+    for i in 0..10 {
+        tree.insert(i, i);
+    }
+}
+
+// ============================================================================
+// REPAIR TESTS
+// ============================================================================
+
+#[cfg(feature = "testing")]
+#[test]
+fn test_repair_fixes_corrupted_separator() {
+    let mut tree = BPlusTreeMap::bulk_load(4, (0..100).map(|i| (i, i * 10))).unwrap();
+    let (_, root_id, is_leaf) = tree.level_order().next().unwrap();
+    assert!(!is_leaf);
+
+    // A separator far below its right subtree misroutes lookups to the left
+    tree.get_branch_mut(root_id).unwrap().keys_mut()[0] = -1000;
+    assert!(tree.check_invariants_detailed().is_err());
+
+    let corrections = tree.repair().unwrap();
+    assert!(corrections > 0);
+    tree.check_invariants_detailed().unwrap();
+    for i in 0..100 {
+        assert_eq!(tree.get(&i), Some(&(i * 10)));
+    }
+    assert_eq!(tree.repair().unwrap(), 0);
+}
+
+#[test]
+fn test_repair_relinks_broken_leaf_chain() {
+    let mut tree = BPlusTreeMap::bulk_load(4, (0..100).map(|i| (i, i))).unwrap();
+    let leaf_ids: Vec<_> = tree
+        .level_order()
+        .filter(|(_, _, is_leaf)| *is_leaf)
+        .map(|(_, id, _)| id)
+        .collect();
+
+    // Skip a leaf in the chain and cut it short at the end
+    tree.set_leaf_next(leaf_ids[2], leaf_ids[4]);
+    tree.set_leaf_next(leaf_ids[10], bplustree::NULL_NODE);
+    assert_ne!(tree.items().count(), 100);
+
+    assert_eq!(tree.repair().unwrap(), 2);
+    tree.check_invariants_detailed().unwrap();
+    assert!(tree.items().map(|(k, _)| *k).eq(0..100));
+}
+
+#[test]
+fn test_repair_on_healthy_tree_is_a_no_op() {
+    let mut tree = BPlusTreeMap::new(4).unwrap();
+    assert_eq!(tree.repair().unwrap(), 0);
+    for i in 0..500 {
+        tree.insert((i * 37) % 500, i);
+    }
+    for i in 0..250 {
+        tree.remove(&(i * 2));
+    }
+    assert_eq!(tree.repair().unwrap(), 0);
+    tree.check_invariants_detailed().unwrap();
+}
+
+// ============================================================================
+// SPLIT AT INDEX TESTS
+// ============================================================================
+
+fn split_test_tree() -> BPlusTreeMap<i32, String> {
+    let mut tree = BPlusTreeMap::new(4).unwrap();
+    for i in 0..50 {
+        tree.insert(i * 2, format!("v{}", i));
+    }
+    tree
+}
+
+#[test]
+fn test_split_at_index_middle() {
+    let mut tree = split_test_tree();
+    let upper = tree.split_at_index(17);
+
+    assert!(tree.keys().copied().eq((0..17).map(|i| i * 2)));
+    assert!(upper.keys().copied().eq((17..50).map(|i| i * 2)));
+    assert_eq!(upper.get(&34), Some(&"v17".to_string()));
+    tree.check_invariants_detailed().unwrap();
+    upper.check_invariants_detailed().unwrap();
+}
+
+#[test]
+fn test_split_at_index_ends() {
+    let mut tree = split_test_tree();
+    let upper = tree.split_at_index(0);
+    assert!(tree.is_empty());
+    assert_eq!(upper.len(), 50);
+    upper.check_invariants_detailed().unwrap();
+
+    let mut tree = split_test_tree();
+    let upper = tree.split_at_index(tree.len());
+    assert_eq!(tree.len(), 50);
+    assert!(upper.is_empty());
+
+    let mut tree = split_test_tree();
+    let upper = tree.split_at_index(1000);
+    assert_eq!(tree.len(), 50);
+    assert!(upper.is_empty());
+    tree.check_invariants_detailed().unwrap();
+}
+
+// ============================================================================
+// INTO SORTED VEC TESTS
+// ============================================================================
+
+#[test]
+fn test_into_sorted_vec_moves_entries_in_order() {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    static CLONES: AtomicUsize = AtomicUsize::new(0);
+
+    #[derive(Debug, PartialEq)]
+    struct Counted(i32);
+
+    impl Clone for Counted {
+        fn clone(&self) -> Self {
+            CLONES.fetch_add(1, Ordering::SeqCst);
+            Counted(self.0)
+        }
+    }
+
+    let mut tree = BPlusTreeMap::new(4).unwrap();
+    for i in (0..200).rev() {
+        tree.insert((i * 7) % 200, Counted(i));
+    }
+    let expected: Vec<i32> = (0..200).collect();
+
+    let before = CLONES.load(Ordering::SeqCst);
+    let entries = tree.into_sorted_vec();
+    assert_eq!(CLONES.load(Ordering::SeqCst), before);
+
+    assert_eq!(entries.len(), 200);
+    assert!(entries.iter().map(|(k, _)| *k).eq(expected));
+    assert!(entries.iter().all(|(k, v)| (v.0 * 7) % 200 == *k));
+}
+
+// ============================================================================
+// PEEK TESTS
+// ============================================================================
+
+#[test]
+fn test_first_last_match_pop_without_mutating() {
+    let mut tree = BPlusTreeMap::new(4).unwrap();
+    for i in 0..100 {
+        tree.insert((i * 37) % 100, i);
+    }
+
+    while !tree.is_empty() {
+        let len = tree.len();
+        let min = tree.first().map(|(k, v)| (*k, *v));
+        let max = tree.last().map(|(k, v)| (*k, *v));
+        assert_eq!(tree.first().map(|(k, v)| (*k, *v)), min);
+        assert_eq!(tree.len(), len);
+
+        assert_eq!(tree.pop_first(), min);
+        if let Some(max) = max.filter(|_| !tree.is_empty()) {
+            assert_eq!(tree.pop_last(), Some(max));
+        }
+    }
+    assert_eq!(tree.first(), None);
+    assert_eq!(tree.last(), None);
+}
+
+#[test]
+fn test_last_descends_spine() {
+    let mut tree = BPlusTreeMap::bulk_load(4, (0..10_000).map(|i| (i, i))).unwrap();
+    tree.enable_counters();
+    assert_eq!(tree.last(), Some((&9_999, &9_999)));
+    assert_eq!(tree.operation_counters().node_visits, tree.height() as u64);
+}
+
+// ============================================================================
+// AUTO COMPACTION TESTS
+// ============================================================================
+
+#[test]
+fn test_auto_compact_fires_during_churn() {
+    let mut tree = BPlusTreeMap::new(4).unwrap();
+    tree.set_auto_compact_threshold(0.2);
+    for i in 0..2000 {
+        tree.insert(i, i);
+    }
+    let peak = tree.leaf_arena_stats().total_capacity;
+
+    for i in 0..1800 {
+        assert_eq!(tree.remove(&i), Some(i));
+        assert!(tree.fragmentation() <= 0.2 || free_nodes(&tree) < AUTO_COMPACT_MIN_FREE_NODES);
+    }
+
+    assert!(tree.leaf_arena_stats().total_capacity < peak);
+    assert!(tree.check_invariants());
+    assert_eq!(
+        tree.keys().copied().collect::<Vec<_>>(),
+        (1800..2000).collect::<Vec<_>>()
+    );
+}
+
+fn free_nodes<K: Ord + Clone, V: Clone>(tree: &BPlusTreeMap<K, V>) -> usize {
+    tree.free_leaf_count() + tree.free_branch_count()
+}
+
+#[test]
+fn test_auto_compact_waits_for_enough_free_nodes() {
+    let mut tree = BPlusTreeMap::new(4).unwrap();
+    tree.set_auto_compact_threshold(0.05);
+    for i in 0..60 {
+        tree.insert(i, i);
+    }
+    let capacity = tree.leaf_arena_stats().total_capacity;
+
+    // A small tree crosses the ratio long before it frees enough slots
+    for i in 0..30 {
+        tree.remove(&i);
+    }
+    assert!(tree.fragmentation() > 0.05);
+    assert!(free_nodes(&tree) < AUTO_COMPACT_MIN_FREE_NODES);
+    assert_eq!(tree.leaf_arena_stats().total_capacity, capacity);
+    assert!(tree.check_invariants());
+}
+
+#[test]
+fn test_auto_compact_runs_after_bulk_removals() {
+    let mut tree = BPlusTreeMap::new(4).unwrap();
+    tree.set_auto_compact_threshold(0.2);
+    for i in 0..4000 {
+        tree.insert(i, i);
+    }
+    let compacted = |tree: &BPlusTreeMap<i32, i32>| {
+        tree.fragmentation() <= 0.2 || free_nodes(tree) < AUTO_COMPACT_MIN_FREE_NODES
+    };
+
+    tree.pop_first_n(600);
+    assert!(compacted(&tree));
+    tree.pop_last_n(600);
+    assert!(compacted(&tree));
+    tree.remove_range(1000..1900);
+    assert!(compacted(&tree));
+    tree.retain_range(2000..3000, |k, _| k % 10 == 0);
+    assert!(compacted(&tree));
+    tree.retain(|k, _| k % 3 == 0);
+    assert!(compacted(&tree));
+    let upper = tree.split_off(&2000);
+    assert!(compacted(&tree));
+    assert!(tree.check_invariants() && upper.check_invariants());
+    tree.drain().take(5).for_each(drop);
+    assert!(compacted(&tree));
+}
+
+#[test]
+fn test_auto_compact_disabled_by_default() {
+    let mut tree = BPlusTreeMap::new(4).unwrap();
+    for i in 0..2000 {
+        tree.insert(i, i);
+    }
+    let peak = tree.leaf_arena_stats().total_capacity;
+    for i in 0..1800 {
+        tree.remove(&i);
+    }
+
+    assert_eq!(tree.leaf_arena_stats().total_capacity, peak);
+    assert!(tree.fragmentation() > 0.5);
+
+    tree.set_auto_compact_threshold(0.5);
+    tree.disable_auto_compact();
+    tree.remove(&1800);
+    assert_eq!(tree.leaf_arena_stats().total_capacity, peak);
+}
+
+// ============================================================================
+// STRUCTURE REPORT TESTS
+// ============================================================================
+
+#[test]
+fn test_structure_report_on_bulk_loaded_tree() {
+    // 40 keys pack into 10 full leaves, grouped under 2 branches of 5
+    // children each, under a root with a single separator
+    let tree = BPlusTreeMap::bulk_load(4, (0..40).map(|i| (i, i))).unwrap();
+    let report = tree.structure_report();
+
+    assert_eq!(report.leaf_count, 10);
+    assert_eq!(report.branch_count, 3);
+    assert_eq!(report.height, 3);
+    assert_eq!(report.nodes_per_level, vec![1, 2, 10]);
+    assert_eq!(
+        report.leaf_fill,
+        FillStats {
+            min: 4,
+            max: 4,
+            avg: 4.0
+        }
+    );
+    assert_eq!(
+        report.branch_fill,
+        FillStats {
+            min: 1,
+            max: 4,
+            avg: 3.0
+        }
+    );
+
+    assert_eq!(report.leaf_count, tree.leaf_count());
+    assert_eq!(report.height, tree.height());
+}
+
+#[test]
+fn test_structure_report_uneven_and_empty() {
+    // The trailing leaf is topped up from its neighbour: sizes 4, 3, 2
+    let tree = BPlusTreeMap::bulk_load(4, (0..9).map(|i| (i, i))).unwrap();
+    let report = tree.structure_report();
+    assert_eq!(report.nodes_per_level, vec![1, 3]);
+    assert_eq!(
+        report.leaf_fill,
+        FillStats {
+            min: 2,
+            max: 4,
+            avg: 3.0
+        }
+    );
+    assert_eq!(
+        report.branch_fill,
+        FillStats {
+            min: 2,
+            max: 2,
+            avg: 2.0
+        }
+    );
+
+    let empty = BPlusTreeMap::<i32, i32>::new(4).unwrap();
+    let report = empty.structure_report();
+    assert_eq!(report.leaf_count, 1);
+    assert_eq!(report.branch_count, 0);
+    assert_eq!(report.height, 1);
+    assert_eq!(report.nodes_per_level, vec![1]);
+    assert_eq!(report.leaf_fill, FillStats::default());
+    assert_eq!(report.branch_fill, FillStats::default());
+}
+
+// ============================================================================
+// KEY SUBSET TESTS
+// ============================================================================
+
+#[test]
+fn test_contains_all_keys_strict_superset() {
+    let all = create_tree_4_with_data(500);
+    let mut some = create_tree_4();
+    for i in (0..500).step_by(7) {
+        some.insert(i, format!("other_{}", i));
+    }
+
+    assert!(all.contains_all_keys(&some));
+    assert!(!some.contains_all_keys(&all));
+    assert!(all.contains_all_keys(&create_tree_4()));
+}
+
+#[test]
+fn test_contains_all_keys_equal_trees() {
+    let a = create_tree_4_with_data(300);
+    let mut b = create_tree_capacity(16);
+    insert_sequential_range(&mut b, 300);
+
+    assert!(a.contains_all_keys(&b));
+    assert!(b.contains_all_keys(&a));
+    assert!(a.contains_all_keys(&a));
+}
+
+#[test]
+fn test_contains_all_keys_one_missing() {
+    let mut superset = create_tree_4_with_data(200);
+    let subset = create_tree_4_with_data(100);
+    assert!(superset.contains_all_keys(&subset));
+
+    superset.remove(&57);
+    assert!(!superset.contains_all_keys(&subset));
+
+    // A key past the end of `self` is missing too
+    let mut beyond = create_tree_4_with_data(10);
+    beyond.insert(1000, "far".to_string());
+    assert!(!superset.contains_all_keys(&beyond));
+}
+
+// ============================================================================
+// OPERATION LOG TESTS
+// ============================================================================
+
+fn replay(log: &[Operation<i32, String>]) -> BPlusTreeMap<i32, String> {
+    let mut tree = create_tree_4();
+    for operation in log {
+        match operation {
+            Operation::Insert { key, value } => {
+                tree.insert(*key, value.clone());
+            }
+            Operation::Remove { key } => {
+                tree.remove(key);
+            }
+        }
+    }
+    tree
+}
+
+#[test]
+fn test_op_log_replay_reproduces_tree() {
+    let log = Arc::new(Mutex::new(Vec::new()));
+    let sink = Arc::clone(&log);
+
+    let mut tree = create_tree_4();
+    tree.set_op_log(move |op| sink.lock().unwrap().push(op.clone()));
+    for i in 0..200 {
+        tree.insert((i * 37) % 200, format!("v{}", i));
+    }
+    for i in (0..200).step_by(3) {
+        tree.insert(i, format!("overwritten{}", i));
+    }
+    for i in (0..200).step_by(4) {
+        tree.remove(&i);
+    }
+    tree.remove_range(150..170);
+    tree.try_insert(1000, "checked".to_string()).unwrap();
+
+    // Misses change nothing and are not recorded
+    let recorded = log.lock().unwrap().len();
+    assert_eq!(tree.remove(&-1), None);
+    assert_eq!(log.lock().unwrap().len(), recorded);
+
+    let replayed = replay(&log.lock().unwrap());
+    assert_eq!(replayed, tree);
+    assert!(replayed.check_invariants());
+}
+
+#[test]
+fn test_op_log_replay_matches_after_every_mutator() {
+    let log = Arc::new(Mutex::new(Vec::new()));
+    let sink = Arc::clone(&log);
+    let mut tree = create_tree_4();
+    tree.set_op_log(move |op| sink.lock().unwrap().push(op.clone()));
+    let check = |tree: &BPlusTreeMap<i32, String>, step: &str| {
+        let replayed = replay(&log.lock().unwrap());
+        assert_eq!(&replayed, tree, "replay diverged after {}", step);
+    };
+    let value = |i: i32| format!("v{}", i);
+
+    for i in 0..200 {
+        tree.insert(i, value(i));
+    }
+    check(&tree, "insert");
+    tree.try_insert(500, value(500)).unwrap();
+    tree.try_insert_checked(501, value(501)).unwrap();
+    check(&tree, "try_insert");
+    assert!(tree.insert_if_absent(502, value(502)).is_ok());
+    assert!(tree.insert_if_absent(502, "lost".to_string()).is_err());
+    check(&tree, "insert_if_absent");
+    tree.merge_with(503, value(503), |old, new| old.push_str(&new));
+    tree.merge_with(503, value(0), |old, new| old.push_str(&new));
+    check(&tree, "merge_with");
+    tree.remove(&500);
+    tree.remove_item(&501).unwrap();
+    tree.try_remove(&502).unwrap();
+    check(&tree, "remove");
+    tree.batch_insert(vec![(600, value(600)), (601, value(601))])
+        .unwrap();
+    check(&tree, "batch_insert");
+    tree.insert_sorted_batch((190..260).map(|i| (i, value(i))).collect())
+        .unwrap();
+    tree.insert_sorted_batch(vec![(3, value(3)), (1, value(1))])
+        .unwrap();
+    check(&tree, "insert_sorted_batch");
+    tree.extend((700..720).map(|i| (i, value(i))));
+    check(&tree, "extend");
+
+    tree.entry(800).or_insert_with(|| value(800));
+    if let Entry::Occupied(mut entry) = tree.entry(800) {
+        entry.insert("replaced".to_string());
+    }
+    if let Entry::Occupied(entry) = tree.entry(800) {
+        entry.remove();
+    }
+    tree.get_or_insert_with(802, || value(802));
+    assert_eq!(tree.get_or_insert_with(802, || value(0)), &value(802));
+    check(&tree, "entry");
+    tree.pop_first();
+    tree.pop_last();
+    tree.first_entry().unwrap().remove();
+    check(&tree, "pop");
+
+    tree.pop_first_n(5);
+    tree.pop_last_n(5);
+    check(&tree, "pop_n");
+    tree.remove_range(50..60);
+    check(&tree, "remove_range");
+    tree.retain(|k, _| k % 5 != 0);
+    check(&tree, "retain");
+    tree.retain_range(100..150, |k, _| k % 2 == 0);
+    check(&tree, "retain_range");
+    tree.coalesce_underfull();
+    tree.compact();
+    check(&tree, "coalesce_underfull");
+
+    drop(tree.split_off(&240));
+    check(&tree, "split_off");
+    drop(tree.split_at_index(tree.len() - 10));
+    check(&tree, "split_at_index");
+    let mut other: BPlusTreeMap<i32, String> = (150..400).map(|i| (i, value(-i))).collect();
+    tree.append(&mut other);
+    check(&tree, "append");
+    tree.remove_range(..);
+    check(&tree, "bulk remove_range");
+
+    tree.reset_with((0..100).map(|i| (i, value(i)))).unwrap();
+    check(&tree, "reset_with");
+    tree.pop_first_n(60);
+    check(&tree, "bulk pop_first_n");
+    tree.pop_last_n(30);
+    check(&tree, "bulk pop_last_n");
+    let drained: Vec<_> = tree.drain().take(3).collect();
+    assert_eq!(drained.len(), 3);
+    check(&tree, "drain");
+    tree.extend((0..50).map(|i| (i, value(i))));
+    tree.clear();
+    check(&tree, "clear");
+    assert!(tree.is_empty());
+}
+
+#[test]
+fn test_op_log_reports_tree_emptied_by_append() {
+    let log = Arc::new(Mutex::new(Vec::new()));
+    let sink = Arc::clone(&log);
+
+    let mut source = create_tree_4_with_data(30);
+    source.set_op_log(move |op| sink.lock().unwrap().push(op.clone()));
+    let mut target = create_tree_4();
+    target.append(&mut source);
+
+    let log = log.lock().unwrap();
+    assert_eq!(log.len(), 30);
+    assert!(log.iter().all(|op| matches!(op, Operation::Remove { .. })));
+}
+
+#[test]
+fn test_op_log_can_be_cleared() {
+    let log = Arc::new(Mutex::new(Vec::new()));
+    let sink = Arc::clone(&log);
+
+    let mut tree = create_tree_4();
+    tree.set_op_log(move |op| sink.lock().unwrap().push(op.clone()));
+    tree.insert(1, "one".to_string());
+    tree.remove(&1);
+    assert_eq!(
+        *log.lock().unwrap(),
+        vec![
+            Operation::Insert {
+                key: 1,
+                value: "one".to_string()
+            },
+            Operation::Remove { key: 1 },
+        ]
+    );
+
+    // Clones start without the sink
+    let mut copy = tree.clone();
+    copy.insert(2, "two".to_string());
+    tree.clear_op_log();
+    tree.insert(3, "three".to_string());
+    assert_eq!(log.lock().unwrap().len(), 2);
+}
+
+// ============================================================================
+// SORTED BATCH INSERT TESTS
+// ============================================================================
+
+#[test]
+fn test_insert_sorted_batch_appends_large_batch() {
+    let mut tree = create_tree_capacity_int(16);
+    for i in 0..1_000 {
+        tree.insert(i, i);
+    }
+
+    tree.insert_sorted_batch((1_000..100_000).map(|i| (i, i * 2)).collect())
+        .unwrap();
+
+    assert_eq!(tree.len(), 100_000);
+    assert!(tree.check_invariants());
+    assert!(tree.keys().copied().eq(0..100_000));
+    assert_eq!(tree.get(&500), Some(&500));
+    assert_eq!(tree.get(&99_999), Some(&199_998));
+
+    // Appended leaves are packed like a bulk load, not half full
+    let report = tree.structure_report();
+    assert!(
+        report.leaf_fill.avg > 15.0,
+        "avg fill {}",
+        report.leaf_fill.avg
+    );
+}
+
+#[test]
+fn test_insert_sorted_batch_keeps_invariants_at_every_size() {
+    for capacity in [4, 5, 7, 16] {
+        for existing in [0, 1, 3, 10, 100] {
+            for batch in [1, 2, 3, 5, 17, 100, 1_000] {
+                let mut tree = create_tree_capacity_int(capacity);
+                for i in 0..existing {
+                    tree.insert(i, i);
+                }
+                tree.insert_sorted_batch((existing..existing + batch).map(|i| (i, -i)).collect())
+                    .unwrap();
+
+                assert!(
+                    tree.check_invariants(),
+                    "capacity {} existing {} batch {}",
+                    capacity,
+                    existing,
+                    batch
+                );
+                assert_eq!(tree.len(), (existing + batch) as usize);
+                assert!(tree.keys().copied().eq(0..existing + batch));
+                assert_eq!(tree.range_count(..), tree.len());
+            }
+        }
+    }
+}
+
+#[test]
+fn test_insert_sorted_batch_after_removals_leave_sparse_right_edge() {
+    for capacity in [4, 5, 8] {
+        for batch in [1, 2, 3, 4, 9, 40] {
+            let mut tree = create_tree_capacity_int(capacity);
+            for i in 0..300 {
+                tree.insert(i, i);
+            }
+            for i in (150..300).rev() {
+                tree.remove(&i);
+            }
+            for i in (0..150).step_by(3) {
+                tree.remove(&i);
+            }
+
+            tree.insert_sorted_batch((1_000..1_000 + batch).map(|i| (i, i)).collect())
+                .unwrap();
+            assert!(
+                tree.check_invariants(),
+                "capacity {} batch {}",
+                capacity,
+                batch
+            );
+            assert_eq!(tree.len(), 100 + batch as usize);
+            assert_eq!(tree.last(), Some((&(999 + batch), &(999 + batch))));
+        }
+    }
+}
+
+#[test]
+fn test_insert_sorted_batch_handles_overlap_and_unsorted_input() {
+    let mut tree = create_tree_4_int();
+    for i in (0..100).step_by(2) {
+        tree.insert(i, 0);
+    }
+
+    // Keys inside the current range are inserted normally, the rest appended
+    tree.insert_sorted_batch((50..150).map(|i| (i, 1)).collect())
+        .unwrap();
+    assert!(tree.check_invariants());
+    assert_eq!(tree.len(), 25 + 100);
+    assert_eq!(tree.get(&50), Some(&1));
+    assert_eq!(tree.get(&48), Some(&0));
+
+    // Out-of-order and duplicate keys fall back to one insert per pair
+    tree.insert_sorted_batch(vec![(500, 1), (-1, 1), (500, 2)])
+        .unwrap();
+    assert!(tree.check_invariants());
+    assert_eq!(tree.get(&500), Some(&2));
+    assert_eq!(tree.get(&-1), Some(&1));
+    assert_eq!(tree.len(), 127);
+}
+
+#[test]
+fn test_insert_sorted_batch_does_less_work_than_single_inserts() {
+    let batch: Vec<(i32, i32)> = (0..20_000).map(|i| (i, i)).collect();
+
+    let mut naive = create_tree_capacity_int(16);
+    naive.enable_counters();
+    for &(key, value) in &batch {
+        naive.insert(key, value);
+    }
+    let naive_counters = naive.operation_counters();
+
+    let mut fast = create_tree_capacity_int(16);
+    fast.enable_counters();
+    fast.insert_sorted_batch(batch).unwrap();
+    let fast_counters = fast.operation_counters();
+
+    println!(
+        "single inserts: {:?}, sorted batch: {:?}",
+        naive_counters, fast_counters
+    );
+    // Appending along the right edge skips the per-key descent entirely
+    assert!(fast_counters.node_visits * 2 < naive_counters.node_visits);
+    assert!(fast_counters.comparisons * 2 < naive_counters.comparisons);
+    assert_eq!(fast, naive);
+}
+
+// ============================================================================
+// PAGINATED SCAN TESTS
+// ============================================================================
+
+fn scan_keys(
+    tree: &BPlusTreeMap<i32, i32>,
+    start: Option<&i32>,
+    offset: usize,
+    limit: usize,
+) -> Vec<i32> {
+    tree.scan(start, offset, limit)
+        .into_iter()
+        .map(|(k, _)| *k)
+        .collect()
+}
+
+#[test]
+fn test_scan_normal_paging() {
+    let tree = BPlusTreeMap::bulk_load(4, (0..100).map(|i| (i * 2, i))).unwrap();
+
+    assert_eq!(scan_keys(&tree, None, 0, 5), vec![0, 2, 4, 6, 8]);
+    assert_eq!(scan_keys(&tree, None, 5, 5), vec![10, 12, 14, 16, 18]);
+    // A start key that is absent begins at the next larger key
+    assert_eq!(scan_keys(&tree, Some(&51), 0, 3), vec![52, 54, 56]);
+    assert_eq!(scan_keys(&tree, Some(&50), 2, 3), vec![54, 56, 58]);
+    assert_eq!(scan_keys(&tree, None, 0, 0), Vec::<i32>::new());
+
+    // Offset-based pages cover the tree exactly once
+    let mut all = Vec::new();
+    for page in 0..10 {
+        all.extend(scan_keys(&tree, None, page * 10, 10));
+    }
+    assert!(all.into_iter().eq((0..100).map(|i| i * 2)));
+}
+
+#[test]
+fn test_scan_offset_beyond_end_and_limit_past_remaining() {
+    let tree = BPlusTreeMap::bulk_load(4, (0..20).map(|i| (i, i))).unwrap();
+
+    assert!(tree.scan(None, 20, 5).is_empty());
+    assert!(tree.scan(None, usize::MAX, 5).is_empty());
+    assert!(tree.scan(Some(&15), 10, 5).is_empty());
+    assert!(tree.scan(Some(&100), 0, 5).is_empty());
+
+    assert_eq!(scan_keys(&tree, None, 17, 100), vec![17, 18, 19]);
+    assert_eq!(scan_keys(&tree, Some(&18), 0, usize::MAX), vec![18, 19]);
+
+    let empty = BPlusTreeMap::<i32, i32>::new(4).unwrap();
+    assert_eq!(empty.scan_page(None, 0, 10), (Vec::new(), None));
+}
+
+#[test]
+fn test_scan_page_cursor_survives_concurrent_changes() {
+    let mut tree = BPlusTreeMap::new(4).unwrap();
+    for i in 0..50 {
+        tree.insert(i, i);
+    }
+
+    let (page, next) = tree.scan_page(None, 0, 10);
+    assert_eq!(page.len(), 10);
+    let next = *next.unwrap();
+    assert_eq!(next, 10);
+
+    // Removing already-seen entries does not shift the cursor
+    for i in 0..5 {
+        tree.remove(&i);
+    }
+    let (page, next) = tree.scan_page(Some(&next), 0, 45);
+    assert_eq!(page.first(), Some(&(&10, &10)));
+    assert_eq!(page.len(), 40);
+    assert_eq!(next, None);
+}
+
+// ============================================================================
+// VALIDATION MODE TESTS
+// ============================================================================
+
+#[test]
+fn test_validation_mode_normal_workload_reports_no_errors() {
+    let mut tree = create_tree_4_int();
+    tree.set_validation_mode(true);
+
+    for i in 0..300 {
+        tree.insert((i * 17) % 300, i);
+    }
+    for i in (0..300).step_by(2) {
+        tree.remove(&i);
+    }
+    tree.remove(&-1);
+    for i in 0..100 {
+        tree.insert(i * 3, i);
+    }
+
+    assert!(tree.last_error().is_none());
+    assert!(tree.check_invariants());
+}
+
+#[cfg(feature = "testing")]
+#[test]
+fn test_validation_mode_records_corruption() {
+    let mut tree = BPlusTreeMap::bulk_load(4, (0..100).map(|i| (i, i))).unwrap();
+    let (_, root_id, _) = tree.level_order().next().unwrap();
+    tree.get_branch_mut(root_id).unwrap().keys_mut()[0] = -1000;
+
+    // Off by default: the corruption goes unnoticed
+    tree.insert(1_000, 0);
+    assert!(tree.last_error().is_none());
+
+    tree.set_validation_mode(true);
+    tree.insert(1_001, 0);
+    assert!(matches!(
+        tree.last_error(),
+        Some(BPlusTreeError::DataIntegrityError(_))
+    ));
+
+    // Switching the mode clears the recorded error
+    tree.set_validation_mode(false);
+    assert!(tree.last_error().is_none());
+}
+
+#[cfg(feature = "testing")]
+#[test]
+fn test_validation_mode_checks_conditional_and_bulk_mutators() {
+    let mut tree = BPlusTreeMap::bulk_load(4, (0..100).map(|i| (i, i))).unwrap();
+    let (_, root_id, _) = tree.level_order().next().unwrap();
+    tree.get_branch_mut(root_id).unwrap().keys_mut()[0] = -1000;
+
+    fn assert_validated(
+        tree: &mut BPlusTreeMap<i32, i32>,
+        name: &str,
+        mutate: impl FnOnce(&mut BPlusTreeMap<i32, i32>),
+    ) {
+        tree.set_validation_mode(true);
+        mutate(tree);
+        assert!(
+            matches!(
+                tree.last_error(),
+                Some(BPlusTreeError::DataIntegrityError(_))
+            ),
+            "{} was not validated",
+            name
+        );
+        tree.set_validation_mode(false);
+    }
+
+    assert_validated(&mut tree, "insert_if_absent", |tree| {
+        let _ = tree.insert_if_absent(1_000, 0);
+    });
+    assert_validated(&mut tree, "merge_with", |tree| {
+        tree.merge_with(99, 1, |total, amount| *total += amount)
+    });
+    assert_validated(&mut tree, "retain", |tree| tree.retain(|k, _| *k != 98));
+}
+
+// ============================================================================
+// RESERVE NODES TESTS
+// ============================================================================
+
+#[test]
+fn test_reserve_nodes_avoids_arena_reallocation() {
+    for capacity in [4, 5, 16, 64] {
+        let mut tree = create_tree_capacity_int(capacity);
+        tree.reserve_nodes(20_000);
+        let leaf_reserved = tree.leaf_arena_stats().reserved_capacity;
+        let branch_reserved = tree.branch_arena_stats().reserved_capacity;
+
+        // Shuffled order splits nodes all over the tree
+        for i in 0..20_000 {
+            tree.insert((i * 7919) % 20_000, i);
+        }
+
+        let leaf_stats = tree.leaf_arena_stats();
+        let branch_stats = tree.branch_arena_stats();
+        assert_eq!(
+            leaf_stats.reserved_capacity, leaf_reserved,
+            "capacity {}",
+            capacity
+        );
+        assert_eq!(
+            branch_stats.reserved_capacity, branch_reserved,
+            "capacity {}",
+            capacity
+        );
+        assert!(leaf_stats.total_capacity <= leaf_reserved);
+        assert!(branch_stats.total_capacity <= branch_reserved);
+        assert_eq!(tree.len(), 20_000);
+        assert!(tree.check_invariants());
+    }
+}
+
+#[test]
+fn test_reserve_nodes_zero_is_noop() {
+    let mut tree = create_tree_4_with_data(10);
+    let before = tree.leaf_arena_stats().reserved_capacity;
+    tree.reserve_nodes(0);
+    assert_eq!(tree.leaf_arena_stats().reserved_capacity, before);
+    assert_eq!(tree.len(), 10);
+}
+
+// ============================================================================
+// MAP KEYS TESTS
+// ============================================================================
+
+#[test]
+fn test_map_keys_preserves_order_and_count() {
+    let mut tree = create_tree_4_int();
+    for i in 0..200 {
+        tree.insert(i, i * 10);
+    }
+
+    let doubled = tree.map_keys(|k| k * 2);
+
+    assert_eq!(doubled.len(), 200);
+    assert!(doubled.check_invariants());
+    let entries: Vec<_> = doubled.items().map(|(k, v)| (*k, *v)).collect();
+    let expected: Vec<_> = (0..200).map(|i| (i * 2, i * 10)).collect();
+    assert_eq!(entries, expected);
+    assert_eq!(doubled.get(&198), Some(&990));
+    assert_eq!(doubled.get(&199), None);
+}
+
+#[test]
+fn test_map_keys_changes_key_type() {
+    let tree = create_tree_4_with_data(20);
+    let padded = tree.map_keys(|k| format!("{:04}", k));
+
+    assert_eq!(padded.len(), 20);
+    assert_eq!(padded.first().map(|(k, _)| k.as_str()), Some("0000"));
+    assert!(padded.contains_key(&"0019".to_string()));
+    assert!(padded.check_invariants());
+}
+
+#[cfg(debug_assertions)]
+#[test]
+#[should_panic(expected = "strictly monotonic")]
+fn test_map_keys_non_monotonic_trips_debug_assert() {
+    let tree = create_tree_4_with_data(20);
+    let _ = tree.map_keys(|k| k % 5);
+}
+
+// ============================================================================
+// MAP VALUES TESTS
+// ============================================================================
+
+#[test]
+fn test_map_values_strings_to_lengths() {
+    let mut tree = create_tree_4();
+    for i in 0..100 {
+        tree.insert(i, "x".repeat(i as usize % 7));
+    }
+    let keys_before: Vec<i32> = tree.keys().copied().collect();
+    let leaf_sizes_before = tree.leaf_sizes();
+
+    let lengths: BPlusTreeMap<i32, usize> = tree.map_values(|s| s.len());
+
+    assert!(lengths.check_invariants());
+    assert_eq!(lengths.keys().copied().collect::<Vec<_>>(), keys_before);
+    assert_eq!(lengths.leaf_sizes(), leaf_sizes_before);
+    for (key, len) in lengths.items() {
+        assert_eq!(*len, *key as usize % 7);
+    }
+}
+
+#[test]
+fn test_map_values_calls_in_key_order() {
+    let mut tree = create_tree_4_int();
+    for i in (0..200).rev() {
+        tree.insert((i * 37) % 200, i);
+    }
+    for i in (0..200).step_by(3) {
+        tree.remove(&i);
+    }
+    let expected: Vec<i32> = tree.values().copied().collect();
+
+    let mut seen = Vec::new();
+    let mapped = tree.map_values(|v| {
+        seen.push(v);
+        v as i64 * 2
+    });
+
+    assert_eq!(seen, expected);
+    assert_eq!(mapped.len(), expected.len());
+    assert!(mapped.check_invariants());
+    assert_eq!(
+        mapped.range_sum(..),
+        expected.iter().map(|&v| v as i64 * 2).sum()
+    );
+}
+
+// ============================================================================
+// MAP SET OPERATION TESTS
+// ============================================================================
+
+fn int_tree_from(keys: impl IntoIterator<Item = i32>, tag: i32) -> BPlusTreeMap<i32, i32> {
+    let mut tree = create_tree_4_int();
+    for key in keys {
+        tree.insert(key, key * 10 + tag);
+    }
+    tree
+}
+
+#[test]
+fn test_union_with_overlapping_combines_only_collisions() {
+    let a = int_tree_from(0..60, 1);
+    let b = int_tree_from((40..100).step_by(2), 2);
+    let calls = std::cell::Cell::new(0);
+
+    let union = a.union_with(&b, |ours, theirs| {
+        calls.set(calls.get() + 1);
+        assert_eq!(ours % 10, 1);
+        assert_eq!(theirs % 10, 2);
+        -1
+    });
+
+    // Collisions are the even keys in 40..60
+    assert_eq!(calls.get(), 10);
+    assert!(union.check_invariants());
+    assert_eq!(union.len(), 60 + 20);
+    for (key, value) in union.items() {
+        let expected = if *key >= 40 && *key < 60 && key % 2 == 0 {
+            -1
+        } else if *key < 60 {
+            key * 10 + 1
+        } else {
+            key * 10 + 2
+        };
+        assert_eq!(*value, expected, "key {}", key);
+    }
+}
+
+#[test]
+fn test_union_with_disjoint_never_combines() {
+    let a = int_tree_from((0..50).map(|i| i * 2), 1);
+    let b = int_tree_from((0..50).map(|i| i * 2 + 1), 2);
+
+    let union = a.union_with(&b, |_, _| panic!("combine called without a collision"));
+
+    assert_eq!(union.len(), 100);
+    assert_eq!(
+        union.keys().copied().collect::<Vec<_>>(),
+        (0..100).collect::<Vec<_>>()
+    );
+    assert!(union.check_invariants());
+}
+
+#[test]
+fn test_intersection_and_difference() {
+    let a = int_tree_from(0..60, 1);
+    let b = int_tree_from((40..100).step_by(2), 2);
+
+    let both = a.intersection(&b);
+    assert!(both.check_invariants());
+    assert_eq!(
+        both.items().map(|(k, v)| (*k, *v)).collect::<Vec<_>>(),
+        (40..60)
+            .step_by(2)
+            .map(|k| (k, k * 10 + 1))
+            .collect::<Vec<_>>()
+    );
+
+    let only_a = a.difference(&b);
+    assert!(only_a.check_invariants());
+    let expected: Vec<i32> = (0..60).filter(|k| *k < 40 || k % 2 == 1).collect();
+    assert_eq!(only_a.keys().copied().collect::<Vec<_>>(), expected);
+    assert_eq!(only_a.get(&41), Some(&411));
+
+    // Disjoint sets
+    let odds = int_tree_from((0..30).map(|i| i * 2 + 1), 2);
+    let evens = int_tree_from((0..30).map(|i| i * 2), 1);
+    assert!(evens.intersection(&odds).is_empty());
+    assert_eq!(evens.difference(&odds).len(), 30);
+
+    // Empty operands
+    let empty = create_tree_4_int();
+    assert!(a.intersection(&empty).is_empty());
+    assert_eq!(a.difference(&empty).len(), a.len());
+    assert!(empty.difference(&a).is_empty());
+}
+
+// ============================================================================
+// CHECKED GET TESTS
+// ============================================================================
+
+#[test]
+fn test_get_checked_distinguishes_absence() {
+    let mut tree = create_tree_4_int();
+    for i in 0..100 {
+        tree.insert(i * 2, i);
+    }
+
+    assert_eq!(tree.get_checked(&10), Ok(Some(&5)));
+    assert_eq!(tree.get_checked(&11), Ok(None));
+    assert_eq!(tree.get_checked(&-1), Ok(None));
+    assert_eq!(tree.get_checked(&1_000), Ok(None));
+}
+
+#[cfg(feature = "testing")]
+#[test]
+fn test_get_checked_reports_dangling_child() {
+    let mut tree = BPlusTreeMap::bulk_load(4, (0..100).map(|i| (i, i))).unwrap();
+    let (_, root_id, _) = tree.level_order().next().unwrap();
+    tree.get_branch_mut(root_id).unwrap().children_mut()[0] = NodeRef::Branch(999_999, PhantomData);
+
+    // Plain `get` can't tell the broken subtree from a miss
+    assert_eq!(tree.get(&0), None);
+    assert!(matches!(
+        tree.get_checked(&0),
+        Err(BPlusTreeError::CorruptedTree(_))
+    ));
+
+    // Keys routed through intact children still resolve
+    assert_eq!(tree.get_checked(&99), Ok(Some(&99)));
+}
+
+// ============================================================================
+// CHANGE TRACKING TESTS
+// ============================================================================
+
+#[test]
+fn test_changed_since_reports_only_later_writes() {
+    let mut tree = create_tree_4_int();
+    for i in 0..100 {
+        tree.insert(i, i);
+    }
+    tree.insert(5, 500);
+    let synced = tree.version();
+    assert!(tree.changed_since(synced).next().is_none());
+
+    // New keys, overwrites and merges after the snapshot
+    tree.insert(150, 150);
+    tree.insert(7, 700);
+    tree.merge_with(42, 1, |total, amount| *total += amount);
+    tree.insert_if_absent(200, 200).unwrap();
+    // Rejected and removed writes are not reported
+    assert!(tree.insert_if_absent(8, 0).is_err());
+    for i in 60..90 {
+        tree.remove(&i);
+    }
+
+    let changed: Vec<(i32, i32)> = tree.changed_since(synced).map(|(k, v)| (*k, *v)).collect();
+    assert_eq!(changed, vec![(7, 700), (42, 43), (150, 150), (200, 200)]);
+    assert!(tree.check_invariants());
+
+    // Everything is newer than version 0
+    assert_eq!(tree.changed_since(0).count(), tree.len());
+}
+
+#[test]
+fn test_changed_since_survives_splits_and_merges() {
+    let mut tree = create_tree_4_int();
+    for i in 0..200 {
+        tree.insert(i * 2, 0);
+    }
+    let synced = tree.version();
+
+    // Odd keys split leaves all over the tree
+    for i in 0..100 {
+        tree.insert(i * 4 + 1, 1);
+    }
+    // Removing even keys forces borrows and merges around the stamped entries
+    for i in 0..200 {
+        if i % 3 != 0 {
+            tree.remove(&(i * 2));
+        }
+    }
+    assert!(tree.check_invariants());
+
+    let changed: Vec<i32> = tree.changed_since(synced).map(|(k, _)| *k).collect();
+    let expected: Vec<i32> = (0..100).map(|i| i * 4 + 1).collect();
+    assert_eq!(changed, expected);
+
+    // Entry API overwrites are tracked too
+    let synced = tree.version();
+    if let bplustree::Entry::Occupied(mut entry) = tree.entry(0) {
+        entry.insert(99);
+    }
+    assert_eq!(
+        tree.changed_since(synced).collect::<Vec<_>>(),
+        vec![(&0, &99)]
+    );
+}
+
+#[test]
+fn test_changed_since_ignores_entries_moved_by_rebuilds() {
+    let mut tree = create_tree_4_int();
+    for i in 0..200 {
+        tree.insert(i, i);
+    }
+    tree.insert(7, 70);
+    let synced = tree.version();
+
+    tree.retain(|k, _| k % 2 == 1);
+    assert!(tree.changed_since(synced).next().is_none());
+
+    let popped = tree.pop_first_n(40);
+    assert_eq!(popped.len(), 40);
+    assert!(tree.changed_since(synced).next().is_none());
+
+    let mut upper = tree.split_off(&151);
+    assert!(tree.changed_since(synced).next().is_none());
+    assert!(upper.changed_since(synced).next().is_none());
+
+    tree.rebuild();
+    assert!(tree.changed_since(synced).next().is_none());
+    assert!(tree.clone().changed_since(synced).next().is_none());
+
+    // Appended entries are new to the receiving tree, its own are not
+    let before = upper.version();
+    let appended: Vec<i32> = tree.keys().copied().collect();
+    upper.append(&mut tree);
+    let changed: Vec<i32> = upper.changed_since(before).map(|(k, _)| *k).collect();
+    assert_eq!(changed, appended);
+    assert!(upper.check_invariants());
+
+    // Entries written before the sync keep their older stamps
+    let mut fresh = create_tree_4_int();
+    for i in 0..100 {
+        fresh.insert(i, 0);
+    }
+    let mid = fresh.version();
+    fresh.insert(42, 1);
+    fresh.retain(|k, _| k % 3 == 0);
+    fresh.rebuild();
+    assert_eq!(
+        fresh.changed_since(mid).collect::<Vec<_>>(),
+        vec![(&42, &1)]
+    );
+}
+
+#[test]
+fn test_changed_since_tracks_mutable_borrows() {
+    let mut tree = create_tree_4_int();
+    for i in 0..100 {
+        tree.insert(i, i);
+    }
+
+    let synced = tree.version();
+    *tree.get_mut(&10).unwrap() += 1;
+    tree.entry(20).and_modify(|v| *v += 1);
+    if let bplustree::Entry::Occupied(mut entry) = tree.entry(30) {
+        *entry.get_mut() += 1;
+    }
+    let changed: Vec<i32> = tree.changed_since(synced).map(|(k, _)| *k).collect();
+    assert_eq!(changed, vec![10, 20, 30]);
+
+    let synced = tree.version();
+    for (_, value) in tree.range_mut(40..45) {
+        *value += 1;
+    }
+    tree.update_range(60..=62, |_, v| *v += 1);
+    let changed: Vec<i32> = tree.changed_since(synced).map(|(k, _)| *k).collect();
+    assert_eq!(changed, vec![40, 41, 42, 43, 44, 60, 61, 62]);
+
+    let synced = tree.version();
+    for value in tree.values_mut().take(3) {
+        *value += 1;
+    }
+    let changed: Vec<i32> = tree.changed_since(synced).map(|(k, _)| *k).collect();
+    assert_eq!(changed, vec![0, 1, 2]);
+
+    // retain_range does not stamp the entries it keeps
+    let synced = tree.version();
+    tree.retain_range(50..60, |k, _| k % 2 == 0);
+    assert!(tree.changed_since(synced).next().is_none());
+    assert!(tree.check_invariants());
+}
+
+// ============================================================================
+// PANIC SAFETY TESTS
+// ============================================================================
+
+thread_local! {
+    /// Comparisons left before `FragileKey::cmp` panics; `None` disarms it.
+    static COMPARISONS_LEFT: std::cell::Cell<Option<usize>> = const { std::cell::Cell::new(None) };
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct FragileKey(i32);
+
+impl Ord for FragileKey {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        COMPARISONS_LEFT.with(|left| match left.get() {
+            Some(0) => panic!("comparator panic"),
+            Some(n) => left.set(Some(n - 1)),
+            None => {}
+        });
+        self.0.cmp(&other.0)
+    }
+}
+
+impl PartialOrd for FragileKey {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Run `op` with the comparator set to panic after `budget` comparisons.
+/// Returns true if it panicked.
+fn panics_after(budget: usize, op: impl FnOnce()) -> bool {
+    COMPARISONS_LEFT.with(|left| left.set(Some(budget)));
+    let panicked = std::panic::catch_unwind(std::panic::AssertUnwindSafe(op)).is_err();
+    COMPARISONS_LEFT.with(|left| left.set(None));
+    panicked
+}
+
+fn fragile_tree(keys: impl IntoIterator<Item = i32>) -> BPlusTreeMap<FragileKey, i32> {
+    let mut tree = BPlusTreeMap::new(4).unwrap();
+    for key in keys {
+        tree.insert(FragileKey(key), key);
+    }
+    tree
+}
+
+#[test]
+fn test_panicking_comparator_during_insert_leaves_tree_valid() {
+    let original: Vec<i32> = (0..80).map(|i| i * 2).collect();
+
+    for new_key in [-1, 1, 31, 79, 159, 161] {
+        for budget in 0.. {
+            let mut tree = fragile_tree(original.iter().copied());
+            let panicked = panics_after(budget, || {
+                tree.insert(FragileKey(new_key), new_key);
+            });
+
+            assert!(tree.check_invariants(), "key {} budget {}", new_key, budget);
+            for key in &original {
+                assert_eq!(tree.get(&FragileKey(*key)), Some(key));
+            }
+            let inserted = tree.contains_key(&FragileKey(new_key));
+            assert_eq!(tree.len(), original.len() + usize::from(inserted));
+            assert_eq!(tree.items().count(), tree.len());
+
+            if !panicked {
+                assert!(inserted);
+                break;
+            }
+        }
+    }
+}
+
+#[test]
+fn test_panicking_comparator_during_remove_leaves_tree_valid() {
+    let original: Vec<i32> = (0..80).collect();
+
+    for target in [0, 17, 40, 79] {
+        for budget in 0.. {
+            let mut tree = fragile_tree(original.iter().copied());
+            for key in (0..80).filter(|k| k % 3 == 0 && *k != target) {
+                tree.remove(&FragileKey(key));
+            }
+            let before: Vec<i32> = tree.values().copied().collect();
+
+            let panicked = panics_after(budget, || {
+                tree.remove(&FragileKey(target));
+            });
+
+            assert!(tree.check_invariants(), "key {} budget {}", target, budget);
+            let after: Vec<i32> = tree.values().copied().collect();
+            if panicked {
+                assert_eq!(after, before);
+            } else {
+                let expected: Vec<i32> = before.into_iter().filter(|k| *k != target).collect();
+                assert_eq!(after, expected);
+                break;
+            }
+        }
+    }
+}
+
+#[test]
+fn test_panicking_comparator_during_rebalancing_remove_leaves_tree_valid() {
+    // Sequential inserts leave most nodes at minimum fill, so removals here
+    // borrow from siblings, merge leaves, and cascade merges up to the root.
+    let original: Vec<i32> = (0..40).collect();
+    let (mut merges, mut collapses) = (0, 0);
+
+    for target in original.iter().copied() {
+        for budget in 0.. {
+            let mut tree = fragile_tree(original.iter().copied());
+            let (leaves, height) = (tree.leaf_count(), tree.height());
+
+            let panicked = panics_after(budget, || {
+                tree.remove(&FragileKey(target));
+            });
+
+            assert!(tree.check_invariants(), "key {} budget {}", target, budget);
+            let keys: Vec<i32> = tree.values().copied().collect();
+            if panicked {
+                assert_eq!(keys, original);
+                assert_eq!((tree.leaf_count(), tree.height()), (leaves, height));
+            } else {
+                let expected: Vec<i32> = (0..40).filter(|k| *k != target).collect();
+                assert_eq!(keys, expected);
+                merges += usize::from(tree.leaf_count() < leaves);
+                collapses += usize::from(tree.height() < height);
+                break;
+            }
+        }
+    }
+
+    assert!(merges > 0, "no removal merged leaves");
+    assert!(collapses > 0, "no removal collapsed the root");
+}
+
+// ============================================================================
+// SHRINK TO FIT TESTS
+// ============================================================================
+
+#[test]
+fn test_shrink_to_fit_releases_node_capacity() {
+    let mut tree = create_tree_capacity_int(32);
+    for i in 0..5_000 {
+        tree.insert(i, i);
+    }
+    for i in 0..5_000 {
+        if i % 20 != 0 {
+            tree.remove(&i);
+        }
+    }
+    let before = tree.memory_stats().estimated_bytes;
+
+    tree.shrink_to_fit();
+
+    assert!(tree.memory_stats().estimated_bytes < before);
+    assert!(tree.check_invariants());
+    assert_eq!(tree.len(), 250);
+    assert_eq!(tree.get(&4_980), Some(&4_980));
+
+    // A shrunk tree still accepts inserts
+    for i in 0..100 {
+        tree.insert(i * 20 + 1, i);
+    }
+    assert_eq!(tree.len(), 350);
+    assert!(tree.check_invariants());
+}
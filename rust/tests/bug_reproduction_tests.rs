@@ -1,6 +1,5 @@
-/// Test cases to reproduce specific bugs found in the B+ tree implementation
-/// Each test demonstrates a concrete failure case for the identified issues
-use bplustree::BPlusTreeMap;
+//! Test cases to reproduce specific bugs found in the B+ tree implementation
+//! Each test demonstrates a concrete failure case for the identified issues
 
 mod test_utils;
 use test_utils::*;
@@ -415,3 +415,37 @@ mod comprehensive_tests {
         println!("✅ Comprehensive error scenario completed successfully");
     }
 }
+
+// ============================================================================
+// CHECKED INSERT TESTS
+// ============================================================================
+
+#[cfg(feature = "testing")]
+#[test]
+fn test_try_insert_checked_returns_internal_error() {
+    let mut tree = BPlusTreeMap::new(4).unwrap();
+    for i in 1..=4 {
+        tree.insert(i, i);
+    }
+
+    // The full leaf has to split, but no node IDs are left for the new leaf
+    tree.limit_node_ids(0);
+    let result = tree.try_insert_checked(5, 50);
+
+    assert!(matches!(result, Err(BPlusTreeError::AllocationError(_))));
+    // The failed insert left the existing entries untouched
+    assert_eq!(tree.len(), 4);
+    assert_eq!(tree.get(&5), None);
+    assert!(tree.check_invariants_detailed().is_ok());
+}
+
+#[test]
+fn test_try_insert_checked_matches_insert() {
+    let mut tree = BPlusTreeMap::new(4).unwrap();
+    for i in 0..200 {
+        assert_eq!(tree.try_insert_checked(i, i).unwrap(), None);
+    }
+    assert_eq!(tree.try_insert_checked(42, -1).unwrap(), Some(42));
+    assert_eq!(tree.len(), 200);
+    assert!(tree.check_invariants());
+}
@@ -0,0 +1,194 @@
+use bplustree::{BPlusTreeMap, Entry};
+
+#[test]
+fn test_entry_word_frequency_count() {
+    let text = "the quick brown fox jumps over the lazy dog the fox barks \
+                and the dog sleeps while a quick cat watches the fox";
+    let mut counts = BPlusTreeMap::new(4).unwrap();
+    for word in text.split_whitespace() {
+        *counts.entry(word.to_string()).or_insert(0) += 1;
+    }
+
+    let mut expected = std::collections::BTreeMap::new();
+    for word in text.split_whitespace() {
+        *expected.entry(word.to_string()).or_insert(0) += 1;
+    }
+
+    assert!(counts.check_invariants());
+    assert_eq!(counts.len(), expected.len());
+    let actual: Vec<(String, i32)> = counts.items().map(|(k, v)| (k.clone(), *v)).collect();
+    let expected: Vec<(String, i32)> = expected.into_iter().collect();
+    assert_eq!(actual, expected);
+    assert_eq!(counts.get(&"the".to_string()), Some(&5));
+}
+
+#[test]
+fn test_entry_or_insert_with_only_runs_when_vacant() {
+    let mut tree = BPlusTreeMap::new(4).unwrap();
+    for i in 0..50 {
+        tree.insert(i, i);
+    }
+
+    let mut calls = 0;
+    let value = tree.entry(10).or_insert_with(|| {
+        calls += 1;
+        -1
+    });
+    assert_eq!(*value, 10);
+    assert_eq!(calls, 0);
+
+    let value = tree.entry(100).or_insert_with(|| {
+        calls += 1;
+        -1
+    });
+    assert_eq!(*value, -1);
+    assert_eq!(calls, 1);
+    assert_eq!(tree.len(), 51);
+    assert!(tree.check_invariants());
+}
+
+#[test]
+fn test_entry_and_modify_and_or_default() {
+    let mut tree: BPlusTreeMap<i32, Vec<i32>> = BPlusTreeMap::new(4).unwrap();
+
+    for i in 0..30 {
+        tree.entry(i % 7)
+            .and_modify(|items| items.push(i))
+            .or_insert_with(|| vec![i]);
+    }
+    assert_eq!(tree.len(), 7);
+    assert_eq!(tree.get(&3), Some(&vec![3, 10, 17, 24]));
+
+    tree.entry(42).or_default().push(1);
+    assert_eq!(tree.get(&42), Some(&vec![1]));
+    assert_eq!(tree.entry(5).key(), &5);
+}
+
+#[test]
+fn test_occupied_and_vacant_entries() {
+    let mut tree = BPlusTreeMap::new(4).unwrap();
+    for i in 0..40 {
+        tree.insert(i, i * 10);
+    }
+
+    match tree.entry(20) {
+        Entry::Occupied(mut entry) => {
+            assert_eq!(entry.key(), &20);
+            assert_eq!(entry.get(), &200);
+            assert_eq!(entry.insert(7), 200);
+            assert_eq!(entry.get(), &7);
+        }
+        Entry::Vacant(_) => panic!("key 20 should be occupied"),
+    }
+
+    match tree.entry(21) {
+        Entry::Occupied(entry) => assert_eq!(entry.remove_entry(), (21, 210)),
+        Entry::Vacant(_) => panic!("key 21 should be occupied"),
+    }
+    assert_eq!(tree.len(), 39);
+    assert!(tree.check_invariants());
+
+    match tree.entry(21) {
+        Entry::Vacant(entry) => {
+            assert_eq!(entry.key(), &21);
+            *entry.insert(1) += 1;
+        }
+        Entry::Occupied(_) => panic!("key 21 should be vacant"),
+    }
+    assert_eq!(tree.get(&21), Some(&2));
+    assert_eq!(tree.len(), 40);
+    assert!(tree.check_invariants());
+}
+
+#[test]
+fn test_entry_inserts_through_splits() {
+    let mut tree = BPlusTreeMap::new(4).unwrap();
+    for i in (0..500).rev() {
+        *tree.entry(i % 250).or_insert(0) += 1;
+    }
+
+    assert_eq!(tree.len(), 250);
+    assert!(tree.values().all(|count| *count == 2));
+    assert!(tree.check_invariants_detailed().is_ok());
+}
+
+#[test]
+fn test_vacant_insert_reuses_the_entry_leaf() {
+    let mut tree = BPlusTreeMap::new(4).unwrap();
+    let mut plain = BPlusTreeMap::new(4).unwrap();
+    for i in 0..50 {
+        tree.insert(i * 2, i * 2);
+        plain.insert(i * 2, i * 2);
+    }
+
+    // Odd keys fill the gaps, so many of these inserts split a full leaf and
+    // the new entry may land in the split-off right half
+    for key in (1..100).step_by(2) {
+        plain.enable_counters();
+        plain.insert(key, key);
+        let insert_visits = plain.operation_counters().node_visits;
+
+        tree.enable_counters();
+        let lookup_visits = tree.height() as u64;
+        let value = match tree.entry(key) {
+            Entry::Vacant(entry) => entry.insert(0),
+            Entry::Occupied(_) => panic!("key {} should be vacant", key),
+        };
+        *value = key;
+        // The entry lookup and a plain insert, then the leaf holding the
+        // value and, after a split, its successor; no further descent
+        let visits = tree.operation_counters().node_visits;
+        assert!(visits <= lookup_visits + insert_visits + 2, "key {}", key);
+    }
+
+    assert!(tree.check_invariants_detailed().is_ok());
+    assert!(tree
+        .items()
+        .map(|(k, v)| (*k, *v))
+        .eq((0..100).map(|i| (i, i))));
+}
+
+#[test]
+fn test_first_and_last_entry_peek_and_mutate() {
+    let mut tree = BPlusTreeMap::new(4).unwrap();
+    assert!(tree.first_entry().is_none());
+    assert!(tree.last_entry().is_none());
+
+    for i in 0..50 {
+        tree.insert(i * 2, i);
+    }
+
+    let mut first = tree.first_entry().unwrap();
+    assert_eq!((*first.key(), *first.get()), (0, 0));
+    *first.get_mut() += 100;
+    assert_eq!(tree.get(&0), Some(&100));
+
+    let mut last = tree.last_entry().unwrap();
+    assert_eq!(*last.key(), 98);
+    assert_eq!(last.insert(-1), 49);
+    assert_eq!(tree.get(&98), Some(&-1));
+    tree.check_invariants_detailed().unwrap();
+}
+
+#[test]
+fn test_first_and_last_entry_peek_and_remove() {
+    let mut tree = BPlusTreeMap::new(4).unwrap();
+    for i in 0..50 {
+        tree.insert(i, i * 10);
+    }
+
+    // Drain from both ends like a scheduling loop
+    let mut order = Vec::new();
+    while let Some(entry) = tree.first_entry() {
+        let (key, _) = entry.remove_entry();
+        order.push(key);
+        if let Some(entry) = tree.last_entry() {
+            order.push(entry.remove_entry().0);
+        }
+        tree.check_invariants_detailed().unwrap();
+    }
+
+    assert!(tree.is_empty());
+    assert_eq!(order.len(), 50);
+    assert_eq!(&order[..4], &[0, 49, 1, 48]);
+}
@@ -0,0 +1,138 @@
+use bplustree::{BPlusTreeMap, BPlusTreeMapN};
+use rand::prelude::*;
+use std::collections::BTreeMap;
+
+/// Apply the same random inserts and removes to an inline tree, a Vec-based
+/// tree of the same capacity and a `BTreeMap`, checking that all three agree
+/// after every step.
+fn check_against_reference<const CAP: usize>(seed: u64, ops: usize, key_space: i32) {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut inline: BPlusTreeMapN<i32, i32, CAP> = BPlusTreeMapN::new();
+    let mut vec_tree = BPlusTreeMap::new(CAP).unwrap();
+    let mut reference = BTreeMap::new();
+
+    for step in 0..ops {
+        let key = rng.gen_range(0..key_space);
+        if rng.gen_bool(0.6) {
+            let value = rng.gen();
+            let expected = reference.insert(key, value);
+            assert_eq!(
+                inline.insert(key, value),
+                expected,
+                "insert {key} at step {step}"
+            );
+            assert_eq!(vec_tree.insert(key, value), expected);
+        } else {
+            let expected = reference.remove(&key);
+            assert_eq!(inline.remove(&key), expected, "remove {key} at step {step}");
+            assert_eq!(vec_tree.remove(&key), expected);
+        }
+
+        assert!(
+            inline.check_invariants(),
+            "invariants broken at step {step}"
+        );
+        assert_eq!(inline.len(), reference.len());
+        assert_eq!(inline.get(&key), reference.get(&key));
+    }
+
+    assert!(inline.iter().eq(reference.iter()));
+    assert!(inline.iter().eq(vec_tree.items()));
+    assert_eq!(inline.first(), reference.first_key_value());
+    assert_eq!(inline.last(), reference.last_key_value());
+}
+
+#[test]
+fn test_inline_map_matches_reference_across_capacities() {
+    check_against_reference::<4>(1, 5_000, 300);
+    check_against_reference::<5>(2, 5_000, 300);
+    check_against_reference::<7>(3, 5_000, 500);
+    check_against_reference::<16>(4, 10_000, 2_000);
+    check_against_reference::<64>(5, 10_000, 5_000);
+}
+
+#[test]
+fn test_inline_map_sequential_fill_and_drain() {
+    let mut tree: BPlusTreeMapN<u32, u32, 4> = BPlusTreeMapN::new();
+    for i in 0..1_000 {
+        assert_eq!(tree.insert(i, i * 2), None);
+    }
+    assert!(tree.check_invariants());
+    assert!(tree.keys().copied().eq(0..1_000));
+
+    for i in (0..1_000).rev().step_by(2) {
+        assert_eq!(tree.remove(&i), Some(i * 2));
+        assert!(tree.check_invariants());
+    }
+    for i in (0..1_000).step_by(2) {
+        assert_eq!(tree.remove(&i), Some(i * 2));
+        assert!(tree.check_invariants());
+    }
+    assert!(tree.is_empty());
+    assert_eq!(tree.first(), None);
+    assert_eq!(tree.last(), None);
+    assert_eq!(tree.remove(&0), None);
+}
+
+#[test]
+fn test_inline_map_range_bounds_match_vec_tree() {
+    let inline: BPlusTreeMapN<i32, i32, 4> = (0..100).map(|i| (i * 2, i)).collect();
+    let mut vec_tree = BPlusTreeMap::new(4).unwrap();
+    for i in 0..100 {
+        vec_tree.insert(i * 2, i);
+    }
+
+    let keys = |iter: bplustree::IterN<'_, i32, i32, 4>| iter.map(|(k, _)| *k).collect::<Vec<_>>();
+    assert_eq!(keys(inline.range(10..20)), vec![10, 12, 14, 16, 18]);
+    assert_eq!(keys(inline.range(11..=20)), vec![12, 14, 16, 18, 20]);
+    assert_eq!(keys(inline.range(195..)), vec![196, 198]);
+    assert_eq!(keys(inline.range(..5)), vec![0, 2, 4]);
+    assert_eq!(keys(inline.range(300..)), Vec::<i32>::new());
+    assert_eq!(keys(inline.range(50..50)), Vec::<i32>::new());
+
+    use std::ops::Bound::{Excluded, Included};
+    assert_eq!(
+        keys(inline.range((Excluded(10), Included(16)))),
+        vec![12, 14, 16]
+    );
+
+    for (start, end) in [(0, 200), (7, 93), (33, 34), (150, 250)] {
+        assert!(inline.range(start..end).eq(vec_tree.range(start..end)));
+    }
+}
+
+#[test]
+fn test_inline_map_get_mut_values_and_clear() {
+    let mut tree: BPlusTreeMapN<i32, i32, 8> = (0..50).map(|i| (i, i)).collect();
+    *tree.get_mut(&10).unwrap() += 100;
+    assert_eq!(tree.get(&10), Some(&110));
+    assert_eq!(tree.get_mut(&99), None);
+    assert!(tree.contains_key(&49));
+    assert!(!tree.contains_key(&50));
+    assert_eq!(tree.values().sum::<i32>(), (0..50).sum::<i32>() + 100);
+
+    tree.clear();
+    assert!(tree.is_empty());
+    assert_eq!(tree.iter().next(), None);
+    tree.insert(1, 1);
+    assert_eq!(tree.len(), 1);
+}
+
+#[test]
+fn test_inline_map_string_keys_and_traits() {
+    let mut tree: BPlusTreeMapN<String, usize, 4> = BPlusTreeMapN::default();
+    for word in ["pear", "apple", "fig", "kiwi", "date", "lime", "plum"] {
+        tree.insert(word.to_string(), word.len());
+    }
+    assert_eq!(tree.remove(&"fig".to_string()), Some(3));
+    assert!(tree.check_invariants());
+    assert_eq!(
+        format!("{:?}", tree),
+        r#"{"apple": 5, "date": 4, "kiwi": 4, "lime": 4, "pear": 4, "plum": 4}"#
+    );
+
+    let copy = tree.clone();
+    assert_eq!(copy, tree);
+    tree.insert("fig".to_string(), 3);
+    assert_ne!(copy, tree);
+}
@@ -330,3 +330,81 @@ fn test_linked_list_stress_consistency() {
 
     println!("\n✅ STRESS TEST COMPLETED - LINKED LIST CONSISTENT");
 }
+
+#[test]
+fn test_chain_break_is_detectable() {
+    let mut tree = create_tree_4_int();
+    for i in 0..40 {
+        tree.insert(i, i);
+    }
+    assert!(!tree.has_chain_break());
+
+    // Redirect the first leaf's next pointer to a leaf that does not exist
+    let first_leaf = tree
+        .level_order()
+        .find(|(_, _, is_leaf)| *is_leaf)
+        .unwrap()
+        .1;
+    assert!(tree.set_leaf_next(first_leaf, 9999));
+    assert!(tree.has_chain_break());
+
+    // The chain-following iterator truncates silently...
+    assert!(tree.items().count() < 40);
+
+    // ...but the lenient iterator still sees every entry
+    let mut lenient = tree.items_lenient();
+    assert_eq!(lenient.by_ref().count(), 40);
+    assert_eq!(lenient.skipped_leaves(), 0);
+}
+
+#[test]
+fn test_lenient_iteration_skips_missing_leaves() {
+    let mut tree = create_tree_4_int();
+    for i in 0..40 {
+        tree.insert(i, i);
+    }
+
+    let leaf_ids: Vec<_> = tree
+        .level_order()
+        .filter(|(_, _, is_leaf)| *is_leaf)
+        .map(|(_, id, _)| id)
+        .collect();
+    let removed = tree.deallocate_leaf(leaf_ids[1]).unwrap();
+    assert!(tree.has_chain_break());
+
+    let mut lenient = tree.items_lenient();
+    let seen = lenient.by_ref().count();
+    assert_eq!(seen, 40 - removed.len());
+    assert_eq!(lenient.skipped_leaves(), 1);
+}
+
+#[test]
+fn test_repair_leaf_chain_restores_broken_links() {
+    let mut tree = create_tree_4_int();
+    for i in 0..60 {
+        tree.insert(i, i);
+    }
+    assert_eq!(tree.repair_leaf_chain(), 0);
+
+    let leaf_ids: Vec<_> = tree
+        .level_order()
+        .filter(|(_, _, is_leaf)| *is_leaf)
+        .map(|(_, id, _)| id)
+        .collect();
+    assert!(leaf_ids.len() > 3);
+
+    // Break the chain in three ways: a dangling pointer, a skipped leaf,
+    // and a last leaf that no longer terminates the list
+    assert!(tree.set_leaf_next(leaf_ids[0], 9999));
+    assert!(tree.set_leaf_next(leaf_ids[1], leaf_ids[3]));
+    assert!(tree.set_leaf_next(*leaf_ids.last().unwrap(), leaf_ids[0]));
+    assert!(tree.has_chain_break());
+    assert!(tree.check_invariants_detailed().is_err());
+
+    assert_eq!(tree.repair_leaf_chain(), 3);
+
+    assert!(!tree.has_chain_break());
+    assert!(tree.check_invariants_detailed().is_ok());
+    assert_eq!(tree.items().count(), 60);
+    assert_eq!(tree.repair_leaf_chain(), 0);
+}
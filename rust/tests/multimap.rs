@@ -0,0 +1,45 @@
+use bplustree::BPlusTreeMultiMap;
+
+#[test]
+fn test_values_under_one_key_keep_insertion_order() {
+    let mut map = BPlusTreeMultiMap::new(4).unwrap();
+    for (key, value) in [(2, "b1"), (1, "a1"), (2, "b2"), (3, "c1"), (2, "b3")] {
+        map.insert(key, value);
+    }
+
+    assert_eq!(map.get_all(&2), &["b1", "b2", "b3"]);
+    assert_eq!(map.get_all(&9), &[] as &[&str]);
+    assert_eq!(map.len(), 5);
+    assert_eq!(map.key_count(), 3);
+    assert_eq!(
+        map.iter().collect::<Vec<_>>(),
+        vec![
+            (&1, &"a1"),
+            (&2, &"b1"),
+            (&2, &"b2"),
+            (&2, &"b3"),
+            (&3, &"c1"),
+        ]
+    );
+    assert_eq!(map.range(2..).count(), 4);
+    assert_eq!(map.keys().copied().collect::<Vec<_>>(), vec![1, 2, 3]);
+}
+
+#[test]
+fn test_remove_all_drops_every_value_for_a_key() {
+    let mut map = BPlusTreeMultiMap::new(4).unwrap();
+    for i in 0..100 {
+        map.insert(i % 7, i);
+    }
+    assert_eq!(map.len(), 100);
+
+    let removed = map.remove_all(&3);
+    assert_eq!(removed, (0..100).filter(|i| i % 7 == 3).collect::<Vec<_>>());
+    assert!(!map.contains_key(&3));
+    assert_eq!(map.len(), 100 - removed.len());
+    assert!(map.remove_all(&3).is_empty());
+
+    map.clear();
+    assert!(map.is_empty());
+    assert_eq!(format!("{:?}", map), "{}");
+}
@@ -0,0 +1,38 @@
+//! Exercises the map through the API available without the `std` feature.
+//!
+//! CI runs this with `--no-default-features --features alloc`; it also runs
+//! as part of the normal test suite.
+
+use bplustree::{BPlusTreeError, BPlusTreeMap};
+
+#[test]
+fn test_core_operations_without_std() {
+    let mut tree = BPlusTreeMap::new(4).unwrap();
+    for i in 0..200 {
+        tree.insert(i, i * 2);
+    }
+    assert_eq!(tree.len(), 200);
+    assert_eq!(tree.get(&150), Some(&300));
+
+    for i in (0..200).step_by(2) {
+        assert_eq!(tree.remove(&i), Some(i * 2));
+    }
+    assert_eq!(tree.len(), 100);
+    assert_eq!(tree.first(), Some((&1, &2)));
+    assert_eq!(tree.last(), Some((&199, &398)));
+    assert_eq!(tree.range(10..20).count(), 5);
+    assert_eq!(tree.range_sum(0..10), 2 + 6 + 10 + 14 + 18);
+    tree.check_invariants_detailed().unwrap();
+}
+
+#[test]
+fn test_errors_and_formatting_without_std() {
+    let error = BPlusTreeMap::<i32, i32>::new(1).unwrap_err();
+    assert!(matches!(error, BPlusTreeError::InvalidCapacity(_)));
+    assert!(error.to_string().starts_with("Invalid capacity"));
+
+    let mut tree = BPlusTreeMap::new(4).unwrap();
+    tree.insert(2, "b");
+    tree.insert(1, "a");
+    assert_eq!(format!("{:?}", tree), r#"{1: "a", 2: "b"}"#);
+}
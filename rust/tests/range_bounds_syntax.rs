@@ -1,4 +1,5 @@
 use bplustree::BPlusTreeMap;
+use std::ops::{Bound, RangeBounds};
 
 #[test]
 fn test_range_syntax_inclusive() {
@@ -97,6 +98,7 @@ fn test_range_syntax_empty_ranges() {
     }
 
     // Empty range - start > end
+    #[allow(clippy::reversed_empty_ranges)]
     let range: Vec<_> = tree.range(7..3).collect();
     assert_eq!(range, vec![]);
 
@@ -197,3 +199,307 @@ fn test_range_syntax_excluded_start() {
     let result: Vec<_> = tree.range(range).map(|(k, _)| *k).collect();
     assert_eq!(result, vec![4, 5, 6]); // 3 is excluded
 }
+
+#[test]
+fn test_range_syntax_excluded_start_not_in_tree() {
+    let mut tree = BPlusTreeMap::new(4).unwrap();
+    for i in 0..10 {
+        tree.insert(i * 2, format!("value{}", i * 2));
+    }
+
+    use std::ops::Bound;
+
+    // An excluded start key that is absent must not drop the next entry
+    let result: Vec<_> = tree
+        .range((Bound::Excluded(3), Bound::Included(8)))
+        .map(|(k, _)| *k)
+        .collect();
+    assert_eq!(result, vec![4, 6, 8]);
+
+    let result: Vec<_> = tree
+        .range((Bound::Excluded(-1), Bound::Unbounded))
+        .map(|(k, _)| *k)
+        .collect();
+    assert_eq!(result.len(), 10);
+}
+
+#[test]
+fn test_range_is_lazy_over_huge_span() {
+    let mut tree = BPlusTreeMap::new(16).unwrap();
+    for i in 0..100_000 {
+        tree.insert(i, i * 2);
+    }
+    let leaves = tree.leaf_count() as u64;
+    tree.enable_counters();
+
+    // Creating the iterator only descends to the start position
+    let mut range = tree.range(10..90_000);
+    let after_create = tree.operation_counters().node_visits;
+    assert!(
+        after_create < 16,
+        "range creation read {} nodes",
+        after_create
+    );
+
+    let first: Vec<(i32, i32)> = range.by_ref().take(5).map(|(k, v)| (*k, *v)).collect();
+    assert_eq!(
+        first,
+        vec![(10, 20), (11, 22), (12, 24), (13, 26), (14, 28)]
+    );
+
+    // A handful of items touches a handful of leaves, not the whole span
+    let visits = tree.operation_counters().node_visits;
+    assert!(
+        visits < 32 && visits * 100 < leaves,
+        "taking 5 items read {} of {} leaves",
+        visits,
+        leaves
+    );
+}
+
+#[test]
+fn test_prefix_range_string_keys() {
+    let mut tree = BPlusTreeMap::new(4).unwrap();
+    for word in [
+        "apple",
+        "apricot",
+        "banana",
+        "app",
+        "apply",
+        "ap",
+        "a",
+        "aq",
+        "b",
+        "applesauce",
+    ] {
+        tree.insert(word.to_string(), word.len());
+    }
+
+    let keys = |prefix: &str| -> Vec<String> {
+        tree.prefix_range(&prefix.to_string())
+            .map(|(k, _)| k.clone())
+            .collect()
+    };
+
+    assert_eq!(keys("apple"), vec!["apple", "applesauce"]);
+    assert_eq!(keys("app"), vec!["app", "apple", "applesauce", "apply"]);
+    assert_eq!(
+        keys("ap"),
+        vec!["ap", "app", "apple", "applesauce", "apply", "apricot"]
+    );
+    assert_eq!(keys("b"), vec!["b", "banana"]);
+    assert!(keys("c").is_empty());
+    assert_eq!(keys("").len(), tree.len());
+}
+
+#[test]
+fn test_prefix_range_at_character_limits() {
+    let mut tree = BPlusTreeMap::new(4).unwrap();
+    let max = char::MAX.to_string();
+    for key in [
+        format!("a{}", max),
+        format!("a{}z", max),
+        "b".to_string(),
+        "x\u{D7FF}".to_string(),
+        "x\u{D7FF}y".to_string(),
+        "x\u{E000}".to_string(),
+    ] {
+        tree.insert(key, ());
+    }
+
+    // Rolls over the maximal last character to increment the one before it
+    let a: Vec<String> = tree
+        .prefix_range(&format!("a{}", max))
+        .map(|(k, _)| k.clone())
+        .collect();
+    assert_eq!(a, vec![format!("a{}", max), format!("a{}z", max)]);
+
+    // Steps over the surrogate gap
+    let x: Vec<String> = tree
+        .prefix_range(&"x\u{D7FF}".to_string())
+        .map(|(k, _)| k.clone())
+        .collect();
+    assert_eq!(x, vec!["x\u{D7FF}".to_string(), "x\u{D7FF}y".to_string()]);
+
+    // Nothing sorts after an all-maximal prefix, so the range is unbounded above
+    assert_eq!(tree.prefix_range(&max).count(), 0);
+}
+
+#[test]
+fn test_keys_and_values_range_match_projected_items() {
+    use std::ops::Bound::{self, Excluded, Included, Unbounded};
+
+    let mut tree = BPlusTreeMap::new(4).unwrap();
+    for i in (0..200).step_by(3) {
+        tree.insert(i, i * 2);
+    }
+
+    let bounds: Vec<(Bound<i32>, Bound<i32>)> = vec![
+        (Included(10), Excluded(100)),
+        (Excluded(9), Included(99)),
+        (Unbounded, Excluded(20)),
+        (Included(150), Unbounded),
+        (Unbounded, Unbounded),
+        (Included(500), Unbounded),
+    ];
+    for range in bounds {
+        let items: Vec<(&i32, &i32)> = tree.range(range).collect();
+        let keys: Vec<&i32> = tree.keys_range(range).collect();
+        let values: Vec<&i32> = tree.values_range(range).collect();
+        assert_eq!(keys, items.iter().map(|(k, _)| *k).collect::<Vec<_>>());
+        assert_eq!(values, items.iter().map(|(_, v)| *v).collect::<Vec<_>>());
+    }
+
+    let keys: Vec<&i32> = tree.keys_range(30..40).collect();
+    let items: Vec<&i32> = tree
+        .items_range(Some(&30), Some(&40))
+        .map(|(k, _)| k)
+        .collect();
+    assert_eq!(keys, items);
+}
+
+#[test]
+fn test_range_step_over_contiguous_range() {
+    let mut tree = BPlusTreeMap::new(4).unwrap();
+    for i in 0..100 {
+        tree.insert(i, i * 10);
+    }
+
+    let every_second: Vec<i32> = tree.range_step(10..20, 2).map(|(k, _)| *k).collect();
+    assert_eq!(every_second, vec![10, 12, 14, 16, 18]);
+
+    let every_third: Vec<(i32, i32)> = tree.range_step(10..=20, 3).map(|(k, v)| (*k, *v)).collect();
+    assert_eq!(
+        every_third,
+        vec![(10, 100), (13, 130), (16, 160), (19, 190)]
+    );
+
+    assert!(tree.range_step(.., 1).eq(tree.range(..)));
+    assert_eq!(tree.range_step(200.., 2).count(), 0);
+}
+
+#[test]
+#[should_panic(expected = "step of at least 1")]
+fn test_range_step_zero_panics() {
+    let tree: BPlusTreeMap<i32, i32> = BPlusTreeMap::new(4).unwrap();
+    let _ = tree.range_step(.., 0);
+}
+
+#[test]
+fn test_range_around_is_inclusive_window() {
+    let mut tree = BPlusTreeMap::new(4).unwrap();
+    for i in 0..100 {
+        tree.insert(i, i * 2);
+    }
+
+    let window: Vec<(i32, i32)> = tree.range_around(&50, &3).map(|(k, v)| (*k, *v)).collect();
+    assert_eq!(
+        window,
+        vec![
+            (47, 94),
+            (48, 96),
+            (49, 98),
+            (50, 100),
+            (51, 102),
+            (52, 104),
+            (53, 106)
+        ]
+    );
+    assert!(tree.range_around(&50, &3).eq(tree.range(47..=53)));
+
+    // Zero radius is an exact lookup; windows are clipped at the tree edges
+    assert_eq!(tree.range_around(&10, &0).count(), 1);
+    assert_eq!(tree.range_around(&2, &5).count(), 8);
+    assert_eq!(tree.range_around(&98, &5).count(), 7);
+    assert_eq!(tree.range_around(&500, &5).count(), 0);
+}
+
+/// Walk the leaf chain from `start` up to, but not including, `end`.
+fn keys_between(
+    tree: &BPlusTreeMap<i32, i32>,
+    start: Option<(u32, usize)>,
+    end: Option<(u32, usize)>,
+) -> Vec<i32> {
+    let mut keys = Vec::new();
+    let Some((mut leaf_id, mut index)) = start else {
+        return keys;
+    };
+    loop {
+        let leaf = tree.get_leaf(leaf_id).unwrap();
+        while index < leaf.keys().len() {
+            if Some((leaf_id, index)) == end {
+                return keys;
+            }
+            keys.push(leaf.keys()[index]);
+            index += 1;
+        }
+        match tree.get_leaf_next(leaf_id) {
+            Some(next) => (leaf_id, index) = (next, 0),
+            None => return keys,
+        }
+    }
+}
+
+#[test]
+fn test_range_endpoints_bracket_exactly_the_range() {
+    let mut tree = BPlusTreeMap::new(4).unwrap();
+    for i in 0..60 {
+        tree.insert(i * 2, i);
+    }
+
+    let ranges: Vec<(Bound<i32>, Bound<i32>)> = vec![
+        (Bound::Included(10), Bound::Excluded(20)),
+        (Bound::Included(11), Bound::Included(21)),
+        (Bound::Excluded(10), Bound::Included(20)),
+        (Bound::Excluded(11), Bound::Excluded(19)),
+        (Bound::Unbounded, Bound::Excluded(7)),
+        (Bound::Included(100), Bound::Unbounded),
+        (Bound::Unbounded, Bound::Unbounded),
+        (Bound::Included(-10), Bound::Included(500)),
+        (Bound::Included(200), Bound::Unbounded),
+        (Bound::Included(30), Bound::Excluded(30)),
+        (Bound::Excluded(30), Bound::Included(30)),
+        (Bound::Included(40), Bound::Included(20)),
+        (Bound::Excluded(118), Bound::Unbounded),
+    ];
+
+    for range in ranges {
+        let expected: Vec<i32> = tree.keys().copied().filter(|k| range.contains(k)).collect();
+        let (start, end) = tree.range_endpoints(range);
+
+        assert_eq!(keys_between(&tree, start, end), expected, "{:?}", range);
+        assert_eq!(start.is_none(), expected.is_empty(), "{:?}", range);
+        assert_eq!(
+            tree.range(range).map(|(k, _)| *k).collect::<Vec<_>>(),
+            expected,
+            "{:?}",
+            range
+        );
+    }
+
+    // An end bound past the last key leaves no end position
+    assert_eq!(tree.range_endpoints(..=118).1, None);
+    assert!(tree.range_endpoints(..118).1.is_some());
+}
+
+#[test]
+fn test_range_scan_running_total() {
+    let mut tree = BPlusTreeMap::new(4).unwrap();
+    for i in 0..100 {
+        tree.insert(i, i * 2);
+    }
+
+    let totals: Vec<i64> = tree
+        .range_scan(10..15, 0i64, |sum, _, v| sum + *v as i64)
+        .collect();
+    assert_eq!(totals, vec![20, 42, 66, 92, 120]);
+
+    // The key is available to the fold, and empty ranges yield nothing
+    let keys_seen: Vec<usize> = tree.range_scan(95.., 0, |count, _, _| count + 1).collect();
+    assert_eq!(keys_seen, vec![1, 2, 3, 4, 5]);
+    let max_key: Vec<i32> = tree
+        .range_scan(..3, i32::MIN, |m, k, _| (*m).max(*k))
+        .collect();
+    assert_eq!(max_key, vec![0, 1, 2]);
+    assert_eq!(tree.range_scan(200.., 0, |s, _, _| *s).count(), 0);
+}
@@ -0,0 +1,175 @@
+use bplustree::BPlusTreeMap;
+use std::ops::Bound;
+
+mod test_utils;
+use test_utils::*;
+
+fn manual_sum<R: std::ops::RangeBounds<i32>>(tree: &BPlusTreeMap<i32, i32>, range: R) -> i32 {
+    tree.items()
+        .filter(|(k, _)| range.contains(*k))
+        .map(|(_, v)| *v)
+        .sum()
+}
+
+#[test]
+fn test_range_sum_matches_manual_fold() {
+    let mut tree = create_tree_4_int();
+    for i in 0..500 {
+        tree.insert(i, i * 3);
+    }
+
+    for (start, end) in [
+        (0, 500),
+        (10, 20),
+        (17, 389),
+        (250, 251),
+        (499, 600),
+        (-5, 3),
+    ] {
+        assert_eq!(tree.range_sum(start..end), manual_sum(&tree, start..end));
+        assert_eq!(tree.range_sum(start..=end), manual_sum(&tree, start..=end));
+    }
+    assert_eq!(tree.range_sum(..), manual_sum(&tree, ..));
+    assert_eq!(tree.range_sum(100..), manual_sum(&tree, 100..));
+    assert_eq!(tree.range_sum(..100), manual_sum(&tree, ..100));
+
+    let excluded = (Bound::Excluded(100), Bound::Excluded(200));
+    assert_eq!(tree.range_sum(excluded), manual_sum(&tree, excluded));
+}
+
+#[test]
+fn test_range_sum_empty_tree_and_empty_range() {
+    let tree = create_tree_4_int();
+    assert_eq!(tree.range_sum(..), 0);
+
+    let tree = create_tree_4_int_with_data(100);
+    assert_eq!(tree.range_sum(1000..2000), 0);
+    assert_eq!(tree.range_sum(50..50), 0);
+}
+
+#[test]
+fn test_range_sum_stays_correct_through_mutations() {
+    let mut tree = create_tree_4_int();
+    tree.enable_range_sums();
+    for i in 0..300 {
+        tree.insert(i, 1);
+    }
+    assert_eq!(tree.range_sum(..), 300);
+
+    // Updating values, inserting, and removing must all be reflected
+    *tree.get_mut(&10).unwrap() = 100;
+    assert_eq!(tree.range_sum(0..20), 20 - 1 + 100);
+
+    for i in 300..400 {
+        tree.insert(i, 2);
+    }
+    assert_eq!(tree.range_sum(..), manual_sum(&tree, ..));
+
+    for i in (0..400).step_by(3) {
+        tree.remove(&i);
+        assert_eq!(tree.range_sum(50..350), manual_sum(&tree, 50..350));
+    }
+    assert_invariants_int(&tree, "after range_sum mutations");
+
+    tree.clear();
+    assert_eq!(tree.range_sum(..), 0);
+}
+
+#[test]
+fn test_enabled_range_sums_follow_every_mutator() {
+    let mut tree = create_tree_4_int();
+    for i in 0..200 {
+        tree.insert(i, i);
+    }
+    tree.enable_range_sums();
+    let check = |tree: &BPlusTreeMap<i32, i32>, step: &str| {
+        for (start, end) in [(0, 1000), (13, 171), (40, 41), (-10, 60), (150, 1000)] {
+            assert_eq!(
+                tree.range_sum(start..end),
+                manual_sum(tree, start..end),
+                "range {}..{} after {}",
+                start,
+                end,
+                step
+            );
+        }
+    };
+    check(&tree, "enable_range_sums");
+
+    tree.insert(500, 7);
+    tree.insert(3, 1000);
+    tree.remove(&100);
+    check(&tree, "insert and remove");
+    *tree.get_mut(&50).unwrap() += 5;
+    check(&tree, "get_mut");
+    *tree.entry(51).or_insert(0) += 9;
+    *tree.get_or_insert_with(600, || 4) += 1;
+    check(&tree, "entry");
+    for value in tree.values_mut() {
+        *value *= 2;
+    }
+    check(&tree, "values_mut");
+    tree.insert(700, 1);
+    check(&tree, "insert after values_mut");
+    for (_, value) in tree.range_mut(20..30) {
+        *value = -1;
+    }
+    tree.update_range(30..40, |_, value| *value = 3);
+    check(&tree, "range_mut and update_range");
+    tree.retain(|k, v| {
+        *v += 1;
+        k % 4 != 0
+    });
+    check(&tree, "retain");
+    tree.merge_with(61, 100, |old, new| *old += new);
+    check(&tree, "merge_with");
+    tree.remove_range(70..90);
+    tree.pop_first_n(10);
+    tree.pop_last_n(3);
+    check(&tree, "bulk removals");
+    tree.coalesce_underfull();
+    tree.compact();
+    check(&tree, "compact");
+
+    let upper = tree.split_off(&120);
+    check(&tree, "split_off");
+    check(&upper, "split_off upper half");
+    let mut upper = upper;
+    tree.append(&mut upper);
+    check(&tree, "append");
+    tree.insert_sorted_batch((1000..1100).map(|i| (i, i)).collect())
+        .unwrap();
+    check(&tree, "insert_sorted_batch");
+    let copy = tree.clone();
+    check(&copy, "clone");
+
+    tree.disable_range_sums();
+    tree.insert(-5, 5);
+    check(&tree, "disable_range_sums");
+    assert_invariants_int(&tree, "after tracked range sums");
+}
+
+#[test]
+fn test_enabled_range_sums_read_covered_subtrees_from_branches() {
+    let mut tree = create_tree_4_int_with_data(2000);
+    let height = tree.height() as u64;
+    let expected = manual_sum(&tree, 100..1900);
+    tree.enable_counters();
+
+    assert_eq!(tree.range_sum(100..1900), expected);
+    let scanning_visits = tree.operation_counters().node_visits;
+
+    tree.enable_range_sums();
+    tree.reset_counters();
+    assert_eq!(tree.range_sum(100..1900), expected);
+    let summed_visits = tree.operation_counters().node_visits;
+
+    // Only the two boundary paths are walked, each touching a few siblings
+    assert!(
+        summed_visits <= 2 * height * 4,
+        "{} visits for height {}",
+        summed_visits,
+        height
+    );
+    assert!(summed_visits * 10 < scanning_visits);
+}
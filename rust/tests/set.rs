@@ -0,0 +1,80 @@
+use bplustree::BPlusTreeSet;
+
+fn set_of(capacity: usize, keys: impl IntoIterator<Item = i32>) -> BPlusTreeSet<i32> {
+    let mut set = BPlusTreeSet::new(capacity).unwrap();
+    set.extend(keys);
+    set
+}
+
+#[test]
+fn test_set_insert_contains_remove() {
+    let mut set = BPlusTreeSet::new(4).unwrap();
+    assert!(set.insert(3));
+    assert!(set.insert(1));
+    assert!(!set.insert(3));
+    assert_eq!(set.len(), 2);
+
+    assert!(set.contains(&1));
+    assert!(!set.contains(&2));
+    assert!(set.remove(&1));
+    assert!(!set.remove(&1));
+    assert_eq!(set.iter().copied().collect::<Vec<_>>(), vec![3]);
+
+    set.clear();
+    assert!(set.is_empty());
+}
+
+#[test]
+fn test_set_iter_and_range_are_sorted() {
+    let set = set_of(4, [9, 2, 7, 4, 0, 5]);
+    assert_eq!(
+        set.iter().copied().collect::<Vec<_>>(),
+        vec![0, 2, 4, 5, 7, 9]
+    );
+    assert_eq!(set.range(3..=7).copied().collect::<Vec<_>>(), vec![4, 5, 7]);
+    assert_eq!(format!("{:?}", set_of(4, [2, 1])), "{1, 2}");
+}
+
+#[test]
+fn test_set_operations_with_overlap() {
+    // Multiples of 2 and of 3 below 100, across many leaves
+    let twos = set_of(4, (0..100).step_by(2));
+    let threes = set_of(5, (0..100).step_by(3));
+
+    let union: Vec<i32> = twos.union(&threes).copied().collect();
+    let expected: Vec<i32> = (0..100).filter(|i| i % 2 == 0 || i % 3 == 0).collect();
+    assert_eq!(union, expected);
+
+    let intersection: Vec<i32> = twos.intersection(&threes).copied().collect();
+    let expected: Vec<i32> = (0..100).step_by(6).collect();
+    assert_eq!(intersection, expected);
+
+    let difference: Vec<i32> = twos.difference(&threes).copied().collect();
+    let expected: Vec<i32> = (0..100).filter(|i| i % 2 == 0 && i % 3 != 0).collect();
+    assert_eq!(difference, expected);
+
+    // Operations are symmetric where they should be
+    assert!(twos.union(&threes).eq(threes.union(&twos)));
+    assert!(twos.intersection(&threes).eq(threes.intersection(&twos)));
+}
+
+#[test]
+fn test_set_operations_with_empty_and_equal_sets() {
+    let set = set_of(4, 0..20);
+    let empty = BPlusTreeSet::new(4).unwrap();
+
+    assert!(set.union(&empty).eq(set.iter()));
+    assert!(empty.union(&set).eq(set.iter()));
+    assert_eq!(set.intersection(&empty).count(), 0);
+    assert!(set.difference(&empty).eq(set.iter()));
+    assert_eq!(empty.difference(&set).count(), 0);
+
+    let same = set.clone();
+    assert!(set.union(&same).eq(set.iter()));
+    assert!(set.intersection(&same).eq(set.iter()));
+    assert_eq!(set.difference(&same).count(), 0);
+
+    // Results collect back into sets
+    let collected: BPlusTreeSet<i32> = set.difference(&set_of(4, 5..20)).copied().collect();
+    assert_eq!(collected, set_of(4, 0..5));
+}
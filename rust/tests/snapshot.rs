@@ -0,0 +1,82 @@
+use bplustree::{BPlusTreeMap, Snapshot};
+use std::thread;
+
+fn assert_send_sync<T: Send + Sync>() {}
+
+#[test]
+fn test_snapshot_is_send_and_sync() {
+    assert_send_sync::<Snapshot<i32, String>>();
+}
+
+#[test]
+fn test_snapshot_is_decoupled_from_source() {
+    let mut tree = BPlusTreeMap::new(4).unwrap();
+    for i in 0..100 {
+        tree.insert(i, i * 2);
+    }
+    let snapshot = tree.snapshot();
+
+    tree.remove(&10);
+    tree.insert(10_000, 0);
+    *tree.get_mut(&20).unwrap() = -1;
+
+    assert_eq!(snapshot.len(), 100);
+    assert_eq!(snapshot.get(&10), Some(&20));
+    assert_eq!(snapshot.get(&20), Some(&40));
+    assert!(!snapshot.contains_key(&10_000));
+    assert_eq!(
+        snapshot
+            .range(5..8)
+            .map(|(k, v)| (*k, *v))
+            .collect::<Vec<_>>(),
+        vec![(5, 10), (6, 12), (7, 14)]
+    );
+    assert!(snapshot.keys().copied().eq(0..100));
+
+    let mut restored = snapshot.to_tree();
+    restored.insert(-5, 0);
+    assert_eq!(snapshot.len(), 100);
+    assert_eq!(restored.len(), 101);
+}
+
+#[test]
+fn test_snapshot_concurrent_reads_while_writer_mutates() {
+    let mut tree = BPlusTreeMap::new(8).unwrap();
+    for i in 0..10_000 {
+        tree.insert(i, i.to_string());
+    }
+    let snapshot = tree.snapshot();
+
+    thread::scope(|scope| {
+        let readers: Vec<_> = (0..4)
+            .map(|reader| {
+                let snapshot = snapshot.clone();
+                scope.spawn(move || {
+                    for round in 0..20 {
+                        let key = (reader * 2_500 + round * 97) % 10_000;
+                        assert_eq!(snapshot.get(&key), Some(&key.to_string()));
+                    }
+                    assert_eq!(snapshot.range(1_000..2_000).count(), 1_000);
+                    assert_eq!(snapshot.items().count(), 10_000);
+                })
+            })
+            .collect();
+
+        // The writer owns the source tree and keeps changing it
+        for i in 0..10_000 {
+            if i % 2 == 0 {
+                tree.remove(&i);
+            } else {
+                tree.insert(i, "changed".to_string());
+            }
+        }
+
+        for reader in readers {
+            reader.join().unwrap();
+        }
+    });
+
+    assert_eq!(tree.len(), 5_000);
+    assert_eq!(snapshot.len(), 10_000);
+    assert_eq!(snapshot.get(&1), Some(&"1".to_string()));
+}
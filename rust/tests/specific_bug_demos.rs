@@ -53,7 +53,7 @@ fn demonstrate_incorrect_split_for_odd_capacity() {
     // BUG: With capacity 5, min_keys = 5/2 = 2, but it should be ceil(5/2) = 3
     // The current implementation creates [2, 4] split instead of [3, 3]
     let min_keys = 5 / 2; // Current incorrect implementation = 2
-    let correct_min_keys = (5 + 1) / 2; // Should be 3
+    let correct_min_keys = 5_usize.div_ceil(2); // Should be 3
 
     println!("Current min_keys calculation: {}", min_keys);
     println!("Correct min_keys should be: {}", correct_min_keys);
@@ -158,7 +158,7 @@ fn demonstrate_linked_list_merge_corruption() {
 
     // Check if iteration is consistent
     let expected: Vec<_> = (0..20)
-        .filter(|&i| i < 8 || i >= 12)
+        .filter(|&i| !(8..12).contains(&i))
         .map(|i| i * 10)
         .collect();
     println!("Expected: {:?}", expected);
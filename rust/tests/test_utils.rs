@@ -30,15 +30,13 @@ pub fn create_tree_6() -> BPlusTreeMap<i32, String> {
 
 /// Generic tree creation with custom capacity
 pub fn create_tree_capacity(capacity: usize) -> BPlusTreeMap<i32, String> {
-    BPlusTreeMap::new(capacity).expect(&format!("Failed to create tree with capacity {}", capacity))
+    BPlusTreeMap::new(capacity).unwrap_or_else(|_| panic!("Failed to create tree with capacity {}", capacity))
 }
 
 /// Generic integer tree creation with custom capacity
 pub fn create_tree_capacity_int(capacity: usize) -> BPlusTreeMap<i32, i32> {
-    BPlusTreeMap::new(capacity).expect(&format!(
-        "Failed to create integer tree with capacity {}",
-        capacity
-    ))
+    BPlusTreeMap::new(capacity).unwrap_or_else(|_| panic!("Failed to create integer tree with capacity {}",
+        capacity))
 }
 
 // ============================================================================
@@ -367,15 +365,18 @@ pub fn create_branch_test_tree(capacity: usize) -> BPlusTreeMap<i32, String> {
     }
 
     // Delete strategically to make siblings exactly at minimum
-    for key in vec![18, 28, 38, 48] {
+    for key in [18, 28, 38, 48] {
         tree.remove(&key);
     }
 
     tree
 }
 
+/// A simulated operation: `(is_insert, key)`
+pub type SimulatedOp = (bool, i32);
+
 /// Standard setup for concurrent access simulation
-pub fn setup_concurrent_simulation() -> (Vec<(bool, i32)>, Vec<(bool, i32)>) {
+pub fn setup_concurrent_simulation() -> (Vec<SimulatedOp>, Vec<SimulatedOp>) {
     let thread1_ops = vec![
         (true, 1),
         (true, 3),